@@ -1,15 +1,21 @@
 use smart_keymap_nickel_helper::{
-    codegen_rust_module, nickel_keymap_rs_for_keymap_path, CodegenInputs,
+    codegen_keymap_build_hash, codegen_rust_module, nickel_keymap_rs_for_keymap_path, CodegenInputs,
 };
 
 fn main() {
     let ncl_import_path = format!("{}/ncl", env!("CARGO_MANIFEST_DIR"));
 
-    codegen_rust_module(CodegenInputs {
+    let keymap_rs = codegen_rust_module(CodegenInputs {
         env_var: "SMART_KEYMAP_CUSTOM_KEYMAP",
         cfg_name: "custom_keymap",
         module_basename: "keymap.rs",
         ncl_import_path: ncl_import_path.as_str(),
         nickel_eval_fn: nickel_keymap_rs_for_keymap_path,
     });
+
+    codegen_keymap_build_hash(
+        env!("CARGO_PKG_VERSION"),
+        keymap_rs.as_deref(),
+        "keymap_build_hash.rs",
+    );
 }
@@ -0,0 +1,112 @@
+Feature: Auto Shift Keys
+
+  Auto Shift keys output the key when tapped,
+   and the shifted key when held.
+
+  `K.auto_shift K.A` is an auto-shift `A` key:
+   tapping it types `a`, and holding it for longer than
+   `config.auto_shift.timeout` (default 175ms) types `A`.
+
+  Pressing another key before the timeout resolves the
+   auto-shift key as unshifted, so rolling quickly across
+   keys does not produce shifted output.
+
+  Which keys are auto-shifted is configured by class with
+   `config.auto_shift.classes` (`alpha`, `numeric`, and `symbols`;
+   all enabled by default). `K.auto_shift_on` and `K.auto_shift_off`
+   opt individual keys in or out, regardless of class.
+
+  An auto-shift key pressed while a modifier is held
+   (including while Caps Word is active) outputs the key immediately.
+
+  By default, the shifted output is tapped once. With
+   `config.auto_shift.repeat_on_hold = true`, the shifted output
+   stays pressed until the key is released (so the host's key repeat
+   repeats the shifted key). `config.auto_shift.quick_tap_ms`
+   outputs the unshifted key immediately when the key is pressed again
+   soon after its previous press (so "tap, then hold" repeats the
+   unshifted key).
+
+  For examples of this key in other smart keyboard firmware, see e.g.:
+
+  - [QMK's Auto Shift](https://docs.qmk.fm/features/auto_shift)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        keys = [
+          K.auto_shift K.A,
+          K.B,
+          K.caps_word.toggle,
+        ]
+      }
+      """
+
+  Example: tapping an auto-shift key outputs the key
+    When the keymap registers the following input
+      """
+      [
+        tap (K.auto_shift K.A),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.A,
+      ]
+      """
+
+  Example: holding an auto-shift key outputs the shifted key
+    When the keymap registers the following input
+      """
+      [
+        press (K.auto_shift K.A),
+        wait 200,
+        release (K.auto_shift K.A),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap (K.A & K.LeftShift),
+      ]
+      """
+
+  Example: pressing another key before the timeout outputs the key
+    When the keymap registers the following input
+      """
+      [
+        press (K.auto_shift K.A),
+        press K.B,
+        release (K.auto_shift K.A),
+        release K.B,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press K.A,
+        press K.B,
+        release K.A,
+        release K.B,
+      ]
+      """
+
+  Example: with Caps Word active, auto-shift keys output immediately
+    When the keymap registers the following input
+      """
+      [
+        tap K.caps_word.toggle,
+        tap (K.auto_shift K.A),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.LeftShift),
+        tap K.A,
+      ]
+      """
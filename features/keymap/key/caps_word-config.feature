@@ -0,0 +1,67 @@
+Feature: Caps Word Key (config)
+
+  The keys which continue a word can be set with `config.caps_word`:
+
+  - `shift_keys` continue the word, and are typed with shift.
+    (Default: the letters A-Z, and `-`, which is typed as `_`).
+
+  - `continue_keys` continue the word, and are typed as-is.
+    Keys with a shift modifier (e.g. `K.Underscore`)
+     only continue the word when typed shifted.
+    (Default: the digits 0-9, Backspace, Delete).
+
+  Any other key (other than modifiers) disables Caps Word.
+
+  `config.caps_word.idle_timeout` disables Caps Word
+   after no key has been typed for that many milliseconds.
+
+  `config.caps_word.mode` can be set to `"CapsLock"`
+   to toggle the host's Caps Lock while Caps Word is active,
+   rather than holding shift while typing letters.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.caps_word = {
+          shift_keys =
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+            |> std.string.characters
+            |> std.array.map (fun c => K."%{c}"),
+          continue_keys = [K.Minus],
+        },
+        keys = [
+          K.caps_word.toggle,
+          K.A,
+          K.Minus,
+          K.Space,
+        ]
+      }
+      """
+
+  Example: caps word types minus unshifted when it is a continue key
+    When the keymap registers the following input
+      """
+      [
+        tap K.caps_word.toggle,
+        tap K.A,
+        tap K.Minus,
+        tap K.A,
+        tap K.Space,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.LeftShift),
+        tap K.A,
+        release (K.LeftShift),
+        tap K.Minus,
+        press (K.LeftShift),
+        tap K.A,
+        release (K.LeftShift),
+        tap K.Space,
+      ]
+      """
@@ -0,0 +1,59 @@
+Feature: Dynamic Macros
+
+  Dynamic macro keys record the keys you press, so they can be played back
+  later with a single key press.
+
+  Pressing `K.dynamic_macro.record_start slot` starts recording into that slot;
+  pressing `K.dynamic_macro.record_stop` (or a record key again) stops recording.
+  Pressing `K.dynamic_macro.play slot` then plays back the recorded keys.
+  (`K.DM_REC1`, `K.DM_REC2`, `K.DM_RSTP`, `K.DM_PLY1`, `K.DM_PLY2`
+  are QMK-style names for the first two slots).
+
+  Recorded macros are kept in RAM, and so are lost when the keyboard is reset.
+  The `config.dynamic_macro` fields configure the macros:
+   `buffer_size` is the number of presses/releases/waits each slot can record,
+   `instruction_duration` is the time taken for each recorded press or release
+   during playback, and `max_wait_ms` limits the recorded wait between presses
+   (set this to `0` to play macros back without waits).
+
+  For examples of this key in other smart keyboard firmware, see e.g.:
+
+  - [QMK's Dynamic Macros](https://docs.qmk.fm/features/dynamic_macros)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.dynamic_macro.buffer_size = 32,
+        keys = [
+          K.DM_REC1,
+          K.DM_RSTP,
+          K.DM_PLY1,
+          K.A,
+          K.B,
+        ]
+      }
+      """
+
+  Example: playing back a recorded macro
+    When the keymap registers the following input
+      """
+      [
+        tap K.DM_REC1,
+        tap K.A,
+        tap K.B,
+        tap K.DM_RSTP,
+        tap K.DM_PLY1,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.A,
+        tap K.B,
+        tap K.A,
+        tap K.B,
+      ]
+      """
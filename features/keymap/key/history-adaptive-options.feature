@@ -0,0 +1,112 @@
+Feature: History Adaptive Key (last two keys, replacing, timeout)
+
+  Adaptive key rules may also:
+
+  - use the last two keys typed as `prev` (e.g. `prev = [K.E, K.A]`).
+    These rules take precedence over rules for only the last key.
+
+  - emit several keys (up to 3), which are tapped in turn.
+
+  - set `replace_prev = true` to tap Backspace before the emitted keys,
+    e.g. to replace the previous key with a digraph.
+
+  `config.history.adaptive_timeout` sets the maximum time (in ms) since the
+  last key was typed for adaptive rules to apply.
+  After this, adaptive keys emit their default.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      let H = K.history.adaptive {
+        default = K.H,
+        rules = [
+          { prev = K.A, emit = K.U },
+          { prev = [K.E, K.A], emit = K.I },
+          { prev = K.G, emit = [K.G, K.L], replace_prev = true },
+        ],
+      } in
+      {
+        config.history.adaptive_timeout = 500,
+        keys = [
+          K.A,
+          K.E,
+          K.G,
+          H,
+        ]
+      }
+      """
+
+  Example: a rule for the last two keys takes precedence
+    When the keymap registers the following input
+      """
+      let K = import "keys.ncl" in
+      let H = K.history.adaptive {
+        default = K.H,
+        rules = [
+          { prev = K.A, emit = K.U },
+          { prev = [K.E, K.A], emit = K.I },
+          { prev = K.G, emit = [K.G, K.L], replace_prev = true },
+        ],
+      } in
+      [
+        tap K.E,
+        tap K.A,
+        press H,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.I] }
+      """
+
+  Example: replace_prev replaces the previous key with a digraph
+    When the keymap registers the following input
+      """
+      let K = import "keys.ncl" in
+      let H = K.history.adaptive {
+        default = K.H,
+        rules = [
+          { prev = K.A, emit = K.U },
+          { prev = [K.E, K.A], emit = K.I },
+          { prev = K.G, emit = [K.G, K.L], replace_prev = true },
+        ],
+      } in
+      [
+        tap K.G,
+        tap H,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.G,
+        tap K.Backspace,
+        tap K.G,
+        tap K.L,
+      ]
+      """
+
+  Example: after the adaptive timeout, the default is emitted
+    When the keymap registers the following input
+      """
+      let K = import "keys.ncl" in
+      let H = K.history.adaptive {
+        default = K.H,
+        rules = [
+          { prev = K.A, emit = K.U },
+          { prev = [K.E, K.A], emit = K.I },
+          { prev = K.G, emit = [K.G, K.L], replace_prev = true },
+        ],
+      } in
+      [
+        tap K.A,
+        wait 600,
+        press H,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.H] }
+      """
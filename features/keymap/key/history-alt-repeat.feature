@@ -1,10 +1,13 @@
 Feature: History Alternate Repeat Key
 
   The Alternate Repeat key emits a configured alternate of the last
-  resolved key output while held (QMK-style alt-repeat for single keys).
+  remembered action while held (QMK-style alt-repeat).
 
   Its rules are defined in Nickel under `config.history.alt_repeat` as
   `{ prev, emit }` pairs using ordinary keyboard keys.
+  (`prev` may also be an array of keys, or a string typed by a string macro).
+  A key typed with modifiers held uses the rule for the unmodified key,
+  with the modifiers applied to the alternate.
 
   Unmapped previous keys (and empty history) contribute no output.
 
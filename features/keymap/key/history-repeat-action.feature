@@ -0,0 +1,78 @@
+Feature: History Repeat Key (actions)
+
+  History remembers the last action, rather than only the last key output:
+
+  - a key pressed while modifier keys are held is remembered with those modifiers,
+  - the taps of an automation key (e.g. a string macro) are remembered together.
+
+  The Repeat key replays the whole action, and `config.history.alt_repeat`
+  rules may use an array of keys, or a string, as the `prev` action.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.history.alt_repeat = [
+          { prev = "ab", emit = K.C },
+        ],
+        keys = [
+          K.string_macro "ab",
+          K.LeftShift,
+          K.A,
+          K.history.repeat,
+          K.history.alt_repeat,
+        ]
+      }
+      """
+
+  Example: repeat after a string macro types the string again
+    When the keymap registers the following input
+      """
+      [
+        tap (K.string_macro "ab"),
+        tap K.history.repeat,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.A,
+        tap K.B,
+        tap K.A,
+        tap K.B,
+      ]
+      """
+
+  Example: repeat includes the modifiers held when the key was pressed
+    When the keymap registers the following input
+      """
+      [
+        press K.LeftShift,
+        tap K.A,
+        release K.LeftShift,
+        press K.history.repeat,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { modifiers = { left_shift = true }, key_codes = [K.A] }
+      """
+
+  Example: alt-repeat rules can match a string macro
+    When the keymap registers the following input
+      """
+      [
+        tap (K.string_macro "ab"),
+        tap K.history.alt_repeat,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.A,
+        tap K.B,
+        tap K.C,
+      ]
+      """
@@ -1,6 +1,7 @@
 Feature: History Repeat Key
 
   The Repeat key re-emits the last resolved key output while held.
+  (See also the Repeat key's behaviour for actions such as string macros).
 
   After typing a letter, tapping Repeat types that letter again.
   Holding Repeat keeps that output pressed until Repeat is released.
@@ -1,9 +1,13 @@
 Feature: Key Lock
 
   The Key Lock key holds down the next key you press until that key is pressed
-  again. You can think of it as Caps Lock, but for any key (including modifiers).
-  Only one key is locked at a time; arming again and locking a different key
-  replaces the previous lock.
+  again. You can think of it as Caps Lock, but for any key (including modifiers,
+  mouse buttons, and layer modifiers).
+  Several keys can be locked at once; arm Key Lock again to lock another key.
+
+  `config.key_lock.release` can be set to `"OnKeyLock"`, so that locked keys
+  stay locked when pressed again, and are all released by tapping Key Lock
+  twice.
 
   For examples of this key in other smart keyboard firmware, see e.g.:
 
@@ -74,3 +78,23 @@ Feature: Key Lock
         tap K.A,
       ]
       """
+
+  Example: key lock locks several keys
+    When the keymap registers the following input
+      """
+      [
+        tap K.key_lock,
+        tap K.LeftShift,
+        tap K.key_lock,
+        tap K.B,
+        press K.A,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press K.LeftShift,
+        press K.B,
+        press K.A,
+      ]
+      """
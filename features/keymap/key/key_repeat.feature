@@ -0,0 +1,57 @@
+Feature: Key Repeat
+
+  `K.key_repeat` wraps a key so that, while it is held, the output the key
+  typed is tapped again, like a typematic key on a conventional keyboard.
+  This is useful for outputs which the host doesn't repeat because they are
+  not held in the HID report (e.g. a string macro). Outputs which are held in
+  the report are left to the host to repeat.
+
+  The first repeat happens after `config.key_repeat.delay` (default 500ms);
+  subsequent repeats happen every `config.key_repeat.interval` (default 33ms).
+  Only the most recently pressed key repeats.
+
+  `config.key_repeat.families` opts in every key of the listed key families
+  (e.g. `["automation", "history"]`), as if each were wrapped with
+  `K.key_repeat`.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.key_repeat = { delay = 100, interval = 20 },
+        keys = [
+          K.key_repeat K.A,
+          K.B,
+        ]
+      }
+      """
+
+  Example: tapping the key outputs it once
+    When the keymap registers the following input
+      """
+      [
+        tap (K.key_repeat K.A),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.A,
+      ]
+      """
+
+  Example: pressing another key stops the repeat
+    When the keymap registers the following input
+      """
+      [
+        press (K.key_repeat K.A),
+        press K.B,
+        wait 200,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.A, K.B] }
+      """
@@ -0,0 +1,64 @@
+Feature: Layer Modifier: Sticky Toggle
+
+  The `K.layer_mod.sticky_toggle` key acts like `K.layer_mod.sticky`:
+  tapping it activates the layer for the next key press,
+  and holding it activates the layer while held.
+
+  Tapping the key several times in a row toggles the layer.
+  The number of taps is `config.layered.sticky_toggle_taps` (default 2),
+  and each tap must follow the last within `config.layered.tap_toggle_timeout`
+  milliseconds (default 200).
+
+  For examples of this feature in other smart keyboard firmware, see e.g.:
+
+  - [QMK's OSL(layer) with ONESHOT_TAP_TOGGLE](https://docs.qmk.fm/one_shot_keys)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+          layers = [
+              [K.layer_mod.sticky_toggle 1, K.A, K.B],
+              [K.TTTT, K.X, K.Y],
+          ],
+      }
+      """
+
+  Example: tapping sticky toggle layer modifier activates the layer for the next pressed key
+
+    When the keymap registers the following input
+      """
+      [
+        tap (K.layer_mod.sticky_toggle 1),
+        tap_keymap_index 1,
+        tap_keymap_index 2,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap (K.X),
+        tap (K.B),
+      ]
+      """
+
+  Example: double tapping sticky toggle layer modifier toggles the layer
+
+    When the keymap registers the following input
+      """
+      [
+        tap (K.layer_mod.sticky_toggle 1),
+        tap (K.layer_mod.sticky_toggle 1),
+        tap_keymap_index 1,
+        tap_keymap_index 2,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap (K.X),
+        tap (K.Y),
+      ]
+      """
@@ -0,0 +1,73 @@
+Feature: Layer Modifier: Tap Toggle
+
+  The `K.layer_mod.tap_toggle` key activates a layer while it is held,
+  similar to `K.layer_mod.hold`.
+
+  Tapping the key several times in a row toggles the layer.
+  The number of taps is `config.layered.tap_toggle_taps` (default 5),
+  and each tap must follow the last within `config.layered.tap_toggle_timeout`
+  milliseconds (default 200).
+
+  For examples of this feature in other smart keyboard firmware, see e.g.:
+
+  - [QMK's TT(layer)](https://docs.qmk.fm/feature_layers#switching-and-toggling-layers)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.layered.tap_toggle_taps = 2,
+        layers = [
+          [
+            K.layer_mod.tap_toggle 1,
+            K.A,
+          ],
+          [
+            K.TTTT,
+            K.B,
+          ],
+        ],
+      }
+      """
+
+  Example: holding the tap toggle layer modifier activates the layer
+    When the keymap registers the following input
+      """
+      [
+        press (K.layer_mod.tap_toggle 1),
+        press (K.B),
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.B] }
+      """
+
+  Example: releasing the tap toggle layer modifier deactivates the layer
+    When the keymap registers the following input
+      """
+      [
+        tap (K.layer_mod.tap_toggle 1),
+        press (K.A),
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.A] }
+      """
+
+  Example: tapping the tap toggle layer modifier enough times toggles the layer
+    When the keymap registers the following input
+      """
+      [
+        tap (K.layer_mod.tap_toggle 1),
+        tap (K.layer_mod.tap_toggle 1),
+        press (K.B),
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.B] }
+      """
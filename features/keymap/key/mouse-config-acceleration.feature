@@ -0,0 +1,42 @@
+Feature: Mouse Keys (configure acceleration and gears)
+
+  By default, mouse cursor and wheel keys move at a constant speed.
+
+  The `config.mouse` fields configure acceleration (similar to QMK's mousekeys):
+   after `delay_ms`, held cursor keys speed up from `move_delta` to `max_speed`
+   over `time_to_max_ms` (and wheel keys from `wheel_delta` to `wheel_max_speed`
+   over `wheel_time_to_max_ms`), stepping every `interval_ms`.
+
+  While a gear key (`K.MouseGear0`, `K.MouseGear1`, `K.MouseGear2`) is held,
+   cursor and wheel keys instead move at the constant speed given
+   by that entry in `config.mouse.gears`.
+
+  Example: accelerating mouse keys with gears
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.mouse = {
+          delay_ms = 200,
+          move_delta = 2,
+          max_speed = 20,
+          time_to_max_ms = 1500,
+          wheel_max_speed = 4,
+          gears = [
+            { move_speed = 1, wheel_speed = 1 },
+            { move_speed = 8, wheel_speed = 1 },
+            { move_speed = 30, wheel_speed = 3 },
+          ],
+        },
+        keys = [
+          K.MouseLeft,
+          K.MouseDown,
+          K.MouseUp,
+          K.MouseRight,
+          K.MouseWheelDown,
+          K.MouseWheelUp,
+          K.MouseGear0,
+          K.MouseGear2,
+        ],
+      }
+      """
@@ -0,0 +1,81 @@
+Feature: Steno Keys
+
+  Steno keys are chorded keys for stenography: the steno keys pressed
+  together form a stroke, which is sent once all the steno keys are
+  released.
+
+  `K.steno."S-"`, `K.steno."A-"`, `K.steno."-T"`, etc. are the keys of
+  the steno layout, using Plover's key names. (`K.STN_S1`, `K.STN_A`,
+  `K.STN_TR`, etc. are QMK-style names for these).
+
+  With `config.steno.output = "Keyboard"` (the default), the stroke is
+  looked up in `config.steno.dictionary`, a Plover-format dictionary
+  (e.g. `config.steno.dictionary = import "main.json"`), and its
+  translation is typed, followed by a space. Translations ending with
+  `{^}` are typed without the space. Multi-stroke entries and other
+  Plover commands aren't supported, and are ignored. Strokes not in the
+  dictionary type nothing.
+
+  With `config.steno.output = "GeminiPr"` or `"TxBolt"`, each stroke is
+  instead written to the keyboard's serial output using that protocol,
+  for use with steno software such as Plover.
+
+  For examples of this key in other smart keyboard firmware, see e.g.:
+
+  - [QMK's Stenography](https://docs.qmk.fm/features/stenography)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        config.steno.dictionary = {
+          "KAT" = "cat",
+          "RE" = "re{^}",
+        },
+        keys = [
+          K.STN_KL, K.STN_RL, K.STN_A, K.STN_E, K.STN_TR,
+        ]
+      }
+      """
+
+  Example: a stroke types its translation
+    When the keymap registers the following input
+      """
+      [
+        press K.STN_KL,
+        press K.STN_A,
+        press K.STN_TR,
+        release K.STN_KL,
+        release K.STN_A,
+        release K.STN_TR,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.C,
+        tap K.A,
+        tap K.T,
+        tap K.Space,
+      ]
+      """
+
+  Example: a translation ending with {^} attaches to the next word
+    When the keymap registers the following input
+      """
+      [
+        press K.STN_RL,
+        press K.STN_E,
+        release K.STN_RL,
+        release K.STN_E,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.R,
+        tap K.E,
+      ]
+      """
@@ -0,0 +1,58 @@
+Feature: TapDance Key (hold definitions)
+
+  Besides `tap_dances`, a TapDance key can have `hold_dances`:
+   what the key does when it is held on its first press ("single hold"),
+   on its second press ("double hold", i.e. "tap then hold"), and so on.
+  A `null` entry has no hold behaviour for that number of presses.
+
+  `config.tap_dance.interrupt_response` sets how the TapDance key
+   responds to presses of other keys (as with `config.tap_hold`).
+  With `"Ignore"` (the default), the TapDance key resolves after its timeout.
+  With `"HoldOnKeyPress"` or `"HoldOnKeyTap"`,
+   another key press (or tap) resolves the TapDance key:
+   as the hold definition if the key is still held,
+   otherwise as the tap definition.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        keys = [
+          K.A & {
+            tap_dances = [K.B],
+            hold_dances = [K.LeftCtrl, K.LeftShift],
+          },
+        ]
+      }
+      """
+
+  Example: tap dance key held on first press resolves as single hold
+    When the keymap registers the following input
+      """
+      [
+        press (K.A & { tap_dances = [K.B], hold_dances = [K.LeftCtrl, K.LeftShift] }),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press K.LeftCtrl,
+      ]
+      """
+
+  Example: tap dance key tapped then held resolves as double hold
+    When the keymap registers the following input
+      """
+      [
+        tap (K.A & { tap_dances = [K.B], hold_dances = [K.LeftCtrl, K.LeftShift] }),
+        press (K.A & { tap_dances = [K.B], hold_dances = [K.LeftCtrl, K.LeftShift] }),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press K.LeftShift,
+      ]
+      """
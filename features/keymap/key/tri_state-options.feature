@@ -0,0 +1,85 @@
+Feature: Tri-state Key (continue-through, reverse tap, timeout)
+
+  A tri-state key built with `K.tri_state.custom` may also have:
+
+  - `continue_through`: keys whose output does not interrupt the session
+     (e.g. Shift, arrow keys). Modifiers may be held with any of the listed keys.
+
+  - `reverse_tap`: a key tapped by `K.tri_state.reverse`
+     while the tri-state key's session is armed.
+
+  - `timeout`: the idle time (in ms) after which the hold is released,
+     counted from the last start, continue, or reverse tap.
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        keys = [
+          K.tri_state.custom {
+            hold = K.LeftAlt,
+            tap = K.Tab,
+            reverse_tap = K.LeftShift & K.Tab,
+            continue_through = [K.LeftShift, K.Left, K.Right],
+            timeout = 1000,
+          },
+          K.tri_state.reverse,
+          K.Left,
+          K.A,
+        ]
+      }
+      """
+
+  Example: an arrow key continues through the session
+    When the keymap registers the following input
+      """
+      [
+        tap (K.tri_state.custom { hold = K.LeftAlt, tap = K.Tab, reverse_tap = K.LeftShift & K.Tab, continue_through = [K.LeftShift, K.Left, K.Right], timeout = 1000 }),
+        press K.Left,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { modifiers = { left_alt = true }, key_codes = [K.Left] }
+      """
+
+  Example: other keys interrupt the session
+    When the keymap registers the following input
+      """
+      [
+        tap (K.tri_state.custom { hold = K.LeftAlt, tap = K.Tab, reverse_tap = K.LeftShift & K.Tab, continue_through = [K.LeftShift, K.Left, K.Right], timeout = 1000 }),
+        press K.A,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { key_codes = [K.A] }
+      """
+
+  Example: the reverse key taps the reverse tap while Alt stays held
+    When the keymap registers the following input
+      """
+      [
+        tap (K.tri_state.custom { hold = K.LeftAlt, tap = K.Tab, reverse_tap = K.LeftShift & K.Tab, continue_through = [K.LeftShift, K.Left, K.Right], timeout = 1000 }),
+        press K.tri_state.reverse,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { modifiers = { left_alt = true, left_shift = true }, key_codes = [K.Tab] }
+      """
+
+  Example: the hold is released after the timeout
+    When the keymap registers the following input
+      """
+      [
+        tap (K.tri_state.custom { hold = K.LeftAlt, tap = K.Tab, reverse_tap = K.LeftShift & K.Tab, continue_through = [K.LeftShift, K.Left, K.Right], timeout = 1000 }),
+        wait 1100,
+      ]
+      """
+    Then the HID keyboard report should equal
+      """
+      { modifiers = {}, key_codes = [] }
+      """
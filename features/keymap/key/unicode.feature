@@ -0,0 +1,82 @@
+Feature: Unicode Keys
+
+  Unicode keys type arbitrary Unicode text, using the host's Unicode
+  input method to enter each character's code point as hex digits.
+
+  `K.unicode "é"` types its text using the keyboard's current Unicode
+  input mode. The supported modes are:
+
+  - `"Linux"`: Ctrl+Shift+U, the hex digits, then Space (IBus / GTK).
+  - `"MacOs"`: the UTF-16 hex digits, typed while holding Option
+    (requires the "Unicode Hex Input" keyboard layout).
+  - `"WinCompose"`: Right Alt (as WinCompose's compose key), U,
+    the hex digits, then Enter.
+  - `"WindowsAltNumpad"`: Numpad Plus then the hex digits, typed while
+    holding Alt (requires the `EnableHexNumpad` registry setting).
+
+  The initial mode is set with `config.unicode.mode` (default `"Linux"`).
+  The mode can be changed at runtime with `K.unicode_mode.set "macos"`
+  (mode names `linux`, `macos`, `win_compose`, or `windows_alt_numpad`)
+  or cycled with `K.unicode_mode.cycle`. (`K.UC_LINX`, `K.UC_MAC`,
+  `K.UC_WINC`, `K.UC_WIN`, and `K.UC_NEXT` are QMK-style names for these).
+
+  `K.unicode_with_mode "macos" "é"` always types with the given mode.
+  `K.unicode_semantic "é"` is a semantic key (see `K.semantic`) which
+  types with the mode matching whichever of the `windows`, `linux`, or
+  `macos` named layers is active; so it works with the same
+  `K.layer_mod.set_semantic_variant_to` keys used for semantic OS keys.
+
+  Each key can type up to 8 code points.
+
+  For examples of this key in other smart keyboard firmware, see e.g.:
+
+  - [QMK's Unicode Support](https://docs.qmk.fm/features/unicode)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      {
+        keys = [
+          K.unicode "é",
+          K.UC_WINC,
+        ]
+      }
+      """
+
+  Example: typing a character with the Linux input method
+    When the keymap registers the following input
+      """
+      [
+        tap (K.unicode "é"),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap (K.U & K.LeftCtrl & K.LeftShift),
+        tap K.E,
+        tap K.N9,
+        tap K.Space,
+      ]
+      """
+
+  Example: typing a character after changing the input mode
+    When the keymap registers the following input
+      """
+      [
+        tap K.UC_WINC,
+        tap (K.unicode "é"),
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.RightAlt,
+        tap K.U,
+        tap K.E,
+        tap K.N9,
+        tap K.Enter,
+      ]
+      """
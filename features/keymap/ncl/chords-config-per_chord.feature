@@ -0,0 +1,157 @@
+Feature: Chords (per-chord config)
+
+  Each entry in `chords` can override the chorded config for that chord:
+
+  - `timeout`: how long a chorded key waits for this chord,
+
+  - `required_idle_time`: idle time required before this chord can activate,
+
+  - `press_within`: all of the chord's keys must be pressed within this many
+    milliseconds of the first key press,
+
+  - `release`: which release of the chord's keys releases the chord's key.
+    `"Completing"` (the default) holds the chord's key until the key
+    which completed the chord is released;
+    `"Any"` releases it when any of the chord's keys is released;
+    `"All"` holds it until all of the chord's keys are released,
+
+  - `layers`: the chord is only active when the highest active layer
+    is one of these layers (`0` is the base layer).
+
+  `chording.ncl` has helpers for `release` and `layers`.
+
+  For examples of this feature in other smart keyboard firmware, see e.g.:
+
+  - [ZMK's combo properties](https://zmk.dev/docs/keymaps/combos)
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      let CH = import "chording.ncl" in
+      {
+        chords = [
+          { indices = [0, 1], key = K.C, } & CH.release.all,
+          { indices = [2, 3], key = K.F, } & CH.release.any,
+          { indices = [4, 5], key = K.I, press_within = 20, },
+          { indices = [7, 8], key = K.M, } & CH.on_layers [1],
+        ],
+        layers = [
+          [
+            K.A, K.B, K.D, K.E, K.G, K.H, K.layer_mod.hold 1, K.K, K.L,
+          ],
+          [
+            K.TTTT, K.TTTT, K.TTTT, K.TTTT, K.TTTT, K.TTTT, K.TTTT, K.TTTT, K.TTTT,
+          ],
+        ],
+      }
+      """
+
+  Example: chord with release "All" is held until all its keys are released
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 0,
+        press_keymap_index 1,
+        release_keymap_index 1,
+        press_keymap_index 2,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.C),
+        press (K.D),
+      ]
+      """
+
+  Example: chord with release "Any" is released when any of its keys is released
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 2,
+        press_keymap_index 3,
+        release_keymap_index 2,
+        press_keymap_index 0,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.F),
+        release (K.F),
+        press (K.A),
+      ]
+      """
+
+  Example: chord keys pressed within press_within resolve as the chord
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 4,
+        wait 10,
+        press_keymap_index 5,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.I),
+      ]
+      """
+
+  Example: chord keys pressed after press_within resolve as separate keys
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 4,
+        wait 50,
+        press_keymap_index 5,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.G),
+        press (K.H),
+      ]
+      """
+
+  Example: chord restricted to a layer is not active on the base layer
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 7,
+        press_keymap_index 8,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.K),
+        press (K.L),
+      ]
+      """
+
+  Example: chord restricted to a layer is active on that layer
+
+    When the keymap registers the following input
+      """
+      [
+        press_keymap_index 6,
+        press_keymap_index 7,
+        press_keymap_index 8,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        press (K.M),
+      ]
+      """
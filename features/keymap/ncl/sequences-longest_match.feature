@@ -0,0 +1,101 @@
+Feature: Sequences: Longest Match
+
+  When a sequence is a prefix of a longer sequence,
+   the shorter sequence is resolved once it's clear
+   the longer sequence won't be completed:
+   when the step times out,
+   or when a key which doesn't continue the longer sequence is pressed.
+
+  The step timeout can be set per sequence with `timeout`;
+   while sequence mode is armed, the step timeout is the longest timeout
+   of the sequences which could still be completed.
+
+  A sequence can type text instead of resolving to a key:
+   `{ indices, text = "hello" }` types the text (as `K.string_macro`),
+   and `{ indices, unicode = "é" }` types it using `K.unicode`.
+
+  `K.sequence_start_held` arms sequence mode for as long as it's held
+   (rather than until a step times out);
+   releasing it resolves the longest match, if any.
+
+  Sequence mode changes are reported to the firmware as
+   `KeymapCallback::Sequence` callbacks (`Armed`, `Continuing`, `Aborted`, `Resolved`),
+   e.g. so firmware can light an LED while sequence mode is armed.
+  (C firmware can register these with `keymap_register_callback`,
+   using `KEYMAP_CALLBACK_SEQUENCE_ARMED`, etc.).
+
+  Background:
+
+    Given a keymap.ncl:
+      """
+      let K = import "keys.ncl" in
+      let Seq = import "sequence.ncl" in
+      {
+        sequences = [
+          { indices = "_ 0 _ _" |> Seq.indices, key = K.C },
+          { indices = "_ 0 1 _" |> Seq.indices, text = "hi", timeout = 1000 },
+        ],
+        config.sequence.timeout = 500,
+        keys = [
+          K.sequence_start,
+          K.A,
+          K.B,
+          K.X,
+        ],
+      }
+      """
+
+  Example: timeout resolves the longest match
+
+    When the keymap registers the following input
+      """
+      [
+        tap K.sequence_start,
+        tap K.A,
+        wait 1100,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.C,
+      ]
+      """
+
+  Example: continuing the sequence resolves the longer sequence
+
+    When the keymap registers the following input
+      """
+      [
+        tap K.sequence_start,
+        tap K.A,
+        tap K.B,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.H,
+        tap K.I,
+      ]
+      """
+
+  Example: a key which doesn't continue the sequence resolves the longest match
+
+    The key then acts as itself.
+
+    When the keymap registers the following input
+      """
+      [
+        tap K.sequence_start,
+        tap K.A,
+        tap K.X,
+      ]
+      """
+    Then the output should be equivalent to output from
+      """
+      [
+        tap K.C,
+        tap K.X,
+      ]
+      """
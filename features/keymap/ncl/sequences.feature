@@ -19,6 +19,10 @@ Feature: Sequences
   `"1 0 _"` is keys 0 then 1 in scan order, but the sequence is
   key 1 then key 0.
 
+  Sequences which are a prefix of a longer sequence, per-sequence
+  timeouts, sequences which type text, and `K.sequence_start_held`
+  are described in "Sequences: Longest Match".
+
   For examples of this feature in other smart keyboard firmware, see e.g.:
 
   - [QMK's Leader Key](https://docs.qmk.fm/features/leader_key)
@@ -13,13 +13,16 @@ NCL_DIR="${REPOSITORY_DIR}/ncl"
 KEYMAP_FEATURE_MD=""
 
 keymap_key_features=(
+    "auto_shift"
     "automation"
     "automation-string"
     "callback"
     "callback-bluetooth"
     "caps_word"
+    "caps_word-config"
     "consumer"
     "custom"
+    "dynamic_macro"
     "keyboard"
     "layered"
     "layer_modifier-default"
@@ -29,12 +32,17 @@ keymap_key_features=(
     "layer_modifier-set_active_layers_mask"
     "layer_modifier-sticky"
     "layer_modifier-sticky-config-timeout"
+    "layer_modifier-sticky_toggle"
     "semantic_os_desktop"
+    "layer_modifier-tap_toggle"
     "layer_modifier-toggle"
     "mouse"
+    "mouse-config-acceleration"
+    "steno"
     "sticky_modifiers"
     "sticky_modifiers-config-release_on_next_press"
     "tap_dance"
+    "tap_dance-hold"
     "tap_hold"
     "tap_hold-config-interrupt-ignore"
     "tap_hold-config-interrupt-presses"
@@ -45,16 +53,19 @@ keymap_key_features=(
     "tap_hold-config-timeout-none"
     "tap_hold-hold_trigger_positions"
     "tap_hold-profiles"
+    "unicode"
 )
 
 keymap_ncl_features=(
     "chords"
+    "chords-config-per_chord"
     "chords-config-required_idle_time"
     "conditional_layers"
     "layer_string"
     "layers"
     "named_layers"
     "sequences"
+    "sequences-longest_match"
 )
 
 KEY_FIELDS_MD="${NCL_DIR}/key-docs.md"
@@ -14,8 +14,6 @@
 
 #define RX_BUFFER_SIZE 16
 
-static uint8_t msg_buffer[MESSAGE_BUFFER_LEN];
-
 static struct {
   volatile uint8_t current_buffer;
   uint8_t rx_buffer[2][RX_BUFFER_SIZE];
@@ -32,8 +30,7 @@ void keymap_split_receive_bytes(uint8_t *buf, uint16_t len) {
   KeymapInputEvent ev;
   for (uint16_t i = 0; i < len; i++) {
     uint8_t recv_byte = buf[i];
-    bool received_event =
-        keymap_message_buffer_receive_byte(&msg_buffer, recv_byte, &ev);
+    bool received_event = keymap_split_receive_byte(recv_byte, &ev);
 
     if (received_event) {
       keymap_register_input_event(ev);
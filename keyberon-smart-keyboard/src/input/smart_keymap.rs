@@ -3,7 +3,12 @@ use core::ops::Index;
 
 use smart_keymap::input;
 use smart_keymap::key;
-use smart_keymap::keymap::{self, Keymap, KeymapOutput, ReportHints, SetKeymapContext};
+use smart_keymap::keymap::{
+    self, KeyOverridesContext, Keymap, KeymapOutput, PersistentContext, ReportHints,
+    SetKeymapContext, SplitStatusContext, HID_NKRO_KEYBOARD_REPORT_LEN,
+};
+use smart_keymap::raw_hid;
+use smart_keymap::split;
 
 /// Callbacks for the keymap.
 pub struct KeymapCallbacks {
@@ -88,6 +93,19 @@ where
         self.keymap.handle_input(event);
     }
 
+    /// Register the keyboard LED state from the host's HID keyboard output report.
+    pub fn set_host_leds(&mut self, leds: u8) {
+        self.keymap.set_host_leds(keymap::HostLeds::from_byte(leds));
+    }
+
+    /// Applies the split status received from the primary half.
+    ///
+    /// The secondary half isn't connected to the host,
+    ///  so it takes the host's LED state from the primary half.
+    pub fn apply_split_status(&mut self, status: &split::Status) {
+        self.keymap.set_host_leds(status.host_leds);
+    }
+
     /// A time event.
     ///
     /// This method must be called regularly, typically every millisecond.
@@ -107,6 +125,81 @@ where
     pub fn keymap_output(&self) -> &KeymapOutput {
         &self.keymap_output
     }
+
+    /// Returns the current keymap output as an HID N-key rollover keyboard report.
+    ///
+    /// See [KeymapOutput::as_hid_nkro_keyboard_report].
+    pub fn nkro_keyboard_report(&self) -> [u8; HID_NKRO_KEYBOARD_REPORT_LEN] {
+        self.keymap_output.as_hid_nkro_keyboard_report()
+    }
+}
+
+impl<I, R, Ctx, Ev, PKS, KS, S> KeyboardBackend<I, R, Ctx, Ev, PKS, KS, S>
+where
+    I: Debug + Index<usize, Output = R>,
+    R: Copy + Debug,
+    Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + PersistentContext,
+    Ev: Copy + Debug,
+    PKS: Debug,
+    KS: Copy + Debug + From<key::NoOpKeyState>,
+    S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+{
+    /// Returns a snapshot of the keymap's persistent state (e.g. toggled layers).
+    ///
+    /// Firmware can store [keymap::PersistentState::to_bytes] in flash,
+    ///  and reapply it with [KeyboardBackend::restore_persistent_state].
+    pub fn persistent_state(&self) -> keymap::PersistentState {
+        self.keymap.persistent_state()
+    }
+
+    /// Restores the keymap's persistent state from a snapshot.
+    pub fn restore_persistent_state(&mut self, state: &keymap::PersistentState) {
+        self.keymap.restore_persistent_state(state);
+    }
+}
+
+impl<I, R, Ctx, Ev, PKS, KS, S> KeyboardBackend<I, R, Ctx, Ev, PKS, KS, S>
+where
+    I: Debug + Index<usize, Output = R>,
+    R: Copy + Debug,
+    Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + SplitStatusContext,
+    Ev: Copy + Debug,
+    PKS: Debug,
+    KS: Copy + Debug + From<key::NoOpKeyState>,
+    S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+{
+    /// Returns the keymap's split status,
+    ///  for sending from the primary half to the secondary half.
+    pub fn split_status(&self) -> split::Status {
+        self.keymap.split_status()
+    }
+}
+
+impl<I, R, Ctx, Ev, PKS, KS, S> KeyboardBackend<I, R, Ctx, Ev, PKS, KS, S>
+where
+    I: Debug + Index<usize, Output = R>,
+    R: Copy + Debug + serde::de::DeserializeOwned,
+    Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + KeyOverridesContext,
+    Ev: Copy + Debug,
+    PKS: Debug,
+    KS: Copy + Debug + From<key::NoOpKeyState>,
+    S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+{
+    /// Handles a raw HID report for editing the keymap (see [raw_hid]),
+    ///  returning the report to send back to the host.
+    pub fn raw_hid_report(&mut self, report: &[u8]) -> raw_hid::Report {
+        raw_hid::handle_report(&mut self.keymap, report)
+    }
+
+    /// Returns the keymap's key overrides, e.g. to store in flash.
+    pub fn key_overrides(&self) -> Option<&keymap::KeyOverrides> {
+        self.keymap.key_overrides()
+    }
+
+    /// Replaces the keymap's key overrides, e.g. with overrides stored in flash.
+    pub fn restore_key_overrides(&mut self, key_overrides: &keymap::KeyOverrides) {
+        self.keymap.restore_key_overrides(key_overrides);
+    }
 }
 
 /// Constructs a [input::Event] from a [keyberon::layout::Event],
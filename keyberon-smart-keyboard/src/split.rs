@@ -1,4 +1,5 @@
 use smart_keymap::input::Event;
+use smart_keymap::split::Status;
 
 /// Messages for the task-oriented frameworks
 ///  which manages the keyboard backend.
@@ -6,6 +7,8 @@ use smart_keymap::input::Event;
 pub enum BackendMessage {
     /// Update the layout with this event.
     Event(Event),
+    /// Apply the split status received from the primary half.
+    SplitStatus(Status),
     /// Tick the layout (and write report to the USB class).
     Tick,
 }
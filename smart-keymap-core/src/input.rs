@@ -4,6 +4,7 @@ use crate::key;
 
 /// Input events for [crate::keymap::Keymap].
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Event {
     /// A physical key press for a given `keymap_index`.
     Press {
@@ -0,0 +1,414 @@
+use serde::{Deserialize, Serialize};
+
+use crate::input;
+
+/// Magic bytes at the start of an input log.
+pub const MAGIC: [u8; 4] = *b"SKIL";
+
+/// Version of the input log format.
+pub const VERSION: u8 = 1;
+
+/// Length of the input log [Header].
+pub const HEADER_LEN: usize = 9;
+
+/// Maximum length of an encoded [Record].
+pub const MAX_RECORD_LEN: usize = 24;
+
+/// Header at the start of an input log.
+///
+/// The magic bytes, the format version, then the keymap build hash (little endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// Hash identifying the keymap build the log was captured with.
+    ///
+    /// (e.g. `smart_keymap::KEYMAP_BUILD_HASH`).
+    pub keymap_build_hash: u32,
+}
+
+impl Header {
+    /// Encodes the header.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[0..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5..9].copy_from_slice(&self.keymap_build_hash.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes the header from the start of the bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes {
+            [m0, m1, m2, m3, ..] if [*m0, *m1, *m2, *m3] != MAGIC => Err(DecodeError::BadMagic),
+            [_, _, _, _, version, ..] if *version != VERSION => {
+                Err(DecodeError::UnsupportedVersion(*version))
+            }
+            [_, _, _, _, _, h0, h1, h2, h3, ..] => Ok(Header {
+                keymap_build_hash: u32::from_le_bytes([*h0, *h1, *h2, *h3]),
+            }),
+            _ => Err(DecodeError::Truncated),
+        }
+    }
+}
+
+/// An input event, and the time since the previous input event.
+///
+/// Encoded with postcard (so the time and keymap index are varints).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    /// Time in ms since the previous record.
+    pub delta_ms: u32,
+    /// The input event.
+    pub event: input::Event,
+}
+
+impl Record {
+    /// Encodes the record.
+    pub fn to_bytes(&self) -> heapless::Vec<u8, MAX_RECORD_LEN> {
+        let mut buf = [0u8; MAX_RECORD_LEN];
+        let len = postcard::to_slice(self, &mut buf).map_or(0, |bytes| bytes.len());
+        heapless::Vec::from_slice(&buf[..len]).unwrap_or_default()
+    }
+}
+
+/// Errors when decoding an input log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The log doesn't start with [MAGIC].
+    BadMagic,
+    /// The log's format version isn't [VERSION].
+    UnsupportedVersion(u8),
+    /// The log ended in the middle of the header.
+    Truncated,
+    /// A record couldn't be decoded.
+    MalformedRecord,
+}
+
+/// Errors when encoding an input log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The buffer is too small for the encoded log.
+    BufferTooSmall,
+}
+
+/// Iterator over the [Record]s of an encoded input log.
+#[derive(Debug, Clone)]
+pub struct Records<'a>(&'a [u8]);
+
+impl Iterator for Records<'_> {
+    type Item = Result<Record, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        match postcard::take_from_bytes::<Record>(self.0) {
+            Ok((record, rest)) => {
+                self.0 = rest;
+                Some(Ok(record))
+            }
+            Err(_) => {
+                self.0 = &[];
+                Some(Err(DecodeError::MalformedRecord))
+            }
+        }
+    }
+}
+
+/// Decodes the header of the input log, and returns it with an iterator over its records.
+pub fn decode(bytes: &[u8]) -> Result<(Header, Records<'_>), DecodeError> {
+    let header = Header::from_bytes(bytes)?;
+    Ok((header, Records(&bytes[HEADER_LEN..])))
+}
+
+/// Ring buffer of the most recent `N` input events, with the time each was received.
+///
+/// Firmware can record each input event the keymap handles,
+///  then encode the log (e.g. when a misfire is noticed)
+///  and dump it over a debug channel, for replaying against the keymap.
+///
+/// When full, recording an input event discards the oldest.
+/// (So, keys may have been pressed before the first record of the log).
+#[derive(Debug)]
+pub struct InputLog<const N: usize> {
+    keymap_build_hash: u32,
+    entries: heapless::Deque<(u32, input::Event), N>,
+}
+
+impl<const N: usize> InputLog<N> {
+    /// Constructs a new, empty input log, for the keymap with the given build hash.
+    pub const fn new(keymap_build_hash: u32) -> Self {
+        Self {
+            keymap_build_hash,
+            entries: heapless::Deque::new(),
+        }
+    }
+
+    /// Records the input event, received at the given time in ms.
+    pub fn record(&mut self, time_ms: u32, event: input::Event) {
+        if self.entries.is_full() {
+            self.entries.pop_front();
+        }
+        let _ = self.entries.push_back((time_ms, event));
+    }
+
+    /// The number of recorded input events.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no input events have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all recorded input events.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The header of the log.
+    pub fn header(&self) -> Header {
+        Header {
+            keymap_build_hash: self.keymap_build_hash,
+        }
+    }
+
+    /// The recorded input events, oldest first.
+    ///
+    /// The first record has a `delta_ms` of `0`.
+    pub fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        let mut previous_time_ms = self.entries.front().map_or(0, |&(time_ms, _)| time_ms);
+        self.entries.iter().map(move |&(time_ms, event)| {
+            let delta_ms = time_ms.wrapping_sub(previous_time_ms);
+            previous_time_ms = time_ms;
+            Record { delta_ms, event }
+        })
+    }
+
+    /// Encodes the log (header, then records) into the buffer,
+    ///  returning the number of bytes written.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let header = self.header().to_bytes();
+        buf.get_mut(..HEADER_LEN)
+            .ok_or(EncodeError::BufferTooSmall)?
+            .copy_from_slice(&header);
+
+        let mut len = HEADER_LEN;
+        for record in self.records() {
+            let bytes = record.to_bytes();
+            buf.get_mut(len..len + bytes.len())
+                .ok_or(EncodeError::BufferTooSmall)?
+                .copy_from_slice(&bytes);
+            len += bytes.len();
+        }
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use crate::key;
+
+    fn header_bytes() -> [u8; HEADER_LEN] {
+        Header {
+            keymap_build_hash: 0,
+        }
+        .to_bytes()
+    }
+
+    #[test]
+    fn test_header_bytes_roundtrip() {
+        // Assemble
+        let header = Header {
+            keymap_build_hash: 0x1234_5678,
+        };
+
+        // Act
+        let bytes = header.to_bytes();
+        let actual_header = Header::from_bytes(&bytes);
+
+        // Assert
+        assert_eq!(Ok(header), actual_header);
+    }
+
+    #[test]
+    fn test_header_from_bytes_rejects_bad_magic() {
+        // Assemble
+        let mut bytes = header_bytes();
+        bytes[0] = b'X';
+
+        // Act
+        let actual = Header::from_bytes(&bytes);
+
+        // Assert
+        assert_eq!(Err(DecodeError::BadMagic), actual);
+    }
+
+    #[test]
+    fn test_header_from_bytes_rejects_other_version() {
+        // Assemble
+        let mut bytes = header_bytes();
+        bytes[4] = VERSION + 1;
+
+        // Act
+        let actual = Header::from_bytes(&bytes);
+
+        // Assert
+        assert_eq!(Err(DecodeError::UnsupportedVersion(VERSION + 1)), actual);
+    }
+
+    #[test]
+    fn test_header_from_bytes_rejects_truncated() {
+        // Assemble
+        let bytes = header_bytes();
+
+        // Act
+        let actual = Header::from_bytes(&bytes[..HEADER_LEN - 1]);
+
+        // Assert
+        assert_eq!(Err(DecodeError::Truncated), actual);
+    }
+
+    #[test]
+    fn test_record_to_bytes_is_compact() {
+        // Assemble
+        let record = Record {
+            delta_ms: 100,
+            event: input::Event::Press { keymap_index: 3 },
+        };
+
+        // Act
+        let bytes = record.to_bytes();
+
+        // Assert
+        assert_eq!(3, bytes.len());
+    }
+
+    #[test]
+    fn test_record_to_bytes_fits_largest_record() {
+        // Assemble
+        let record = Record {
+            delta_ms: u32::MAX,
+            event: input::Event::VirtualKeyPress {
+                key_output: key::KeyOutput::from_mouse_output(key::MouseOutput {
+                    pressed_buttons: u8::MAX,
+                    x: i8::MIN,
+                    y: i8::MIN,
+                    vertical_scroll: i8::MIN,
+                    horizontal_scroll: i8::MIN,
+                }),
+            },
+        };
+
+        // Act
+        let bytes = record.to_bytes();
+        let actual_records: Vec<_> = Records(&bytes).collect();
+
+        // Assert
+        assert_eq!(vec![Ok(record)], actual_records);
+    }
+
+    #[test]
+    fn test_records_reports_malformed_record() {
+        // Assemble
+        let bytes = [0x05, 0xFF];
+
+        // Act
+        let actual_records: Vec<_> = Records(&bytes).collect();
+
+        // Assert
+        assert_eq!(vec![Err(DecodeError::MalformedRecord)], actual_records);
+    }
+
+    #[test]
+    fn test_input_log_records_deltas_from_previous_event() {
+        // Assemble
+        let mut log: InputLog<4> = InputLog::new(0);
+
+        // Act
+        log.record(1000, input::Event::Press { keymap_index: 0 });
+        log.record(1050, input::Event::Press { keymap_index: 1 });
+        log.record(1250, input::Event::Release { keymap_index: 0 });
+
+        // Assert
+        let expected_records = vec![
+            Record {
+                delta_ms: 0,
+                event: input::Event::Press { keymap_index: 0 },
+            },
+            Record {
+                delta_ms: 50,
+                event: input::Event::Press { keymap_index: 1 },
+            },
+            Record {
+                delta_ms: 200,
+                event: input::Event::Release { keymap_index: 0 },
+            },
+        ];
+        let actual_records: Vec<_> = log.records().collect();
+        assert_eq!(expected_records, actual_records);
+    }
+
+    #[test]
+    fn test_input_log_discards_oldest_when_full() {
+        // Assemble
+        let mut log: InputLog<2> = InputLog::new(0);
+
+        // Act
+        log.record(0, input::Event::Press { keymap_index: 0 });
+        log.record(10, input::Event::Press { keymap_index: 1 });
+        log.record(30, input::Event::Press { keymap_index: 2 });
+
+        // Assert
+        let expected_records = vec![
+            Record {
+                delta_ms: 0,
+                event: input::Event::Press { keymap_index: 1 },
+            },
+            Record {
+                delta_ms: 20,
+                event: input::Event::Press { keymap_index: 2 },
+            },
+        ];
+        let actual_records: Vec<_> = log.records().collect();
+        assert_eq!(expected_records, actual_records);
+    }
+
+    #[test]
+    fn test_input_log_encode_decode_roundtrip() {
+        // Assemble
+        let mut log: InputLog<4> = InputLog::new(0xCAFE_F00D);
+        log.record(5, input::Event::Press { keymap_index: 300 });
+        log.record(305, input::Event::Release { keymap_index: 300 });
+        let mut buf = [0u8; 64];
+
+        // Act
+        let len = log.encode(&mut buf).unwrap();
+        let (header, records) = decode(&buf[..len]).unwrap();
+
+        // Assert
+        assert_eq!(0xCAFE_F00D, header.keymap_build_hash);
+        let expected_records: Vec<_> = log.records().map(Ok).collect();
+        let actual_records: Vec<_> = records.collect();
+        assert_eq!(expected_records, actual_records);
+    }
+
+    #[test]
+    fn test_input_log_encode_rejects_small_buffer() {
+        // Assemble
+        let mut log: InputLog<4> = InputLog::new(0);
+        log.record(0, input::Event::Press { keymap_index: 0 });
+        let mut buf = [0u8; HEADER_LEN + 1];
+
+        // Act
+        let actual = log.encode(&mut buf);
+
+        // Assert
+        assert_eq!(Err(EncodeError::BufferTooSmall), actual);
+    }
+}
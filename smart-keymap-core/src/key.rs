@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::input;
 
+/// Auto Shift keys (tap for the key, hold for the shifted key).
+pub mod auto_shift;
 /// Automation (macro) keys.
 pub mod automation;
 /// Keymap Callback keys
@@ -16,10 +18,14 @@ pub mod chorded;
 pub mod consumer;
 /// Custom keys.
 pub mod custom;
+/// Dynamic macro keys (record and play back key presses).
+pub mod dynamic_macro;
 /// History keys (Repeat / Alt-Repeat / Adaptive of last output).
 pub mod history;
 /// Key Lock (hold the next key until pressed again).
 pub mod key_lock;
+/// Key repeat keys (repeat the wrapped key while held).
+pub mod key_repeat;
 /// HID Keyboard keys.
 pub mod keyboard;
 /// Layered keys. (Layering functionality).
@@ -30,6 +36,8 @@ pub mod mod_conditioned;
 pub mod mouse;
 /// Sequence keys (QMK leader-style ordered sequences).
 pub mod sequence;
+/// Steno keys (chorded strokes looked up in a steno dictionary).
+pub mod steno;
 /// Sticky Modifier keys.
 pub mod sticky;
 /// Tap-Dance keys.
@@ -38,6 +46,8 @@ pub mod tap_dance;
 pub mod tap_hold;
 /// Tri-state keys (start / continue / interrupt; e.g. Alt-Tab swapper).
 pub mod tri_state;
+/// Unicode input keys.
+pub mod unicode;
 
 /// The maximum number of key events that are emitted by [crate::key::System] implementations.
 pub const MAX_KEY_EVENTS: usize = 4;
@@ -543,6 +553,21 @@ impl core::fmt::Debug for KeyOutput {
     }
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for KeyOutput {
+    fn format(&self, f: defmt::Formatter) {
+        match self.key_code {
+            KeyUsage::Keyboard(key_code) => defmt::write!(f, "Keyboard({=u8:#04x})", key_code),
+            KeyUsage::Consumer(usage) => defmt::write!(f, "Consumer({=u8:#04x})", usage),
+            KeyUsage::Custom(code) => defmt::write!(f, "Custom({=u8:#04x})", code),
+            KeyUsage::Mouse(_) => defmt::write!(f, "Mouse"),
+        }
+        if self.key_modifiers != KeyboardModifiers::NONE {
+            defmt::write!(f, " + modifiers {=u8:#04x}", *self.key_modifiers);
+        }
+    }
+}
+
 impl KeyOutput {
     /// A key output with no key code and no modifiers.
     pub const NO_OUTPUT: KeyOutput = KeyOutput {
@@ -883,6 +908,7 @@ impl<T> From<input::Event> for Event<T> {
 /// Prefer constructing via [`KeyEvents::event`] / [`KeyEvents::schedule_event`]
 ///  rather than building these variants by hand.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Schedule {
     /// Same-turn: pending queue, drained before returning to the caller.
     Immediate,
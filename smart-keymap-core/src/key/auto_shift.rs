@@ -0,0 +1,691 @@
+//! Auto Shift: tap a key for its usual output, hold it for the shifted output.
+//!
+//! An auto-shift key is pending until it is released (unshifted),
+//!  another key is pressed (unshifted, so fast rolls are not shifted),
+//!  or [Config::timeout] elapses (shifted).
+//!
+//! The physical key is a [`crate::key::NewPressedKey::NoOp`]; HID output is
+//!  injected by the [Context] with [`crate::input::Event::VirtualKeyPress`] /
+//!  [`crate::input::Event::VirtualKeyRelease`],
+//!  and announced as a [`keymap::KeymapEvent::ResolvedKeyOutput`]
+//!  so that Caps Word, Key Lock, etc. see the output.
+//!
+//! Keys are auto-shifted by class ([Config::classes]: letters, numbers, symbols);
+//!  individual keys can opt in or out with [Key::enabled].
+//! A key pressed while any modifier is already held
+//!  (e.g. with Caps Word active, or while holding Ctrl)
+//!  is not auto-shifted, and outputs immediately.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use serde::Deserialize;
+
+use crate::input;
+use crate::key;
+use crate::keymap;
+
+/// Reference for an auto-shift key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ref(pub Key);
+
+/// Maximum number of auto-shift outputs held at the same time.
+pub const MAX_HELD_OUTPUTS: usize = 4;
+
+/// Duration (in milliseconds) of an auto-shift output tap.
+const TAP_DURATION_MS: u16 = 1;
+
+/// An auto-shift key: a HID keyboard key code, shifted when held.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    /// The HID keyboard key code.
+    pub key_code: u8,
+    /// Per-key opt-in (`Some(true)`) or opt-out (`Some(false)`).
+    ///
+    /// When `None`, whether the key is auto-shifted depends on its [Class]
+    ///  and [Config::classes].
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+impl Key {
+    /// Constructs an auto-shift key, auto-shifted according to its [Class].
+    pub const fn new(key_code: u8) -> Self {
+        Key {
+            key_code,
+            enabled: None,
+        }
+    }
+
+    /// Constructs an auto-shift key which opts in to (or out of) auto-shift.
+    pub const fn with_enabled(key_code: u8, enabled: bool) -> Self {
+        Key {
+            key_code,
+            enabled: Some(enabled),
+        }
+    }
+
+    /// The key's unshifted output.
+    pub const fn key_output(&self) -> key::KeyOutput {
+        key::KeyOutput::from_key_code(self.key_code)
+    }
+
+    /// The key's shifted output.
+    pub const fn shifted_key_output(&self) -> key::KeyOutput {
+        key::KeyOutput::from_key_code_with_modifiers(
+            self.key_code,
+            key::KeyboardModifiers::LEFT_SHIFT,
+        )
+    }
+}
+
+/// Classes of keys which can be auto-shifted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    /// Letters `A`-`Z`.
+    Alpha,
+    /// Numbers `1`-`0` (on the number row).
+    Numeric,
+    /// Symbols: `-`, `=`, brackets, `\`, `;`, `'`, `` ` ``, `,`, `.`, `/`.
+    Symbol,
+}
+
+impl Class {
+    /// The class of the given HID keyboard key code, if any.
+    pub const fn from_key_code(key_code: u8) -> Option<Class> {
+        match key_code {
+            0x04..=0x1D => Some(Class::Alpha),
+            0x1E..=0x27 => Some(Class::Numeric),
+            0x2D..=0x38 => Some(Class::Symbol),
+            _ => None,
+        }
+    }
+}
+
+/// Which [Class]es of keys are auto-shifted.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Classes {
+    /// Whether letters are auto-shifted.
+    #[serde(default = "default_true")]
+    pub alpha: bool,
+    /// Whether numbers are auto-shifted.
+    #[serde(default = "default_true")]
+    pub numeric: bool,
+    /// Whether symbols are auto-shifted.
+    #[serde(default = "default_true")]
+    pub symbols: bool,
+}
+
+impl Classes {
+    /// All classes are auto-shifted.
+    pub const ALL: Classes = Classes {
+        alpha: true,
+        numeric: true,
+        symbols: true,
+    };
+
+    /// Whether the given class is auto-shifted.
+    pub const fn includes(&self, class: Class) -> bool {
+        match class {
+            Class::Alpha => self.alpha,
+            Class::Numeric => self.numeric,
+            Class::Symbol => self.symbols,
+        }
+    }
+}
+
+impl Default for Classes {
+    fn default() -> Self {
+        Classes::ALL
+    }
+}
+
+/// Configuration settings for auto-shift keys.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// How long (in milliseconds) a key must be held for the shifted output.
+    #[serde(default = "default_timeout")]
+    pub timeout: u16,
+
+    /// Which classes of keys are auto-shifted.
+    #[serde(default)]
+    pub classes: Classes,
+
+    /// Whether the shifted output stays pressed until the key is released
+    ///  (so the host's key repeat applies to the shifted output).
+    ///
+    /// When false, the shifted output is tapped once.
+    #[serde(default)]
+    pub repeat_on_hold: bool,
+
+    /// If an auto-shift key is pressed again within this many milliseconds
+    ///  of its previous press, immediately output the unshifted key.
+    ///
+    /// This allows "tap, then hold" to repeat the unshifted key.
+    /// `None` disables (default).
+    #[serde(default)]
+    pub quick_tap_ms: Option<u16>,
+}
+
+/// The default timeout.
+pub const DEFAULT_TIMEOUT: u16 = 175;
+
+fn default_timeout() -> u16 {
+    DEFAULT_TIMEOUT
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Default auto-shift config.
+pub const DEFAULT_CONFIG: Config = Config {
+    timeout: DEFAULT_TIMEOUT,
+    classes: Classes::ALL,
+    repeat_on_hold: false,
+    quick_tap_ms: None,
+};
+
+impl Config {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        DEFAULT_CONFIG
+    }
+
+    /// Whether the given key is auto-shifted under this config.
+    pub const fn is_auto_shifted(&self, key: &Key) -> bool {
+        match key.enabled {
+            Some(enabled) => enabled,
+            None => match Class::from_key_code(key.key_code) {
+                Some(class) => self.classes.includes(class),
+                None => false,
+            },
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+/// Auto-shift context: config, keymap context, and held outputs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Context {
+    config: Config,
+    time_ms: u32,
+    pressed_modifiers: key::KeyboardModifiers,
+    recent_presses: [(u16, u32); keymap::MAX_RECENT_PRESSES],
+    recent_press_count: u8,
+    /// Outputs held until their physical key is released.
+    held: [Option<(u16, key::KeyOutput)>; MAX_HELD_OUTPUTS],
+}
+
+impl Context {
+    /// Constructs a context from the given config.
+    pub const fn from_config(config: Config) -> Context {
+        Context {
+            config,
+            time_ms: 0,
+            pressed_modifiers: key::KeyboardModifiers::NONE,
+            recent_presses: [(0, 0); keymap::MAX_RECENT_PRESSES],
+            recent_press_count: 0,
+            held: [None; MAX_HELD_OUTPUTS],
+        }
+    }
+
+    /// Re-construct from context's [Config], forgetting held outputs.
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// Updates the context with the given keymap context.
+    pub fn update_keymap_context(
+        &mut self,
+        keymap::KeymapContext {
+            time_ms,
+            pressed_modifiers,
+            recent_presses,
+            recent_press_count,
+            ..
+        }: &keymap::KeymapContext,
+    ) {
+        self.time_ms = *time_ms;
+        self.pressed_modifiers = *pressed_modifiers;
+        self.recent_presses = *recent_presses;
+        self.recent_press_count = *recent_press_count;
+    }
+
+    fn last_press_time_ms(&self, keymap_index: u16) -> Option<u32> {
+        self.recent_presses[..self.recent_press_count as usize]
+            .iter()
+            .rev()
+            .find(|(ki, _)| *ki == keymap_index)
+            .map(|(_, t)| *t)
+    }
+
+    /// Whether a re-press of `keymap_index` falls within `quick_tap_ms`.
+    fn is_quick_tap(&self, keymap_index: u16) -> bool {
+        self.config
+            .quick_tap_ms
+            .zip(self.last_press_time_ms(keymap_index))
+            .is_some_and(|(quick_tap_ms, last_t)| {
+                self.time_ms.saturating_sub(last_t) < quick_tap_ms as u32
+            })
+    }
+
+    /// Whether a press of `key` should wait to see if it is held.
+    fn should_defer(&self, keymap_index: u16, key: &Key) -> bool {
+        self.config.is_auto_shifted(key)
+            && self.pressed_modifiers == key::KeyboardModifiers::NONE
+            && !self.is_quick_tap(keymap_index)
+    }
+
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::Output { key_output, held },
+            } => {
+                let mut pke =
+                    key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
+                        key_output,
+                    }));
+                pke.add_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                    keymap_index,
+                    key_output,
+                }));
+
+                let free_slot = self.held.iter_mut().find(|slot| slot.is_none());
+                match free_slot {
+                    Some(slot) if held => {
+                        *slot = Some((keymap_index, key_output));
+                    }
+                    _ => {
+                        pke.schedule_event(
+                            TAP_DURATION_MS,
+                            key::Event::Input(input::Event::VirtualKeyRelease { key_output }),
+                        );
+                    }
+                }
+
+                pke
+            }
+            key::Event::Input(input::Event::Release { keymap_index }) => {
+                let held_slot = self
+                    .held
+                    .iter_mut()
+                    .find(|slot| matches!(slot, Some((ki, _)) if *ki == keymap_index));
+                match held_slot {
+                    Some(slot) => match slot.take() {
+                        Some((_, key_output)) => key::KeyEvents::event(key::Event::Input(
+                            input::Event::VirtualKeyRelease { key_output },
+                        )),
+                        None => key::KeyEvents::no_events(),
+                    },
+                    None => key::KeyEvents::no_events(),
+                }
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::from_config(DEFAULT_CONFIG)
+    }
+}
+
+impl key::Context for Context {
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Auto-shift events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// The key has been held for [Config::timeout].
+    Timeout,
+    /// The key resolved: output `key_output`.
+    Output {
+        /// The (shifted or unshifted) output.
+        key_output: key::KeyOutput,
+        /// Whether the output is held until the physical key is released
+        ///  (otherwise it is tapped).
+        held: bool,
+    },
+}
+
+/// Pending key state for auto-shift keys. (Resolution depends only on the event).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingKeyState;
+
+/// Key state used by [System]. (No per-key state; output is on [Context].)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyState;
+
+/// The [key::System] implementation for auto-shift keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<R> {
+    marker: PhantomData<R>,
+}
+
+impl<R> System<R> {
+    /// Constructs a new [System].
+    pub const fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R> Default for System<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn resolve<R>(
+    keymap_index: u16,
+    key_output: key::KeyOutput,
+    held: bool,
+) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Event>) {
+    let key_event = Event::Output { key_output, held };
+    (
+        Some(key::NewPressedKey::NoOp),
+        key::KeyEvents::event(key::Event::key_event(keymap_index, key_event)),
+    )
+}
+
+impl<R: Debug> key::System<R> for System<R> {
+    type Ref = Ref;
+    type Context = Context;
+    type Event = Event;
+    type PendingKeyState = PendingKeyState;
+    type KeyState = KeyState;
+
+    fn new_pressed_key(
+        &self,
+        keymap_index: u16,
+        context: &Self::Context,
+        Ref(key): Ref,
+    ) -> (
+        key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
+        key::KeyEvents<Self::Event>,
+    ) {
+        if context.should_defer(keymap_index, &key) {
+            let timeout_ev = key::Event::key_event(keymap_index, Event::Timeout);
+            let pke = key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                context.config.timeout,
+                timeout_ev,
+            ));
+            (key::PressedKeyResult::Pending(PendingKeyState), pke)
+        } else {
+            let key_event = Event::Output {
+                key_output: key.key_output(),
+                held: true,
+            };
+            let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp);
+            let pke = key::KeyEvents::event(key::Event::key_event(keymap_index, key_event));
+            (pkr, pke)
+        }
+    }
+
+    fn update_pending_state(
+        &self,
+        _pending_state: &mut Self::PendingKeyState,
+        keymap_index: u16,
+        context: &Self::Context,
+        Ref(key): Ref,
+        event: key::Event<Self::Event>,
+    ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
+        match event {
+            // Released before the timeout: unshifted.
+            //  (The replayed release then releases the output).
+            key::Event::Input(input::Event::Release { keymap_index: ki }) if ki == keymap_index => {
+                resolve(keymap_index, key.key_output(), true)
+            }
+            // Interrupted by another key (e.g. a fast roll): unshifted.
+            key::Event::Input(input::Event::Press { keymap_index: ki }) if ki != keymap_index => {
+                resolve(keymap_index, key.key_output(), true)
+            }
+            // Held long enough: shifted.
+            key::Event::Key {
+                keymap_index: ki,
+                key_event: Event::Timeout,
+            } if ki == keymap_index => resolve(
+                keymap_index,
+                key.shifted_key_output(),
+                context.config.repeat_on_hold,
+            ),
+            _ => (None, key::KeyEvents::no_events()),
+        }
+    }
+
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        _ref: &Self::Ref,
+        _context: &Self::Context,
+        _keymap_index: u16,
+        _event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        panic!() // auto_shift has no key state
+    }
+
+    fn key_output(
+        &self,
+        _key_ref: &Self::Ref,
+        _key_state: &Self::KeyState,
+    ) -> Option<key::KeyOutput> {
+        panic!() // auto_shift has no key state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::key::System as _;
+
+    const KEYMAP_INDEX: u16 = 0;
+    const OTHER_INDEX: u16 = 1;
+    const KEY_A: Key = Key::new(0x04);
+
+    fn system() -> System<u8> {
+        System::new()
+    }
+
+    fn output_event(pke: key::KeyEvents<Event>) -> Option<(key::KeyOutput, bool)> {
+        pke.into_iter().find_map(|sch_ev| match sch_ev.event {
+            key::Event::Key {
+                key_event: Event::Output { key_output, held },
+                ..
+            } => Some((key_output, held)),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_sizeof_ref() {
+        assert_eq!(2, core::mem::size_of::<Ref>());
+    }
+
+    #[test]
+    fn test_class_from_key_code() {
+        assert_eq!(Some(Class::Alpha), Class::from_key_code(0x04));
+        assert_eq!(Some(Class::Numeric), Class::from_key_code(0x27));
+        assert_eq!(Some(Class::Symbol), Class::from_key_code(0x2D));
+        assert_eq!(None, Class::from_key_code(0x2C)); // Space
+    }
+
+    #[test]
+    fn test_per_key_enabled_overrides_classes() {
+        // Assemble
+        let config = Config {
+            classes: Classes {
+                alpha: false,
+                ..Classes::ALL
+            },
+            ..DEFAULT_CONFIG
+        };
+
+        // Act + Assert
+        assert!(!config.is_auto_shifted(&KEY_A));
+        assert!(config.is_auto_shifted(&Key::with_enabled(0x04, true)));
+        assert!(!config.is_auto_shifted(&Key::with_enabled(0x1E, false)));
+        assert!(!config.is_auto_shifted(&Key::new(0x2C)));
+    }
+
+    #[test]
+    fn test_press_is_pending() {
+        // Assemble
+        let context = Context::default();
+
+        // Act
+        let (pkr, _pke) = system().new_pressed_key(KEYMAP_INDEX, &context, Ref(KEY_A));
+
+        // Assert
+        assert!(matches!(pkr, key::PressedKeyResult::Pending(_)));
+    }
+
+    #[test]
+    fn test_press_with_modifier_held_outputs_immediately() {
+        // Assemble
+        let mut context = Context::default();
+        context.update_keymap_context(&keymap::KeymapContext {
+            pressed_modifiers: key::KeyboardModifiers::LEFT_SHIFT,
+            ..keymap::KeymapContext::new()
+        });
+
+        // Act
+        let (pkr, pke) = system().new_pressed_key(KEYMAP_INDEX, &context, Ref(KEY_A));
+
+        // Assert
+        assert_eq!(
+            key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp),
+            pkr
+        );
+        assert_eq!(Some((KEY_A.key_output(), true)), output_event(pke));
+    }
+
+    #[test]
+    fn test_release_resolves_unshifted() {
+        // Assemble
+        let context = Context::default();
+
+        // Act
+        let (npk, pke) = system().update_pending_state(
+            &mut PendingKeyState,
+            KEYMAP_INDEX,
+            &context,
+            Ref(KEY_A),
+            key::Event::Input(input::Event::Release {
+                keymap_index: KEYMAP_INDEX,
+            }),
+        );
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::NoOp), npk);
+        assert_eq!(Some((KEY_A.key_output(), true)), output_event(pke));
+    }
+
+    #[test]
+    fn test_other_press_resolves_unshifted() {
+        // Assemble
+        let context = Context::default();
+
+        // Act
+        let (npk, pke) = system().update_pending_state(
+            &mut PendingKeyState,
+            KEYMAP_INDEX,
+            &context,
+            Ref(KEY_A),
+            key::Event::Input(input::Event::Press {
+                keymap_index: OTHER_INDEX,
+            }),
+        );
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::NoOp), npk);
+        assert_eq!(Some((KEY_A.key_output(), true)), output_event(pke));
+    }
+
+    #[test]
+    fn test_timeout_resolves_shifted_tap() {
+        // Assemble
+        let context = Context::default();
+
+        // Act
+        let (npk, pke) = system().update_pending_state(
+            &mut PendingKeyState,
+            KEYMAP_INDEX,
+            &context,
+            Ref(KEY_A),
+            key::Event::key_event(KEYMAP_INDEX, Event::Timeout),
+        );
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::NoOp), npk);
+        assert_eq!(Some((KEY_A.shifted_key_output(), false)), output_event(pke));
+    }
+
+    #[test]
+    fn test_timeout_with_repeat_on_hold_resolves_shifted_hold() {
+        // Assemble
+        let context = Context::from_config(Config {
+            repeat_on_hold: true,
+            ..DEFAULT_CONFIG
+        });
+
+        // Act
+        let (_npk, pke) = system().update_pending_state(
+            &mut PendingKeyState,
+            KEYMAP_INDEX,
+            &context,
+            Ref(KEY_A),
+            key::Event::key_event(KEYMAP_INDEX, Event::Timeout),
+        );
+
+        // Assert
+        assert_eq!(Some((KEY_A.shifted_key_output(), true)), output_event(pke));
+    }
+
+    #[test]
+    fn test_held_output_released_on_physical_release() {
+        // Assemble
+        let mut context = Context::default();
+        let key_output = KEY_A.key_output();
+        let _ = context.handle_event(key::Event::key_event(
+            KEYMAP_INDEX,
+            Event::Output {
+                key_output,
+                held: true,
+            },
+        ));
+
+        // Act
+        let pke = context.handle_event(key::Event::Input(input::Event::Release {
+            keymap_index: KEYMAP_INDEX,
+        }));
+
+        // Assert
+        let events: heapless::Vec<_, { key::MAX_KEY_EVENTS }> =
+            pke.into_iter().map(|sch_ev| sch_ev.event).collect();
+        assert_eq!(
+            &[key::Event::Input(input::Event::VirtualKeyRelease {
+                key_output
+            })],
+            events.as_slice()
+        );
+    }
+}
@@ -361,17 +361,30 @@ pub fn key_events_for<const INSTRUCTION_COUNT: usize>(
         }
     };
 
+    instruction_key_events(instruction, config.instruction_duration, next_key_ev)
+}
+
+/// Key events which perform the instruction,
+///  then emit `next_key_ev` once the instruction has finished.
+///
+/// Each instruction takes `instruction_duration` ticks,
+///  except for [Instruction::Wait].
+pub fn instruction_key_events<E: Copy + Debug>(
+    instruction: Instruction,
+    instruction_duration: u16,
+    next_key_ev: key::Event<E>,
+) -> key::KeyEvents<E> {
     match instruction {
         Instruction::NoOp => {
             let mut pke = key::KeyEvents::no_events();
-            pke.schedule_event(config.instruction_duration, next_key_ev);
+            pke.schedule_event(instruction_duration, next_key_ev);
             pke
         }
         Instruction::Press(key_output) => {
             let mut pke = key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
                 key_output,
             }));
-            pke.schedule_event(config.instruction_duration, next_key_ev);
+            pke.schedule_event(instruction_duration, next_key_ev);
             pke
         }
         Instruction::Release(key_output) => {
@@ -379,7 +392,7 @@ pub fn key_events_for<const INSTRUCTION_COUNT: usize>(
                 key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyRelease {
                     key_output,
                 }));
-            pke.schedule_event(config.instruction_duration, next_key_ev);
+            pke.schedule_event(instruction_duration, next_key_ev);
             pke
         }
         Instruction::Tap(key_output) => {
@@ -387,10 +400,10 @@ pub fn key_events_for<const INSTRUCTION_COUNT: usize>(
                 key_output,
             }));
             pke.schedule_event(
-                config.instruction_duration,
+                instruction_duration,
                 key::Event::Input(input::Event::VirtualKeyRelease { key_output }),
             );
-            pke.schedule_event(config.instruction_duration, next_key_ev);
+            pke.schedule_event(instruction_duration, next_key_ev);
             pke
         }
         Instruction::Wait(ticks) => {
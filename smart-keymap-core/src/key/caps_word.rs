@@ -6,15 +6,207 @@ use serde::Deserialize;
 use crate::input;
 use crate::key;
 use crate::keymap;
+use crate::split;
 
 /// Reference for a caps word key.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Ref(pub Key);
 
+/// The capacity of the list a [KeyCodeSet] is deserialized from.
+pub const MAX_KEY_CODE_LIST_LEN: usize = 256;
+
+/// A set of HID keyboard usage codes.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "heapless::Vec<u8, MAX_KEY_CODE_LIST_LEN>")]
+pub struct KeyCodeSet([u32; 8]);
+
+impl KeyCodeSet {
+    /// The set with no key codes.
+    pub const EMPTY: KeyCodeSet = KeyCodeSet([0; 8]);
+
+    /// Constructs a set with the given key codes.
+    pub const fn from_key_codes(key_codes: &[u8]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut i = 0;
+        while i < key_codes.len() {
+            set = set.with_key_code(key_codes[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Constructs a set with the key codes from `first` to `last` (inclusive).
+    pub const fn from_range(first: u8, last: u8) -> Self {
+        let mut set = Self::EMPTY;
+        let mut key_code = first;
+        while key_code <= last {
+            set = set.with_key_code(key_code);
+            if key_code == u8::MAX {
+                break;
+            }
+            key_code += 1;
+        }
+        set
+    }
+
+    /// This set, with the given key code added.
+    pub const fn with_key_code(self, key_code: u8) -> Self {
+        let KeyCodeSet(mut words) = self;
+        words[(key_code / 32) as usize] |= 1 << (key_code % 32);
+        KeyCodeSet(words)
+    }
+
+    /// The union of this set and the other set.
+    pub const fn union(self, other: KeyCodeSet) -> Self {
+        let KeyCodeSet(mut words) = self;
+        let mut i = 0;
+        while i < words.len() {
+            words[i] |= other.0[i];
+            i += 1;
+        }
+        KeyCodeSet(words)
+    }
+
+    /// Whether the set contains the key code.
+    pub const fn contains(&self, key_code: u8) -> bool {
+        self.0[(key_code / 32) as usize] & (1 << (key_code % 32)) != 0
+    }
+}
+
+impl From<heapless::Vec<u8, MAX_KEY_CODE_LIST_LEN>> for KeyCodeSet {
+    fn from(key_codes: heapless::Vec<u8, MAX_KEY_CODE_LIST_LEN>) -> Self {
+        Self::from_key_codes(&key_codes)
+    }
+}
+
+/// How Caps Word shifts keys.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Holds a virtual Left Shift while typing [Config::shift_keys].
+    #[default]
+    Shift,
+    /// Toggles the host's Caps Lock while Caps Word is active.
+    ///
+    /// Caps Lock is only tapped if the host's Caps Lock LED
+    ///  (see [keymap::Keymap::set_host_leds]) isn't already in the wanted state.
+    ///
+    /// Letters are left to Caps Lock;
+    ///  other [Config::shift_keys] (e.g. `-`) still use a virtual Left Shift.
+    CapsLock,
+}
+
+/// The HID usage code for Left Shift.
+const LEFT_SHIFT: u8 = 0xE1;
+
+/// The HID usage code for Right Shift.
+const RIGHT_SHIFT: u8 = 0xE5;
+
+/// The HID usage code for Caps Lock.
+const CAPS_LOCK: u8 = 0x39;
+
+/// How long (in ms) Caps Lock is held when toggled.
+pub const CAPS_LOCK_TAP_DURATION: u16 = 10;
+
+/// The default [Config::shift_keys]: A-Z, and `-` (typed as `_`).
+pub const DEFAULT_SHIFT_KEYS: KeyCodeSet = KeyCodeSet::from_range(0x04, 0x1D).with_key_code(0x2D);
+
+/// The default [Config::continue_keys]: 0-9, Backspace, Delete.
+pub const DEFAULT_CONTINUE_KEYS: KeyCodeSet = KeyCodeSet::from_range(0x1E, 0x27)
+    .with_key_code(0x2A)
+    .with_key_code(0x4C);
+
+/// Caps Word configuration.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Keys which continue the word, and are typed shifted.
+    ///
+    /// Matched by key code (whether or not the key is already shifted).
+    #[serde(default = "default_shift_keys")]
+    pub shift_keys: KeyCodeSet,
+
+    /// Keys which continue the word, typed as they are (when not shifted).
+    #[serde(default = "default_continue_keys")]
+    pub continue_keys: KeyCodeSet,
+
+    /// Keys which continue the word, typed as they are (when shifted).
+    ///
+    /// e.g. `_` (shifted `-`), if `-` isn't one of the [Config::shift_keys].
+    #[serde(default = "default_shifted_continue_keys")]
+    pub shifted_continue_keys: KeyCodeSet,
+
+    /// Caps Word is disabled if no key is typed for this long (in ms).
+    #[serde(default)]
+    pub idle_timeout: Option<u16>,
+
+    /// How Caps Word shifts keys.
+    #[serde(default)]
+    pub mode: Mode,
+}
+
+fn default_shift_keys() -> KeyCodeSet {
+    DEFAULT_SHIFT_KEYS
+}
+
+fn default_continue_keys() -> KeyCodeSet {
+    DEFAULT_CONTINUE_KEYS
+}
+
+fn default_shifted_continue_keys() -> KeyCodeSet {
+    KeyCodeSet::EMPTY
+}
+
+impl Config {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        Self {
+            shift_keys: DEFAULT_SHIFT_KEYS,
+            continue_keys: DEFAULT_CONTINUE_KEYS,
+            shifted_continue_keys: KeyCodeSet::EMPTY,
+            idle_timeout: None,
+            mode: Mode::Shift,
+        }
+    }
+
+    /// Whether the key output continues the word.
+    pub fn continues_word(&self, key_code: u8, is_shifted: bool) -> bool {
+        let continue_keys = if is_shifted {
+            &self.shifted_continue_keys
+        } else {
+            &self.continue_keys
+        };
+        self.shift_keys.contains(key_code) || continue_keys.contains(key_code)
+    }
+
+    /// Whether Caps Word types the key code with a virtual shift.
+    pub fn shifts(&self, key_code: u8) -> bool {
+        let is_letter = (0x04..=0x1D).contains(&key_code);
+        self.shift_keys.contains(key_code) && !(self.mode == Mode::CapsLock && is_letter)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Caps Word context.
 #[derive(Debug, Clone, Copy)]
 pub struct Context {
+    config: Config,
     is_active: bool,
+    /// Whether the virtual Left Shift is pressed.
+    is_shifted: bool,
+    /// Whether the host reports Caps Lock on.
+    host_caps_lock: bool,
+    /// Whether Caps Word tapped Caps Lock on,
+    ///  and the host hasn't (yet) reported Caps Lock on.
+    ///
+    /// So Caps Lock is still tapped off if the host doesn't report its LEDs.
+    caps_lock_unconfirmed: bool,
+    /// Incremented each time an idle timeout is scheduled;
+    ///  an [Event::IdleTimeout] is stale if its generation differs.
+    generation: u8,
 }
 
 impl Default for Context {
@@ -24,14 +216,107 @@ impl Default for Context {
 }
 
 impl Context {
-    /// Constructs a new [Context].
+    /// Constructs a new [Context] with the default [Config].
     pub const fn new() -> Self {
-        Context { is_active: false }
+        Self::from_config(Config::new())
+    }
+
+    /// Constructs a new [Context] with the given [Config].
+    pub const fn from_config(config: Config) -> Self {
+        Context {
+            config,
+            is_active: false,
+            is_shifted: false,
+            host_caps_lock: false,
+            caps_lock_unconfirmed: false,
+            generation: 0,
+        }
     }
 
     /// Clear caps-word active state.
     pub fn reset(&mut self) {
-        *self = Self::new();
+        *self = Self::from_config(self.config);
+    }
+
+    /// Updates the context with the host's Caps Lock state.
+    pub fn update_keymap_context(
+        &mut self,
+        keymap::KeymapContext { host_leds, .. }: &keymap::KeymapContext,
+    ) {
+        self.host_caps_lock = host_leds.caps_lock();
+        if self.host_caps_lock {
+            self.caps_lock_unconfirmed = false;
+        }
+    }
+
+    /// Presses or releases the virtual Left Shift.
+    fn set_shifted(&mut self, is_shifted: bool) -> key::KeyEvents<Event> {
+        if self.is_shifted == is_shifted {
+            key::KeyEvents::no_events()
+        } else {
+            self.is_shifted = is_shifted;
+
+            let key_output = key::KeyOutput::from_key_code(LEFT_SHIFT);
+            let vk_ev = if is_shifted {
+                input::Event::VirtualKeyPress { key_output }
+            } else {
+                input::Event::VirtualKeyRelease { key_output }
+            };
+            key::KeyEvents::event(key::Event::Input(vk_ev))
+        }
+    }
+
+    fn tap_caps_lock() -> key::KeyEvents<Event> {
+        let key_output = key::KeyOutput::from_key_code(CAPS_LOCK);
+        let mut pke = key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output,
+        }));
+        pke.schedule_event(
+            CAPS_LOCK_TAP_DURATION,
+            key::Event::Input(input::Event::VirtualKeyRelease { key_output }),
+        );
+        pke
+    }
+
+    fn schedule_idle_timeout(&mut self) -> key::KeyEvents<Event> {
+        match self.config.idle_timeout {
+            Some(idle_timeout) => {
+                self.generation = self.generation.wrapping_add(1);
+                let generation = self.generation;
+                key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                    idle_timeout,
+                    key::Event::key_event(0, Event::IdleTimeout { generation }),
+                ))
+            }
+            None => key::KeyEvents::no_events(),
+        }
+    }
+
+    fn enable(&mut self) -> key::KeyEvents<Event> {
+        self.is_active = true;
+
+        let mut pke = match self.config.mode {
+            Mode::Shift => self.set_shifted(true),
+            Mode::CapsLock if self.host_caps_lock => key::KeyEvents::no_events(),
+            Mode::CapsLock => {
+                self.caps_lock_unconfirmed = true;
+                Self::tap_caps_lock()
+            }
+        };
+        pke.extend(self.schedule_idle_timeout());
+        pke
+    }
+
+    fn disable(&mut self) -> key::KeyEvents<Event> {
+        self.is_active = false;
+
+        let mut pke = self.set_shifted(false);
+        if self.config.mode == Mode::CapsLock && (self.host_caps_lock || self.caps_lock_unconfirmed)
+        {
+            pke.extend(Self::tap_caps_lock());
+        }
+        self.caps_lock_unconfirmed = false;
+        pke
     }
 
     /// Updates the context with the given event.
@@ -45,57 +330,34 @@ impl Context {
                     },
                 ..
             }) if self.is_active => {
-                // CapsWord is deactivated for key presses other than:
-                //   - A-Z
-                //   - 0-9
-                //   - Backspace, Delete
-                //   - Minus, Underscore
+                // CapsWord is deactivated for key presses other than
+                //  the configured shift keys and continue keys
+                //  (by default: A-Z, 0-9, Backspace, Delete, Minus, Underscore),
+                //  and modifiers.
                 let is_shifted = key_modifiers.has_modifiers(
                     &key::KeyboardModifiers::LEFT_SHIFT.union(&key::KeyboardModifiers::RIGHT_SHIFT),
                 );
-                let exit_caps_word = match key_code {
-                    0x04..=0x1D => false,                // A-Z
-                    0x1E..=0x27 if !is_shifted => false, // 0-9
-                    0x2A => false,                       // Backspace
-                    0x2D => false,                       // `-` minus
-                    0x4C => false,                       // Delete
-                    0xE1 => false,                       // Left Shift
-                    0xE5 => false,                       // Right Shift
-                    0x00 => false,                       // No key code (modifier)
-                    _ => true,
-                };
-
-                if exit_caps_word {
-                    self.is_active = false;
-
-                    let key_code = 0xE1;
-                    let vk_ev = input::Event::VirtualKeyRelease {
-                        key_output: key::KeyOutput::from_key_code(key_code),
-                    };
-                    key::KeyEvents::event(key::Event::Input(vk_ev))
-                } else {
-                    key::KeyEvents::no_events()
+                match key_code {
+                    // No key code (modifier), or Shift.
+                    0x00 | LEFT_SHIFT | RIGHT_SHIFT => key::KeyEvents::no_events(),
+                    _ if self.config.continues_word(key_code, is_shifted) => {
+                        let mut pke = self.set_shifted(self.config.shifts(key_code));
+                        pke.extend(self.schedule_idle_timeout());
+                        pke
+                    }
+                    _ => self.disable(),
                 }
             }
             key::Event::Key { key_event, .. } => match key_event {
-                Event::EnableCapsWord => {
-                    self.is_active = true;
-
-                    let key_code = 0xE1;
-                    let vk_ev = input::Event::VirtualKeyPress {
-                        key_output: key::KeyOutput::from_key_code(key_code),
-                    };
-                    key::KeyEvents::event(key::Event::Input(vk_ev))
-                }
-                Event::DisableCapsWord => {
-                    self.is_active = false;
-
-                    let key_code = 0xE1;
-                    let vk_ev = input::Event::VirtualKeyRelease {
-                        key_output: key::KeyOutput::from_key_code(key_code),
-                    };
-                    key::KeyEvents::event(key::Event::Input(vk_ev))
+                Event::EnableCapsWord => self.enable(),
+                Event::DisableCapsWord => self.disable(),
+                // Only the most recently scheduled timeout disables Caps Word.
+                Event::IdleTimeout { generation }
+                    if self.is_active && generation == self.generation =>
+                {
+                    self.disable()
                 }
+                Event::IdleTimeout { .. } => key::KeyEvents::no_events(),
             },
             _ => key::KeyEvents::no_events(),
         }
@@ -114,6 +376,12 @@ impl key::Context for Context {
     }
 }
 
+impl keymap::SplitStatusContext for Context {
+    fn export_split_status(&self, status: &mut split::Status) {
+        status.caps_word_active = self.is_active;
+    }
+}
+
 /// Caps Word events.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Event {
@@ -121,6 +389,11 @@ pub enum Event {
     EnableCapsWord,
     /// Disables Caps Word.
     DisableCapsWord,
+    /// Disables Caps Word, if no key has been typed since this was scheduled.
+    IdleTimeout {
+        /// The [Context] generation when this was scheduled.
+        generation: u8,
+    },
 }
 
 /// A key for HID Keyboard usage codes.
@@ -238,6 +511,8 @@ impl<R: Debug> key::System<R> for System<R> {
 mod tests {
     use super::*;
 
+    use key::ScheduledEvent;
+
     #[test]
     fn test_sizeof_ref() {
         assert_eq!(0, core::mem::size_of::<Ref>());
@@ -245,6 +520,187 @@ mod tests {
 
     #[test]
     fn test_sizeof_event() {
-        assert_eq!(1, core::mem::size_of::<Event>());
+        assert_eq!(2, core::mem::size_of::<Event>());
+    }
+
+    fn resolved(key_output: key::KeyOutput) -> key::Event<Event> {
+        key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index: 1,
+            key_output,
+        })
+    }
+
+    fn enable() -> key::Event<Event> {
+        key::Event::key_event(0, Event::EnableCapsWord)
+    }
+
+    fn input_events(pke: key::KeyEvents<Event>) -> heapless::Vec<input::Event, 4> {
+        pke.into_iter()
+            .filter_map(|ScheduledEvent { event, .. }| match event {
+                key::Event::Input(ev) => Some(ev),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const SHIFT: key::KeyOutput = key::KeyOutput::from_key_code(LEFT_SHIFT);
+
+    #[test]
+    fn test_key_code_set_contains() {
+        // Assemble
+        let first = KeyCodeSet::from_range(0x04, 0x1D);
+        let second = KeyCodeSet::from_key_codes(&[0xE1]);
+
+        // Act
+        let set = first.union(second);
+
+        // Assert
+        assert!(set.contains(0x04));
+        assert!(set.contains(0x1D));
+        assert!(set.contains(0xE1));
+        assert!(!set.contains(0x1E));
+        assert!(!set.contains(0x00));
+    }
+
+    #[test]
+    fn test_default_config_continues_word() {
+        // Assemble
+        let config = Config::default();
+
+        // Act
+        let continues_a = config.continues_word(0x04, false);
+        let continues_underscore = config.continues_word(0x2D, true);
+        let continues_0 = config.continues_word(0x27, false);
+        let continues_close_paren = config.continues_word(0x27, true);
+        let continues_space = config.continues_word(0x2C, false);
+
+        // Assert
+        assert!(continues_a);
+        assert!(continues_underscore);
+        assert!(continues_0);
+        assert!(!continues_close_paren);
+        assert!(!continues_space);
+    }
+
+    #[test]
+    fn test_shifted_continue_key_continues_word() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            shift_keys: KeyCodeSet::from_range(0x04, 0x1D),
+            shifted_continue_keys: KeyCodeSet::from_key_codes(&[0x2D]),
+            ..Config::new()
+        });
+        context.handle_event(enable());
+
+        // Act
+        let underscore =
+            key::KeyOutput::from_key_code_with_modifiers(0x2D, key::KeyboardModifiers::LEFT_SHIFT);
+        let pke_underscore = context.handle_event(resolved(underscore));
+        let pke_minus = context.handle_event(resolved(key::KeyOutput::from_key_code(0x2D)));
+
+        // Assert
+        assert_eq!(
+            &[input::Event::VirtualKeyRelease { key_output: SHIFT }],
+            input_events(pke_underscore).as_slice()
+        );
+        assert!(input_events(pke_minus).is_empty());
+        assert!(!context.is_active);
+    }
+
+    #[test]
+    fn test_caps_lock_mode_taps_caps_lock() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            mode: Mode::CapsLock,
+            ..Config::new()
+        });
+        let caps_lock = key::KeyOutput::from_key_code(CAPS_LOCK);
+
+        // Act
+        let pke_enable = context.handle_event(enable());
+        let pke_letter = context.handle_event(resolved(key::KeyOutput::from_key_code(0x04)));
+        let pke_space = context.handle_event(resolved(key::KeyOutput::from_key_code(0x2C)));
+
+        // Assert
+        let expected_tap = [
+            input::Event::VirtualKeyPress {
+                key_output: caps_lock,
+            },
+            input::Event::VirtualKeyRelease {
+                key_output: caps_lock,
+            },
+        ];
+        assert_eq!(&expected_tap, input_events(pke_enable).as_slice());
+        assert!(input_events(pke_letter).is_empty());
+        assert_eq!(&expected_tap, input_events(pke_space).as_slice());
+    }
+
+    #[test]
+    fn test_idle_timeout_disables_after_last_key() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            idle_timeout: Some(1000),
+            ..Config::new()
+        });
+        context.handle_event(enable());
+        context.handle_event(resolved(key::KeyOutput::from_key_code(0x04)));
+        let stale_timeout = key::Event::key_event(0, Event::IdleTimeout { generation: 1 });
+        let last_timeout = key::Event::key_event(0, Event::IdleTimeout { generation: 2 });
+
+        // Act
+        context.handle_event(stale_timeout);
+        let is_active_after_stale_timeout = context.is_active;
+        let pke = context.handle_event(last_timeout);
+
+        // Assert
+        assert!(is_active_after_stale_timeout);
+        assert!(!context.is_active);
+        assert_eq!(
+            &[input::Event::VirtualKeyRelease { key_output: SHIFT }],
+            input_events(pke).as_slice()
+        );
+    }
+
+    fn host_leds_context(host_leds: u8) -> keymap::KeymapContext {
+        keymap::KeymapContext {
+            host_leds: keymap::HostLeds::from_byte(host_leds),
+            ..keymap::KeymapContext::new()
+        }
+    }
+
+    #[test]
+    fn test_caps_lock_mode_skips_tap_when_host_caps_lock_already_on() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            mode: Mode::CapsLock,
+            ..Config::new()
+        });
+        context.update_keymap_context(&host_leds_context(keymap::HostLeds::CAPS_LOCK_U8));
+
+        // Act
+        let pke_enable = context.handle_event(enable());
+
+        // Assert
+        assert!(context.is_active);
+        assert!(input_events(pke_enable).is_empty());
+    }
+
+    #[test]
+    fn test_caps_lock_mode_skips_tap_when_host_caps_lock_turned_off() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            mode: Mode::CapsLock,
+            ..Config::new()
+        });
+        context.handle_event(enable());
+        context.update_keymap_context(&host_leds_context(keymap::HostLeds::CAPS_LOCK_U8));
+        context.update_keymap_context(&host_leds_context(0));
+
+        // Act
+        let pke_space = context.handle_event(resolved(key::KeyOutput::from_key_code(0x2C)));
+
+        // Assert
+        assert!(!context.is_active);
+        assert!(input_events(pke_space).is_empty());
     }
 }
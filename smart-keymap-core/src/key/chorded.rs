@@ -4,7 +4,7 @@ use core::ops::Index;
 
 use serde::Deserialize;
 
-use crate::{input, key, keymap, slice::Slice};
+use crate::{input, key, key::layered::LayerBitset, keymap, slice::Slice};
 
 /// Reference for a chorded key.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
@@ -60,6 +60,73 @@ impl<const MAX_CHORD_SIZE: usize> From<heapless::Vec<u16, MAX_CHORD_SIZE>>
     }
 }
 
+/// Which release of the chord's keys releases the chord's key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordRelease {
+    /// The chord is held by the key press which completed the chord.
+    Completing,
+    /// The chord is released when any of its keys is released.
+    Any,
+    /// The chord is held until all of its keys are released.
+    All,
+}
+
+/// Per-chord overrides of the [Config].
+///
+/// Chords without an entry in [Config::chord_configs] use [ChordConfig::new].
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ChordConfig {
+    /// Overrides [Config::timeout] for this chord.
+    ///
+    /// A pending chorded key waits for the longest timeout
+    ///  of the chords it could still resolve to.
+    #[serde(default)]
+    pub timeout: Option<u16>,
+
+    /// Overrides [Config::required_idle_time] for this chord.
+    #[serde(default)]
+    pub required_idle_time: Option<u16>,
+
+    /// All of the chord's keys must be pressed within this many milliseconds
+    ///  of the first key press.
+    ///
+    /// Otherwise, the chord can no longer be satisfied by the pending key.
+    #[serde(default)]
+    pub press_within: Option<u16>,
+
+    /// Which release of the chord's keys releases the chord's key.
+    #[serde(default = "default_release")]
+    pub release: ChordRelease,
+
+    /// When set, the chord is only active when the highest active layer
+    ///  is in this bitset (bit `i` = layer `i`; the base layer is `0`).
+    #[serde(default)]
+    pub layers: Option<LayerBitset>,
+}
+
+const fn default_release() -> ChordRelease {
+    ChordRelease::Completing
+}
+
+impl ChordConfig {
+    /// Constructs a new [ChordConfig] with no overrides.
+    pub const fn new() -> Self {
+        Self {
+            timeout: None,
+            required_idle_time: None,
+            press_within: None,
+            release: ChordRelease::Completing,
+            layers: None,
+        }
+    }
+}
+
+impl Default for ChordConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Chord definitions.
 #[derive(Deserialize, Clone, Copy, PartialEq)]
 pub struct Config<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> {
@@ -82,6 +149,10 @@ pub struct Config<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> {
     /// This reduces disruption from unexpected chord resolutions
     ///  when typing quickly.
     pub required_idle_time: Option<u16>,
+
+    /// Per-chord overrides, indexed the same as [Self::chords].
+    #[serde(default)]
+    pub chord_configs: Slice<ChordConfig, MAX_CHORDS>,
 }
 
 impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> core::fmt::Debug
@@ -92,6 +163,7 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> core::fmt::Debug
             .field("timeout", &self.timeout)
             .field("chords", &self.chords.as_slice())
             .field("required_idle_time", &self.required_idle_time)
+            .field("chord_configs", &self.chord_configs.as_slice())
             .finish()
     }
 }
@@ -110,8 +182,17 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> Config<MAX_CHORDS, MA
             timeout: DEFAULT_TIMEOUT,
             chords: Slice::from_slice(&[]),
             required_idle_time: None,
+            chord_configs: Slice::from_slice(&[]),
         }
     }
+
+    /// The overrides for the given chord.
+    pub fn chord_config(&self, chord_id: usize) -> ChordConfig {
+        self.chord_configs
+            .get(chord_id)
+            .copied()
+            .unwrap_or(ChordConfig::new())
+    }
 }
 
 impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize> Default
@@ -191,10 +272,14 @@ pub struct Context<
     pressed_indices: [Option<u16>; MAX_PRESSED_INDICES],
     pressed_chords: [bool; MAX_CHORDS],
     idle_time_ms: u32,
+    time_ms: u32,
+    active_layers: u32,
     ignore_idle_time: bool,
     latest_resolved_chord: Option<ChordId>,
     /// Index whose press completed the latest chord; nests the chord `Ref`.
     activating_index: Option<u16>,
+    /// For each pressed chord, the index whose press completed the chord.
+    chord_activators: [Option<u16>; MAX_CHORDS],
 }
 
 impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_INDICES: usize> Debug
@@ -216,9 +301,12 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
                 },
             )
             .field("idle_time_ms", &self.idle_time_ms)
+            .field("time_ms", &self.time_ms)
+            .field("active_layers", &self.active_layers)
             .field("ignore_idle_time", &self.ignore_idle_time)
             .field("latest_resolved_chord", &self.latest_resolved_chord)
             .field("activating_index", &self.activating_index)
+            .field("chord_activators", &self.chord_activators)
             .finish()
     }
 }
@@ -234,9 +322,12 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
             pressed_indices,
             pressed_chords: [false; MAX_CHORDS],
             idle_time_ms: 0,
+            time_ms: 0,
+            active_layers: 0,
             ignore_idle_time: false,
             latest_resolved_chord: None,
             activating_index: None,
+            chord_activators: [None; MAX_CHORDS],
         }
     }
 
@@ -248,9 +339,16 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
     /// Updates the context with the given keymap context.
     pub fn update_keymap_context(
         &mut self,
-        keymap::KeymapContext { idle_time_ms, .. }: &keymap::KeymapContext,
+        keymap::KeymapContext {
+            idle_time_ms,
+            time_ms,
+            active_layers,
+            ..
+        }: &keymap::KeymapContext,
     ) {
         self.idle_time_ms = *idle_time_ms;
+        self.time_ms = *time_ms;
+        self.active_layers = *active_layers;
     }
 
     /// Whether `keymap_index` completed the latest resolved chord.
@@ -258,11 +356,83 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
         self.activating_index == Some(keymap_index)
     }
 
-    fn sufficient_idle_time(&self) -> bool {
+    fn highest_active_layer(&self) -> usize {
+        (u32::BITS - self.active_layers.leading_zeros()).saturating_sub(1) as usize
+    }
+
+    /// Whether the chord can be pressed, given the idle time and active layers.
+    fn chord_is_available(&self, chord_id: usize) -> bool {
+        let chord_config = self.config.chord_config(chord_id);
+
+        let required_idle_time = chord_config
+            .required_idle_time
+            .or(self.config.required_idle_time)
+            .unwrap_or(0);
         let sufficient_idle_time =
-            self.idle_time_ms >= self.config.required_idle_time.unwrap_or(0) as u32;
+            self.ignore_idle_time || self.idle_time_ms >= required_idle_time as u32;
+
+        let active_on_layer = match chord_config.layers {
+            Some(layers) => layers.contains(self.highest_active_layer()),
+            None => true,
+        };
+
+        sufficient_idle_time && active_on_layer
+    }
+
+    fn chord_timeout(&self, chord_id: usize) -> u16 {
+        self.config
+            .chord_config(chord_id)
+            .timeout
+            .unwrap_or(self.config.timeout)
+    }
+
+    fn is_pressed_index(&self, index: u16) -> bool {
+        self.pressed_indices
+            .binary_search_by_key(&index, |&k| k.unwrap_or(u16::MAX))
+            .is_ok()
+    }
+
+    /// Releases the chords' keys, for chords which release other than by their completing key.
+    fn release_chords_with_index(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        let mut pke = key::KeyEvents::no_events();
+
+        for (chord_id, chord) in self.config.chords.iter().enumerate() {
+            if !chord.has_index(keymap_index) {
+                continue;
+            }
+            let Some(activator) = self.chord_activators[chord_id] else {
+                continue;
+            };
+
+            let release_activator = match self.config.chord_config(chord_id).release {
+                ChordRelease::Completing => {
+                    if keymap_index == activator {
+                        self.chord_activators[chord_id] = None;
+                    }
+                    false
+                }
+                ChordRelease::Any => {
+                    self.chord_activators[chord_id] = None;
+                    keymap_index != activator
+                }
+                ChordRelease::All => {
+                    // The completing key's release was deferred.
+                    let all_released = chord.as_slice().iter().all(|&i| !self.is_pressed_index(i));
+                    if all_released {
+                        self.chord_activators[chord_id] = None;
+                    }
+                    all_released
+                }
+            };
 
-        sufficient_idle_time || self.ignore_idle_time
+            if release_activator {
+                pke.add_event(key::Event::Input(input::Event::Release {
+                    keymap_index: activator,
+                }));
+            }
+        }
+
+        pke
     }
 
     fn pressed_chord_with_index(&self, keymap_index: u16) -> Option<ChordState<MAX_CHORD_SIZE>> {
@@ -399,7 +569,7 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
     }
 
     /// Updates the context for the given key event.
-    fn handle_event(&mut self, event: key::Event<Event>) {
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
         match event {
             key::Event::Input(input::Event::Press { keymap_index }) => {
                 self.press_index(keymap_index);
@@ -424,6 +594,7 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
                         self.latest_resolved_chord = None;
                     }
                 }
+                key::KeyEvents::no_events()
             }
             key::Event::Input(input::Event::Release { keymap_index }) => {
                 self.release_index(keymap_index);
@@ -440,6 +611,8 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
                         }
                     });
                 self.activating_index = None;
+
+                self.release_chords_with_index(keymap_index)
             }
             key::Event::Key {
                 keymap_index: _,
@@ -447,14 +620,32 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
             } => {
                 self.pressed_chords[chord_id as usize] = true;
                 self.latest_resolved_chord = Some(chord_id);
+                key::KeyEvents::no_events()
             }
             key::Event::Key {
                 key_event: Event::ChordActivated { keymap_index },
                 ..
             } => {
                 self.activating_index = Some(keymap_index);
+
+                match self.latest_resolved_chord {
+                    Some(chord_id) => {
+                        let chord_id = chord_id as usize;
+                        self.chord_activators[chord_id] = Some(keymap_index);
+
+                        if self.config.chord_config(chord_id).release == ChordRelease::All {
+                            // Hold the chord's key until all the chord's keys are released.
+                            key::KeyEvents::event(key::Event::Keymap(
+                                keymap::KeymapEvent::DeferRelease { keymap_index },
+                            ))
+                        } else {
+                            key::KeyEvents::no_events()
+                        }
+                    }
+                    None => key::KeyEvents::no_events(),
+                }
             }
-            _ => {}
+            _ => key::KeyEvents::no_events(),
         }
     }
 }
@@ -465,8 +656,7 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
     type Event = Event;
 
     fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
-        self.handle_event(event);
-        key::KeyEvents::no_events()
+        self.handle_event(event)
     }
 
     fn reset(&mut self) {
@@ -522,11 +712,7 @@ impl<
     ) {
         let pks = PendingKeyState::new(context, keymap_index);
 
-        let chord_resolution = if context.sufficient_idle_time() {
-            pks.check_resolution()
-        } else {
-            PendingChordState::Resolved(ChordResolution::Passthrough)
-        };
+        let chord_resolution = pks.check_resolution();
 
         if let PendingChordState::Resolved(resolution) = chord_resolution {
             let maybe_new_key_ref = match resolution {
@@ -552,11 +738,12 @@ impl<
                 (pkr, pke)
             }
         } else {
+            let timeout = pks.timeout(context);
             let pkr = key::PressedKeyResult::Pending(pks);
 
             let timeout_ev = Event::Timeout;
             let sch_ev = key::ScheduledEvent::after(
-                context.config.timeout,
+                timeout,
                 key::Event::key_event(keymap_index, timeout_ev),
             );
             let pke = key::KeyEvents::scheduled_event(sch_ev);
@@ -591,6 +778,7 @@ impl<
         event: key::Event<Event>,
         lookup: impl Fn(ChordId) -> Option<R>,
     ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Event>) {
+        pending_state.expire_chords(context);
         let ch_state = pending_state.handle_event(keymap_index, event);
 
         if let Some(ch_state) = ch_state {
@@ -656,11 +844,7 @@ impl<
     ) {
         let pks = PendingKeyState::new(context, keymap_index);
 
-        let chord_resolution = if context.sufficient_idle_time() {
-            pks.check_resolution()
-        } else {
-            PendingChordState::Resolved(ChordResolution::Passthrough)
-        };
+        let chord_resolution = pks.check_resolution();
 
         if let PendingChordState::Resolved(resolution) = chord_resolution {
             match resolution {
@@ -688,11 +872,12 @@ impl<
                 }
             }
         } else {
+            let timeout = pks.timeout(context);
             let pkr = key::PressedKeyResult::Pending(pks);
 
             let timeout_ev = Event::Timeout;
             let sch_ev = key::ScheduledEvent::after(
-                context.config.timeout,
+                timeout,
                 key::Event::key_event(keymap_index, timeout_ev),
             );
             let pke = key::KeyEvents::scheduled_event(sch_ev);
@@ -717,6 +902,7 @@ impl<
         event: key::Event<Event>,
         lookup: impl Fn(ChordId) -> Option<R>,
     ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Event>) {
+        pending_state.expire_chords(context);
         let ch_state = pending_state.handle_event(keymap_index, event);
         if let Some(ch_state) = ch_state {
             resolved_chord_npk(
@@ -849,6 +1035,8 @@ pub struct PendingKeyState<
     possible_chords: heapless::Vec<ChordState<MAX_CHORD_SIZE>, { MAX_CHORDS }>,
     /// Most recent other-key press while pending (completes the chord on timeout).
     last_foreign_press: Option<u16>,
+    /// Time (in milliseconds) of the key press.
+    pressed_at_ms: u32,
     marker: PhantomData<[(); MAX_PRESSED_INDICES]>,
 }
 
@@ -862,16 +1050,45 @@ impl<const MAX_CHORDS: usize, const MAX_CHORD_SIZE: usize, const MAX_PRESSED_IND
     ) -> Self {
         let mut pressed_indices = heapless::Vec::new();
         let _ = pressed_indices.push(keymap_index);
-        let possible_chords = context.chords_for_keymap_index(keymap_index);
+        let mut possible_chords = context.chords_for_keymap_index(keymap_index);
+        // Already-pressed chords remain available.
+        possible_chords.retain(|chord_state| {
+            chord_state.is_satisfied || context.chord_is_available(chord_state.index)
+        });
 
         Self {
             pressed_indices,
             possible_chords,
             last_foreign_press: None,
+            pressed_at_ms: context.time_ms,
             marker: PhantomData,
         }
     }
 
+    /// The timeout for the pending key: the longest timeout of its possible chords.
+    fn timeout(&self, context: &Context<MAX_CHORDS, MAX_CHORD_SIZE, MAX_PRESSED_INDICES>) -> u16 {
+        self.possible_chords
+            .iter()
+            .map(|chord_state| context.chord_timeout(chord_state.index))
+            .max()
+            .unwrap_or(context.config.timeout)
+    }
+
+    /// Drops unsatisfied chords whose keys were not all pressed within their `press_within`.
+    fn expire_chords(
+        &mut self,
+        context: &Context<MAX_CHORDS, MAX_CHORD_SIZE, MAX_PRESSED_INDICES>,
+    ) {
+        let elapsed_ms = context.time_ms.wrapping_sub(self.pressed_at_ms);
+        self.possible_chords.retain(|chord_state| {
+            chord_state.is_satisfied
+                || match context.config.chord_config(chord_state.index).press_within {
+                    Some(press_within) => elapsed_ms <= press_within as u32,
+                    None => true,
+                }
+        });
+    }
+
     /// Finds the chord state amongst possible_chords which is satisfied (if it exists).
     fn satisfied_chord(&self) -> Option<&ChordState<MAX_CHORD_SIZE>> {
         self.possible_chords
@@ -1372,4 +1589,220 @@ mod tests {
         });
         assert!(activated);
     }
+
+    fn chord_01_context(
+        chord_config: ChordConfig,
+    ) -> Context<MAX_CHORDS, MAX_CHORD_SIZE, MAX_PRESSED_INDICES> {
+        Context::from_config(Config {
+            chords: Slice::from_slice(&[ChordIndices::from_slice(&[0, 1])]),
+            chord_configs: Slice::from_slice(&[chord_config]),
+            ..Config::new()
+        })
+    }
+
+    #[test]
+    fn chord_not_on_active_layer_resolves_as_passthrough() {
+        // Assemble: chord [0, 1] only on layer 1; layer 0 is active.
+        let context = chord_01_context(ChordConfig {
+            layers: Some(LayerBitset::from_bits(0b10)),
+            ..ChordConfig::new()
+        });
+        let pks = PendingKeyState::new(&context, 0);
+
+        // Act
+        let actual_resolution = pks.check_resolution();
+
+        // Assert
+        let expected_resolution = PendingChordState::Resolved(ChordResolution::Passthrough);
+        assert_eq!(expected_resolution, actual_resolution);
+    }
+
+    #[test]
+    fn chord_on_active_layer_is_pending() {
+        // Assemble: chord [0, 1] only on layer 1; layer 1 is active.
+        let mut context = chord_01_context(ChordConfig {
+            layers: Some(LayerBitset::from_bits(0b10)),
+            ..ChordConfig::new()
+        });
+        context.update_keymap_context(&keymap::KeymapContext {
+            active_layers: 0b10,
+            ..keymap::KeymapContext::new()
+        });
+        let pks = PendingKeyState::new(&context, 0);
+
+        // Act
+        let actual_resolution = pks.check_resolution();
+
+        // Assert
+        let expected_resolution = PendingChordState::Pending(None);
+        assert_eq!(expected_resolution, actual_resolution);
+    }
+
+    #[test]
+    fn chord_required_idle_time_overrides_config() {
+        // Assemble: no global idle requirement; chord requires 100ms idle.
+        let mut context = chord_01_context(ChordConfig {
+            required_idle_time: Some(100),
+            ..ChordConfig::new()
+        });
+        context.update_keymap_context(&keymap::KeymapContext {
+            idle_time_ms: 50,
+            ..keymap::KeymapContext::new()
+        });
+        let pks = PendingKeyState::new(&context, 0);
+
+        // Act
+        let actual_resolution = pks.check_resolution();
+
+        // Assert
+        let expected_resolution = PendingChordState::Resolved(ChordResolution::Passthrough);
+        assert_eq!(expected_resolution, actual_resolution);
+    }
+
+    #[test]
+    fn pending_key_timeout_is_longest_chord_timeout() {
+        // Assemble: chords [0, 1] (timeout 300) and [0, 2] (default timeout).
+        let context = Context::from_config(Config {
+            chords: Slice::from_slice(&[
+                ChordIndices::from_slice(&[0, 1]),
+                ChordIndices::from_slice(&[0, 2]),
+            ]),
+            chord_configs: Slice::from_slice(&[ChordConfig {
+                timeout: Some(300),
+                ..ChordConfig::new()
+            }]),
+            ..Config::new()
+        });
+        let key = ChordedKey::new(
+            &[
+                (0, keyboard::Ref::KeyCode(0x06)),
+                (1, keyboard::Ref::KeyCode(0x07)),
+            ],
+            keyboard::Ref::KeyCode(0x04),
+        );
+
+        // Act
+        let (_pkr, pke) = key.new_pressed_key(&context, 0, |id| key.binding_for(id));
+
+        // Assert
+        let timeout = pke.into_iter().find_map(|sch_ev| match sch_ev {
+            key::ScheduledEvent {
+                schedule: key::Schedule::After(delay),
+                event:
+                    key::Event::Key {
+                        key_event: Event::Timeout,
+                        ..
+                    },
+            } => Some(delay),
+            _ => None,
+        });
+        assert_eq!(Some(300), timeout);
+    }
+
+    #[test]
+    fn press_after_press_within_resolves_as_passthrough() {
+        // Assemble: chord [0, 1] must be pressed within 50ms.
+        let mut context = chord_01_context(ChordConfig {
+            press_within: Some(50),
+            ..ChordConfig::new()
+        });
+        let key = AuxiliaryKey::new(keyboard::Ref::KeyCode(0x04));
+        let mut pks = PendingKeyState::new(&context, 0);
+        let lookup = |_id: ChordId| Some(keyboard::Ref::KeyCode(0x06));
+        context.update_keymap_context(&keymap::KeymapContext {
+            time_ms: 60,
+            ..keymap::KeymapContext::new()
+        });
+
+        // Act: press the other chord key, 60ms after the first.
+        let (maybe_npk, _pke) = key.update_pending_state(
+            &mut pks,
+            0,
+            &context,
+            input::Event::Press { keymap_index: 1 }.into(),
+            lookup,
+        );
+
+        // Assert
+        assert_eq!(
+            maybe_npk,
+            Some(key::NewPressedKey::key(keyboard::Ref::KeyCode(0x04)))
+        );
+    }
+
+    fn activate_chord_01(
+        context: &mut Context<MAX_CHORDS, MAX_CHORD_SIZE, MAX_PRESSED_INDICES>,
+    ) -> key::KeyEvents<Event> {
+        context.handle_event(input::Event::Press { keymap_index: 0 }.into());
+        context.handle_event(input::Event::Press { keymap_index: 1 }.into());
+        context.handle_event(key::Event::key_event(
+            0,
+            Event::ChordResolved(ChordResolution::Chord(0)),
+        ));
+        context.handle_event(key::Event::key_event(
+            1,
+            Event::ChordActivated { keymap_index: 1 },
+        ))
+    }
+
+    #[test]
+    fn release_any_releases_completing_key() {
+        // Assemble: chord [0, 1] releases on any key release; completed by 1.
+        let mut context = chord_01_context(ChordConfig {
+            release: ChordRelease::Any,
+            ..ChordConfig::new()
+        });
+        activate_chord_01(&mut context);
+
+        // Act: release the non-completing key.
+        let pke = context.handle_event(input::Event::Release { keymap_index: 0 }.into());
+
+        // Assert
+        let released = pke.into_iter().any(|sch_ev| {
+            sch_ev.event == key::Event::Input(input::Event::Release { keymap_index: 1 })
+        });
+        assert!(released);
+    }
+
+    #[test]
+    fn release_all_defers_completing_key_release() {
+        // Assemble: chord [0, 1] releases after all keys release.
+        let mut context = chord_01_context(ChordConfig {
+            release: ChordRelease::All,
+            ..ChordConfig::new()
+        });
+
+        // Act
+        let pke = activate_chord_01(&mut context);
+
+        // Assert
+        let deferred = pke.into_iter().any(|sch_ev| {
+            sch_ev.event
+                == key::Event::Keymap(keymap::KeymapEvent::DeferRelease { keymap_index: 1 })
+        });
+        assert!(deferred);
+    }
+
+    #[test]
+    fn release_all_releases_completing_key_after_last_key_release() {
+        // Assemble: chord [0, 1] releases after all keys release; completed by 1.
+        let mut context = chord_01_context(ChordConfig {
+            release: ChordRelease::All,
+            ..ChordConfig::new()
+        });
+        activate_chord_01(&mut context);
+
+        // Act: release the completing key, then the other key.
+        let first_pke = context.handle_event(input::Event::Release { keymap_index: 1 }.into());
+        let last_pke = context.handle_event(input::Event::Release { keymap_index: 0 }.into());
+
+        // Assert
+        let release_ev = key::Event::Input(input::Event::Release { keymap_index: 1 });
+        assert!(!first_pke
+            .into_iter()
+            .any(|sch_ev| sch_ev.event == release_ev));
+        assert!(last_pke
+            .into_iter()
+            .any(|sch_ev| sch_ev.event == release_ev));
+    }
 }
@@ -0,0 +1,685 @@
+//! Dynamic macros: record key presses at runtime, and play them back.
+//!
+//! Pressing `Key::RecordStart(slot)` starts recording the resolved key outputs
+//!  (see [keymap::KeymapEvent::ResolvedKeyOutput]) into that slot,
+//!  until `Key::RecordStop` (or another record key) is pressed.
+//!
+//! Recordings are stored as [automation::Instruction]s:
+//!  key presses and releases, with waits for the time between them.
+//! Pressing `Key::Play(slot)` plays the slot back,
+//!  similar to how [automation] keys execute their instructions.
+//!
+//! Each slot holds at most `BUFFER_SIZE` instructions;
+//!  key presses which don't fit in the buffer are not recorded.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use serde::Deserialize;
+
+use crate::input;
+use crate::key;
+use crate::keymap;
+
+use key::automation::{self, Instruction};
+
+/// The maximum number of keys which can be held down while recording.
+pub const MAX_HELD_KEYS: usize = 8;
+
+/// Reference for a dynamic macro key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ref(pub Key);
+
+/// Dynamic macro keys.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    /// Starts recording into the given slot.
+    ///
+    /// Stops recording, if already recording.
+    RecordStart(u8),
+    /// Stops recording.
+    RecordStop,
+    /// Plays back the macro recorded in the given slot.
+    ///
+    /// Stops recording, if recording.
+    Play(u8),
+}
+
+impl Key {
+    /// Constructs pressed-key events for this key.
+    pub fn new_pressed_key(&self, keymap_index: u16) -> key::KeyEvents<Event> {
+        let key_event = match *self {
+            Key::RecordStart(slot) => Event::RecordStart(slot),
+            Key::RecordStop => Event::RecordStop,
+            Key::Play(slot) => Event::Play(slot),
+        };
+        key::KeyEvents::event(key::Event::key_event(keymap_index, key_event))
+    }
+}
+
+/// Config for dynamic macros.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Duration (in ticks) of each instruction, when playing back a macro.
+    #[serde(default = "default_instruction_duration")]
+    pub instruction_duration: u16,
+
+    /// Maximum duration (in ms) of a recorded wait between key presses/releases.
+    ///
+    /// Set to `0` to play back macros without the recorded waits.
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u16,
+}
+
+fn default_instruction_duration() -> u16 {
+    DEFAULT_INSTRUCTION_DURATION
+}
+
+fn default_max_wait_ms() -> u16 {
+    DEFAULT_MAX_WAIT_MS
+}
+
+/// Default instruction duration.
+pub const DEFAULT_INSTRUCTION_DURATION: u16 = automation::DEFAULT_INSTRUCTION_DURATION;
+
+/// Default maximum recorded wait.
+pub const DEFAULT_MAX_WAIT_MS: u16 = 1000;
+
+/// The default [Config].
+pub const DEFAULT_CONFIG: Config = Config {
+    instruction_duration: DEFAULT_INSTRUCTION_DURATION,
+    max_wait_ms: DEFAULT_MAX_WAIT_MS,
+};
+
+impl Config {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        DEFAULT_CONFIG
+    }
+
+    /// The wait to record, given the time elapsed since the previous instruction.
+    ///
+    /// Playback already takes `instruction_duration` per instruction,
+    ///  so this is subtracted from the elapsed time.
+    pub fn recorded_wait(&self, elapsed_ms: u32) -> u16 {
+        let wait = elapsed_ms.saturating_sub(self.instruction_duration as u32);
+        wait.min(self.max_wait_ms as u32) as u16
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A bounded buffer of recorded instructions.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Slot<const BUFFER_SIZE: usize> {
+    instructions: [Instruction; BUFFER_SIZE],
+    len: u16,
+}
+
+impl<const BUFFER_SIZE: usize> core::fmt::Debug for Slot<BUFFER_SIZE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.instructions()).finish()
+    }
+}
+
+impl<const BUFFER_SIZE: usize> Slot<BUFFER_SIZE> {
+    /// An empty slot.
+    pub const EMPTY: Self = Self {
+        instructions: [Instruction::NoOp; BUFFER_SIZE],
+        len: 0,
+    };
+
+    /// The recorded instructions.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions[..self.len as usize]
+    }
+
+    /// Whether the slot has no recorded instructions.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of instructions which can still be recorded.
+    pub const fn remaining(&self) -> usize {
+        BUFFER_SIZE - self.len as usize
+    }
+
+    fn push(&mut self, instruction: Instruction) {
+        if self.remaining() > 0 {
+            self.instructions[self.len as usize] = instruction;
+            self.len += 1;
+        }
+    }
+}
+
+/// A key pressed while recording, which hasn't been released yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct HeldKey {
+    keymap_index: u16,
+    key_output: key::KeyOutput,
+}
+
+/// State while recording a slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Recording {
+    slot: u8,
+    last_time_ms: u32,
+    held_keys: [Option<HeldKey>; MAX_HELD_KEYS],
+}
+
+impl Recording {
+    fn held_count(&self) -> usize {
+        self.held_keys.iter().filter(|hk| hk.is_some()).count()
+    }
+}
+
+/// State while playing back a slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Playback {
+    keymap_index: u16,
+    slot: u8,
+    position: u16,
+}
+
+/// Context for dynamic macro keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Context<const SLOT_COUNT: usize, const BUFFER_SIZE: usize> {
+    config: Config,
+    slots: [Slot<BUFFER_SIZE>; SLOT_COUNT],
+    recording: Option<Recording>,
+    playback: Option<Playback>,
+    time_ms: u32,
+}
+
+impl<const SLOT_COUNT: usize, const BUFFER_SIZE: usize> Default
+    for Context<SLOT_COUNT, BUFFER_SIZE>
+{
+    fn default() -> Self {
+        Self::from_config(DEFAULT_CONFIG)
+    }
+}
+
+impl<const SLOT_COUNT: usize, const BUFFER_SIZE: usize> Context<SLOT_COUNT, BUFFER_SIZE> {
+    /// Constructs a context from the given config, with empty slots.
+    pub const fn from_config(config: Config) -> Self {
+        Self {
+            config,
+            slots: [Slot::EMPTY; SLOT_COUNT],
+            recording: None,
+            playback: None,
+            time_ms: 0,
+        }
+    }
+
+    /// Clears the recorded macros (keeps config).
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// Updates the time from the keymap engine.
+    pub fn update_keymap_context(
+        &mut self,
+        keymap::KeymapContext { time_ms, .. }: &keymap::KeymapContext,
+    ) {
+        self.time_ms = *time_ms;
+    }
+
+    /// The slot currently being recorded, if any.
+    pub fn recording_slot(&self) -> Option<u8> {
+        self.recording.map(|r| r.slot)
+    }
+
+    /// Whether a macro is being played back.
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// The recorded instructions for the slot.
+    pub fn slot(&self, slot: u8) -> Option<&Slot<BUFFER_SIZE>> {
+        self.slots.get(slot as usize)
+    }
+
+    fn start_recording(&mut self, slot: u8) {
+        if let Some(recorded_slot) = self.slots.get_mut(slot as usize) {
+            *recorded_slot = Slot::EMPTY;
+            self.recording = Some(Recording {
+                slot,
+                last_time_ms: self.time_ms,
+                held_keys: [None; MAX_HELD_KEYS],
+            });
+        }
+    }
+
+    /// Stops recording, recording releases for keys which are still held.
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            let slot = &mut self.slots[recording.slot as usize];
+            recording
+                .held_keys
+                .iter()
+                .flatten()
+                .for_each(|hk| slot.push(Instruction::Release(hk.key_output)));
+        }
+    }
+
+    fn record_press(&mut self, keymap_index: u16, key_output: key::KeyOutput) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        let Some(held_index) = recording.held_keys.iter().position(|hk| hk.is_none()) else {
+            return;
+        };
+
+        let slot = &mut self.slots[recording.slot as usize];
+
+        // Keep space for releasing this key, and the other held keys.
+        let reserved = recording.held_count() + 1;
+        if slot.remaining() < reserved + 1 {
+            return;
+        }
+
+        let wait = self
+            .config
+            .recorded_wait(self.time_ms.wrapping_sub(recording.last_time_ms));
+        if wait > 0 && slot.remaining() > reserved + 1 {
+            slot.push(Instruction::Wait(wait));
+        }
+        slot.push(Instruction::Press(key_output));
+
+        recording.held_keys[held_index] = Some(HeldKey {
+            keymap_index,
+            key_output,
+        });
+        recording.last_time_ms = self.time_ms;
+    }
+
+    fn record_release(&mut self, keymap_index: u16) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        let Some(held_key) = recording
+            .held_keys
+            .iter_mut()
+            .find(|hk| hk.is_some_and(|hk| hk.keymap_index == keymap_index))
+        else {
+            return;
+        };
+        let Some(HeldKey { key_output, .. }) = held_key.take() else {
+            return;
+        };
+
+        let slot = &mut self.slots[recording.slot as usize];
+
+        // Keep space for releasing the other held keys.
+        let reserved = recording.held_count() + 1;
+        let wait = self
+            .config
+            .recorded_wait(self.time_ms.wrapping_sub(recording.last_time_ms));
+        if wait > 0 && slot.remaining() > reserved {
+            slot.push(Instruction::Wait(wait));
+        }
+        slot.push(Instruction::Release(key_output));
+
+        recording.last_time_ms = self.time_ms;
+    }
+
+    fn start_playback(&mut self, keymap_index: u16, slot: u8) -> key::KeyEvents<Event> {
+        match self.slots.get(slot as usize) {
+            Some(recorded_slot) if !recorded_slot.is_empty() && self.playback.is_none() => {
+                self.playback = Some(Playback {
+                    keymap_index,
+                    slot,
+                    position: 0,
+                });
+                self.play_next_instruction()
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+
+    fn play_next_instruction(&mut self) -> key::KeyEvents<Event> {
+        let Some(playback) = self.playback.as_mut() else {
+            return key::KeyEvents::no_events();
+        };
+
+        let instructions = self.slots[playback.slot as usize].instructions();
+        match instructions.get(playback.position as usize) {
+            Some(&instruction) => {
+                playback.position += 1;
+                let next_key_ev =
+                    key::Event::key_event(playback.keymap_index, Event::NextInstruction);
+                automation::instruction_key_events(
+                    instruction,
+                    self.config.instruction_duration,
+                    next_key_ev,
+                )
+            }
+            None => {
+                self.playback = None;
+                key::KeyEvents::no_events()
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                key_event: Event::RecordStart(slot),
+                ..
+            } => {
+                if self.recording.is_some() {
+                    self.stop_recording();
+                } else if self.playback.is_none() {
+                    self.start_recording(slot);
+                }
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::RecordStop,
+                ..
+            } => {
+                self.stop_recording();
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::Play(slot),
+                keymap_index,
+            } => {
+                if self.recording.is_some() {
+                    self.stop_recording();
+                    key::KeyEvents::no_events()
+                } else {
+                    self.start_playback(keymap_index, slot)
+                }
+            }
+            key::Event::Key {
+                key_event: Event::NextInstruction,
+                ..
+            } => self.play_next_instruction(),
+            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index,
+                key_output,
+            }) if key_output != key::KeyOutput::NO_OUTPUT => {
+                self.record_press(keymap_index, key_output);
+                key::KeyEvents::no_events()
+            }
+            key::Event::Input(input::Event::Release { keymap_index }) => {
+                self.record_release(keymap_index);
+                key::KeyEvents::no_events()
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+}
+
+impl<const SLOT_COUNT: usize, const BUFFER_SIZE: usize> key::Context
+    for Context<SLOT_COUNT, BUFFER_SIZE>
+{
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Dynamic macro events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// Starts (or stops) recording into the slot.
+    RecordStart(u8),
+    /// Stops recording.
+    RecordStop,
+    /// Plays back the slot.
+    Play(u8),
+    /// Indicates to the context to play the next instruction.
+    NextInstruction,
+}
+
+/// Pending key state type for dynamic macro keys. (No pending state.)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingKeyState;
+
+/// Key state used by [System]. (No per-key state; behaviour is on [Context].)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyState;
+
+/// The [key::System] implementation for dynamic macro keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<R, const SLOT_COUNT: usize, const BUFFER_SIZE: usize>(PhantomData<R>);
+
+impl<R, const SLOT_COUNT: usize, const BUFFER_SIZE: usize> System<R, SLOT_COUNT, BUFFER_SIZE> {
+    /// Constructs a new [System].
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R, const SLOT_COUNT: usize, const BUFFER_SIZE: usize> Default
+    for System<R, SLOT_COUNT, BUFFER_SIZE>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Debug, const SLOT_COUNT: usize, const BUFFER_SIZE: usize> key::System<R>
+    for System<R, SLOT_COUNT, BUFFER_SIZE>
+{
+    type Ref = Ref;
+    type Context = Context<SLOT_COUNT, BUFFER_SIZE>;
+    type Event = Event;
+    type PendingKeyState = PendingKeyState;
+    type KeyState = KeyState;
+
+    fn new_pressed_key(
+        &self,
+        keymap_index: u16,
+        _context: &Self::Context,
+        Ref(key): Ref,
+    ) -> (
+        key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
+        key::KeyEvents<Self::Event>,
+    ) {
+        let pke = key.new_pressed_key(keymap_index);
+        let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp);
+        (pkr, pke)
+    }
+
+    fn update_pending_state(
+        &self,
+        _pending_state: &mut Self::PendingKeyState,
+        _keymap_index: u16,
+        _context: &Self::Context,
+        _key_ref: Ref,
+        _event: key::Event<Self::Event>,
+    ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
+        panic!()
+    }
+
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        _ref: &Self::Ref,
+        _context: &Self::Context,
+        _keymap_index: u16,
+        _event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        panic!()
+    }
+
+    fn key_output(
+        &self,
+        _key_ref: &Self::Ref,
+        _key_state: &Self::KeyState,
+    ) -> Option<key::KeyOutput> {
+        panic!()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const KEY_A: key::KeyOutput = key::KeyOutput::from_key_code(0x04);
+    const KEY_B: key::KeyOutput = key::KeyOutput::from_key_code(0x05);
+
+    fn set_time(ctx: &mut Context<2, 16>, time_ms: u32) {
+        ctx.update_keymap_context(&keymap::KeymapContext {
+            time_ms,
+            ..keymap::KeymapContext::new()
+        });
+    }
+
+    fn press(ctx: &mut Context<2, 16>, keymap_index: u16, key_output: key::KeyOutput) {
+        let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index,
+            key_output,
+        }));
+    }
+
+    fn release(ctx: &mut Context<2, 16>, keymap_index: u16) {
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Release { keymap_index }));
+    }
+
+    #[test]
+    fn test_sizeof_ref() {
+        assert_eq!(2, core::mem::size_of::<Ref>());
+    }
+
+    #[test]
+    fn test_sizeof_event() {
+        assert_eq!(2, core::mem::size_of::<Event>());
+    }
+
+    #[test]
+    fn test_recording_records_presses_releases_and_waits() {
+        // Assemble
+        let mut ctx: Context<2, 16> = Context::default();
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStart(1)));
+
+        // Act
+        set_time(&mut ctx, 100);
+        press(&mut ctx, 1, KEY_A);
+        set_time(&mut ctx, 150);
+        release(&mut ctx, 1);
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStop));
+
+        // Assert
+        let expected_instructions = &[
+            Instruction::Wait(90),
+            Instruction::Press(KEY_A),
+            Instruction::Wait(40),
+            Instruction::Release(KEY_A),
+        ];
+        assert_eq!(None, ctx.recording_slot());
+        assert_eq!(expected_instructions, ctx.slot(1).unwrap().instructions());
+        assert!(ctx.slot(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stop_recording_releases_held_keys() {
+        // Assemble
+        let mut ctx: Context<2, 16> = Context::from_config(Config {
+            max_wait_ms: 0,
+            ..Config::new()
+        });
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStart(0)));
+
+        // Act
+        press(&mut ctx, 1, KEY_A);
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStop));
+
+        // Assert
+        let expected_instructions = &[Instruction::Press(KEY_A), Instruction::Release(KEY_A)];
+        assert_eq!(expected_instructions, ctx.slot(0).unwrap().instructions());
+    }
+
+    #[test]
+    fn test_recording_drops_presses_which_dont_fit_in_buffer() {
+        // Assemble
+        let mut ctx: Context<1, 3> = Context::from_config(Config {
+            max_wait_ms: 0,
+            ..Config::new()
+        });
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStart(0)));
+
+        // Act
+        for (keymap_index, key_output) in [(1, KEY_A), (2, KEY_B)] {
+            let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index,
+                key_output,
+            }));
+        }
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Release { keymap_index: 2 }));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Release { keymap_index: 1 }));
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStop));
+
+        // Assert
+        let expected_instructions = &[Instruction::Press(KEY_A), Instruction::Release(KEY_A)];
+        assert_eq!(expected_instructions, ctx.slot(0).unwrap().instructions());
+    }
+
+    #[test]
+    fn test_play_emits_recorded_instructions() {
+        // Assemble
+        let mut ctx: Context<2, 16> = Context::from_config(Config {
+            max_wait_ms: 0,
+            ..Config::new()
+        });
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStart(0)));
+        press(&mut ctx, 1, KEY_A);
+        release(&mut ctx, 1);
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStop));
+
+        // Act
+        let play_events = ctx.handle_event(key::Event::key_event(3, Event::Play(0)));
+        let next_events = ctx.handle_event(key::Event::key_event(3, Event::NextInstruction));
+        let finished_events = ctx.handle_event(key::Event::key_event(3, Event::NextInstruction));
+
+        // Assert
+        let play_events: heapless::Vec<key::Event<Event>, 4> =
+            play_events.into_iter().map(|sch_ev| sch_ev.event).collect();
+        let next_events: heapless::Vec<key::Event<Event>, 4> =
+            next_events.into_iter().map(|sch_ev| sch_ev.event).collect();
+        assert_eq!(
+            &[
+                key::Event::Input(input::Event::VirtualKeyPress { key_output: KEY_A }),
+                key::Event::key_event(3, Event::NextInstruction),
+            ],
+            play_events.as_slice()
+        );
+        assert_eq!(
+            &[
+                key::Event::Input(input::Event::VirtualKeyRelease { key_output: KEY_A }),
+                key::Event::key_event(3, Event::NextInstruction),
+            ],
+            next_events.as_slice()
+        );
+        assert_eq!(0, finished_events.into_iter().count());
+        assert!(!ctx.is_playing());
+    }
+
+    #[test]
+    fn test_play_while_recording_stops_recording() {
+        // Assemble
+        let mut ctx: Context<2, 16> = Context::default();
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::RecordStart(0)));
+
+        // Act
+        let play_events = ctx.handle_event(key::Event::key_event(0, Event::Play(0)));
+
+        // Assert
+        assert_eq!(None, ctx.recording_slot());
+        assert!(!ctx.is_playing());
+        assert_eq!(0, play_events.into_iter().count());
+    }
+}
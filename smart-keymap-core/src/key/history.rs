@@ -1,13 +1,18 @@
 //! History keys: behaviours that depend on previously resolved key output.
 //!
+//! The history context remembers the last [Action](crate::key::history::Action):
+//!  the output of a resolved key (e.g. a keyboard key, or a chord's binding)
+//!  along with any modifiers held when it was pressed,
+//!  or the sequence of keys tapped by an automation (e.g. a string macro).
+//!
 //! - [Key::Repeat](crate::key::history::Key::Repeat) re-emits the last remembered
-//!   [crate::key::KeyOutput] as the pressed key's own output while held.
+//!   single output as the pressed key's own output while held,
+//!   or replays the taps of a remembered automation.
 //! - [Key::AltRepeat](crate::key::history::Key::AltRepeat) looks up that last
-//!   output in a Nickel-defined table ([Config::alt_repeat](crate::key::history::Config::alt_repeat))
-//!   and emits the mapped alternate while held (QMK-style alternate repeat for
-//!   single keys).
-//! - [Key::Adaptive](crate::key::history::Key::Adaptive) looks up that last
-//!   output in a per-key rule table (Hands Down / ZMK adaptive-key style).
+//!   action in a Nickel-defined table ([Config::alt_repeat](crate::key::history::Config::alt_repeat))
+//!   and emits the mapped alternate while held (QMK-style alternate repeat).
+//! - [Key::Adaptive](crate::key::history::Key::Adaptive) looks up the last
+//!   remembered output in a per-key rule table (Hands Down / ZMK adaptive-key style).
 //!   On a miss, the key emits its default output.
 
 use core::fmt::Debug;
@@ -16,6 +21,7 @@ use core::ops::Index;
 
 use serde::Deserialize;
 
+use crate::input;
 use crate::key;
 use crate::keymap;
 
@@ -26,11 +32,14 @@ pub struct Ref(pub Key);
 /// History key kinds.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum Key {
-    /// Re-emit the last remembered key output while pressed.
+    /// Re-emit the last remembered action.
+    ///
+    /// A single output is emitted while pressed;
+    ///  an automation's taps are replayed when pressed.
     Repeat,
-    /// Emit the configured alternate of the last remembered key output while pressed.
+    /// Emit the configured alternate of the last remembered action while pressed.
     ///
-    /// If the last output is unmapped (or history is empty), contributes no output.
+    /// If the last action is unmapped (or history is empty), contributes no output.
     AltRepeat,
     /// Per-site adaptive key.
     ///
@@ -57,17 +66,170 @@ impl Key {
     }
 }
 
+/// Maximum number of key outputs remembered for one [Action].
+pub const MAX_ACTION_OUTPUTS: usize = 16;
+
+/// Ticks each output is held for when [Key::Repeat] replays an automation.
+pub const REPLAY_TAP_DURATION: u16 = key::automation::DEFAULT_INSTRUCTION_DURATION;
+
+/// HID usage of Backspace; erases the last output for adaptive keys.
+const BACKSPACE: u8 = 0x2A;
+
+/// An action remembered by the history [Context]: the key outputs it tapped.
+///
+/// A resolved key (including a chord's binding, or a key pressed
+///  while modifier keys were held) is a single output.
+/// An automation (e.g. a string macro) is the sequence of keys it tapped.
+///
+/// Deserializes from either a single [key::KeyOutput], or a list of them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Action {
+    outputs: [key::KeyOutput; MAX_ACTION_OUTPUTS],
+    len: u8,
+}
+
+impl core::fmt::Debug for Action {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Action").field(&self.outputs()).finish()
+    }
+}
+
+impl Action {
+    /// An action with no outputs.
+    pub const EMPTY: Self = Self {
+        outputs: [key::KeyOutput::NO_OUTPUT; MAX_ACTION_OUTPUTS],
+        len: 0,
+    };
+
+    /// Constructs an action of a single output.
+    pub const fn from_output(output: key::KeyOutput) -> Self {
+        let mut action = Self::EMPTY;
+        action.outputs[0] = output;
+        action.len = 1;
+        action
+    }
+
+    /// Constructs an action from a sequence of outputs (codegen helper).
+    ///
+    /// Panics if there are more than [MAX_ACTION_OUTPUTS] outputs.
+    pub const fn from_outputs(outputs: &[key::KeyOutput]) -> Self {
+        if outputs.len() > MAX_ACTION_OUTPUTS {
+            panic!("Too many outputs for history Action");
+        }
+
+        let mut action = Self::EMPTY;
+        let mut i = 0;
+        while i < outputs.len() {
+            action.outputs[i] = outputs[i];
+            i += 1;
+        }
+        action.len = outputs.len() as u8;
+        action
+    }
+
+    /// The outputs of the action.
+    pub fn outputs(&self) -> &[key::KeyOutput] {
+        &self.outputs[..self.len as usize]
+    }
+
+    /// The output, if the action is a single output.
+    pub fn single_output(&self) -> Option<key::KeyOutput> {
+        match self.outputs() {
+            [output] => Some(*output),
+            _ => None,
+        }
+    }
+
+    /// The last output of the action, if any.
+    pub fn last_output(&self) -> Option<key::KeyOutput> {
+        self.outputs().last().copied()
+    }
+
+    /// Appends an output. Returns false if the action is full.
+    fn push(&mut self, output: key::KeyOutput) -> bool {
+        if (self.len as usize) < MAX_ACTION_OUTPUTS {
+            self.outputs[self.len as usize] = output;
+            self.len += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (outputs, len) = deserialize_outputs(deserializer)?;
+        Ok(Self { outputs, len })
+    }
+}
+
+/// Deserializes either a single [key::KeyOutput], or a list of (up to `N`) of them.
+fn deserialize_outputs<'de, D, const N: usize>(
+    deserializer: D,
+) -> Result<([key::KeyOutput; N], u8), D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct OutputsVisitor<const N: usize>;
+
+    impl<'de, const N: usize> serde::de::Visitor<'de> for OutputsVisitor<N> {
+        type Value = ([key::KeyOutput; N], u8);
+
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            write!(f, "a key output, or a list of up to {} key outputs", N)
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let output =
+                key::KeyOutput::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+            let mut outputs = [key::KeyOutput::NO_OUTPUT; N];
+            outputs[0] = output;
+            Ok((outputs, 1))
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut outputs = [key::KeyOutput::NO_OUTPUT; N];
+            let mut len = 0;
+            while let Some(output) = seq.next_element()? {
+                if len >= N {
+                    return Err(serde::de::Error::invalid_length(N + 1, &self));
+                }
+                outputs[len] = output;
+                len += 1;
+            }
+            Ok((outputs, len as u8))
+        }
+    }
+
+    deserializer.deserialize_any(OutputsVisitor::<N>)
+}
+
 /// Maximum number of `{ prev, emit }` rules stored on one [AdaptiveKey].
 ///
-/// Unused slots are [AltRepeatRule::EMPTY].
-pub const MAX_ADAPTIVE_RULES: usize = 8;
+/// Unused slots are [AdaptiveRule::EMPTY].
+pub const MAX_ADAPTIVE_RULES: usize = 16;
 
-/// One alternate-repeat mapping: when the last remembered output equals [Self::prev],
+/// Maximum number of outputs emitted by one [AdaptiveRule].
+///
+/// (e.g. Backspace, to replace the previous output, followed by a digraph).
+pub const MAX_ADAPTIVE_EMIT_OUTPUTS: usize = 3;
+
+/// One alternate-repeat mapping: when the last remembered action equals [Self::prev],
 /// [Key::AltRepeat] emits [Self::emit].
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct AltRepeatRule {
-    /// Previous resolved output that triggers this rule.
-    pub prev: key::KeyOutput,
+    /// Previous action that triggers this rule.
+    pub prev: Action,
     /// Output to emit instead of repeating [Self::prev].
     pub emit: key::KeyOutput,
 }
@@ -75,16 +237,135 @@ pub struct AltRepeatRule {
 impl AltRepeatRule {
     /// Empty placeholder rule (used to pad fixed-size arrays).
     pub const EMPTY: Self = Self {
-        prev: key::KeyOutput::NO_OUTPUT,
+        prev: Action::EMPTY,
         emit: key::KeyOutput::NO_OUTPUT,
     };
 
     /// Constructs a rule.
-    pub const fn new(prev: key::KeyOutput, emit: key::KeyOutput) -> Self {
+    pub const fn new(prev: Action, emit: key::KeyOutput) -> Self {
         Self { prev, emit }
     }
 }
 
+/// The outputs emitted by an [AdaptiveRule].
+///
+/// A single output is held while the adaptive key is pressed;
+///  several outputs are tapped in order.
+///
+/// Deserializes from either a single [key::KeyOutput], or a list of them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveEmit {
+    outputs: [key::KeyOutput; MAX_ADAPTIVE_EMIT_OUTPUTS],
+    len: u8,
+}
+
+impl core::fmt::Debug for AdaptiveEmit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("AdaptiveEmit")
+            .field(&self.outputs())
+            .finish()
+    }
+}
+
+impl AdaptiveEmit {
+    /// No outputs.
+    pub const EMPTY: Self = Self {
+        outputs: [key::KeyOutput::NO_OUTPUT; MAX_ADAPTIVE_EMIT_OUTPUTS],
+        len: 0,
+    };
+
+    /// Constructs an emit of a single output.
+    pub const fn from_output(output: key::KeyOutput) -> Self {
+        Self::from_outputs(&[output])
+    }
+
+    /// Constructs an emit from a sequence of outputs (codegen helper).
+    ///
+    /// Panics if there are more than [MAX_ADAPTIVE_EMIT_OUTPUTS] outputs.
+    pub const fn from_outputs(outputs: &[key::KeyOutput]) -> Self {
+        if outputs.len() > MAX_ADAPTIVE_EMIT_OUTPUTS {
+            panic!("Too many outputs for AdaptiveEmit");
+        }
+
+        let mut emit = Self::EMPTY;
+        let mut i = 0;
+        while i < outputs.len() {
+            emit.outputs[i] = outputs[i];
+            i += 1;
+        }
+        emit.len = outputs.len() as u8;
+        emit
+    }
+
+    /// The outputs to emit.
+    pub fn outputs(&self) -> &[key::KeyOutput] {
+        &self.outputs[..self.len as usize]
+    }
+}
+
+impl<'de> Deserialize<'de> for AdaptiveEmit {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let (outputs, len) = deserialize_outputs(deserializer)?;
+        Ok(Self { outputs, len })
+    }
+}
+
+/// One adaptive-key mapping: when the last remembered output equals [Self::prev]
+///  (and the output before it equals [Self::before_prev], if given),
+///  the [AdaptiveKey] emits [Self::emit].
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveRule {
+    /// The output before [Self::prev], for rules keyed on the last two outputs.
+    #[serde(default)]
+    pub before_prev: Option<key::KeyOutput>,
+    /// Previous resolved output that triggers this rule.
+    pub prev: key::KeyOutput,
+    /// Outputs to emit instead of the key's default.
+    pub emit: AdaptiveEmit,
+}
+
+impl AdaptiveRule {
+    /// Empty placeholder rule (used to pad fixed-size arrays).
+    pub const EMPTY: Self = Self {
+        before_prev: None,
+        prev: key::KeyOutput::NO_OUTPUT,
+        emit: AdaptiveEmit::EMPTY,
+    };
+
+    /// Constructs a rule keyed on the last output.
+    pub const fn new(prev: key::KeyOutput, emit: AdaptiveEmit) -> Self {
+        Self {
+            before_prev: None,
+            prev,
+            emit,
+        }
+    }
+
+    /// Constructs a rule keyed on the last two outputs.
+    pub const fn new_after(
+        before_prev: key::KeyOutput,
+        prev: key::KeyOutput,
+        emit: AdaptiveEmit,
+    ) -> Self {
+        Self {
+            before_prev: Some(before_prev),
+            prev,
+            emit,
+        }
+    }
+
+    fn matches(&self, prev: &key::KeyOutput, before_prev: Option<&key::KeyOutput>) -> bool {
+        *self != Self::EMPTY
+            && self.prev == *prev
+            && self
+                .before_prev
+                .is_none_or(|before| Some(&before) == before_prev)
+    }
+}
+
 /// Per-site adaptive key.
 ///
 /// Holds a default output and a table of last-output → emit rules.
@@ -94,35 +375,58 @@ pub struct AdaptiveKey {
     pub default: key::KeyOutput,
     /// Sparse last-output → emit mappings for this key site.
     #[serde(deserialize_with = "deserialize_adaptive_rules")]
-    pub rules: [AltRepeatRule; MAX_ADAPTIVE_RULES],
+    pub rules: [AdaptiveRule; MAX_ADAPTIVE_RULES],
 }
 
 impl AdaptiveKey {
     /// Empty adaptive key (no-op default, no rules).
     pub const EMPTY: Self = Self {
         default: key::KeyOutput::NO_OUTPUT,
-        rules: [AltRepeatRule::EMPTY; MAX_ADAPTIVE_RULES],
+        rules: [AdaptiveRule::EMPTY; MAX_ADAPTIVE_RULES],
     };
 
     /// Constructs an adaptive key from a default and a padded rule array.
-    pub const fn new(default: key::KeyOutput, rules: [AltRepeatRule; MAX_ADAPTIVE_RULES]) -> Self {
+    pub const fn new(default: key::KeyOutput, rules: [AdaptiveRule; MAX_ADAPTIVE_RULES]) -> Self {
         Self { default, rules }
     }
 
-    /// Looks up `prev` in this key's rule table.
-    pub fn lookup(&self, prev: &key::KeyOutput) -> Option<key::KeyOutput> {
-        self.rules
-            .iter()
-            .find(|r| r.prev == *prev && **r != AltRepeatRule::EMPTY)
-            .map(|r| r.emit)
+    /// Looks up the last outputs in this key's rule table.
+    ///
+    /// Rules keyed on the last two outputs are preferred
+    ///  over rules keyed on only the last output.
+    pub fn lookup(
+        &self,
+        prev: &key::KeyOutput,
+        before_prev: Option<&key::KeyOutput>,
+    ) -> Option<AdaptiveEmit> {
+        self.lookup_rule(prev, before_prev)
+            .map(|rule| self.rules[rule].emit)
+    }
+
+    /// Looks up the index of the rule [Self::lookup] would use.
+    pub fn lookup_rule(
+        &self,
+        prev: &key::KeyOutput,
+        before_prev: Option<&key::KeyOutput>,
+    ) -> Option<usize> {
+        let rules = || {
+            self.rules
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.matches(prev, before_prev))
+        };
+        rules()
+            .find(|(_, r)| r.before_prev.is_some())
+            .or_else(|| rules().next())
+            .map(|(rule, _)| rule)
     }
 }
 
 /// Builds a fixed-size adaptive rule array from a shorter const list (codegen helper).
 pub const fn adaptive_rules<const N: usize>(
-    rules: [AltRepeatRule; N],
-) -> [AltRepeatRule; MAX_ADAPTIVE_RULES] {
-    let mut out: [AltRepeatRule; MAX_ADAPTIVE_RULES] = [AltRepeatRule::EMPTY; MAX_ADAPTIVE_RULES];
+    rules: [AdaptiveRule; N],
+) -> [AdaptiveRule; MAX_ADAPTIVE_RULES] {
+    let mut out: [AdaptiveRule; MAX_ADAPTIVE_RULES] = [AdaptiveRule::EMPTY; MAX_ADAPTIVE_RULES];
 
     if N > MAX_ADAPTIVE_RULES {
         panic!("Too many adaptive rules for AdaptiveKey");
@@ -138,15 +442,15 @@ pub const fn adaptive_rules<const N: usize>(
 
 fn deserialize_adaptive_rules<'de, D>(
     deserializer: D,
-) -> Result<[AltRepeatRule; MAX_ADAPTIVE_RULES], D::Error>
+) -> Result<[AdaptiveRule; MAX_ADAPTIVE_RULES], D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let rules_vec: heapless::Vec<AltRepeatRule, MAX_ADAPTIVE_RULES> =
+    let rules_vec: heapless::Vec<AdaptiveRule, MAX_ADAPTIVE_RULES> =
         Deserialize::deserialize(deserializer)?;
 
-    let mut rules_array: [AltRepeatRule; MAX_ADAPTIVE_RULES] =
-        [AltRepeatRule::EMPTY; MAX_ADAPTIVE_RULES];
+    let mut rules_array: [AdaptiveRule; MAX_ADAPTIVE_RULES] =
+        [AdaptiveRule::EMPTY; MAX_ADAPTIVE_RULES];
     for (i, rule) in rules_vec.iter().enumerate() {
         rules_array[i] = *rule;
     }
@@ -162,12 +466,19 @@ fn output_or_none(output: key::KeyOutput) -> Option<key::KeyOutput> {
     }
 }
 
-/// Config for history keys (alt-repeat lookup table).
+/// Config for history keys (alt-repeat lookup table, adaptive key timing).
 #[derive(Deserialize, Clone, Copy, PartialEq)]
 pub struct Config<const ALT_REPEAT_RULE_COUNT: usize> {
-    /// Sparse map of previous output → alternate output for [Key::AltRepeat].
+    /// Sparse map of previous action → alternate output for [Key::AltRepeat].
     #[serde(deserialize_with = "deserialize_alt_repeat")]
     pub alt_repeat: [AltRepeatRule; ALT_REPEAT_RULE_COUNT],
+
+    /// Maximum time (in ms) between the last output and an adaptive key press
+    ///  for the adaptive key's rules to apply.
+    ///
+    /// After this, adaptive keys emit their default. If `None`, there is no limit.
+    #[serde(default)]
+    pub adaptive_timeout: Option<u16>,
 }
 
 struct AltRepeatDebugHelper<'a, const N: usize> {
@@ -200,6 +511,7 @@ impl<const ALT_REPEAT_RULE_COUNT: usize> core::fmt::Debug for Config<ALT_REPEAT_
                     rules: &self.alt_repeat,
                 },
             )
+            .field("adaptive_timeout", &self.adaptive_timeout)
             .finish()
     }
 }
@@ -246,11 +558,36 @@ impl<const ALT_REPEAT_RULE_COUNT: usize> Config<ALT_REPEAT_RULE_COUNT> {
     pub const fn new() -> Self {
         Self {
             alt_repeat: [AltRepeatRule::EMPTY; ALT_REPEAT_RULE_COUNT],
+            adaptive_timeout: None,
         }
     }
 
-    /// Looks up the alternate for `prev`, if any rule matches exactly.
-    pub fn lookup_alt(&self, prev: &key::KeyOutput) -> Option<key::KeyOutput> {
+    /// Looks up the alternate for `prev`.
+    ///
+    /// Rules which match `prev` exactly are preferred.
+    /// Otherwise, a modified single output matches the rule for its unmodified
+    ///  key, and the modifiers are applied to the emitted output.
+    pub fn lookup_alt(&self, prev: &Action) -> Option<key::KeyOutput> {
+        self.find_alt(prev).or_else(|| {
+            let output = prev.single_output()?;
+            let key_modifiers = output.key_modifiers();
+            let unmodified = key::KeyOutput::from_usage(output.key_code());
+            if key_modifiers == key::KeyboardModifiers::NONE
+                || unmodified == key::KeyOutput::NO_OUTPUT
+            {
+                return None;
+            }
+
+            self.find_alt(&Action::from_output(unmodified)).map(|emit| {
+                key::KeyOutput::from_usage_with_modifiers(
+                    emit.key_code(),
+                    emit.key_modifiers().union(&key_modifiers),
+                )
+            })
+        })
+    }
+
+    fn find_alt(&self, prev: &Action) -> Option<key::KeyOutput> {
         self.alt_repeat
             .iter()
             .find(|r| r.prev == *prev && **r != AltRepeatRule::EMPTY)
@@ -272,12 +609,48 @@ pub fn is_rememberable(key_output: &key::KeyOutput) -> bool {
     *key_output != key::KeyOutput::NO_OUTPUT
 }
 
-/// Context for history keys: tracks the last rememberable resolved output.
+/// Whether a [key::KeyOutput] is only keyboard modifiers.
+fn is_modifiers_only(key_output: &key::KeyOutput) -> bool {
+    key_output.key_code() == key::KeyUsage::NO_USAGE
+        && key_output.key_modifiers() != key::KeyboardModifiers::NONE
+}
+
+fn with_modifiers(
+    key_output: key::KeyOutput,
+    key_modifiers: key::KeyboardModifiers,
+) -> key::KeyOutput {
+    key::KeyOutput::from_usage_with_modifiers(
+        key_output.key_code(),
+        key_output.key_modifiers().union(&key_modifiers),
+    )
+}
+
+/// An action being replayed by [Key::Repeat].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Replay {
+    action: Action,
+    next: u8,
+}
+
+/// Context for history keys: tracks the last remembered action.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Context<const ALT_REPEAT_RULE_COUNT: usize = 0> {
     /// History / alt-repeat configuration.
     pub config: Config<ALT_REPEAT_RULE_COUNT>,
-    last: Option<key::KeyOutput>,
+    last: Option<Action>,
+    // The last two outputs (most recent first), for adaptive keys.
+    recent_outputs: [Option<key::KeyOutput>; 2],
+    recent_output_time_ms: u32,
+    time_ms: u32,
+    pressed_modifiers: key::KeyboardModifiers,
+    // Whether virtual key presses (e.g. from an automation)
+    //  are appended to the last action.
+    recording_virtual: bool,
+    virtual_modifiers: key::KeyboardModifiers,
+    // The keymap may pass a virtual key press to the context more than once,
+    //  so the virtually pressed output is only remembered once.
+    virtual_pressed: Option<key::KeyOutput>,
+    replay: Option<Replay>,
 }
 
 impl<const ALT_REPEAT_RULE_COUNT: usize> Default for Context<ALT_REPEAT_RULE_COUNT> {
@@ -294,7 +667,18 @@ impl<const ALT_REPEAT_RULE_COUNT: usize> Context<ALT_REPEAT_RULE_COUNT> {
 
     /// Constructs a context from the given config.
     pub const fn from_config(config: Config<ALT_REPEAT_RULE_COUNT>) -> Self {
-        Context { config, last: None }
+        Context {
+            config,
+            last: None,
+            recent_outputs: [None; 2],
+            recent_output_time_ms: 0,
+            time_ms: 0,
+            pressed_modifiers: key::KeyboardModifiers::NONE,
+            recording_virtual: false,
+            virtual_modifiers: key::KeyboardModifiers::NONE,
+            virtual_pressed: None,
+            replay: None,
+        }
     }
 
     /// Clear remembered history (keeps config).
@@ -302,17 +686,154 @@ impl<const ALT_REPEAT_RULE_COUNT: usize> Context<ALT_REPEAT_RULE_COUNT> {
         *self = Self::from_config(self.config);
     }
 
-    /// The last rememberable resolved key output, if any.
-    pub fn last(&self) -> Option<key::KeyOutput> {
+    /// The last remembered action, if any.
+    pub fn last(&self) -> Option<Action> {
         self.last
     }
 
+    /// The last remembered output (ignoring modifier-only outputs), if any.
+    pub fn last_output(&self) -> Option<key::KeyOutput> {
+        self.recent_outputs[0]
+    }
+
+    /// The remembered output before [Self::last_output], if any.
+    pub fn output_before_last(&self) -> Option<key::KeyOutput> {
+        self.recent_outputs[1]
+    }
+
+    /// Whether the last output was recent enough for adaptive keys' rules to apply.
+    pub fn is_within_adaptive_timeout(&self) -> bool {
+        match self.config.adaptive_timeout {
+            Some(timeout) => {
+                self.time_ms.saturating_sub(self.recent_output_time_ms) <= timeout as u32
+            }
+            None => true,
+        }
+    }
+
+    fn remember_recent_output(&mut self, key_output: key::KeyOutput) {
+        if key_output.key_code() == key::KeyUsage::Keyboard(BACKSPACE) {
+            // Backspace erases the last output (e.g. an adaptive rule replacing it),
+            //  so the output before it is the last output again.
+            self.recent_outputs = [self.recent_outputs[1], None];
+        } else if !is_modifiers_only(&key_output) {
+            self.recent_outputs = [Some(key_output), self.recent_outputs[0]];
+            self.recent_output_time_ms = self.time_ms;
+        }
+    }
+
+    /// Updates the context with the given keymap context.
+    pub fn update_keymap_context(
+        &mut self,
+        keymap::KeymapContext {
+            time_ms,
+            pressed_modifiers,
+            ..
+        }: &keymap::KeymapContext,
+    ) {
+        self.time_ms = *time_ms;
+        self.pressed_modifiers = *pressed_modifiers;
+    }
+
+    fn remember_resolved(&mut self, key_output: key::KeyOutput) {
+        self.recording_virtual = false;
+        self.remember_recent_output(key_output);
+
+        let key_output = with_modifiers(
+            key_output,
+            self.pressed_modifiers.union(&self.virtual_modifiers),
+        );
+        self.last = Some(Action::from_output(key_output));
+    }
+
+    fn remember_virtual(&mut self, key_output: key::KeyOutput) {
+        if is_modifiers_only(&key_output) {
+            self.virtual_modifiers = self.virtual_modifiers.union(&key_output.key_modifiers());
+            return;
+        }
+
+        if self.virtual_pressed == Some(key_output) {
+            return;
+        }
+        self.virtual_pressed = Some(key_output);
+
+        self.remember_recent_output(key_output);
+
+        // The keymap context's pressed modifiers may include the automation's
+        //  previous tap, so only modifiers pressed by the automation are applied.
+        let key_output = with_modifiers(key_output, self.virtual_modifiers);
+        if self.recording_virtual {
+            // An automation which overflows the action isn't remembered.
+            if let Some(action) = self.last.as_mut() {
+                if !action.push(key_output) {
+                    self.last = None;
+                }
+            }
+        } else {
+            self.recording_virtual = true;
+            self.last = Some(Action::from_output(key_output));
+        }
+    }
+
+    fn replay_next(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        match self.replay.as_mut() {
+            Some(Replay { action, next }) if (*next as usize) < action.outputs().len() => {
+                let output = action.outputs()[*next as usize];
+                *next += 1;
+                key::automation::instruction_key_events(
+                    key::automation::Instruction::Tap(output),
+                    REPLAY_TAP_DURATION,
+                    key::Event::key_event(keymap_index, Event::ReplayNext),
+                )
+            }
+            _ => {
+                self.replay = None;
+                key::KeyEvents::no_events()
+            }
+        }
+    }
+
     fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
-        if let key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput { key_output, .. }) = event
-        {
-            if is_rememberable(&key_output) {
-                self.last = Some(key_output);
+        match event {
+            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput { key_output, .. })
+                if is_rememberable(&key_output) =>
+            {
+                self.remember_resolved(key_output);
+            }
+            key::Event::Input(input::Event::Press { .. }) => {
+                self.recording_virtual = false;
+            }
+            key::Event::Input(input::Event::VirtualKeyPress { key_output })
+                if is_rememberable(&key_output) =>
+            {
+                self.remember_virtual(key_output);
+            }
+            key::Event::Input(input::Event::VirtualKeyRelease { key_output })
+                if is_modifiers_only(&key_output) =>
+            {
+                self.virtual_modifiers = self
+                    .virtual_modifiers
+                    .difference(&key_output.key_modifiers());
+            }
+            key::Event::Input(input::Event::VirtualKeyRelease { key_output })
+                if self.virtual_pressed == Some(key_output) =>
+            {
+                self.virtual_pressed = None;
+            }
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::ReplayAction,
+            } => {
+                self.replay = self.last.map(|action| Replay { action, next: 0 });
+                return self.replay_next(keymap_index);
             }
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::ReplayNext,
+            } => {
+                return self.replay_next(keymap_index);
+            }
+            _ => {}
         }
         key::KeyEvents::no_events()
     }
@@ -330,9 +851,26 @@ impl<const ALT_REPEAT_RULE_COUNT: usize> key::Context for Context<ALT_REPEAT_RUL
     }
 }
 
-/// Events for history keys. (None for v1.)
+/// Events for history keys.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Event;
+pub enum Event {
+    /// Replays the taps of the last remembered action.
+    ReplayAction,
+    /// Taps the next output of the action being replayed.
+    ReplayNext,
+    /// Taps an output of an adaptive key's rule which emits several outputs.
+    ///
+    /// Handled by the pressed adaptive key; each output schedules its tap,
+    ///  then the rule's next output.
+    TapOutput {
+        /// The index of the adaptive key.
+        adaptive: u8,
+        /// The index of the rule in the adaptive key.
+        rule: u8,
+        /// The index of the output in the rule's emit.
+        output: u8,
+    },
+}
 
 /// Pending key state type for history keys. (No pending state.)
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -373,6 +911,53 @@ impl<R, Keys, const ALT_REPEAT_RULE_COUNT: usize> System<R, Keys, ALT_REPEAT_RUL
     }
 }
 
+impl<R, Keys: Index<usize, Output = AdaptiveKey>, const ALT_REPEAT_RULE_COUNT: usize>
+    System<R, Keys, ALT_REPEAT_RULE_COUNT>
+{
+    /// Schedules the tap of an output of an adaptive key's rule,
+    ///  followed by the rule's next output (if any).
+    ///
+    /// The taps are all scheduled while the adaptive key is pressed,
+    ///  so releasing the key doesn't cut them short.
+    fn tap_adaptive_output(
+        &self,
+        keymap_index: u16,
+        adaptive: u8,
+        rule: u8,
+        output: u8,
+    ) -> key::KeyEvents<Event> {
+        let outputs = self.keys[adaptive as usize].rules[rule as usize]
+            .emit
+            .outputs();
+        match outputs.get(output as usize) {
+            Some(&key_output) => {
+                let delay = 2 * REPLAY_TAP_DURATION * output as u16;
+                let mut pke = key::KeyEvents::no_events();
+                pke.schedule_event(
+                    delay,
+                    key::Event::Input(input::Event::VirtualKeyPress { key_output }),
+                );
+                pke.schedule_event(
+                    delay + REPLAY_TAP_DURATION,
+                    key::Event::Input(input::Event::VirtualKeyRelease { key_output }),
+                );
+                if (output as usize) + 1 < outputs.len() {
+                    pke.add_event(key::Event::key_event(
+                        keymap_index,
+                        Event::TapOutput {
+                            adaptive,
+                            rule,
+                            output: output + 1,
+                        },
+                    ));
+                }
+                pke
+            }
+            None => key::KeyEvents::no_events(),
+        }
+    }
+}
+
 impl<R, Keys: Default, const ALT_REPEAT_RULE_COUNT: usize> Default
     for System<R, Keys, ALT_REPEAT_RULE_COUNT>
 {
@@ -395,7 +980,7 @@ impl<
 
     fn new_pressed_key(
         &self,
-        _keymap_index: u16,
+        keymap_index: u16,
         context: &Self::Context,
         Ref(key): Ref,
     ) -> (
@@ -403,17 +988,46 @@ impl<
         key::KeyEvents<Self::Event>,
     ) {
         let output = match key {
-            Key::Repeat => context.last(),
+            Key::Repeat => match context.last() {
+                Some(action) if action.single_output().is_none() => {
+                    // Replay the taps of a multi-output action (e.g. a string macro).
+                    return (
+                        key::PressedKeyResult::Resolved(KeyState::new(None)),
+                        key::KeyEvents::event(key::Event::key_event(
+                            keymap_index,
+                            Event::ReplayAction,
+                        )),
+                    );
+                }
+                last => last.and_then(|action| action.single_output()),
+            },
             Key::AltRepeat => context
                 .last()
                 .and_then(|last| context.config.lookup_alt(&last)),
             Key::Adaptive(index) => {
                 let spec = &self.keys[index as usize];
-                let raw = context
-                    .last()
-                    .and_then(|last| spec.lookup(&last))
-                    .unwrap_or(spec.default);
-                output_or_none(raw)
+                let rule = context
+                    .last_output()
+                    .filter(|_| context.is_within_adaptive_timeout())
+                    .and_then(|last| {
+                        spec.lookup_rule(&last, context.output_before_last().as_ref())
+                    });
+                match rule.map(|rule| (rule, spec.rules[rule].emit.outputs())) {
+                    Some((_, &[output])) => output_or_none(output),
+                    Some((rule, _)) => {
+                        // Tap each of several outputs in turn.
+                        let pke = key::KeyEvents::event(key::Event::key_event(
+                            keymap_index,
+                            Event::TapOutput {
+                                adaptive: index,
+                                rule: rule as u8,
+                                output: 0,
+                            },
+                        ));
+                        return (key::PressedKeyResult::Resolved(KeyState::new(None)), pke);
+                    }
+                    None => output_or_none(spec.default),
+                }
             }
         };
         (
@@ -433,6 +1047,33 @@ impl<
         panic!()
     }
 
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        &Ref(key): &Self::Ref,
+        _context: &Self::Context,
+        keymap_index: u16,
+        event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        match (key, event) {
+            (
+                Key::Adaptive(index),
+                key::Event::Key {
+                    keymap_index: ev_keymap_index,
+                    key_event:
+                        Event::TapOutput {
+                            adaptive,
+                            rule,
+                            output,
+                        },
+                },
+            ) if ev_keymap_index == keymap_index && adaptive == index => {
+                self.tap_adaptive_output(keymap_index, adaptive, rule, output)
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+
     fn key_output(
         &self,
         _key_ref: &Self::Ref,
@@ -455,7 +1096,7 @@ mod tests {
 
     #[test]
     fn test_sizeof_event() {
-        assert_eq!(0, core::mem::size_of::<Event>());
+        assert_eq!(4, core::mem::size_of::<Event>());
     }
 
     #[test]
@@ -472,14 +1113,14 @@ mod tests {
             }),
         );
 
-        assert_eq!(Some(key_output), ctx.last());
+        assert_eq!(Some(Action::from_output(key_output)), ctx.last());
     }
 
     #[test]
     fn context_ignores_empty_output() {
         let mut ctx = Context::<0>::new();
         let remembered = key::KeyOutput::from_key_code(0x04);
-        ctx.last = Some(remembered);
+        ctx.last = Some(Action::from_output(remembered));
 
         let _ = key::Context::handle_event(
             &mut ctx,
@@ -489,7 +1130,7 @@ mod tests {
             }),
         );
 
-        assert_eq!(Some(remembered), ctx.last());
+        assert_eq!(Some(Action::from_output(remembered)), ctx.last());
     }
 
     #[test]
@@ -497,7 +1138,7 @@ mod tests {
         let system = System::<()>::new([]);
         let mut ctx = Context::<0>::new();
         let key_output = key::KeyOutput::from_key_code(0x05);
-        ctx.last = Some(key_output);
+        ctx.last = Some(Action::from_output(key_output));
 
         let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Repeat));
         let ks = pkr.unwrap_resolved();
@@ -509,10 +1150,11 @@ mod tests {
         let left = key::KeyOutput::from_key_code(0x50);
         let right = key::KeyOutput::from_key_code(0x4F);
         let config = Config {
-            alt_repeat: [AltRepeatRule::new(left, right)],
+            alt_repeat: [AltRepeatRule::new(Action::from_output(left), right)],
+            adaptive_timeout: None,
         };
         let mut ctx = Context::from_config(config);
-        ctx.last = Some(left);
+        ctx.last = Some(Action::from_output(left));
 
         let system = System::<(), [AdaptiveKey; 0], 1>::new([]);
         let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::AltRepeat));
@@ -524,7 +1166,7 @@ mod tests {
     fn alt_repeat_unmapped_is_none() {
         let system = System::<()>::new([]);
         let mut ctx = Context::<0>::new();
-        ctx.last = Some(key::KeyOutput::from_key_code(0x04));
+        ctx.last = Some(Action::from_output(key::KeyOutput::from_key_code(0x04)));
 
         let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::AltRepeat));
         let ks = pkr.unwrap_resolved();
@@ -539,11 +1181,11 @@ mod tests {
         let u = key::KeyOutput::from_key_code(0x18);
         let keys = [AdaptiveKey::new(
             h,
-            adaptive_rules([AltRepeatRule::new(a, u)]),
+            adaptive_rules([AdaptiveRule::new(a, AdaptiveEmit::from_output(u))]),
         )];
         let system = System::<(), _>::new(keys);
         let mut ctx = Context::<0>::new();
-        ctx.last = Some(a);
+        ctx.recent_outputs = [Some(a), None];
 
         // Act: press adaptive H
         let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Adaptive(0)));
@@ -562,11 +1204,11 @@ mod tests {
         let u = key::KeyOutput::from_key_code(0x18);
         let keys = [AdaptiveKey::new(
             h,
-            adaptive_rules([AltRepeatRule::new(a, u)]),
+            adaptive_rules([AdaptiveRule::new(a, AdaptiveEmit::from_output(u))]),
         )];
         let system = System::<(), _>::new(keys);
         let mut ctx = Context::<0>::new();
-        ctx.last = Some(b);
+        ctx.recent_outputs = [Some(b), None];
 
         // Act: press adaptive H
         let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Adaptive(0)));
@@ -605,4 +1247,442 @@ mod tests {
         // Assert: no output
         assert_eq!(None, system.key_output(&Ref(Key::Adaptive(0)), &ks));
     }
+
+    #[test]
+    fn context_remembers_output_with_pressed_modifiers() {
+        // Assemble: LeftShift is pressed
+        let mut ctx = Context::<0>::new();
+        ctx.update_keymap_context(&keymap::KeymapContext {
+            pressed_modifiers: key::KeyboardModifiers::LEFT_SHIFT,
+            ..keymap::KeymapContext::new()
+        });
+
+        // Act: A is resolved
+        let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index: 1,
+            key_output: key::KeyOutput::from_key_code(0x04),
+        }));
+
+        // Assert: the action is Shift+A
+        let expected =
+            key::KeyOutput::from_key_code_with_modifiers(0x04, key::KeyboardModifiers::LEFT_SHIFT);
+        assert_eq!(Some(Action::from_output(expected)), ctx.last());
+    }
+
+    #[test]
+    fn context_remembers_virtual_key_presses_as_one_action() {
+        // Assemble
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let b = key::KeyOutput::from_key_code(0x05);
+
+        // Act: a key press, then an automation taps A, B
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+        for key_output in [a, b] {
+            let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+                key_output,
+            }));
+            let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyRelease {
+                key_output,
+            }));
+        }
+
+        // Assert
+        assert_eq!(Some(Action::from_outputs(&[a, b])), ctx.last());
+    }
+
+    #[test]
+    fn context_remembers_repeated_virtual_key_event_once() {
+        // Assemble
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let b = key::KeyOutput::from_key_code(0x05);
+
+        // Act: a key press, then an automation taps A, B, A;
+        //  with each virtual key event handled twice.
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+        for key_output in [a, b, a] {
+            for _ in 0..2 {
+                let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+                    key_output,
+                }));
+            }
+            for _ in 0..2 {
+                let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyRelease {
+                    key_output,
+                }));
+            }
+        }
+
+        // Assert
+        assert_eq!(Some(Action::from_outputs(&[a, b, a])), ctx.last());
+    }
+
+    #[test]
+    fn context_press_starts_new_virtual_action() {
+        // Assemble: an automation tapped A
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let b = key::KeyOutput::from_key_code(0x05);
+        let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output: a,
+        }));
+
+        // Act: another key press, then an automation taps B
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 1 }));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output: b,
+        }));
+
+        // Assert
+        assert_eq!(Some(Action::from_output(b)), ctx.last());
+    }
+
+    #[test]
+    fn context_virtual_modifiers_apply_to_virtual_taps() {
+        // Assemble
+        let mut ctx = Context::<0>::new();
+        let shift = key::KeyOutput::from_key_code(0xE1);
+        let a = key::KeyOutput::from_key_code(0x04);
+
+        // Act: automation presses Shift, taps A, releases Shift
+        let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output: shift,
+        }));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output: a,
+        }));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyRelease {
+            key_output: shift,
+        }));
+
+        // Assert
+        let expected =
+            key::KeyOutput::from_key_code_with_modifiers(0x04, key::KeyboardModifiers::LEFT_SHIFT);
+        assert_eq!(Some(Action::from_output(expected)), ctx.last());
+        assert_eq!(key::KeyboardModifiers::NONE, ctx.virtual_modifiers);
+    }
+
+    #[test]
+    fn repeat_multi_output_action_replays_taps() {
+        // Assemble: last action taps A, B
+        let system = System::<()>::new([]);
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let b = key::KeyOutput::from_key_code(0x05);
+        ctx.last = Some(Action::from_outputs(&[a, b]));
+
+        // Act: press Repeat
+        let (pkr, pke) = system.new_pressed_key(3, &ctx, Ref(Key::Repeat));
+
+        // Assert: the key has no output of its own; it starts a replay
+        let ks = pkr.unwrap_resolved();
+        assert_eq!(None, system.key_output(&Ref(Key::Repeat), &ks));
+        assert_eq!(
+            key::KeyEvents::event(key::Event::key_event(3, Event::ReplayAction)),
+            pke
+        );
+
+        // Act: the context handles the replay
+        let pke = ctx.handle_event(key::Event::key_event(3, Event::ReplayAction));
+
+        // Assert: taps A, then schedules the next tap
+        let expected = key::automation::instruction_key_events(
+            key::automation::Instruction::Tap(a),
+            REPLAY_TAP_DURATION,
+            key::Event::key_event(3, Event::ReplayNext),
+        );
+        assert_eq!(expected, pke);
+
+        // Act: the next tap
+        let pke = ctx.handle_event(key::Event::key_event(3, Event::ReplayNext));
+
+        // Assert: taps B
+        let expected = key::automation::instruction_key_events(
+            key::automation::Instruction::Tap(b),
+            REPLAY_TAP_DURATION,
+            key::Event::key_event(3, Event::ReplayNext),
+        );
+        assert_eq!(expected, pke);
+
+        // Act/Assert: the replay finishes
+        let pke = ctx.handle_event(key::Event::key_event(3, Event::ReplayNext));
+        assert_eq!(key::KeyEvents::no_events(), pke);
+    }
+
+    #[test]
+    fn alt_repeat_looks_up_multi_output_action() {
+        let t = key::KeyOutput::from_key_code(0x17);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let e = key::KeyOutput::from_key_code(0x08);
+        let space = key::KeyOutput::from_key_code(0x2C);
+        let config = Config {
+            alt_repeat: [AltRepeatRule::new(Action::from_outputs(&[t, h, e]), space)],
+            adaptive_timeout: None,
+        };
+
+        assert_eq!(
+            Some(space),
+            config.lookup_alt(&Action::from_outputs(&[t, h, e]))
+        );
+        assert_eq!(None, config.lookup_alt(&Action::from_outputs(&[t, h])));
+    }
+
+    #[test]
+    fn alt_repeat_modified_output_uses_unmodified_rule() {
+        let left = key::KeyOutput::from_key_code(0x50);
+        let right = key::KeyOutput::from_key_code(0x4F);
+        let config = Config {
+            alt_repeat: [AltRepeatRule::new(Action::from_output(left), right)],
+            adaptive_timeout: None,
+        };
+
+        let shift_left =
+            key::KeyOutput::from_key_code_with_modifiers(0x50, key::KeyboardModifiers::LEFT_SHIFT);
+        let shift_right =
+            key::KeyOutput::from_key_code_with_modifiers(0x4F, key::KeyboardModifiers::LEFT_SHIFT);
+        assert_eq!(
+            Some(shift_right),
+            config.lookup_alt(&Action::from_output(shift_left))
+        );
+    }
+
+    #[test]
+    fn action_deserializes_from_output_or_list() {
+        let single = serde_json::from_str::<Action>(r#"{"key_code":{"Keyboard":4}}"#).ok();
+        assert_eq!(
+            Some(Action::from_output(key::KeyOutput::from_key_code(0x04))),
+            single
+        );
+
+        let list = serde_json::from_str::<Action>(
+            r#"[{"key_code":{"Keyboard":4}},{"key_code":{"Keyboard":5}}]"#,
+        )
+        .ok();
+        assert_eq!(
+            Some(Action::from_outputs(&[
+                key::KeyOutput::from_key_code(0x04),
+                key::KeyOutput::from_key_code(0x05)
+            ])),
+            list
+        );
+    }
+
+    #[test]
+    fn adaptive_prefers_rule_for_last_two_outputs() {
+        // Assemble: H with A → U, and E, A → I
+        let a = key::KeyOutput::from_key_code(0x04);
+        let e = key::KeyOutput::from_key_code(0x08);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let i = key::KeyOutput::from_key_code(0x0C);
+        let u = key::KeyOutput::from_key_code(0x18);
+        let keys = [AdaptiveKey::new(
+            h,
+            adaptive_rules([
+                AdaptiveRule::new(a, AdaptiveEmit::from_output(u)),
+                AdaptiveRule::new_after(e, a, AdaptiveEmit::from_output(i)),
+            ]),
+        )];
+        let system = System::<(), _>::new(keys);
+        let mut ctx = Context::<0>::new();
+
+        // Act: last outputs were E, A
+        ctx.recent_outputs = [Some(a), Some(e)];
+        let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Adaptive(0)));
+        let ks = pkr.unwrap_resolved();
+
+        // Assert: the two-output rule applies
+        assert_eq!(Some(i), system.key_output(&Ref(Key::Adaptive(0)), &ks));
+
+        // Act: last outputs were H, A
+        ctx.recent_outputs = [Some(a), Some(h)];
+        let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Adaptive(0)));
+        let ks = pkr.unwrap_resolved();
+
+        // Assert: the one-output rule applies
+        assert_eq!(Some(u), system.key_output(&Ref(Key::Adaptive(0)), &ks));
+    }
+
+    #[test]
+    fn adaptive_after_timeout_uses_default() {
+        // Assemble: H with A → U, timeout of 100ms
+        let a = key::KeyOutput::from_key_code(0x04);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let u = key::KeyOutput::from_key_code(0x18);
+        let keys = [AdaptiveKey::new(
+            h,
+            adaptive_rules([AdaptiveRule::new(a, AdaptiveEmit::from_output(u))]),
+        )];
+        let system = System::<(), _>::new(keys);
+        let mut ctx = Context::<0>::from_config(Config {
+            adaptive_timeout: Some(100),
+            ..Config::new()
+        });
+        ctx.update_keymap_context(&keymap::KeymapContext {
+            time_ms: 50,
+            ..keymap::KeymapContext::new()
+        });
+        let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index: 1,
+            key_output: a,
+        }));
+
+        // Act: press adaptive H after the timeout
+        ctx.update_keymap_context(&keymap::KeymapContext {
+            time_ms: 200,
+            ..keymap::KeymapContext::new()
+        });
+        let (pkr, _) = system.new_pressed_key(0, &ctx, Ref(Key::Adaptive(0)));
+        let ks = pkr.unwrap_resolved();
+
+        // Assert: emits default H
+        assert_eq!(Some(h), system.key_output(&Ref(Key::Adaptive(0)), &ks));
+    }
+
+    #[test]
+    fn adaptive_multiple_outputs_are_tapped() {
+        // Assemble: H with A → Backspace, U
+        let a = key::KeyOutput::from_key_code(0x04);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let u = key::KeyOutput::from_key_code(0x18);
+        let backspace = key::KeyOutput::from_key_code(0x2A);
+        let keys = [AdaptiveKey::new(
+            h,
+            adaptive_rules([AdaptiveRule::new(
+                a,
+                AdaptiveEmit::from_outputs(&[backspace, u]),
+            )]),
+        )];
+        let system = System::<(), _>::new(keys);
+        let mut ctx = Context::<0>::new();
+        ctx.recent_outputs = [Some(a), None];
+
+        // Act
+        let (pkr, pke) = system.new_pressed_key(2, &ctx, Ref(Key::Adaptive(0)));
+
+        // Assert: no held output; the rule's first output is tapped
+        let ks = pkr.unwrap_resolved();
+        assert_eq!(None, system.key_output(&Ref(Key::Adaptive(0)), &ks));
+        assert_eq!(
+            key::KeyEvents::event(key::Event::key_event(
+                2,
+                Event::TapOutput {
+                    adaptive: 0,
+                    rule: 0,
+                    output: 0,
+                },
+            )),
+            pke
+        );
+    }
+
+    #[test]
+    fn adaptive_tap_output_taps_then_schedules_next_output() {
+        // Assemble: H with A → Backspace, U
+        let a = key::KeyOutput::from_key_code(0x04);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let u = key::KeyOutput::from_key_code(0x18);
+        let backspace = key::KeyOutput::from_key_code(0x2A);
+        let keys = [AdaptiveKey::new(
+            h,
+            adaptive_rules([AdaptiveRule::new(
+                a,
+                AdaptiveEmit::from_outputs(&[backspace, u]),
+            )]),
+        )];
+        let system = System::<(), _>::new(keys);
+        let ctx = Context::<0>::new();
+        let mut ks = KeyState::new(None);
+        let tap_output = |output| {
+            key::Event::key_event(
+                2,
+                Event::TapOutput {
+                    adaptive: 0,
+                    rule: 0,
+                    output,
+                },
+            )
+        };
+
+        // Act
+        let first_pke =
+            system.update_state(&mut ks, &Ref(Key::Adaptive(0)), &ctx, 2, tap_output(0));
+        let last_pke = system.update_state(&mut ks, &Ref(Key::Adaptive(0)), &ctx, 2, tap_output(1));
+
+        // Assert: each output is tapped in turn
+        let mut expected_first = key::KeyEvents::no_events();
+        expected_first.schedule_event(
+            0,
+            key::Event::Input(input::Event::VirtualKeyPress {
+                key_output: backspace,
+            }),
+        );
+        expected_first.schedule_event(
+            REPLAY_TAP_DURATION,
+            key::Event::Input(input::Event::VirtualKeyRelease {
+                key_output: backspace,
+            }),
+        );
+        expected_first.add_event(tap_output(1));
+        assert_eq!(expected_first, first_pke);
+        let mut expected_last = key::KeyEvents::no_events();
+        expected_last.schedule_event(
+            2 * REPLAY_TAP_DURATION,
+            key::Event::Input(input::Event::VirtualKeyPress { key_output: u }),
+        );
+        expected_last.schedule_event(
+            3 * REPLAY_TAP_DURATION,
+            key::Event::Input(input::Event::VirtualKeyRelease { key_output: u }),
+        );
+        assert_eq!(expected_last, last_pke);
+    }
+
+    #[test]
+    fn context_backspace_erases_last_output_for_adaptive() {
+        // Assemble
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let h = key::KeyOutput::from_key_code(0x0B);
+        let u = key::KeyOutput::from_key_code(0x18);
+        let backspace = key::KeyOutput::from_key_code(0x2A);
+        let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index: 0,
+            key_output: a,
+        }));
+        let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index: 1,
+            key_output: h,
+        }));
+
+        // Act: an adaptive rule replaces H with U
+        for key_output in [backspace, u] {
+            let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyPress {
+                key_output,
+            }));
+            let _ = ctx.handle_event(key::Event::Input(input::Event::VirtualKeyRelease {
+                key_output,
+            }));
+        }
+
+        // Assert
+        assert_eq!(Some(u), ctx.last_output());
+        assert_eq!(Some(a), ctx.output_before_last());
+    }
+
+    #[test]
+    fn context_ignores_modifiers_only_output_for_adaptive() {
+        let mut ctx = Context::<0>::new();
+        let a = key::KeyOutput::from_key_code(0x04);
+        let shift = key::KeyOutput::from_key_code(0xE1);
+
+        for key_output in [a, shift] {
+            let _ = ctx.handle_event(key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index: 0,
+                key_output,
+            }));
+        }
+
+        assert_eq!(Some(a), ctx.last_output());
+        assert_eq!(None, ctx.output_before_last());
+    }
 }
@@ -1,12 +1,11 @@
 //! Key Lock: hold the next key until it is pressed again.
 //!
-//! After arming with `Key::KeyLock`, the next resolved keyboard key output is
-//! kept held via a virtual key press. Pressing a key that produces the same
-//! output again releases the lock.
+//! After arming with `Key::KeyLock`, the next pressed key is kept held
+//! (its physical release is deferred; see [keymap::KeymapEvent::DeferRelease]).
+//! This works for any key: keyboard keys, mouse buttons, layer modifiers, etc.
 //!
-//! Only one output is locked at a time (QMK-style). Arming again and locking a
-//! different key replaces the previous lock. Simultaneous multi-lock can be
-//! added later if needed.
+//! Up to [MAX_LOCKS] keys can be locked at once; arm again to lock another key.
+//! How locked keys are released is set by [Config::release].
 
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -16,33 +15,79 @@ use serde::Deserialize;
 use crate::input;
 use crate::key;
 use crate::keymap;
+use crate::split;
+
+/// The maximum number of simultaneously locked keys.
+pub const MAX_LOCKS: usize = 4;
 
 /// Reference for a key lock key.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Ref(pub Key);
 
+/// How locked keys are released.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Release {
+    /// A locked key is released when it is pressed again
+    ///  (or when another key with the same output is pressed).
+    #[default]
+    OnPressAgain,
+    /// Locked keys stay locked when pressed again;
+    ///  pressing the key lock key again (while it is watching)
+    ///  releases all locked keys.
+    OnKeyLock,
+}
+
+/// Key Lock configuration.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// How locked keys are released.
+    #[serde(default)]
+    pub release: Release,
+}
+
+impl Config {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        Self {
+            release: Release::OnPressAgain,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Whether a resolved [key::KeyOutput] can be locked.
 ///
-/// Only non-empty keyboard usage outputs are lockable.
+/// Keyboard and mouse outputs are lockable.
+///  (Keys which resolve without output, such as layer modifiers, are also locked).
 pub fn is_lockable(key_output: &key::KeyOutput) -> bool {
     *key_output != key::KeyOutput::NO_OUTPUT
-        && matches!(key_output.key_code(), key::KeyUsage::Keyboard(_))
+        && matches!(
+            key_output.key_code(),
+            key::KeyUsage::Keyboard(_) | key::KeyUsage::Mouse(_)
+        )
 }
 
-/// Key awaiting physical release before its virtual lock is applied.
+/// A locked key.
 #[derive(Debug, Clone, Copy, PartialEq)]
-struct PendingLock {
-    keymap_index: u16,
-    key_output: key::KeyOutput,
+pub struct LockedKey {
+    /// The keymap index of the locked key.
+    pub keymap_index: u16,
+    /// The output the locked key resolved to (if any).
+    pub key_output: Option<key::KeyOutput>,
 }
 
-/// Key Lock context: watching arm and at most one active virtual lock.
+/// Key Lock context: watching arm and the locked keys.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Context {
-    watching: bool,
-    /// Next key to lock once its physical key is released (avoids duplicate HID codes).
-    pending_lock: Option<PendingLock>,
-    locked: Option<key::KeyOutput>,
+    config: Config,
+    /// The keymap index of the key lock key which is watching for the next key.
+    watching: Option<u16>,
+    locked: [Option<LockedKey>; MAX_LOCKS],
 }
 
 impl Default for Context {
@@ -52,116 +97,172 @@ impl Default for Context {
 }
 
 impl Context {
-    /// Constructs a new [Context].
+    /// Constructs a new [Context] with the default [Config].
     pub const fn new() -> Self {
+        Self::from_config(Config::new())
+    }
+
+    /// Constructs a new [Context] with the given [Config].
+    pub const fn from_config(config: Config) -> Self {
         Context {
-            watching: false,
-            pending_lock: None,
-            locked: None,
+            config,
+            watching: None,
+            locked: [None; MAX_LOCKS],
         }
     }
 
-    /// Clear watching and the locked key (does not emit virtual releases).
+    /// Clear watching and the locked keys (does not emit releases).
     pub fn reset(&mut self) {
-        *self = Self::new();
+        *self = Self::from_config(self.config);
     }
 
     /// Whether key lock is watching for the next key to lock.
     pub fn is_watching(&self) -> bool {
-        self.watching
+        self.watching.is_some()
+    }
+
+    /// The currently locked keys.
+    pub fn locked_keys(&self) -> impl Iterator<Item = &LockedKey> {
+        self.locked.iter().flatten()
+    }
+
+    /// The number of currently locked keys.
+    pub fn locked_count(&self) -> usize {
+        self.locked_keys().count()
     }
 
     /// Whether `key_output` is currently locked.
     pub fn is_locked(&self, key_output: &key::KeyOutput) -> bool {
-        self.locked.as_ref() == Some(key_output)
-    }
-
-    /// Apply a pending lock after physical release.
-    ///
-    /// Replaces any previously locked output (releasing it virtually first).
-    fn commit_lock(&mut self, key_output: key::KeyOutput) -> key::KeyEvents<Event> {
-        match self.locked {
-            Some(existing) if existing == key_output => key::KeyEvents::no_events(),
-            Some(existing) => {
-                self.locked = Some(key_output);
-                let mut pke =
-                    key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyRelease {
-                        key_output: existing,
-                    }));
-                pke.add_event(key::Event::Input(input::Event::VirtualKeyPress {
-                    key_output,
-                }));
-                pke
-            }
-            None => {
-                self.locked = Some(key_output);
-                key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
-                    key_output,
+        self.locked_keys()
+            .any(|lk| lk.key_output.as_ref() == Some(key_output))
+    }
+
+    /// Whether the key at `keymap_index` is currently locked.
+    pub fn is_key_locked(&self, keymap_index: u16) -> bool {
+        self.locked_keys().any(|lk| lk.keymap_index == keymap_index)
+    }
+
+    fn locked_slot(&mut self, keymap_index: u16) -> Option<&mut Option<LockedKey>> {
+        self.locked
+            .iter_mut()
+            .find(|slot| matches!(slot, Some(lk) if lk.keymap_index == keymap_index))
+    }
+
+    /// Lock the key, deferring its physical release.
+    fn lock(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        match self.locked.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(LockedKey {
+                    keymap_index,
+                    key_output: None,
+                });
+                key::KeyEvents::event(key::Event::Keymap(keymap::KeymapEvent::DeferRelease {
+                    keymap_index,
                 }))
             }
+            // All slots are in use; the key is not locked.
+            None => key::KeyEvents::no_events(),
         }
     }
 
-    /// Clear the lock if it matches `key_output`; returns whether it was unlocked.
-    fn unlock_if_matches(&mut self, key_output: &key::KeyOutput) -> bool {
-        match self.locked {
-            Some(locked) if locked == *key_output => {
-                self.locked = None;
-                true
+    /// Unlock the key, releasing it.
+    fn unlock(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        match self.locked_slot(keymap_index) {
+            Some(slot) => {
+                *slot = None;
+                key::KeyEvents::event(key::Event::Input(input::Event::Release { keymap_index }))
+            }
+            None => key::KeyEvents::no_events(),
+        }
+    }
+
+    /// Unlock all the locked keys.
+    fn unlock_all(&mut self) -> key::KeyEvents<Event> {
+        let mut pke = key::KeyEvents::no_events();
+        self.locked.iter_mut().for_each(|slot| {
+            if let Some(LockedKey { keymap_index, .. }) = slot.take() {
+                pke.add_event(key::Event::Input(input::Event::Release { keymap_index }));
+            }
+        });
+        pke
+    }
+
+    fn toggle_watching(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        // Another key lock key pressed while watching was locked as the next key;
+        //  treat it as pressing the key lock key again.
+        let was_watching = self.watching.is_some() || self.is_key_locked(keymap_index);
+        let mut pke = self.unlock(keymap_index);
+
+        if was_watching {
+            self.watching = None;
+            if self.config.release == Release::OnKeyLock {
+                pke.extend(self.unlock_all());
             }
-            _ => false,
+        } else {
+            self.watching = Some(keymap_index);
         }
+
+        pke
     }
 
     fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
         match event {
             key::Event::Key {
+                keymap_index,
                 key_event: Event::ToggleWatching,
-                ..
-            } => {
-                self.watching = !self.watching;
-                // Cancelling watch also drops a pending lock that was never committed.
-                if !self.watching {
-                    self.pending_lock = None;
+            } => self.toggle_watching(keymap_index),
+            key::Event::Input(input::Event::Press { keymap_index })
+                if self.is_key_locked(keymap_index) =>
+            {
+                match self.config.release {
+                    Release::OnPressAgain => self.unlock(keymap_index),
+                    // Keep the key held through the release of this press.
+                    Release::OnKeyLock => key::KeyEvents::event(key::Event::Keymap(
+                        keymap::KeymapEvent::DeferRelease { keymap_index },
+                    )),
+                }
+            }
+            key::Event::Input(input::Event::Press { keymap_index }) => match self.watching {
+                // Pressing the key lock key which is watching is handled by its event.
+                Some(watching_index) if watching_index != keymap_index => {
+                    self.watching = None;
+                    self.lock(keymap_index)
+                }
+                _ => key::KeyEvents::no_events(),
+            },
+            key::Event::Keymap(keymap::KeymapEvent::ReleaseNotDeferred { keymap_index }) => {
+                // The key's release will not be held back, so it isn't locked.
+                if let Some(slot) = self.locked_slot(keymap_index) {
+                    *slot = None;
                 }
                 key::KeyEvents::no_events()
             }
             key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
                 keymap_index,
                 key_output,
-            }) => match (self.watching, is_lockable(&key_output)) {
-                (true, true) => {
-                    // Defer virtual press until physical release so the HID
-                    // report does not carry a duplicate key code.
-                    self.watching = false;
-                    self.pending_lock = Some(PendingLock {
-                        keymap_index,
-                        key_output,
-                    });
-                    key::KeyEvents::no_events()
-                }
-                (true, false) => {
-                    // Non-lockable keys cancel watching without locking.
-                    self.watching = false;
+            }) => {
+                if let Some(Some(lk)) = self.locked_slot(keymap_index) {
+                    if is_lockable(&key_output) {
+                        lk.key_output = Some(key_output);
+                        key::KeyEvents::no_events()
+                    } else {
+                        // Non-lockable outputs are not kept locked.
+                        self.unlock(keymap_index)
+                    }
+                } else if self.config.release == Release::OnPressAgain {
+                    // Another key with the same output releases the lock.
+                    let same_output_index = self
+                        .locked_keys()
+                        .find(|lk| lk.key_output == Some(key_output))
+                        .map(|lk| lk.keymap_index);
+                    match same_output_index {
+                        Some(locked_index) => self.unlock(locked_index),
+                        None => key::KeyEvents::no_events(),
+                    }
+                } else {
                     key::KeyEvents::no_events()
                 }
-                (false, true) if self.unlock_if_matches(&key_output) => {
-                    key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyRelease {
-                        key_output,
-                    }))
-                }
-                (false, _) => key::KeyEvents::no_events(),
-            },
-            key::Event::Input(input::Event::Release { keymap_index }) => match self.pending_lock {
-                Some(PendingLock {
-                    keymap_index: pending_index,
-                    key_output,
-                }) if pending_index == keymap_index => {
-                    self.pending_lock = None;
-                    self.commit_lock(key_output)
-                }
-                _ => key::KeyEvents::no_events(),
-            },
+            }
             _ => key::KeyEvents::no_events(),
         }
     }
@@ -179,6 +280,14 @@ impl key::Context for Context {
     }
 }
 
+impl keymap::SplitStatusContext for Context {
+    fn export_split_status(&self, status: &mut split::Status) {
+        status.key_lock_watching = self.is_watching();
+        status.key_locked = self.locked_count() > 0;
+        status.key_lock_count = self.locked_count() as u8;
+    }
+}
+
 /// Key Lock events.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Event {
@@ -344,18 +453,148 @@ mod tests {
         assert!(!ctx.is_watching());
     }
 
+    fn press(keymap_index: u16) -> key::Event<Event> {
+        key::Event::Input(input::Event::Press { keymap_index })
+    }
+
+    fn resolved(keymap_index: u16, key_output: key::KeyOutput) -> key::Event<Event> {
+        key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            keymap_index,
+            key_output,
+        })
+    }
+
+    fn tap_key_lock(ctx: &mut Context) -> key::KeyEvents<Event> {
+        let _ = key::Context::handle_event(ctx, press(0));
+        key::Context::handle_event(ctx, key::Event::key_event(0, Event::ToggleWatching))
+    }
+
+    fn lock_key(ctx: &mut Context, keymap_index: u16) {
+        let _ = tap_key_lock(ctx);
+        let _ = key::Context::handle_event(ctx, press(keymap_index));
+    }
+
+    fn released_indices(pke: key::KeyEvents<Event>) -> heapless::Vec<u16, 4> {
+        pke.into_iter()
+            .filter_map(|key::ScheduledEvent { event, .. }| match event {
+                key::Event::Input(input::Event::Release { keymap_index }) => Some(keymap_index),
+                _ => None,
+            })
+            .collect()
+    }
+
     #[test]
     fn non_lockable_cancels_watching() {
         let mut ctx = Context::new();
-        let _ =
-            key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::ToggleWatching));
+        let _ = tap_key_lock(&mut ctx);
+        let _ = key::Context::handle_event(&mut ctx, press(1));
         let _ = key::Context::handle_event(
             &mut ctx,
-            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+            resolved(1, key::KeyOutput::from_consumer_code(0x01)),
+        );
+        assert!(!ctx.is_watching());
+        assert_eq!(0, ctx.locked_count());
+    }
+
+    #[test]
+    fn press_while_watching_defers_release() {
+        let mut ctx = Context::new();
+        let _ = tap_key_lock(&mut ctx);
+
+        let pke = key::Context::handle_event(&mut ctx, press(1));
+
+        assert_eq!(
+            Some(key::Event::Keymap(keymap::KeymapEvent::DeferRelease {
+                keymap_index: 1
+            })),
+            pke.into_iter().map(|sch_ev| sch_ev.event).next()
+        );
+        assert!(ctx.is_key_locked(1));
+    }
+
+    #[test]
+    fn release_not_deferred_does_not_lock_key() {
+        let mut ctx = Context::new();
+        lock_key(&mut ctx, 1);
+
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::Keymap(keymap::KeymapEvent::ReleaseNotDeferred { keymap_index: 1 }),
+        );
+
+        assert!(released_indices(pke).is_empty());
+        assert!(!ctx.is_key_locked(1));
+        assert_eq!(0, ctx.locked_count());
+    }
+
+    #[test]
+    fn key_without_output_stays_locked() {
+        // e.g. a layer modifier
+        let mut ctx = Context::new();
+
+        lock_key(&mut ctx, 1);
+
+        assert_eq!(
+            Some(&LockedKey {
                 keymap_index: 1,
-                key_output: key::KeyOutput::from_consumer_code(0x01),
+                key_output: None
             }),
+            ctx.locked_keys().next()
         );
+    }
+
+    #[test]
+    fn locks_multiple_keys() {
+        let mut ctx = Context::new();
+
+        lock_key(&mut ctx, 1);
+        let _ =
+            key::Context::handle_event(&mut ctx, resolved(1, key::KeyOutput::from_key_code(0xE1)));
+        lock_key(&mut ctx, 2);
+
+        assert_eq!(2, ctx.locked_count());
+        assert!(ctx.is_locked(&key::KeyOutput::from_key_code(0xE1)));
+    }
+
+    #[test]
+    fn press_again_releases_only_that_key() {
+        let mut ctx = Context::new();
+        lock_key(&mut ctx, 1);
+        lock_key(&mut ctx, 2);
+
+        let pke = key::Context::handle_event(&mut ctx, press(1));
+
+        assert_eq!(&[1], released_indices(pke).as_slice());
+        assert!(!ctx.is_key_locked(1));
+        assert!(ctx.is_key_locked(2));
+    }
+
+    #[test]
+    fn on_key_lock_release_keeps_key_locked_when_pressed_again() {
+        let mut ctx = Context::from_config(Config {
+            release: Release::OnKeyLock,
+        });
+        lock_key(&mut ctx, 1);
+
+        let pke = key::Context::handle_event(&mut ctx, press(1));
+
+        assert!(released_indices(pke).is_empty());
+        assert!(ctx.is_key_locked(1));
+    }
+
+    #[test]
+    fn on_key_lock_release_releases_all_when_key_lock_pressed_again() {
+        let mut ctx = Context::from_config(Config {
+            release: Release::OnKeyLock,
+        });
+        lock_key(&mut ctx, 1);
+        lock_key(&mut ctx, 2);
+
+        let _ = tap_key_lock(&mut ctx);
+        let pke = tap_key_lock(&mut ctx);
+
+        assert_eq!(&[1, 2], released_indices(pke).as_slice());
+        assert_eq!(0, ctx.locked_count());
         assert!(!ctx.is_watching());
     }
 }
@@ -0,0 +1,529 @@
+//! Key repeat: typematic repeat for held keys, emulated by the keymap.
+//!
+//! Hosts repeat keys which are held in the HID report,
+//!  but outputs which are synthesised on press
+//!  (e.g. a [history](crate::key::history) `Repeat` key, a string macro,
+//!  or a [tri-state](crate::key::tri_state) tap) are not repeated that way.
+//!
+//! A [Key] wraps another key, opting it in to key repeat.
+//!  (A keymap.ncl can opt in whole families with `config.key_repeat.families`,
+//!  which wraps each key of those families).
+//! While the wrapped key is held, after [Config::delay],
+//!  and then every [Config::interval],
+//!  the [Context] taps the key's synthesised output again
+//!  (with [crate::input::Event::VirtualKeyPress] / [crate::input::Event::VirtualKeyRelease]).
+//! The wrapped key's physical input is not replayed.
+//!
+//! The repeated output is the last output the keymap synthesised
+//!  while the wrapped key was held;
+//!  so for a key which types several outputs (e.g. a string macro),
+//!  its last output repeats.
+//! Keys whose output is held in the HID report are left to the host to repeat.
+//!
+//! Repeat timing uses scheduled events,
+//!  so it is driven by [crate::keymap::Keymap::tick]
+//!  and [crate::keymap::Keymap::tick_to_next_scheduled_event].
+//!
+//! As with typematic repeat, only the most recently pressed key repeats;
+//!  pressing another key stops the repeat.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::Index;
+
+use serde::Deserialize;
+
+use crate::input;
+use crate::key;
+
+/// Reference for a key repeat key (index into [System] key data).
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ref(pub u8);
+
+/// A key which repeats the wrapped key while held.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Key<R> {
+    /// The wrapped key.
+    pub key: R,
+}
+
+impl<R> Key<R> {
+    /// Constructs a key repeat key wrapping the given key.
+    pub const fn new(key: R) -> Self {
+        Self { key }
+    }
+}
+
+/// Default delay (in ms) before the first repeat.
+pub const DEFAULT_DELAY: u16 = 500;
+
+/// Default interval (in ms) between repeats.
+pub const DEFAULT_INTERVAL: u16 = 33;
+
+/// Key repeat configuration.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Time (in ms) the key is held before it first repeats.
+    #[serde(default = "default_delay")]
+    pub delay: u16,
+
+    /// Time (in ms) between repeats.
+    ///
+    /// Each repeated output is held for the first half of the interval.
+    #[serde(default = "default_interval")]
+    pub interval: u16,
+}
+
+fn default_delay() -> u16 {
+    DEFAULT_DELAY
+}
+
+fn default_interval() -> u16 {
+    DEFAULT_INTERVAL
+}
+
+impl Config {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        Self {
+            delay: DEFAULT_DELAY,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Time (in ms) a repeated output is held before it is released.
+    const fn tap_time(&self) -> u16 {
+        if self.interval > 1 {
+            self.interval / 2
+        } else {
+            1
+        }
+    }
+
+    /// Time (in ms) from one repeat to the next.
+    const fn repeat_time(&self) -> u16 {
+        if self.interval > 1 {
+            self.interval
+        } else {
+            1
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The key which is currently repeating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Repeating {
+    keymap_index: u16,
+    /// The last output synthesised while the key is held.
+    key_output: Option<key::KeyOutput>,
+}
+
+/// Key repeat context.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Context {
+    config: Config,
+    repeating: Option<Repeating>,
+    /// Incremented whenever the repeat is (re)armed; stale timer events are ignored.
+    generation: u8,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::from_config(Config::new())
+    }
+}
+
+impl Context {
+    /// Constructs a context from the given config.
+    pub const fn from_config(config: Config) -> Self {
+        Self {
+            config,
+            repeating: None,
+            generation: 0,
+        }
+    }
+
+    /// Clear runtime state, keeping the config.
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// The keymap index of the key which is repeating, if any.
+    pub fn repeating_keymap_index(&self) -> Option<u16> {
+        self.repeating.map(|r| r.keymap_index)
+    }
+
+    fn schedule(keymap_index: u16, delay: u16, event: Event) -> key::KeyEvents<Event> {
+        key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+            delay,
+            key::Event::key_event(keymap_index, event),
+        ))
+    }
+
+    fn arm(&mut self, keymap_index: u16) -> key::KeyEvents<Event> {
+        self.generation = self.generation.wrapping_add(1);
+        self.repeating = Some(Repeating {
+            keymap_index,
+            key_output: None,
+        });
+        let generation = self.generation;
+        Self::schedule(
+            keymap_index,
+            self.config.delay,
+            Event::Repeat { generation },
+        )
+    }
+
+    /// Taps the output, and schedules the next repeat.
+    fn repeat(&self, keymap_index: u16, key_output: key::KeyOutput) -> key::KeyEvents<Event> {
+        let mut pke = key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
+            key_output,
+        }));
+        pke.schedule_event(
+            self.config.tap_time(),
+            key::Event::Input(input::Event::VirtualKeyRelease { key_output }),
+        );
+        let generation = self.generation;
+        pke.extend(Self::schedule(
+            keymap_index,
+            self.config.repeat_time(),
+            Event::Repeat { generation },
+        ));
+        pke
+    }
+
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::Pressed,
+            } => self.arm(keymap_index),
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::Repeat { generation },
+            } if generation == self.generation => match self.repeating {
+                Some(Repeating {
+                    keymap_index: ki,
+                    key_output: Some(key_output),
+                }) if ki == keymap_index => self.repeat(keymap_index, key_output),
+                // Nothing synthesised to repeat.
+                _ => key::KeyEvents::no_events(),
+            },
+            key::Event::Input(input::Event::VirtualKeyPress { key_output })
+                if key_output.key_code() != key::KeyUsage::NO_USAGE =>
+            {
+                if let Some(r) = self.repeating.as_mut() {
+                    r.key_output = Some(key_output);
+                }
+                key::KeyEvents::no_events()
+            }
+            key::Event::Input(input::Event::Press { keymap_index }) => {
+                // Pressing another key stops the repeat.
+                if self
+                    .repeating
+                    .is_some_and(|r| r.keymap_index != keymap_index)
+                {
+                    self.repeating = None;
+                }
+                key::KeyEvents::no_events()
+            }
+            key::Event::Input(input::Event::Release { keymap_index }) => {
+                if self
+                    .repeating
+                    .is_some_and(|r| r.keymap_index == keymap_index)
+                {
+                    self.repeating = None;
+                }
+                key::KeyEvents::no_events()
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+}
+
+impl key::Context for Context {
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Key repeat events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// A key repeat key was pressed.
+    Pressed,
+    /// Time to repeat the key's output.
+    Repeat {
+        /// The [Context] generation when this was scheduled.
+        generation: u8,
+    },
+}
+
+/// Pending key state type for key repeat keys. (No pending state).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingKeyState;
+
+/// Key state used by [System]. (Key repeat keys resolve to the wrapped key).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyState;
+
+/// The [key::System] implementation for key repeat keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<R, Keys: Index<usize, Output = Key<R>>> {
+    keys: Keys,
+    marker: PhantomData<R>,
+}
+
+impl<R, Keys: Index<usize, Output = Key<R>>> System<R, Keys> {
+    /// Constructs a new [System] with the given key data.
+    pub const fn new(keys: Keys) -> Self {
+        Self {
+            keys,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Copy + Debug, Keys: Debug + Index<usize, Output = Key<R>>> key::System<R>
+    for System<R, Keys>
+{
+    type Ref = Ref;
+    type Context = Context;
+    type Event = Event;
+    type PendingKeyState = PendingKeyState;
+    type KeyState = KeyState;
+
+    fn new_pressed_key(
+        &self,
+        keymap_index: u16,
+        _context: &Self::Context,
+        Ref(key_index): Ref,
+    ) -> (
+        key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
+        key::KeyEvents<Self::Event>,
+    ) {
+        let Key { key } = self.keys[key_index as usize];
+        let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::key(key));
+        let pke = key::KeyEvents::event(key::Event::key_event(keymap_index, Event::Pressed));
+        (pkr, pke)
+    }
+
+    fn update_pending_state(
+        &self,
+        _pending_state: &mut Self::PendingKeyState,
+        _keymap_index: u16,
+        _context: &Self::Context,
+        _key_ref: Ref,
+        _event: key::Event<Self::Event>,
+    ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
+        panic!()
+    }
+
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        _ref: &Self::Ref,
+        _context: &Self::Context,
+        _keymap_index: u16,
+        _event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        panic!()
+    }
+
+    fn key_output(
+        &self,
+        _key_ref: &Self::Ref,
+        _key_state: &Self::KeyState,
+    ) -> Option<key::KeyOutput> {
+        panic!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(pke: key::KeyEvents<Event>) -> heapless::Vec<key::ScheduledEvent<Event>, 4> {
+        pke.into_iter().collect()
+    }
+
+    #[test]
+    fn test_sizeof_ref() {
+        assert_eq!(1, core::mem::size_of::<Ref>());
+    }
+
+    #[test]
+    fn test_sizeof_event() {
+        assert_eq!(2, core::mem::size_of::<Event>());
+    }
+
+    const A: key::KeyOutput = key::KeyOutput::from_key_code(0x04);
+
+    fn virtual_press(key_output: key::KeyOutput) -> key::Event<Event> {
+        key::Event::Input(input::Event::VirtualKeyPress { key_output })
+    }
+
+    #[test]
+    fn pressed_schedules_repeat_after_delay() {
+        // Assemble
+        let mut ctx = Context::from_config(Config {
+            delay: 300,
+            interval: 50,
+        });
+
+        // Act
+        let pke = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+
+        // Assert
+        assert_eq!(Some(0), ctx.repeating_keymap_index());
+        assert_eq!(
+            key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                300,
+                key::Event::key_event(0, Event::Repeat { generation: 1 }),
+            )),
+            pke
+        );
+    }
+
+    #[test]
+    fn repeat_taps_synthesised_output() {
+        // Assemble
+        let mut ctx = Context::from_config(Config {
+            delay: 300,
+            interval: 50,
+        });
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+        let _ = key::Context::handle_event(&mut ctx, virtual_press(A));
+
+        // Act
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Repeat { generation: 1 }),
+        );
+
+        // Assert
+        assert_eq!(
+            &[
+                key::ScheduledEvent::immediate(virtual_press(A)),
+                key::ScheduledEvent::after(
+                    25,
+                    key::Event::Input(input::Event::VirtualKeyRelease { key_output: A }),
+                ),
+                key::ScheduledEvent::after(
+                    50,
+                    key::Event::key_event(0, Event::Repeat { generation: 1 })
+                ),
+            ],
+            events(pke).as_slice()
+        );
+    }
+
+    #[test]
+    fn repeat_does_not_replay_physical_input() {
+        // Assemble
+        let mut ctx = Context::default();
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+        let _ = key::Context::handle_event(&mut ctx, virtual_press(A));
+
+        // Act
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Repeat { generation: 1 }),
+        );
+
+        // Assert
+        assert!(events(pke).iter().all(|sch_ev| !matches!(
+            sch_ev.event,
+            key::Event::Input(input::Event::Press { .. } | input::Event::Release { .. })
+        )));
+    }
+
+    #[test]
+    fn repeat_without_synthesised_output_does_nothing() {
+        // Assemble
+        let mut ctx = Context::default();
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+
+        // Act
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Repeat { generation: 1 }),
+        );
+
+        // Assert
+        assert_eq!(key::KeyEvents::no_events(), pke);
+    }
+
+    #[test]
+    fn repeat_ignores_modifier_only_output() {
+        // Assemble
+        let mut ctx = Context::default();
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+        let _ = key::Context::handle_event(&mut ctx, virtual_press(A));
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            virtual_press(key::KeyOutput::from_key_modifiers(
+                key::KeyboardModifiers::LEFT_SHIFT,
+            )),
+        );
+
+        // Act
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Repeat { generation: 1 }),
+        );
+
+        // Assert
+        assert_eq!(virtual_press(A), events(pke)[0].event);
+    }
+
+    #[test]
+    fn physical_release_stops_repeat() {
+        // Assemble
+        let mut ctx = Context::default();
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+        let _ = key::Context::handle_event(&mut ctx, virtual_press(A));
+
+        // Act
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::Input(input::Event::Release { keymap_index: 0 }),
+        );
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Repeat { generation: 1 }),
+        );
+
+        // Assert
+        assert_eq!(None, ctx.repeating_keymap_index());
+        assert_eq!(key::KeyEvents::no_events(), pke);
+    }
+
+    #[test]
+    fn other_key_press_stops_repeat() {
+        // Assemble
+        let mut ctx = Context::default();
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Pressed));
+
+        // Act
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::Input(input::Event::Press { keymap_index: 1 }),
+        );
+
+        // Assert
+        assert_eq!(None, ctx.repeating_keymap_index());
+    }
+}
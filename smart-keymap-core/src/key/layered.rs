@@ -2,12 +2,15 @@ use core::fmt::Debug;
 use core::marker::Copy;
 use core::ops::{BitAnd, BitOr, Index, Not};
 
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
 use crate::input;
 use crate::key;
 use crate::key::KeyboardModifiers;
+use crate::keymap;
 use crate::slice::Slice;
+use crate::split;
 
 /// The type used for layer index.
 ///
@@ -190,6 +193,16 @@ pub enum ModifierKey {
     /// While a layer is locked, releasing a [`ModifierKey::Hold`] that activated it leaves the
     /// layer active. Pressing that [`ModifierKey::Hold`] again unlocks and turns the layer off.
     Lock(LayerLockTarget),
+    /// Layer tap-toggle (c.f. QMK's `TT`).
+    ///
+    /// Activates the given layer while held.
+    /// Tapping the key [Config::tap_toggle_taps] times toggles the layer.
+    TapToggle(LayerIndex),
+    /// Sticky layer modifier which toggles the layer when tapped repeatedly.
+    ///
+    /// Acts the same as `Sticky` variant,
+    ///  except that tapping the key [Config::sticky_toggle_taps] times toggles the layer.
+    StickyToggle(LayerIndex),
 }
 
 impl ModifierKey {
@@ -273,6 +286,18 @@ impl ModifierKey {
         ModifierKey::Lock(LayerLockTarget::Layer(layer))
     }
 
+    /// Create a new [ModifierKey] that activates the layer when held,
+    ///  or toggles it when tapped repeatedly.
+    pub const fn tap_toggle(layer: LayerIndex) -> Self {
+        ModifierKey::TapToggle(layer)
+    }
+
+    /// Create a new [ModifierKey] that makes the layer sticky when tapped,
+    ///  or toggles it when tapped repeatedly.
+    pub const fn sticky_toggle(layer: LayerIndex) -> Self {
+        ModifierKey::StickyToggle(layer)
+    }
+
     /// Create a new [input::PressedKey] and [key::ScheduledEvent] for the given keymap index.
     ///
     /// Pressing a [ModifierKey::Hold] emits a [LayerEvent::Activated] event.
@@ -300,6 +325,14 @@ impl ModifierKey {
                 ModifierKeyState::new(),
                 Some(LayerEvent::LockInvert(*target)),
             ),
+            ModifierKey::TapToggle(layer) => (
+                ModifierKeyState::new(),
+                Some(LayerEvent::TapTogglePressed(*layer)),
+            ),
+            ModifierKey::StickyToggle(layer) => (
+                ModifierKeyState::new(),
+                Some(LayerEvent::StickyTogglePressed(*layer)),
+            ),
         }
     }
 }
@@ -461,13 +494,46 @@ pub struct Config<const CONDITIONAL_LAYER_COUNT: usize = 0> {
     /// Rules that activate a then-layer when all of their if-layers are active.
     #[serde(default)]
     pub conditional_layers: Slice<ConditionalLayer, CONDITIONAL_LAYER_COUNT>,
+
+    /// Number of taps of a [ModifierKey::TapToggle] which toggle its layer.
+    #[serde(default = "default_tap_toggle_taps")]
+    pub tap_toggle_taps: u8,
+
+    /// Number of taps of a [ModifierKey::StickyToggle] which toggle its layer.
+    #[serde(default = "default_sticky_toggle_taps")]
+    pub sticky_toggle_taps: u8,
+
+    /// Timeout (ms) for tap-toggle keys.
+    ///
+    /// A tap-toggle key held longer than this is not counted as a tap,
+    ///  and taps must follow each other within this time.
+    #[serde(default = "default_tap_toggle_timeout")]
+    pub tap_toggle_timeout: u16,
+}
+
+/// Default number of taps of a [ModifierKey::TapToggle] which toggle its layer.
+pub const DEFAULT_TAP_TOGGLE_TAPS: u8 = 5;
+
+/// Default number of taps of a [ModifierKey::StickyToggle] which toggle its layer.
+pub const DEFAULT_STICKY_TOGGLE_TAPS: u8 = 2;
+
+/// Default tap-toggle timeout (ms).
+pub const DEFAULT_TAP_TOGGLE_TIMEOUT: u16 = 200;
+
+fn default_tap_toggle_taps() -> u8 {
+    DEFAULT_TAP_TOGGLE_TAPS
+}
+
+fn default_sticky_toggle_taps() -> u8 {
+    DEFAULT_STICKY_TOGGLE_TAPS
+}
+
+fn default_tap_toggle_timeout() -> u16 {
+    DEFAULT_TAP_TOGGLE_TIMEOUT
 }
 
 /// Default layered config (no sticky timeout, no conditional layers).
-pub const DEFAULT_CONFIG: Config = Config {
-    sticky_timeout: None,
-    conditional_layers: Slice::from_slice(&[]),
-};
+pub const DEFAULT_CONFIG: Config = Config::new();
 
 impl<const CONDITIONAL_LAYER_COUNT: usize> Config<CONDITIONAL_LAYER_COUNT> {
     /// Constructs a new default [Config].
@@ -475,6 +541,9 @@ impl<const CONDITIONAL_LAYER_COUNT: usize> Config<CONDITIONAL_LAYER_COUNT> {
         Self {
             sticky_timeout: None,
             conditional_layers: Slice::from_slice(&[]),
+            tap_toggle_taps: DEFAULT_TAP_TOGGLE_TAPS,
+            sticky_toggle_taps: DEFAULT_STICKY_TOGGLE_TAPS,
+            tap_toggle_timeout: DEFAULT_TAP_TOGGLE_TIMEOUT,
         }
     }
 }
@@ -485,6 +554,29 @@ impl<const CONDITIONAL_LAYER_COUNT: usize> Default for Config<CONDITIONAL_LAYER_
     }
 }
 
+/// Which kind of tap-toggle [ModifierKey] is being tapped.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum TapToggleStyle {
+    /// [ModifierKey::TapToggle]: momentary when not toggled.
+    Hold,
+    /// [ModifierKey::StickyToggle]: sticky when not toggled.
+    Sticky,
+}
+
+/// Taps of a tap-toggle [ModifierKey], counted towards toggling its layer.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct TapToggleSequence {
+    keymap_index: u16,
+    layer: LayerIndex,
+    style: TapToggleStyle,
+    /// Whether the layer was (regular) active before the first tap.
+    was_active: bool,
+    taps: u8,
+    is_held: bool,
+    /// Whether the current press may still count as a tap.
+    is_tap: bool,
+}
+
 /// [crate::key::Context] for [LayeredKey] that tracks active layers.
 #[derive(Clone, Copy)]
 pub struct Context<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize = 0> {
@@ -499,6 +591,12 @@ pub struct Context<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usiz
     pressed_keymap_index: Option<u16>,
     // Invalidates pending sticky-timeout events when advanced.
     sticky_timeout_id: u8,
+    // Taps of the most recently pressed tap-toggle key.
+    tap_toggle: Option<TapToggleSequence>,
+    // Invalidates pending tap-toggle timeout events when advanced.
+    tap_toggle_timeout_id: u8,
+    /// Runtime overrides of [LayeredKey] key refs.
+    key_overrides: keymap::KeyOverrides,
 }
 
 impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> Debug
@@ -517,6 +615,9 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> Debug
             .field("locked_layers", &self.locked_layers)
             .field("pressed_keymap_index", &self.pressed_keymap_index)
             .field("sticky_timeout_id", &self.sticky_timeout_id)
+            .field("tap_toggle", &self.tap_toggle)
+            .field("tap_toggle_timeout_id", &self.tap_toggle_timeout_id)
+            .field("key_overrides", &self.key_overrides)
             .finish()
     }
 }
@@ -538,12 +639,30 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
             locked_layers: LayerBitset::EMPTY,
             pressed_keymap_index: None,
             sticky_timeout_id: 0,
+            tap_toggle: None,
+            tap_toggle_timeout_id: 0,
+            key_overrides: keymap::KeyOverrides::new(),
         }
     }
 
     /// Re-construct from context's [Config], clearing active layers and sticky state.
+    ///
+    /// Key overrides are kept.
     pub fn reset(&mut self) {
-        *self = Self::from_config(self.config);
+        *self = Self {
+            key_overrides: self.key_overrides,
+            ..Self::from_config(self.config)
+        };
+    }
+
+    /// The runtime key overrides for [LayeredKey]s.
+    pub fn key_overrides(&self) -> &keymap::KeyOverrides {
+        &self.key_overrides
+    }
+
+    /// The runtime key overrides for [LayeredKey]s, for editing.
+    pub fn key_overrides_mut(&mut self) -> &mut keymap::KeyOverrides {
+        &mut self.key_overrides
     }
 
     fn invalidate_sticky_timeouts(&mut self) {
@@ -563,6 +682,56 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
         self.locked_layers.contains(layer as usize)
     }
 
+    /// Writes the active layers, locked layers and default layer into the snapshot.
+    ///
+    /// Sticky layers are momentary, and are not included.
+    pub fn export_persistent_state(&self, state: &mut keymap::PersistentState) {
+        let max_layer = 1 + LAYER_COUNT.min(MAX_BITSET_LAYER);
+        let active_layers = (1..max_layer).fold(LayerBitset::EMPTY, |bits, li| {
+            if self.active_layers[li - 1] == Activity::Active(ActivationStyle::Regular) {
+                bits.insert(li)
+            } else {
+                bits
+            }
+        });
+
+        state.default_layer = self.default_layer.unwrap_or(0) as u8;
+        state.active_layers = active_layers.bits();
+        state.locked_layers = self.locked_layers.bits();
+    }
+
+    /// Restores the active layers, locked layers and default layer from the snapshot.
+    ///
+    /// Layers outside of this context's layer count are ignored.
+    pub fn restore_persistent_state(&mut self, state: &keymap::PersistentState) {
+        let max_layer = 1 + LAYER_COUNT.min(MAX_BITSET_LAYER);
+        let active_layers = LayerBitset::from_bits(state.active_layers);
+        let locked_layers = LayerBitset::from_bits(state.locked_layers);
+
+        self.default_layer = match state.default_layer as usize {
+            layer @ 1.. if layer <= LAYER_COUNT => Some(layer as LayerIndex),
+            _ => None,
+        };
+
+        self.locked_layers = LayerBitset::EMPTY;
+        for li in 1..max_layer {
+            if locked_layers.contains(li) {
+                self.set_layer_lock(li as LayerIndex);
+            }
+            if active_layers.contains(li) || locked_layers.contains(li) {
+                self.active_layers
+                    .activate(li as LayerIndex, ActivationStyle::Regular);
+            } else {
+                self.active_layers.deactivate(li as LayerIndex);
+            }
+        }
+
+        self.pressed_keymap_index = None;
+        self.invalidate_sticky_timeouts();
+        self.tap_toggle = None;
+        self.apply_conditional_layers();
+    }
+
     fn set_layer_lock(&mut self, layer: LayerIndex) {
         self.locked_layers = self.locked_layers.insert(layer as usize);
     }
@@ -605,8 +774,9 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
         self.apply_conditional_layers();
     }
 
-    /// Bitset of currently active layers (bit `i` = layer `i`).
-    fn active_layers_bitset(&self) -> LayerBitset {
+    /// Bitset of currently active layers (bit `i` = layer `i`),
+    ///  including held and sticky layers.
+    pub fn active_layers_bitset(&self) -> LayerBitset {
         let max_layer = 1 + LAYER_COUNT.min(MAX_BITSET_LAYER);
         (1..max_layer).fold(LayerBitset::EMPTY, |bits, li| {
             if self.active_layers[li - 1].is_active() {
@@ -655,6 +825,152 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
             }
         }
     }
+
+    /// Schedules a tap-toggle timeout, superseding any earlier one.
+    fn schedule_tap_toggle_timeout(&mut self, keymap_index: u16) -> key::KeyEvents<LayerEvent> {
+        self.tap_toggle_timeout_id = self.tap_toggle_timeout_id.wrapping_add(1);
+        key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+            self.config.tap_toggle_timeout,
+            key::Event::key_event(
+                keymap_index,
+                LayerEvent::TapToggleTimeout(self.tap_toggle_timeout_id),
+            ),
+        ))
+    }
+
+    /// A tap-toggle key was pressed.
+    ///
+    /// Continues the tap sequence if the same key was the last tapped,
+    ///  and activates the layer (momentary or sticky) unless it was already toggled on.
+    fn tap_toggle_pressed(
+        &mut self,
+        keymap_index: u16,
+        layer: LayerIndex,
+        style: TapToggleStyle,
+    ) -> key::KeyEvents<LayerEvent> {
+        let sequence = match self.tap_toggle {
+            Some(sequence)
+                if sequence.keymap_index == keymap_index
+                    && sequence.layer == layer
+                    && sequence.style == style =>
+            {
+                sequence
+            }
+            _ => TapToggleSequence {
+                keymap_index,
+                layer,
+                style,
+                was_active: self.active_layers[layer as usize - 1]
+                    == Activity::Active(ActivationStyle::Regular),
+                taps: 0,
+                is_held: true,
+                is_tap: true,
+            },
+        };
+        self.tap_toggle = Some(TapToggleSequence {
+            is_held: true,
+            is_tap: true,
+            ..sequence
+        });
+
+        if !sequence.was_active {
+            match style {
+                TapToggleStyle::Hold => {
+                    self.active_layers.activate(layer, ActivationStyle::Regular);
+                }
+                TapToggleStyle::Sticky => {
+                    self.active_layers.activate(layer, ActivationStyle::Sticky);
+                    self.pressed_keymap_index = None;
+                }
+            }
+            self.invalidate_sticky_timeouts();
+            self.apply_conditional_layers();
+        }
+
+        self.schedule_tap_toggle_timeout(keymap_index)
+    }
+
+    /// A tap-toggle key was released.
+    ///
+    /// Toggles the layer if enough taps were counted;
+    ///  otherwise the layer goes back to how it was before the sequence
+    ///  (or stays sticky, for a tapped [ModifierKey::StickyToggle]).
+    fn tap_toggle_released(&mut self, layer: LayerIndex) -> key::KeyEvents<LayerEvent> {
+        let sequence = match self.tap_toggle {
+            Some(sequence) if sequence.layer == layer && sequence.is_held => sequence,
+            _ => return key::KeyEvents::no_events(),
+        };
+        let taps = sequence.taps.saturating_add(1);
+        let toggle_taps = match sequence.style {
+            TapToggleStyle::Hold => self.config.tap_toggle_taps,
+            TapToggleStyle::Sticky => self.config.sticky_toggle_taps,
+        };
+
+        if !sequence.is_tap {
+            // Held or interrupted: acts as a momentary layer modifier.
+            self.tap_toggle = None;
+            if sequence.was_active {
+                key::KeyEvents::no_events()
+            } else {
+                self.handle_layer_event(LayerEvent::Deactivated(layer))
+            }
+        } else if taps >= toggle_taps {
+            self.tap_toggle = None;
+            self.tap_toggle_timeout_id = self.tap_toggle_timeout_id.wrapping_add(1);
+            if sequence.was_active {
+                self.active_layers.deactivate(layer);
+                self.clear_layer_lock(layer);
+            } else {
+                // Toggled on: no longer sticky.
+                self.active_layers.activate(layer, ActivationStyle::Regular);
+                self.pressed_keymap_index = None;
+                self.invalidate_sticky_timeouts();
+            }
+            self.apply_conditional_layers();
+            key::KeyEvents::no_events()
+        } else {
+            self.tap_toggle = Some(TapToggleSequence {
+                taps,
+                is_held: false,
+                ..sequence
+            });
+            let mut events = self.schedule_tap_toggle_timeout(sequence.keymap_index);
+            if !sequence.was_active {
+                let layer_events = match sequence.style {
+                    TapToggleStyle::Hold => self.handle_layer_event(LayerEvent::Deactivated(layer)),
+                    TapToggleStyle::Sticky => self.handle_layer_event(LayerEvent::StickyReleased),
+                };
+                events.extend(layer_events);
+            }
+            events
+        }
+    }
+
+    /// Another key was pressed: the tap sequence is interrupted.
+    ///
+    /// A held [ModifierKey::StickyToggle] then acts as a momentary layer modifier.
+    fn interrupt_tap_toggle(&mut self, keymap_index: u16) {
+        match self.tap_toggle {
+            Some(sequence) if sequence.keymap_index != keymap_index => {
+                if sequence.is_held {
+                    self.tap_toggle = Some(TapToggleSequence {
+                        is_tap: false,
+                        ..sequence
+                    });
+                    let layer = sequence.layer;
+                    if self.active_layers[layer as usize - 1]
+                        == Activity::Active(ActivationStyle::Sticky)
+                    {
+                        self.active_layers.activate(layer, ActivationStyle::Regular);
+                        self.invalidate_sticky_timeouts();
+                    }
+                } else {
+                    self.tap_toggle = None;
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> Default
@@ -791,6 +1107,25 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
                 }
                 key::KeyEvents::no_events()
             }
+            LayerEvent::TapTogglePressed(_) | LayerEvent::StickyTogglePressed(_) => {
+                // Needs the keymap index; see handle_event.
+                key::KeyEvents::no_events()
+            }
+            LayerEvent::TapToggleReleased(layer) => self.tap_toggle_released(layer),
+            LayerEvent::TapToggleTimeout(timeout_id) => {
+                if timeout_id == self.tap_toggle_timeout_id {
+                    self.tap_toggle = match self.tap_toggle {
+                        // Held too long to count as a tap.
+                        Some(sequence) if sequence.is_held => Some(TapToggleSequence {
+                            is_tap: false,
+                            ..sequence
+                        }),
+                        // Next tap didn't come in time.
+                        _ => None,
+                    };
+                }
+                key::KeyEvents::no_events()
+            }
         }
     }
 
@@ -798,6 +1133,8 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
     fn handle_event(&mut self, event: key::Event<LayerEvent>) -> key::KeyEvents<LayerEvent> {
         match event {
             key::Event::Input(input::Event::Press { keymap_index, .. }) => {
+                self.interrupt_tap_toggle(keymap_index);
+
                 if let Some(sticky_layer_index) = self.sticky_layer() {
                     if self.pressed_keymap_index.is_some() {
                         // The sticky layer modifier has already been used;
@@ -819,12 +1156,58 @@ impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize>
                 }
                 key::KeyEvents::no_events()
             }
+            key::Event::Key {
+                keymap_index,
+                key_event: LayerEvent::TapTogglePressed(layer),
+            } => self.tap_toggle_pressed(keymap_index, layer, TapToggleStyle::Hold),
+            key::Event::Key {
+                keymap_index,
+                key_event: LayerEvent::StickyTogglePressed(layer),
+            } => self.tap_toggle_pressed(keymap_index, layer, TapToggleStyle::Sticky),
             key::Event::Key { key_event, .. } => self.handle_layer_event(key_event),
             _ => key::KeyEvents::no_events(),
         }
     }
 }
 
+impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> keymap::PersistentContext
+    for Context<LAYER_COUNT, CONDITIONAL_LAYER_COUNT>
+{
+    fn export_persistent_state(&self, state: &mut keymap::PersistentState) {
+        Context::export_persistent_state(self, state);
+    }
+
+    fn restore_persistent_state(&mut self, state: &keymap::PersistentState) {
+        Context::restore_persistent_state(self, state);
+    }
+}
+
+impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> keymap::SplitStatusContext
+    for Context<LAYER_COUNT, CONDITIONAL_LAYER_COUNT>
+{
+    /// Writes the active layers (including held and sticky layers) into the status.
+    fn export_split_status(&self, status: &mut split::Status) {
+        status.active_layers = self.active_layers_bitset().bits();
+    }
+}
+
+impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> keymap::KeyOverridesContext
+    for Context<LAYER_COUNT, CONDITIONAL_LAYER_COUNT>
+{
+    fn layer_count(&self) -> u8 {
+        // Base layer, plus the layered keys' layers.
+        (1 + LAYER_COUNT).min(u8::MAX as usize) as u8
+    }
+
+    fn key_overrides(&self) -> Option<&keymap::KeyOverrides> {
+        Some(&self.key_overrides)
+    }
+
+    fn key_overrides_mut(&mut self) -> Option<&mut keymap::KeyOverrides> {
+        Some(&mut self.key_overrides)
+    }
+}
+
 impl<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT: usize> key::Context
     for Context<LAYER_COUNT, CONDITIONAL_LAYER_COUNT>
 {
@@ -989,7 +1372,35 @@ fn is_exit_deactivatable<const LAYER_COUNT: usize, const CONDITIONAL_LAYER_COUNT
         )
 }
 
-impl<R: Copy + Debug + PartialEq, const LAYER_COUNT: usize> LayeredKey<R, LAYER_COUNT> {
+impl<R: Copy + Debug + PartialEq + DeserializeOwned, const LAYER_COUNT: usize>
+    LayeredKey<R, LAYER_COUNT>
+{
+    /// The key ref for the given (1-based) layer, or the base key for layer `0`.
+    ///
+    /// A runtime key override (see [keymap::KeyOverrides]) takes precedence
+    ///  over the compiled-in key ref.
+    fn layer_ref<const CONDITIONAL_LAYER_COUNT: usize>(
+        &self,
+        context: &Context<LAYER_COUNT, CONDITIONAL_LAYER_COUNT>,
+        keymap_index: u16,
+        layer_index: LayerIndex,
+    ) -> Option<R> {
+        let compiled_ref = match layer_index as usize {
+            0 => Some(self.base),
+            li => self.layered[li - 1],
+        };
+
+        if context.key_overrides.is_empty() {
+            return compiled_ref;
+        }
+
+        u8::try_from(layer_index)
+            .ok()
+            .and_then(|layer| context.key_overrides.get(keymap_index, layer))
+            .and_then(|ko| ko.key_ref())
+            .or(compiled_ref)
+    }
+
     /// Presses the key: highest defined active binding, with exit-on-skip side effects.
     fn new_pressed_key<const CONDITIONAL_LAYER_COUNT: usize>(
         &self,
@@ -1010,7 +1421,8 @@ impl<R: Copy + Debug + PartialEq, const LAYER_COUNT: usize> LayeredKey<R, LAYER_
 
         // First defined cell among active layers (and its 1-based layer index).
         let picked = active_in_range().find_map(|layer_index| {
-            self.layered[layer_index as usize - 1].map(|r| (layer_index, r))
+            self.layer_ref(context, keymap_index, layer_index)
+                .map(|r| (layer_index, r))
         });
 
         // Exit holes strictly above the pick (or all active holes if none defined).
@@ -1022,7 +1434,7 @@ impl<R: Copy + Debug + PartialEq, const LAYER_COUNT: usize> LayeredKey<R, LAYER_
                     .unwrap_or(true)
             })
             .filter(|&layer_index| {
-                self.layered[layer_index as usize - 1].is_none()
+                self.layer_ref(context, keymap_index, layer_index).is_none()
                     && self.exit_on_skip.contains(layer_index as usize)
                     && is_exit_deactivatable(context, layer_index)
             })
@@ -1038,13 +1450,13 @@ impl<R: Copy + Debug + PartialEq, const LAYER_COUNT: usize> LayeredKey<R, LAYER_
             .map(|(_, r)| r)
             .or_else(|| {
                 context.default_layer.and_then(|layer_index| {
-                    let layer_index = layer_index as usize;
                     (1..=LAYER_COUNT)
-                        .contains(&layer_index)
-                        .then(|| self.layered[layer_index - 1])
+                        .contains(&(layer_index as usize))
+                        .then(|| self.layer_ref(context, keymap_index, layer_index))
                         .flatten()
                 })
             })
+            .or_else(|| self.layer_ref(context, keymap_index, 0))
             .unwrap_or(self.base);
 
         (key::NewPressedKey::key(passthrough_ref), events)
@@ -1076,6 +1488,16 @@ pub enum LayerEvent {
     ///
     /// See [ModifierKey::Lock].
     LockInvert(LayerLockTarget),
+    /// [ModifierKey::TapToggle] pressed.
+    TapTogglePressed(LayerIndex),
+    /// [ModifierKey::StickyToggle] pressed.
+    StickyTogglePressed(LayerIndex),
+    /// [ModifierKey::TapToggle] or [ModifierKey::StickyToggle] released.
+    TapToggleReleased(LayerIndex),
+    /// Tap-toggle key held too long, or not tapped again in time.
+    ///
+    /// The payload is a generation id used to ignore stale timeouts.
+    TapToggleTimeout(u8),
 }
 
 /// Struct for layer system pending key state. (No pending state).
@@ -1172,6 +1594,14 @@ impl ModifierKeyState {
                 _ => None,
             },
             ModifierKey::Lock(_) => None,
+            ModifierKey::TapToggle(layer) | ModifierKey::StickyToggle(layer) => match event {
+                key::Event::Input(input::Event::Release { keymap_index: ki })
+                    if keymap_index == ki =>
+                {
+                    Some(LayerEvent::TapToggleReleased(*layer))
+                }
+                _ => None,
+            },
         }
     }
 }
@@ -1211,7 +1641,7 @@ impl<
 }
 
 impl<
-        R: Copy + Debug + PartialEq,
+        R: Copy + Debug + PartialEq + DeserializeOwned,
         ModifierKeys: Debug + Index<usize, Output = ModifierKey>,
         LayeredKeys: Debug + Index<usize, Output = LayeredKey<R, LAYER_COUNT>>,
         const LAYER_COUNT: usize,
@@ -1452,6 +1882,7 @@ mod tests {
         super::Context::from_config(Config {
             sticky_timeout: None,
             conditional_layers: Slice::from_slice(&[ConditionalLayer::from_if_layers(3, &[1, 2])]),
+            ..Config::new()
         })
     }
 
@@ -1525,6 +1956,7 @@ mod tests {
                 ConditionalLayer::from_if_layers(3, &[1, 2]),
                 ConditionalLayer::from_if_layers(5, &[3, 4]),
             ]),
+            ..Config::new()
         });
         context.handle_layer_event(LayerEvent::Activated(1));
         context.handle_layer_event(LayerEvent::Activated(2));
@@ -1657,6 +2089,38 @@ mod tests {
         assert_eq!(expected_pkr, pkr,);
     }
 
+    #[test]
+    fn test_pressing_layered_key_uses_key_override_on_active_layer() {
+        // Assemble: layered key, with an override for transparent layer 2
+        let mut context = Context::default();
+        let expected_ref = keyboard::Ref::KeyCode(0x0A);
+        let layered_key = LayeredKey::new(
+            keyboard::Ref::KeyCode(0x04),
+            [Some(keyboard::Ref::KeyCode(0x05)), None, None],
+        );
+        let system = System::new([], [layered_key]);
+        let keymap_index = 9; // arbitrary
+
+        // postcard encoding of keyboard::Ref::KeyCode(0x0A): variant index, then value.
+        let override_bytes = [0x00, 0x0A];
+        context
+            .key_overrides_mut()
+            .set(keymap_index, 2, &override_bytes)
+            .unwrap();
+
+        // Act: activate layers 1 and 2, reset context (keeps overrides), press layered key
+        context.reset();
+        context.handle_layer_event(LayerEvent::Activated(1));
+        context.handle_layer_event(LayerEvent::Activated(2));
+        let key_ref = Ref::Layered(0);
+        let (pkr, _pke) = system.new_pressed_key(keymap_index, &context, key_ref);
+
+        // Assert
+        let expected_pkr =
+            key::PressedKeyResult::NewPressedKey(key::NewPressedKey::Key(expected_ref));
+        assert_eq!(expected_pkr, pkr,);
+    }
+
     #[test]
     fn test_pressing_layered_key_with_some_transparency_acts_as_highest_defined_active_layer() {
         // Assemble: layered key (with no layered definitions)
@@ -1884,6 +2348,230 @@ mod tests {
         assert!(!context.active_layers.iter().any(|a| a.is_active()));
     }
 
+    #[test]
+    fn test_persistent_state_roundtrip_keeps_toggled_locked_and_default_layers() {
+        // Assemble: toggle layer 1, lock layer 3, set default layer 2, sticky layer 2
+        let mut context = Context::default();
+        context.handle_layer_event(LayerEvent::Toggled(1));
+        context.handle_layer_event(LayerEvent::LockInvert(LayerLockTarget::Layer(3)));
+        context.handle_layer_event(LayerEvent::SetDefault(2));
+        context.handle_layer_event(LayerEvent::StickyActivated(2));
+        let mut state = keymap::PersistentState::new();
+        context.export_persistent_state(&mut state);
+
+        // Act: restore into a fresh context
+        let mut restored = Context::default();
+        restored.restore_persistent_state(&state);
+
+        // Assert: sticky layer 2 is not restored
+        assert!(restored.active_layers[0].is_active());
+        assert!(!restored.active_layers[1].is_active());
+        assert!(restored.active_layers[2].is_active());
+        assert!(restored.is_layer_locked(3));
+        assert_eq!(Some(2), restored.default_layer);
+    }
+
+    #[test]
+    fn test_split_status_includes_held_and_sticky_layers() {
+        // Assemble: toggle layer 1, sticky layer 3
+        let mut context = Context::default();
+        context.handle_layer_event(LayerEvent::Toggled(1));
+        context.handle_layer_event(LayerEvent::StickyActivated(3));
+        let mut status = split::Status::new();
+
+        // Act
+        keymap::SplitStatusContext::export_split_status(&context, &mut status);
+
+        // Assert
+        assert_eq!(0b1010, status.active_layers);
+    }
+
+    fn tap_tap_toggle_key(context: &mut Context, keymap_index: u16, press_event: LayerEvent) {
+        context.handle_event(key::Event::Input(input::Event::Press { keymap_index }));
+        context.handle_event(key::Event::key_event(keymap_index, press_event));
+        context.handle_event(key::Event::Input(input::Event::Release { keymap_index }));
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapToggleReleased(1),
+        ));
+    }
+
+    #[test]
+    fn test_tap_toggle_held_is_momentary() {
+        // Assemble
+        let mut context = Context::default();
+        let keymap_index = 0;
+
+        // Act
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapTogglePressed(1),
+        ));
+        let is_active_while_held = context.active_layers[0].is_active();
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapToggleReleased(1),
+        ));
+
+        // Assert
+        assert!(is_active_while_held);
+        assert!(!context.active_layers[0].is_active());
+    }
+
+    #[test]
+    fn test_tap_toggle_tapped_toggles_layer_on_and_off() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            tap_toggle_taps: 2,
+            ..Config::new()
+        });
+        let keymap_index = 0;
+
+        // Act
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+        let is_active_after_one_tap = context.active_layers[0].is_active();
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+        let is_active_after_two_taps = context.active_layers[0].is_active();
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+
+        // Assert
+        assert!(!is_active_after_one_tap);
+        assert!(is_active_after_two_taps);
+        assert!(!context.active_layers[0].is_active());
+    }
+
+    #[test]
+    fn test_tap_toggle_taps_interrupted_by_other_key() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            tap_toggle_taps: 2,
+            ..Config::new()
+        });
+        let keymap_index = 0;
+        let other_keymap_index = 1;
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+
+        // Act
+        context.handle_event(key::Event::Input(input::Event::Press {
+            keymap_index: other_keymap_index,
+        }));
+        tap_tap_toggle_key(&mut context, keymap_index, LayerEvent::TapTogglePressed(1));
+
+        // Assert
+        assert!(!context.active_layers[0].is_active());
+    }
+
+    #[test]
+    fn test_tap_toggle_held_past_timeout_is_not_a_tap() {
+        // Assemble
+        let mut context = Context::from_config(Config {
+            tap_toggle_taps: 1,
+            ..Config::new()
+        });
+        let keymap_index = 0;
+        let timeout_events = context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapTogglePressed(1),
+        ));
+        let timeout_event = timeout_events
+            .into_iter()
+            .next()
+            .map(|scheduled| scheduled.event)
+            .unwrap();
+
+        // Act
+        context.handle_event(timeout_event);
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapToggleReleased(1),
+        ));
+
+        // Assert
+        assert!(!context.active_layers[0].is_active());
+    }
+
+    #[test]
+    fn test_sticky_toggle_tapped_once_is_sticky() {
+        // Assemble
+        let mut context = Context::default();
+        let keymap_index = 0;
+
+        // Act
+        tap_tap_toggle_key(
+            &mut context,
+            keymap_index,
+            LayerEvent::StickyTogglePressed(1),
+        );
+
+        // Assert
+        assert_eq!(
+            Activity::Active(ActivationStyle::Sticky),
+            context.active_layers[0]
+        );
+    }
+
+    #[test]
+    fn test_sticky_toggle_double_tapped_toggles_layer() {
+        // Assemble
+        let mut context = Context::default();
+        let keymap_index = 0;
+        let other_keymap_index = 1;
+
+        // Act
+        tap_tap_toggle_key(
+            &mut context,
+            keymap_index,
+            LayerEvent::StickyTogglePressed(1),
+        );
+        tap_tap_toggle_key(
+            &mut context,
+            keymap_index,
+            LayerEvent::StickyTogglePressed(1),
+        );
+        context.handle_event(key::Event::Input(input::Event::Press {
+            keymap_index: other_keymap_index,
+        }));
+        context.handle_event(key::Event::Input(input::Event::Release {
+            keymap_index: other_keymap_index,
+        }));
+
+        // Assert
+        assert_eq!(
+            Activity::Active(ActivationStyle::Regular),
+            context.active_layers[0]
+        );
+    }
+
+    #[test]
+    fn test_sticky_toggle_held_and_interrupted_is_momentary() {
+        // Assemble
+        let mut context = Context::default();
+        let keymap_index = 0;
+        let other_keymap_index = 1;
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::StickyTogglePressed(1),
+        ));
+
+        // Act
+        context.handle_event(key::Event::Input(input::Event::Press {
+            keymap_index: other_keymap_index,
+        }));
+        let activity_while_held = context.active_layers[0];
+        context.handle_event(key::Event::key_event(
+            keymap_index,
+            LayerEvent::TapToggleReleased(1),
+        ));
+
+        // Assert
+        assert_eq!(
+            Activity::Active(ActivationStyle::Regular),
+            activity_while_held
+        );
+        assert!(!context.active_layers[0].is_active());
+    }
+
     #[test]
     fn deserialize_lock_json() {
         // Assemble / Act
@@ -4,6 +4,7 @@ use core::ops::Index;
 
 use serde::Deserialize;
 
+use crate::input;
 use crate::key;
 
 /// Mouse action (button, cursor movement, or wheel).
@@ -27,6 +28,9 @@ pub enum Action {
     WheelLeft,
     /// Scroll wheel right.
     WheelRight,
+    /// While held, cursor and wheel keys move at the constant speed of the given gear.
+    ///  (Value is the gear index, `0..GEAR_COUNT`).
+    Gear(u8),
 }
 
 /// Reference for a mouse key.
@@ -70,18 +74,298 @@ impl Key {
     }
 }
 
-/// Context for mouse keys. (No context).
+/// Number of constant-speed gears selectable with [Action::Gear].
+pub const GEAR_COUNT: usize = 3;
+
+/// Constant movement speeds used while an [Action::Gear] key is held.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Gear {
+    /// Cursor movement per report.
+    pub move_speed: u8,
+    /// Wheel movement per report.
+    pub wheel_speed: u8,
+}
+
+/// Mouse key configuration.
+///
+/// Cursor (and wheel) keys move by `move_delta` (`wheel_delta`) per report
+///  when pressed. After `delay_ms`, the speed increases linearly
+///  up to `max_speed` (`wheel_max_speed`) over `time_to_max_ms` (`wheel_time_to_max_ms`).
+///
+/// Acceleration is disabled when the max speed is not greater than the initial speed.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Delay (in ms) after pressing a cursor or wheel key before it accelerates.
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u16,
+
+    /// Interval (in ms) between acceleration steps.
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u16,
+
+    /// Cursor movement per report when a cursor key is pressed.
+    #[serde(default = "default_move_delta")]
+    pub move_delta: u8,
+
+    /// Maximum cursor movement per report.
+    #[serde(default = "default_max_speed")]
+    pub max_speed: u8,
+
+    /// Time (in ms) after the delay to accelerate from `move_delta` to `max_speed`.
+    #[serde(default = "default_time_to_max_ms")]
+    pub time_to_max_ms: u16,
+
+    /// Wheel movement per report when a wheel key is pressed.
+    #[serde(default = "default_wheel_delta")]
+    pub wheel_delta: u8,
+
+    /// Maximum wheel movement per report.
+    #[serde(default = "default_wheel_max_speed")]
+    pub wheel_max_speed: u8,
+
+    /// Time (in ms) after the delay to accelerate from `wheel_delta` to `wheel_max_speed`.
+    #[serde(default = "default_wheel_time_to_max_ms")]
+    pub wheel_time_to_max_ms: u16,
+
+    /// Constant speeds used while an [Action::Gear] key is held.
+    #[serde(default = "default_gears")]
+    pub gears: [Gear; GEAR_COUNT],
+}
+
+/// Default delay before acceleration.
+pub const DEFAULT_DELAY_MS: u16 = 100;
+
+/// Default interval between acceleration steps.
+pub const DEFAULT_INTERVAL_MS: u16 = 20;
+
+/// Default cursor movement per report.
+pub const DEFAULT_MOVE_DELTA: u8 = 5;
+
+/// Default maximum cursor movement per report. (No acceleration).
+pub const DEFAULT_MAX_SPEED: u8 = DEFAULT_MOVE_DELTA;
+
+/// Default time to accelerate the cursor to max speed.
+pub const DEFAULT_TIME_TO_MAX_MS: u16 = 1000;
+
+/// Default wheel movement per report.
+pub const DEFAULT_WHEEL_DELTA: u8 = 1;
+
+/// Default maximum wheel movement per report. (No acceleration).
+pub const DEFAULT_WHEEL_MAX_SPEED: u8 = DEFAULT_WHEEL_DELTA;
+
+/// Default time to accelerate the wheel to max speed.
+pub const DEFAULT_WHEEL_TIME_TO_MAX_MS: u16 = 1000;
+
+/// Default gears: slow, medium, fast.
+pub const DEFAULT_GEARS: [Gear; GEAR_COUNT] = [
+    Gear {
+        move_speed: 1,
+        wheel_speed: 1,
+    },
+    Gear {
+        move_speed: 5,
+        wheel_speed: 1,
+    },
+    Gear {
+        move_speed: 20,
+        wheel_speed: 2,
+    },
+];
+
+fn default_delay_ms() -> u16 {
+    DEFAULT_DELAY_MS
+}
+
+fn default_interval_ms() -> u16 {
+    DEFAULT_INTERVAL_MS
+}
+
+fn default_move_delta() -> u8 {
+    DEFAULT_MOVE_DELTA
+}
+
+fn default_max_speed() -> u8 {
+    DEFAULT_MAX_SPEED
+}
+
+fn default_time_to_max_ms() -> u16 {
+    DEFAULT_TIME_TO_MAX_MS
+}
+
+fn default_wheel_delta() -> u8 {
+    DEFAULT_WHEEL_DELTA
+}
+
+fn default_wheel_max_speed() -> u8 {
+    DEFAULT_WHEEL_MAX_SPEED
+}
+
+fn default_wheel_time_to_max_ms() -> u16 {
+    DEFAULT_WHEEL_TIME_TO_MAX_MS
+}
+
+fn default_gears() -> [Gear; GEAR_COUNT] {
+    DEFAULT_GEARS
+}
+
+/// The default [Config].
+pub const DEFAULT_CONFIG: Config = Config {
+    delay_ms: DEFAULT_DELAY_MS,
+    interval_ms: DEFAULT_INTERVAL_MS,
+    move_delta: DEFAULT_MOVE_DELTA,
+    max_speed: DEFAULT_MAX_SPEED,
+    time_to_max_ms: DEFAULT_TIME_TO_MAX_MS,
+    wheel_delta: DEFAULT_WHEEL_DELTA,
+    wheel_max_speed: DEFAULT_WHEEL_MAX_SPEED,
+    wheel_time_to_max_ms: DEFAULT_WHEEL_TIME_TO_MAX_MS,
+    gears: DEFAULT_GEARS,
+};
+
+/// Speed after `elapsed_ms`, ramping linearly from `initial` to `max`.
+const fn ramp_speed(
+    initial: u8,
+    max: u8,
+    elapsed_ms: u16,
+    delay_ms: u16,
+    time_to_max_ms: u16,
+) -> u8 {
+    if max <= initial || elapsed_ms < delay_ms {
+        initial
+    } else {
+        let accelerating_ms = elapsed_ms - delay_ms;
+        if accelerating_ms >= time_to_max_ms {
+            max
+        } else {
+            let extra = (max - initial) as u32 * accelerating_ms as u32 / time_to_max_ms as u32;
+            initial + extra as u8
+        }
+    }
+}
+
+impl Config {
+    /// Constructs a new default config.
+    pub const fn new() -> Self {
+        DEFAULT_CONFIG
+    }
+
+    /// Whether cursor or wheel keys accelerate while held.
+    pub const fn accelerates(&self) -> bool {
+        self.max_speed > self.move_delta || self.wheel_max_speed > self.wheel_delta
+    }
+
+    /// Time (in ms) after which cursor and wheel keys have reached max speed.
+    const fn acceleration_end_ms(&self) -> u16 {
+        let time_to_max_ms = if self.time_to_max_ms > self.wheel_time_to_max_ms {
+            self.time_to_max_ms
+        } else {
+            self.wheel_time_to_max_ms
+        };
+        self.delay_ms.saturating_add(time_to_max_ms)
+    }
+
+    /// Cursor movement per report, for a key held `elapsed_ms`.
+    pub const fn cursor_speed(&self, elapsed_ms: u16) -> u8 {
+        ramp_speed(
+            self.move_delta,
+            self.max_speed,
+            elapsed_ms,
+            self.delay_ms,
+            self.time_to_max_ms,
+        )
+    }
+
+    /// Wheel movement per report, for a key held `elapsed_ms`.
+    pub const fn wheel_speed(&self, elapsed_ms: u16) -> u8 {
+        ramp_speed(
+            self.wheel_delta,
+            self.wheel_max_speed,
+            elapsed_ms,
+            self.delay_ms,
+            self.wheel_time_to_max_ms,
+        )
+    }
+}
+
+impl Default for Config {
+    /// Returns the default config.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Context for mouse keys.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Context;
+pub struct Context {
+    /// The mouse key configuration.
+    pub config: Config,
+    /// The gear of the held [Action::Gear] key, if any.
+    pub active_gear: Option<u8>,
+}
 
 impl Context {
-    /// No runtime state to clear.
-    pub fn reset(&mut self) {}
+    /// Constructs a context from the given config
+    pub const fn from_config(config: Config) -> Context {
+        Context {
+            config,
+            active_gear: None,
+        }
+    }
+
+    /// Re-construct from context's [Config], clearing the active gear.
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// Updates the context with the given event.
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                key_event: Event::GearPressed(gear),
+                ..
+            } => {
+                self.active_gear = Some(gear);
+            }
+            key::Event::Key {
+                key_event: Event::GearReleased(gear),
+                ..
+            } if self.active_gear == Some(gear) => {
+                self.active_gear = None;
+            }
+            _ => {}
+        }
+
+        key::KeyEvents::no_events()
+    }
 }
 
-/// The event type for mouse keys. (No events).
+impl Default for Context {
+    fn default() -> Self {
+        Self::from_config(Config::new())
+    }
+}
+
+impl key::Context for Context {
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Events for mouse keys.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Event;
+pub enum Event {
+    /// Acceleration step for the held cursor or wheel key (at the event's keymap index).
+    Accelerate,
+    /// An [Action::Gear] key with the given gear was pressed.
+    GearPressed(u8),
+    /// An [Action::Gear] key with the given gear was released.
+    GearReleased(u8),
+}
 
 /// The pending key state type for mouse keys. (No pending state).
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -89,7 +373,47 @@ pub struct PendingKeyState;
 
 /// Key state used by [System].
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct KeyState;
+pub struct KeyState {
+    /// Time (in ms) the key has been held, counted in acceleration steps.
+    elapsed_ms: u16,
+    /// Gear of a held [Action::Gear] key, if any.
+    gear: Option<u8>,
+    /// Cursor movement per report.
+    move_speed: u8,
+    /// Wheel movement per report.
+    wheel_speed: u8,
+}
+
+impl KeyState {
+    /// Constructs the key state for a newly pressed key.
+    fn new(config: &Config, gear: Option<u8>) -> Self {
+        let mut ks = KeyState {
+            elapsed_ms: 0,
+            gear,
+            move_speed: 0,
+            wheel_speed: 0,
+        };
+        ks.update_speeds(config);
+        ks
+    }
+
+    /// Recompute movement speeds from the config, elapsed time and gear.
+    fn update_speeds(&mut self, config: &Config) {
+        match self.gear.and_then(|g| config.gears.get(g as usize)) {
+            Some(Gear {
+                move_speed,
+                wheel_speed,
+            }) => {
+                self.move_speed = *move_speed;
+                self.wheel_speed = *wheel_speed;
+            }
+            None => {
+                self.move_speed = config.cursor_speed(self.elapsed_ms);
+                self.wheel_speed = config.wheel_speed(self.elapsed_ms);
+            }
+        }
+    }
+}
 
 /// The [key::System] implementation for mouse keys.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -108,47 +432,57 @@ impl<R: Debug, Keys: Index<usize, Output = Key>> System<R, Keys> {
             marker: PhantomData,
         }
     }
+
+    /// The key (action + modifiers) for the given ref.
+    fn key_for_ref(&self, key_ref: &Ref) -> Key {
+        match key_ref {
+            Ref::Action(action) => Key::new(*action),
+            Ref::Key(idx) => self.keys[*idx as usize],
+        }
+    }
 }
 
-fn mouse_output_for_action(action: Action) -> key::MouseOutput {
-    const MOVE_AMOUNT: i8 = 5;
+fn mouse_output_for_action(action: Action, key_state: &KeyState) -> key::MouseOutput {
+    let move_amount = key_state.move_speed.min(i8::MAX as u8) as i8;
+    let wheel_amount = key_state.wheel_speed.min(i8::MAX as u8) as i8;
     match action {
         Action::Button(b) => key::MouseOutput {
             pressed_buttons: 1 << (b - 1),
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::CursorLeft => key::MouseOutput {
-            x: -MOVE_AMOUNT,
+            x: -move_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::CursorRight => key::MouseOutput {
-            x: MOVE_AMOUNT,
+            x: move_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::CursorUp => key::MouseOutput {
-            y: -MOVE_AMOUNT,
+            y: -move_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::CursorDown => key::MouseOutput {
-            y: MOVE_AMOUNT,
+            y: move_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::WheelUp => key::MouseOutput {
-            vertical_scroll: 1,
+            vertical_scroll: wheel_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::WheelDown => key::MouseOutput {
-            vertical_scroll: -1,
+            vertical_scroll: -wheel_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::WheelLeft => key::MouseOutput {
-            horizontal_scroll: -1,
+            horizontal_scroll: -wheel_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
         Action::WheelRight => key::MouseOutput {
-            horizontal_scroll: 1,
+            horizontal_scroll: wheel_amount,
             ..key::MouseOutput::NO_OUTPUT
         },
+        Action::Gear(_) => key::MouseOutput::NO_OUTPUT,
     }
 }
 
@@ -161,17 +495,35 @@ impl<R: Debug, Keys: Debug + Index<usize, Output = Key>> key::System<R> for Syst
 
     fn new_pressed_key(
         &self,
-        _keymap_index: u16,
-        _context: &Self::Context,
-        _key_ref: Ref,
+        keymap_index: u16,
+        context: &Self::Context,
+        key_ref: Ref,
     ) -> (
         key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
         key::KeyEvents<Self::Event>,
     ) {
-        (
-            key::PressedKeyResult::Resolved(KeyState),
-            key::KeyEvents::no_events(),
-        )
+        let Context {
+            config,
+            active_gear,
+        } = context;
+        let key_state = KeyState::new(config, *active_gear);
+
+        let events = match self.key_for_ref(&key_ref).action {
+            Action::Gear(gear) => key::KeyEvents::event(key::Event::key_event(
+                keymap_index,
+                Event::GearPressed(gear),
+            )),
+            Action::Button(_) => key::KeyEvents::no_events(),
+            _ if config.accelerates() => {
+                key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                    config.interval_ms,
+                    key::Event::key_event(keymap_index, Event::Accelerate),
+                ))
+            }
+            _ => key::KeyEvents::no_events(),
+        };
+
+        (key::PressedKeyResult::Resolved(key_state), events)
     }
 
     fn update_pending_state(
@@ -187,36 +539,80 @@ impl<R: Debug, Keys: Debug + Index<usize, Output = Key>> key::System<R> for Syst
 
     fn update_state(
         &self,
-        _key_state: &mut Self::KeyState,
-        _ref: &Self::Ref,
-        _context: &Self::Context,
-        _keymap_index: u16,
-        _event: key::Event<Self::Event>,
+        key_state: &mut Self::KeyState,
+        key_ref: &Self::Ref,
+        context: &Self::Context,
+        keymap_index: u16,
+        event: key::Event<Self::Event>,
     ) -> key::KeyEvents<Self::Event> {
-        key::KeyEvents::no_events()
+        let config = &context.config;
+        let Key { action, .. } = self.key_for_ref(key_ref);
+
+        match event {
+            key::Event::Key {
+                keymap_index: ki,
+                key_event: Event::Accelerate,
+            } if ki == keymap_index => {
+                key_state.elapsed_ms = key_state.elapsed_ms.saturating_add(config.interval_ms);
+                key_state.update_speeds(config);
+
+                if key_state.elapsed_ms < config.acceleration_end_ms() {
+                    key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                        config.interval_ms,
+                        key::Event::key_event(keymap_index, Event::Accelerate),
+                    ))
+                } else {
+                    key::KeyEvents::no_events()
+                }
+            }
+            key::Event::Key {
+                key_event: Event::GearPressed(gear),
+                ..
+            } => {
+                key_state.gear = Some(gear);
+                key_state.update_speeds(config);
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::GearReleased(gear),
+                ..
+            } if key_state.gear == Some(gear) => {
+                key_state.gear = None;
+                key_state.update_speeds(config);
+                key::KeyEvents::no_events()
+            }
+            key::Event::Input(input::Event::Release { keymap_index: ki }) if ki == keymap_index => {
+                match action {
+                    Action::Gear(gear) => key::KeyEvents::event(key::Event::key_event(
+                        keymap_index,
+                        Event::GearReleased(gear),
+                    )),
+                    _ => key::KeyEvents::no_events(),
+                }
+            }
+            _ => key::KeyEvents::no_events(),
+        }
     }
 
     fn key_output(
         &self,
         key_ref: &Self::Ref,
-        _key_state: &Self::KeyState,
+        key_state: &Self::KeyState,
     ) -> Option<key::KeyOutput> {
-        match key_ref {
-            Ref::Action(action) => Some(key::KeyOutput::from_mouse_output(
-                mouse_output_for_action(*action),
+        let Key { action, modifiers } = self.key_for_ref(key_ref);
+        match action {
+            // Gears only change the speed of the other mouse keys.
+            Action::Gear(_) => None,
+            _ => Some(key::KeyOutput::from_usage_with_modifiers(
+                key::KeyUsage::Mouse(mouse_output_for_action(action, key_state)),
+                modifiers,
             )),
-            Ref::Key(idx) => {
-                let Key { action, modifiers } = self.keys[*idx as usize];
-                Some(key::KeyOutput::from_usage_with_modifiers(
-                    key::KeyUsage::Mouse(mouse_output_for_action(action)),
-                    modifiers,
-                ))
-            }
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
 
@@ -228,6 +624,120 @@ mod tests {
 
     #[test]
     fn test_sizeof_event() {
-        assert_eq!(0, core::mem::size_of::<Event>());
+        assert_eq!(2, core::mem::size_of::<Event>());
+    }
+
+    #[test]
+    fn test_config_default_does_not_accelerate() {
+        // Assemble
+        let config = Config::new();
+
+        // Act
+        let initial_speed = config.cursor_speed(0);
+        let later_speed = config.cursor_speed(5000);
+
+        // Assert
+        assert!(!config.accelerates());
+        assert_eq!(DEFAULT_MOVE_DELTA, initial_speed);
+        assert_eq!(DEFAULT_MOVE_DELTA, later_speed);
+    }
+
+    #[test]
+    fn test_config_cursor_speed_ramps_after_delay() {
+        // Assemble
+        let config = Config {
+            delay_ms: 100,
+            move_delta: 2,
+            max_speed: 12,
+            time_to_max_ms: 1000,
+            ..Config::new()
+        };
+
+        // Act
+        let speed_before_delay = config.cursor_speed(50);
+        let speed_half_way = config.cursor_speed(600);
+        let speed_after_max = config.cursor_speed(2000);
+
+        // Assert
+        assert_eq!(2, speed_before_delay);
+        assert_eq!(7, speed_half_way);
+        assert_eq!(12, speed_after_max);
+    }
+
+    #[test]
+    fn test_accelerate_event_increases_cursor_speed() {
+        use key::System as _;
+
+        // Assemble
+        let context = Context::from_config(Config {
+            delay_ms: 0,
+            interval_ms: 10,
+            move_delta: 1,
+            max_speed: 11,
+            time_to_max_ms: 100,
+            ..Config::new()
+        });
+        let system: System<Ref, [Key; 0]> = System::new([]);
+        let key_ref = Ref::Action(Action::CursorRight);
+        let (pkr, pke) = system.new_pressed_key(0, &context, key_ref);
+        let mut key_state = pkr.unwrap_resolved();
+
+        // Act
+        let ev = pke.into_iter().next().unwrap().event;
+        let next_events = system.update_state(&mut key_state, &key_ref, &context, 0, ev);
+        let actual_output = system.key_output(&key_ref, &key_state);
+
+        // Assert
+        let expected_output = Some(key::KeyOutput::from_mouse_output(key::MouseOutput {
+            x: 2,
+            ..key::MouseOutput::NO_OUTPUT
+        }));
+        assert_eq!(expected_output, actual_output);
+        assert_eq!(1, next_events.into_iter().count());
+    }
+
+    #[test]
+    fn test_gear_pressed_sets_constant_cursor_speed() {
+        use key::System as _;
+
+        // Assemble
+        let context = Context::default();
+        let system: System<Ref, [Key; 0]> = System::new([]);
+        let key_ref = Ref::Action(Action::CursorDown);
+        let (pkr, _pke) = system.new_pressed_key(0, &context, key_ref);
+        let mut key_state = pkr.unwrap_resolved();
+
+        // Act
+        let ev = key::Event::key_event(1, Event::GearPressed(2));
+        system.update_state(&mut key_state, &key_ref, &context, 0, ev);
+        let actual_output = system.key_output(&key_ref, &key_state);
+
+        // Assert
+        let expected_output = Some(key::KeyOutput::from_mouse_output(key::MouseOutput {
+            y: DEFAULT_GEARS[2].move_speed as i8,
+            ..key::MouseOutput::NO_OUTPUT
+        }));
+        assert_eq!(expected_output, actual_output);
+    }
+
+    #[test]
+    fn test_gear_key_with_modifiers_has_no_output() {
+        use key::System as _;
+
+        // Assemble
+        let context = Context::default();
+        let system: System<Ref, [Key; 1]> = System::new([Key {
+            action: Action::Gear(0),
+            modifiers: key::KeyboardModifiers::LEFT_SHIFT,
+        }]);
+        let key_ref = Ref::Key(0);
+        let (pkr, _pke) = system.new_pressed_key(0, &context, key_ref);
+        let key_state = pkr.unwrap_resolved();
+
+        // Act
+        let actual_output = system.key_output(&key_ref, &key_state);
+
+        // Assert
+        assert_eq!(None, actual_output);
     }
 }
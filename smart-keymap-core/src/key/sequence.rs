@@ -6,14 +6,29 @@
 //!  append to a buffer in [Context](crate::key::sequence::Context).
 //! An exact match resolves to a bound key (looked up from primary sequence keys).
 //!
-//! Behaviour (v1):
+//! Behaviour (v2):
 //! - SequenceStart: no HID output; arms mode (or restarts if already armed).
+//! - SequenceStartHeld: as SequenceStart, but sequence mode lasts
+//!   while the key is held (no step timeout);
+//!   releasing it ends the sequence.
 //! - Steps: press edges only; buffer lives on
-//!   [Context](crate::key::sequence::Context) (no pending session).
-//! - Timeout: per-step, refreshed on each valid step; exact match on timeout
-//!   resolves (first config entry wins); strict prefix only aborts.
+//!   [Context](crate::key::sequence::Context).
+//! - An exact match with no longer candidates resolves immediately
+//!   (first config entry wins).
+//! - An exact match which could still be extended is the sequence's
+//!   *longest match* so far. The step key is pending until:
+//!   the step times out, the held start key is released,
+//!   or a press which doesn't continue the sequence
+//!   (these resolve the longest match);
+//!   or a press which continues the sequence (the step key is a no-op).
+//! - Timeout: per-step, refreshed on each valid step;
+//!   the longest of the remaining candidates' timeouts
+//!   (see [SequenceConfig::timeout]).
+//!   Without a longest match, timeout aborts.
 //! - Unknown / dead path: abort without sequence output.
 //! - When mode is inactive, member keys act as passthrough.
+//! - Sequence mode changes are reported as [Feedback] callbacks
+//!   (e.g. for an LED).
 
 use core::fmt::Debug;
 use core::ops::Index;
@@ -34,6 +49,8 @@ pub enum Ref {
     Auxiliary(u8),
     /// Arms (or restarts) sequence mode. JSON/Nickel token is `SequenceStart`.
     SequenceStart,
+    /// Arms (or restarts) sequence mode while held. JSON/Nickel token is `SequenceStartHeld`.
+    SequenceStartHeld,
 }
 
 /// Identifier of a sequence in [`Config::sequences`].
@@ -68,6 +85,32 @@ impl<const MAX_SEQUENCE_LEN: usize> From<heapless::Vec<u16, MAX_SEQUENCE_LEN>>
     }
 }
 
+/// Per-sequence overrides of the [Config].
+///
+/// Sequences without an entry in [Config::sequence_configs] use [SequenceConfig::new].
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SequenceConfig {
+    /// Overrides [Config::timeout] for this sequence.
+    ///
+    /// While armed, the step timeout is the longest timeout
+    ///  of the sequences the buffer could still resolve to.
+    #[serde(default)]
+    pub timeout: Option<u16>,
+}
+
+impl SequenceConfig {
+    /// Constructs a sequence config with no overrides.
+    pub const fn new() -> Self {
+        Self { timeout: None }
+    }
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Sequence definitions and timing.
 #[derive(Deserialize, Clone, Copy, PartialEq)]
 pub struct Config<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> {
@@ -80,6 +123,12 @@ pub struct Config<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> {
 
     /// Minimum idle time (ms) before SequenceStart can arm mode.
     pub required_idle_time: Option<u16>,
+
+    /// Per-sequence overrides, indexed by sequence id.
+    ///
+    /// May be shorter than [Self::sequences].
+    #[serde(default)]
+    pub sequence_configs: Slice<SequenceConfig, MAX_SEQUENCES>,
 }
 
 impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> core::fmt::Debug
@@ -90,6 +139,7 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> core::fmt::Debug
             .field("timeout", &self.timeout)
             .field("sequences", &self.sequences.as_slice())
             .field("required_idle_time", &self.required_idle_time)
+            .field("sequence_configs", &self.sequence_configs.as_slice())
             .finish()
     }
 }
@@ -110,8 +160,22 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
             timeout: DEFAULT_TIMEOUT,
             sequences: Slice::from_slice(&[]),
             required_idle_time: None,
+            sequence_configs: Slice::from_slice(&[]),
         }
     }
+
+    /// The overrides for the given sequence.
+    pub fn sequence_config(&self, id: SequenceId) -> SequenceConfig {
+        self.sequence_configs
+            .get(id as usize)
+            .copied()
+            .unwrap_or(SequenceConfig::new())
+    }
+
+    /// The timeout for the given sequence.
+    pub fn sequence_timeout(&self, id: SequenceId) -> u16 {
+        self.sequence_config(id).timeout.unwrap_or(self.timeout)
+    }
 }
 
 impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> Default
@@ -129,21 +193,51 @@ pub enum PressOutcome {
     Inactive,
     /// Step accepted; waiting for more keys or timeout.
     Continue,
+    /// Step accepted; this is the longest sequence matched so far,
+    ///  but the buffer could still continue to a longer sequence.
+    ContinueWithMatch(SequenceId),
     /// Sequence completed with this id — emit bound key.
     Resolved(SequenceId),
     /// Aborted; no sequence output.
     Aborted,
 }
 
+/// Sequence mode feedback, reported as [keymap::KeymapCallback::Sequence] callbacks.
+///
+/// e.g. firmware can light an LED while sequence mode is armed.
+#[derive(Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Feedback {
+    /// Sequence mode was armed (or restarted).
+    Armed,
+    /// A step was accepted; sequence mode is still armed.
+    Continuing,
+    /// Sequence mode ended without resolving a sequence.
+    Aborted,
+    /// Sequence mode ended by resolving a sequence.
+    Resolved,
+}
+
+impl Feedback {
+    fn key_events<E: Copy + Debug>(self) -> key::KeyEvents<E> {
+        key::KeyEvents::event(key::Event::Keymap(keymap::KeymapEvent::Callback(
+            keymap::KeymapCallback::Sequence(self),
+        )))
+    }
+}
+
 /// Global sequence mode state.
 #[derive(Clone, Copy, PartialEq)]
 pub struct Context<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> {
     config: Config<MAX_SEQUENCES, MAX_SEQUENCE_LEN>,
     mode_active: bool,
+    /// Keymap index of the held start key, if armed by [Ref::SequenceStartHeld].
+    held_start: Option<u16>,
     idle_time_ms: u32,
     timeout_generation: u16,
     buffer: [u16; MAX_SEQUENCE_LEN],
     buffer_len: usize,
+    /// The longest sequence matched by the buffer so far.
+    longest_match: Option<SequenceId>,
     /// Set on each Input press; read by sequence keys in `new_pressed_key`.
     last_press_outcome: PressOutcome,
 }
@@ -155,9 +249,11 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize> Debug
         f.debug_struct("Context")
             .field("config", &self.config)
             .field("mode_active", &self.mode_active)
+            .field("held_start", &self.held_start)
             .field("idle_time_ms", &self.idle_time_ms)
             .field("timeout_generation", &self.timeout_generation)
             .field("buffer", &&self.buffer[..self.buffer_len])
+            .field("longest_match", &self.longest_match)
             .field("last_press_outcome", &self.last_press_outcome)
             .finish()
     }
@@ -171,10 +267,12 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
         Self {
             config,
             mode_active: false,
+            held_start: None,
             idle_time_ms: 0,
             timeout_generation: 0,
             buffer: [0; MAX_SEQUENCE_LEN],
             buffer_len: 0,
+            longest_match: None,
             last_press_outcome: PressOutcome::Inactive,
         }
     }
@@ -199,6 +297,28 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
         self.last_press_outcome
     }
 
+    /// Keymap index of the held start key, if armed by [Ref::SequenceStartHeld].
+    pub fn held_start(&self) -> Option<u16> {
+        self.held_start
+    }
+
+    /// Whether the timeout with the given generation is for the current step.
+    pub fn is_current_timeout(&self, gen_id: u16) -> bool {
+        self.mode_active && gen_id == self.timeout_generation
+    }
+
+    /// Whether pressing the given key would continue the sequence.
+    pub fn continues_with(&self, keymap_index: u16) -> bool {
+        let buffer = self.buffer_slice();
+        self.mode_active
+            && self.config.sequences.iter().any(|seq| {
+                let s = seq.as_slice();
+                s.len() > buffer.len()
+                    && s[..buffer.len()] == *buffer
+                    && s[buffer.len()] == keymap_index
+            })
+    }
+
     fn sufficient_idle_time(&self) -> bool {
         self.idle_time_ms >= self.config.required_idle_time.unwrap_or(0) as u32
     }
@@ -208,16 +328,20 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
         self.timeout_generation
     }
 
-    fn arm(&mut self) {
+    fn arm(&mut self, held_start: Option<u16>) {
         self.mode_active = true;
+        self.held_start = held_start;
         self.buffer_len = 0;
+        self.longest_match = None;
         self.bump_timeout();
         self.last_press_outcome = PressOutcome::Inactive;
     }
 
     fn disarm(&mut self) {
         self.mode_active = false;
+        self.held_start = None;
         self.buffer_len = 0;
+        self.longest_match = None;
         self.bump_timeout();
     }
 
@@ -225,11 +349,32 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
         &self.buffer[..self.buffer_len]
     }
 
+    /// The longest timeout of the sequences the buffer could still resolve to.
+    fn step_timeout(&self) -> u16 {
+        self.candidates_for_buffer()
+            .iter()
+            .map(|&id| self.config.sequence_timeout(id))
+            .max()
+            .unwrap_or(self.config.timeout)
+    }
+
+    /// Schedules the step timeout, unless the start key is held.
     fn schedule_timeout(&self, gen_id: u16) -> key::KeyEvents<Event> {
-        key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
-            self.config.timeout,
-            key::Event::key_event(0, Event::Timeout(gen_id)),
-        ))
+        if self.held_start.is_some() {
+            key::KeyEvents::no_events()
+        } else {
+            key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                self.step_timeout(),
+                key::Event::key_event(0, Event::Timeout(gen_id)),
+            ))
+        }
+    }
+
+    fn arm_events(&mut self, held_start: Option<u16>) -> key::KeyEvents<Event> {
+        self.arm(held_start);
+        let mut pke = self.schedule_timeout(self.timeout_generation);
+        pke.extend(Feedback::Armed.key_events());
+        pke
     }
 
     fn candidates_for_buffer(&self) -> heapless::Vec<SequenceId, MAX_SEQUENCES> {
@@ -269,10 +414,8 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
             self.buffer[self.buffer_len] = keymap_index;
             self.buffer_len += 1;
             let candidates = self.candidates_for_buffer();
-            match (
-                self.exact_match_id(&candidates),
-                self.has_longer(&candidates),
-            ) {
+            let exact_match = self.exact_match_id(&candidates);
+            match (exact_match, self.has_longer(&candidates)) {
                 (Some(id), false) => {
                     self.disarm();
                     self.last_press_outcome = PressOutcome::Resolved(id);
@@ -284,7 +427,11 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
                 }
                 _ => {
                     // Still waiting (maybe exact+longer, or only longer).
-                    self.last_press_outcome = PressOutcome::Continue;
+                    self.longest_match = exact_match.or(self.longest_match);
+                    self.last_press_outcome = match self.longest_match {
+                        Some(id) => PressOutcome::ContinueWithMatch(id),
+                        None => PressOutcome::Continue,
+                    };
                     self.bump_timeout();
                 }
             }
@@ -305,15 +452,27 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
                 if self.mode_active {
                     self.step_press(keymap_index);
                     match self.last_press_outcome {
-                        PressOutcome::Continue => self.schedule_timeout(self.timeout_generation),
-                        PressOutcome::Resolved(id) => key::KeyEvents::event(key::Event::key_event(
-                            keymap_index,
-                            Event::SequenceResolved(id),
-                        )),
-                        PressOutcome::Aborted => key::KeyEvents::event(key::Event::key_event(
-                            keymap_index,
-                            Event::Aborted,
-                        )),
+                        PressOutcome::Continue | PressOutcome::ContinueWithMatch(_) => {
+                            let mut pke = self.schedule_timeout(self.timeout_generation);
+                            pke.extend(Feedback::Continuing.key_events());
+                            pke
+                        }
+                        PressOutcome::Resolved(id) => {
+                            let mut pke = key::KeyEvents::event(key::Event::key_event(
+                                keymap_index,
+                                Event::SequenceResolved(id),
+                            ));
+                            pke.extend(Feedback::Resolved.key_events());
+                            pke
+                        }
+                        PressOutcome::Aborted => {
+                            let mut pke = key::KeyEvents::event(key::Event::key_event(
+                                keymap_index,
+                                Event::Aborted,
+                            ));
+                            pke.extend(Feedback::Aborted.key_events());
+                            pke
+                        }
                         PressOutcome::Inactive => key::KeyEvents::no_events(),
                     }
                 } else {
@@ -321,13 +480,21 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
                     key::KeyEvents::no_events()
                 }
             }
+            key::Event::Input(input::Event::Release { keymap_index })
+                if self.mode_active && self.held_start == Some(keymap_index) =>
+            {
+                // Releasing the held start key ends the sequence.
+                // (With a longest match, the pending step key resolves it first).
+                self.disarm();
+                self.last_press_outcome = PressOutcome::Aborted;
+                Feedback::Aborted.key_events()
+            }
             key::Event::Key {
                 key_event: Event::Arm,
                 ..
             } => {
                 if self.sufficient_idle_time() {
-                    self.arm();
-                    self.schedule_timeout(self.timeout_generation)
+                    self.arm_events(None)
                 } else {
                     key::KeyEvents::no_events()
                 }
@@ -335,22 +502,42 @@ impl<const MAX_SEQUENCES: usize, const MAX_SEQUENCE_LEN: usize>
             key::Event::Key {
                 key_event: Event::Restart,
                 ..
+            } => self.arm_events(None),
+            key::Event::Key {
+                key_event: Event::ArmHeld,
+                keymap_index,
             } => {
-                self.arm();
-                self.schedule_timeout(self.timeout_generation)
+                if self.mode_active || self.sufficient_idle_time() {
+                    self.arm_events(Some(keymap_index))
+                } else {
+                    key::KeyEvents::no_events()
+                }
             }
             key::Event::Key {
                 key_event: Event::Timeout(gen),
                 ..
             } => {
-                if self.mode_active && gen == self.timeout_generation {
-                    // v1: timeout always aborts without sequence output
-                    //  (including exact match).
-                    // Emitting a binding needs a press path; pure timeout has none.
+                if self.is_current_timeout(gen) {
+                    // Timeout with a longest match is resolved by the pending step key
+                    //  (which needs a press path to emit the binding);
+                    //  so here the timeout aborts.
                     self.disarm();
                     self.last_press_outcome = PressOutcome::Aborted;
+                    Feedback::Aborted.key_events()
+                } else {
+                    key::KeyEvents::no_events()
+                }
+            }
+            key::Event::Key {
+                key_event: Event::MatchResolved(_),
+                ..
+            } => {
+                if self.mode_active {
+                    self.disarm();
+                    Feedback::Resolved.key_events()
+                } else {
+                    key::KeyEvents::no_events()
                 }
-                key::KeyEvents::no_events()
             }
             _ => key::KeyEvents::no_events(),
         }
@@ -378,17 +565,27 @@ pub enum Event {
     Arm,
     /// Restart sequence mode (from SequenceStart when already armed).
     Restart,
+    /// Arm (or restart) sequence mode while the key is held (from SequenceStartHeld).
+    ArmHeld,
     /// Timeout; generation must match context generation.
     Timeout(u16),
     /// A sequence resolved.
     SequenceResolved(SequenceId),
     /// Sequence aborted.
     Aborted,
+    /// A pending step key resolved the longest match.
+    MatchResolved(SequenceId),
 }
 
-/// No pending state (buffer lives on [`Context`]).
+/// Pending state of a step key whose press matched a sequence
+///  which could still be continued.
+///
+/// (The buffer lives on [`Context`]).
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct PendingKeyState;
+pub struct PendingKeyState {
+    /// The longest sequence matched when the step key was pressed.
+    longest_match: SequenceId,
+}
 
 /// No key state for sequence keys (resolution nests to another ref).
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -497,6 +694,10 @@ impl<
         key::KeyEvents<Self::Event>,
     ) {
         match key_ref {
+            Ref::SequenceStartHeld => (
+                key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp),
+                key::KeyEvents::event(key::Event::key_event(keymap_index, Event::ArmHeld)),
+            ),
             Ref::SequenceStart => {
                 let ev = if context.is_armed() {
                     // Was armed before this press;
@@ -516,7 +717,7 @@ impl<
                 let passthrough = match key_ref {
                     Ref::Sequence(idx) => self.keys[idx as usize].passthrough,
                     Ref::Auxiliary(idx) => self.auxiliary_keys[idx as usize].passthrough,
-                    Ref::SequenceStart => unreachable!(),
+                    Ref::SequenceStart | Ref::SequenceStartHeld => unreachable!(),
                 };
                 let _ = i;
 
@@ -532,6 +733,12 @@ impl<
                         key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp),
                         key::KeyEvents::no_events(),
                     ),
+                    // Pending until the longest match is resolved,
+                    //  or the sequence continues.
+                    PressOutcome::ContinueWithMatch(longest_match) => (
+                        key::PressedKeyResult::Pending(PendingKeyState { longest_match }),
+                        key::KeyEvents::no_events(),
+                    ),
                     PressOutcome::Resolved(id) => {
                         if let Some(r) = self.binding_for(id) {
                             (
@@ -552,13 +759,47 @@ impl<
 
     fn update_pending_state(
         &self,
-        _pending_state: &mut Self::PendingKeyState,
-        _keymap_index: u16,
-        _context: &Self::Context,
+        pending_state: &mut Self::PendingKeyState,
+        keymap_index: u16,
+        context: &Self::Context,
         _key_ref: Ref,
-        _event: key::Event<Self::Event>,
+        event: key::Event<Self::Event>,
     ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
-        (None, key::KeyEvents::no_events())
+        // The context hasn't seen the event yet;
+        //  (inputs are replayed to it once the pending key resolves).
+        let resolves_match = match event {
+            key::Event::Input(input::Event::Press { keymap_index }) => {
+                if context.continues_with(keymap_index) {
+                    return (Some(key::NewPressedKey::NoOp), key::KeyEvents::no_events());
+                }
+                true
+            }
+            key::Event::Input(input::Event::Release { keymap_index }) => {
+                context.held_start() == Some(keymap_index)
+            }
+            key::Event::Key {
+                key_event: Event::Timeout(gen),
+                ..
+            } => context.is_current_timeout(gen),
+            _ => false,
+        };
+
+        if resolves_match {
+            let PendingKeyState { longest_match } = *pending_state;
+            let npk = match self.binding_for(longest_match) {
+                Some(r) => key::NewPressedKey::key(r),
+                None => key::NewPressedKey::NoOp,
+            };
+            (
+                Some(npk),
+                key::KeyEvents::event(key::Event::key_event(
+                    keymap_index,
+                    Event::MatchResolved(longest_match),
+                )),
+            )
+        } else {
+            (None, key::KeyEvents::no_events())
+        }
     }
 
     fn update_state(
@@ -662,4 +903,185 @@ mod tests {
         assert_eq!(ctx.last_press_outcome(), PressOutcome::Aborted);
         assert!(!ctx.is_armed());
     }
+
+    #[test]
+    fn exact_match_with_longer_continues_with_match() {
+        // Assemble: context with sequences [0] and [0, 1]
+        let mut ctx = ctx_with(&[&[0], &[0, 1]]);
+
+        // Act: start sequence, press 0
+        let _ = ctx.handle_event(key::Event::key_event(9, Event::Arm));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+
+        // Assert: [0] is the longest match; the sequence continues with 1
+        assert_eq!(ctx.last_press_outcome(), PressOutcome::ContinueWithMatch(0));
+        assert!(ctx.continues_with(1));
+        assert!(!ctx.continues_with(2));
+    }
+
+    #[test]
+    fn step_timeout_is_longest_candidate_timeout() {
+        // Assemble: sequences [0, 1] (default timeout) and [0, 2] (timeout 3000)
+        let mut ctx = Ctx::from_config(Config {
+            sequences: Slice::from_slice(&[
+                SequenceIndices::from_slice(&[0, 1]),
+                SequenceIndices::from_slice(&[0, 2]),
+            ]),
+            sequence_configs: Slice::from_slice(&[
+                SequenceConfig::new(),
+                SequenceConfig {
+                    timeout: Some(3000),
+                },
+            ]),
+            ..Config::new()
+        });
+
+        // Act
+        let _ = ctx.handle_event(key::Event::key_event(9, Event::Arm));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+
+        // Assert
+        assert_eq!(3000, ctx.step_timeout());
+    }
+
+    #[test]
+    fn held_start_release_aborts() {
+        // Assemble: context with sequence [0, 1]
+        let mut ctx = ctx_with(&[&[0, 1]]);
+
+        // Act: hold start (index 9), press 0, release start
+        let _ = ctx.handle_event(key::Event::key_event(9, Event::ArmHeld));
+        let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+        let actual_events: heapless::Vec<_, 4> = ctx
+            .handle_event(key::Event::Input(input::Event::Release { keymap_index: 9 }))
+            .into_iter()
+            .map(|sch_ev| sch_ev.event)
+            .collect();
+
+        // Assert
+        assert!(!ctx.is_armed());
+        assert_eq!(
+            &[key::Event::Keymap(keymap::KeymapEvent::Callback(
+                keymap::KeymapCallback::Sequence(Feedback::Aborted)
+            ))],
+            actual_events.as_slice()
+        );
+    }
+
+    #[test]
+    fn arm_reports_armed_feedback() {
+        // Assemble
+        let mut ctx = ctx_with(&[&[0, 1]]);
+
+        // Act
+        let actual_events: heapless::Vec<_, 4> = ctx
+            .handle_event(key::Event::key_event(9, Event::Arm))
+            .into_iter()
+            .map(|sch_ev| sch_ev.event)
+            .collect();
+
+        // Assert
+        assert!(
+            actual_events.contains(&key::Event::Keymap(keymap::KeymapEvent::Callback(
+                keymap::KeymapCallback::Sequence(Feedback::Armed)
+            )))
+        );
+    }
+
+    mod system {
+        use super::*;
+
+        use crate::key::System as _;
+
+        type TestSystem = System<u8, [Key<u8, 2>; 1], [AuxiliaryKey<u8>; 1], 4, 4, 2>;
+
+        // Sequences [0] (binds 10) and [0, 1] (binds 11).
+        fn system() -> TestSystem {
+            System::new([Key::new(&[(0, 10), (1, 11)], 0)], [AuxiliaryKey::new(1)])
+        }
+
+        fn armed_ctx_after_step() -> Ctx {
+            let mut ctx = ctx_with(&[&[0], &[0, 1]]);
+            let _ = ctx.handle_event(key::Event::key_event(9, Event::Arm));
+            let _ = ctx.handle_event(key::Event::Input(input::Event::Press { keymap_index: 0 }));
+            ctx
+        }
+
+        #[test]
+        fn step_with_longer_candidates_is_pending() {
+            // Assemble
+            let system = system();
+            let ctx = armed_ctx_after_step();
+
+            // Act
+            let (pkr, _) = system.new_pressed_key(0, &ctx, Ref::Sequence(0));
+
+            // Assert
+            assert_eq!(
+                key::PressedKeyResult::Pending(PendingKeyState { longest_match: 0 }),
+                pkr
+            );
+        }
+
+        #[test]
+        fn pending_step_resolves_longest_match_on_timeout() {
+            // Assemble
+            let system = system();
+            let ctx = armed_ctx_after_step();
+            let mut pks = PendingKeyState { longest_match: 0 };
+            let gen = ctx.timeout_generation;
+
+            // Act
+            let (maybe_npk, _) = system.update_pending_state(
+                &mut pks,
+                0,
+                &ctx,
+                Ref::Sequence(0),
+                key::Event::key_event(0, Event::Timeout(gen)),
+            );
+
+            // Assert
+            assert_eq!(Some(key::NewPressedKey::key(10)), maybe_npk);
+        }
+
+        #[test]
+        fn pending_step_is_no_op_when_sequence_continues() {
+            // Assemble
+            let system = system();
+            let ctx = armed_ctx_after_step();
+            let mut pks = PendingKeyState { longest_match: 0 };
+
+            // Act
+            let (maybe_npk, _) = system.update_pending_state(
+                &mut pks,
+                0,
+                &ctx,
+                Ref::Sequence(0),
+                key::Event::Input(input::Event::Press { keymap_index: 1 }),
+            );
+
+            // Assert
+            assert_eq!(Some(key::NewPressedKey::NoOp), maybe_npk);
+        }
+
+        #[test]
+        fn pending_step_resolves_longest_match_on_other_press() {
+            // Assemble
+            let system = system();
+            let ctx = armed_ctx_after_step();
+            let mut pks = PendingKeyState { longest_match: 0 };
+
+            // Act
+            let (maybe_npk, _) = system.update_pending_state(
+                &mut pks,
+                0,
+                &ctx,
+                Ref::Sequence(0),
+                key::Event::Input(input::Event::Press { keymap_index: 5 }),
+            );
+
+            // Assert
+            assert_eq!(Some(key::NewPressedKey::key(10)), maybe_npk);
+        }
+    }
 }
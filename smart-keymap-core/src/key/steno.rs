@@ -0,0 +1,832 @@
+//! Stenography: steno keys are chorded together into a *stroke*.
+//!
+//! Like [crate::key::chorded] keys, steno keys are pressed together;
+//!  but rather than resolving to a key when the chord is pressed,
+//!  the keys pressed are accumulated until all of the steno keys have been released.
+//!  The resulting [Stroke] is then sent to the configured [Output]:
+//!
+//! - [Output::Keyboard] looks the stroke up in the config's dictionary,
+//!    and types the entry's translation
+//!    (as [automation::Instruction]s, in the same way as [automation] keys).
+//!
+//! - [Output::GeminiPr] and [Output::TxBolt] write the stroke as a packet
+//!    of the steno protocol to the keymap's serial output
+//!    (see [keymap::KeymapEvent::SerialOutput]),
+//!    for use with steno software such as Plover.
+//!
+//! The dictionary is generated from a (Plover-format) JSON dictionary at compile time;
+//!  its entries are sorted by stroke.
+//!
+//! Steno is a separate family, rather than a mode of [crate::key::chorded]:
+//!  a chord is a pending key, which resolves (on timeout, or when interrupted)
+//!  by replacing itself with a single key;
+//!  whereas a stroke has no timeout, never resolves to a key,
+//!  and only completes when every steno key has been released.
+//! Steno keys are never pending, so they don't hold up other keys' input,
+//!  and none of the chord resolution (timeouts, passthrough, deferred releases) applies.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use serde::Deserialize;
+
+use crate::input;
+use crate::key;
+use crate::keymap;
+use crate::slice::Slice;
+
+use key::automation::{self, Execution, Instruction};
+
+/// The number of texts which can be queued to be typed.
+pub const TYPING_QUEUE_SIZE: usize = 4;
+
+/// Length of a GeminiPR packet.
+pub const GEMINI_PR_PACKET_LEN: usize = 6;
+
+/// Maximum length of a TX Bolt packet. (Up to four key sets, then a terminating zero byte).
+pub const MAX_TX_BOLT_PACKET_LEN: usize = 5;
+
+/// Steno keys, in steno order.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// `#`: the number bar.
+    Number,
+    /// `S-`
+    LeftS,
+    /// `T-`
+    LeftT,
+    /// `K-`
+    LeftK,
+    /// `P-`
+    LeftP,
+    /// `W-`
+    LeftW,
+    /// `H-`
+    LeftH,
+    /// `R-`
+    LeftR,
+    /// `A-`
+    A,
+    /// `O-`
+    O,
+    /// `*`
+    Star,
+    /// `-E`
+    E,
+    /// `-U`
+    U,
+    /// `-F`
+    RightF,
+    /// `-R`
+    RightR,
+    /// `-P`
+    RightP,
+    /// `-B`
+    RightB,
+    /// `-L`
+    RightL,
+    /// `-G`
+    RightG,
+    /// `-T`
+    RightT,
+    /// `-S`
+    RightS,
+    /// `-D`
+    RightD,
+    /// `-Z`
+    RightZ,
+}
+
+impl Key {
+    /// The number of steno keys.
+    pub const COUNT: usize = 23;
+
+    /// All of the steno keys, in steno order.
+    pub const ALL: [Key; Self::COUNT] = [
+        Key::Number,
+        Key::LeftS,
+        Key::LeftT,
+        Key::LeftK,
+        Key::LeftP,
+        Key::LeftW,
+        Key::LeftH,
+        Key::LeftR,
+        Key::A,
+        Key::O,
+        Key::Star,
+        Key::E,
+        Key::U,
+        Key::RightF,
+        Key::RightR,
+        Key::RightP,
+        Key::RightB,
+        Key::RightL,
+        Key::RightG,
+        Key::RightT,
+        Key::RightS,
+        Key::RightD,
+        Key::RightZ,
+    ];
+
+    /// The key's position in steno order. (Its bit in a [Stroke]).
+    pub const fn steno_order(self) -> u8 {
+        self as u8
+    }
+
+    /// The key's index in the GeminiPR key chart.
+    ///
+    /// (The chart has 42 keys, 7 per byte;
+    ///  where the chart has more than one key (e.g. `S1-`, `S2-`), the first is used).
+    pub const fn gemini_pr_index(self) -> u8 {
+        match self {
+            Key::Number => 1,
+            Key::LeftS => 7,
+            Key::LeftT => 9,
+            Key::LeftK => 10,
+            Key::LeftP => 11,
+            Key::LeftW => 12,
+            Key::LeftH => 13,
+            Key::LeftR => 14,
+            Key::A => 15,
+            Key::O => 16,
+            Key::Star => 17,
+            Key::E => 24,
+            Key::U => 25,
+            Key::RightF => 26,
+            Key::RightR => 27,
+            Key::RightP => 28,
+            Key::RightB => 29,
+            Key::RightL => 30,
+            Key::RightG => 31,
+            Key::RightT => 32,
+            Key::RightS => 33,
+            Key::RightD => 34,
+            Key::RightZ => 41,
+        }
+    }
+
+    /// The key's index in the TX Bolt key chart. (6 keys per key set).
+    pub const fn tx_bolt_index(self) -> u8 {
+        match self {
+            Key::Number => 22,
+            _ => self.steno_order() - 1,
+        }
+    }
+}
+
+/// Reference for a steno key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ref(pub Key);
+
+/// A set of steno keys. (Bit `i` is the key with [Key::steno_order] `i`).
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Stroke(pub u32);
+
+impl Stroke {
+    /// The stroke with no keys.
+    pub const EMPTY: Stroke = Stroke(0);
+
+    /// Constructs a stroke with the given keys.
+    pub const fn from_keys(keys: &[Key]) -> Self {
+        let mut stroke = Stroke::EMPTY;
+        let mut i = 0;
+        while i < keys.len() {
+            stroke = stroke.with_key(keys[i]);
+            i += 1;
+        }
+        stroke
+    }
+
+    /// This stroke, with the given key added.
+    pub const fn with_key(self, key: Key) -> Self {
+        Stroke(self.0 | (1 << key.steno_order()))
+    }
+
+    /// Whether the stroke includes the key.
+    pub const fn contains(&self, key: Key) -> bool {
+        self.0 & (1 << key.steno_order()) != 0
+    }
+
+    /// Whether the stroke has no keys.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The keys of the stroke, in steno order.
+    pub fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        Key::ALL.into_iter().filter(|&k| self.contains(k))
+    }
+
+    /// The stroke as a GeminiPR packet.
+    ///
+    /// The first byte has its high bit set; each byte holds 7 keys of the key chart.
+    pub fn gemini_pr_packet(&self) -> [u8; GEMINI_PR_PACKET_LEN] {
+        let mut packet = [0; GEMINI_PR_PACKET_LEN];
+        packet[0] = 0x80;
+        for key in self.keys() {
+            let index = key.gemini_pr_index() as usize;
+            packet[index / 7] |= 0x40 >> (index % 7);
+        }
+        packet
+    }
+
+    /// The stroke as a TX Bolt packet.
+    ///
+    /// Each byte has the key set in its top 2 bits, and 6 keys of that set;
+    ///  only non-empty key sets are sent, followed by a zero byte.
+    pub fn tx_bolt_packet(&self) -> heapless::Vec<u8, MAX_TX_BOLT_PACKET_LEN> {
+        let mut key_sets = [0u8; 4];
+        for key in self.keys() {
+            let index = key.tx_bolt_index();
+            key_sets[(index / 6) as usize] |= 1 << (index % 6);
+        }
+
+        let mut packet = heapless::Vec::new();
+        for (key_set, &keys) in key_sets.iter().enumerate() {
+            if keys != 0 {
+                let _ = packet.push(((key_set as u8) << 6) | keys);
+            }
+        }
+        let _ = packet.push(0);
+        packet
+    }
+}
+
+/// An entry in the steno dictionary.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    /// The stroke for the entry.
+    pub stroke: Stroke,
+    /// The instructions (in [Config::instructions]) which type the entry's translation.
+    pub instructions: Execution,
+}
+
+/// Where completed strokes are sent.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Type the stroke's dictionary translation with the keyboard.
+    ///
+    /// Strokes not in the dictionary are ignored.
+    #[default]
+    Keyboard,
+    /// Write the stroke to the serial output, using the GeminiPR protocol.
+    GeminiPr,
+    /// Write the stroke to the serial output, using the TX Bolt protocol.
+    TxBolt,
+}
+
+/// Config for steno keys.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> {
+    /// The steno dictionary. Entries are sorted by stroke.
+    #[serde(default)]
+    pub dictionary: Slice<Entry, ENTRY_COUNT>,
+
+    /// Concatenation of all the dictionary entries' instructions.
+    #[serde(default)]
+    pub instructions: Slice<Instruction, INSTRUCTION_COUNT>,
+
+    /// Where completed strokes are sent.
+    #[serde(default)]
+    pub output: Output,
+
+    /// Duration (in ticks) of each instruction.
+    #[serde(default = "default_instruction_duration")]
+    pub instruction_duration: u16,
+}
+
+fn default_instruction_duration() -> u16 {
+    DEFAULT_INSTRUCTION_DURATION
+}
+
+/// Default instruction duration.
+pub const DEFAULT_INSTRUCTION_DURATION: u16 = automation::DEFAULT_INSTRUCTION_DURATION;
+
+impl<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize>
+    Config<ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        Self {
+            dictionary: Slice::from_slice(&[]),
+            instructions: Slice::from_slice(&[]),
+            output: Output::Keyboard,
+            instruction_duration: DEFAULT_INSTRUCTION_DURATION,
+        }
+    }
+
+    /// The dictionary entry for the stroke.
+    pub fn lookup(&self, stroke: Stroke) -> Option<&Entry> {
+        self.dictionary
+            .binary_search_by_key(&stroke, |entry| entry.stroke)
+            .ok()
+            .map(|i| &self.dictionary[i])
+    }
+}
+
+impl<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> Default
+    for Config<ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A translation being typed (or queued to be typed).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Typing {
+    keymap_index: u16,
+    execution: Execution,
+}
+
+/// Context for steno keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Context<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> {
+    config: Config<ENTRY_COUNT, INSTRUCTION_COUNT>,
+    stroke: Stroke,
+    pressed_indices: [Option<u16>; keymap::MAX_PRESSED_KEYS],
+    typing_queue: [Option<Typing>; TYPING_QUEUE_SIZE],
+}
+
+impl<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> Default
+    for Context<ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    fn default() -> Self {
+        Self::from_config(Config::new())
+    }
+}
+
+impl<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize>
+    Context<ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    /// Constructs a context from the given config.
+    pub const fn from_config(config: Config<ENTRY_COUNT, INSTRUCTION_COUNT>) -> Self {
+        Self {
+            config,
+            stroke: Stroke::EMPTY,
+            pressed_indices: [None; keymap::MAX_PRESSED_KEYS],
+            typing_queue: [None; TYPING_QUEUE_SIZE],
+        }
+    }
+
+    /// Clears the stroke being pressed, and stops typing.
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// The keys pressed so far in the current stroke.
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    /// Whether a translation is being typed.
+    pub fn is_typing(&self) -> bool {
+        self.typing_queue[0].is_some()
+    }
+
+    fn press(&mut self, keymap_index: u16, key: Key) {
+        if !self.pressed_indices.contains(&Some(keymap_index)) {
+            if let Some(slot) = self.pressed_indices.iter_mut().find(|i| i.is_none()) {
+                *slot = Some(keymap_index);
+            }
+        }
+        self.stroke = self.stroke.with_key(key);
+    }
+
+    /// Releases the steno key; returns the stroke if all steno keys have been released.
+    fn release(&mut self, keymap_index: u16) -> Option<Stroke> {
+        let slot = self
+            .pressed_indices
+            .iter_mut()
+            .find(|i| **i == Some(keymap_index))?;
+        *slot = None;
+
+        if self.pressed_indices.iter().all(|i| i.is_none()) {
+            let stroke = self.stroke;
+            self.stroke = Stroke::EMPTY;
+            Some(stroke)
+        } else {
+            None
+        }
+    }
+
+    fn enqueue(&mut self, typing: Typing) {
+        // Queue is full: drop the new translation.
+        if let Some(slot) = self.typing_queue.iter_mut().find(|t| t.is_none()) {
+            *slot = Some(typing);
+        }
+    }
+
+    fn type_next_instruction(&mut self) -> key::KeyEvents<Event> {
+        while let Some(typing) = self.typing_queue[0].as_mut() {
+            match self
+                .config
+                .instructions
+                .get(typing.execution.start as usize)
+                .filter(|_| !typing.execution.is_empty())
+            {
+                Some(&instruction) => {
+                    typing.execution.incr();
+                    let next_key_ev =
+                        key::Event::key_event(typing.keymap_index, Event::NextInstruction);
+                    return automation::instruction_key_events(
+                        instruction,
+                        self.config.instruction_duration,
+                        next_key_ev,
+                    );
+                }
+                None => {
+                    // Finished typing the translation; continue with the next in the queue.
+                    self.typing_queue.rotate_left(1);
+                    self.typing_queue[TYPING_QUEUE_SIZE - 1] = None;
+                }
+            }
+        }
+
+        key::KeyEvents::no_events()
+    }
+
+    fn send_stroke(&mut self, keymap_index: u16, stroke: Stroke) -> key::KeyEvents<Event> {
+        match self.config.output {
+            Output::Keyboard => match self.config.lookup(stroke) {
+                Some(&Entry { instructions, .. }) => {
+                    let type_immediately = !self.is_typing();
+                    self.enqueue(Typing {
+                        keymap_index,
+                        execution: instructions,
+                    });
+
+                    if type_immediately {
+                        self.type_next_instruction()
+                    } else {
+                        key::KeyEvents::no_events()
+                    }
+                }
+                None => key::KeyEvents::no_events(),
+            },
+            Output::GeminiPr => serial_output_events(&stroke.gemini_pr_packet()),
+            Output::TxBolt => serial_output_events(&stroke.tx_bolt_packet()),
+        }
+    }
+
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                key_event: Event::Press(key),
+                keymap_index,
+            } => {
+                self.press(keymap_index, key);
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::NextInstruction,
+                ..
+            } => self.type_next_instruction(),
+            key::Event::Input(input::Event::Release { keymap_index }) => {
+                match self.release(keymap_index) {
+                    Some(stroke) => self.send_stroke(keymap_index, stroke),
+                    None => key::KeyEvents::no_events(),
+                }
+            }
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+}
+
+fn serial_output_events(packet: &[u8]) -> key::KeyEvents<Event> {
+    key::KeyEvents::event(key::Event::Keymap(keymap::KeymapEvent::SerialOutput(
+        keymap::SerialPacket::from_slice(packet),
+    )))
+}
+
+impl<const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> key::Context
+    for Context<ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Steno key events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// The steno key was pressed; adds it to the current stroke.
+    Press(Key),
+    /// Indicates to the context to type the next instruction.
+    NextInstruction,
+}
+
+/// Pending key state type for steno keys. (No pending state.)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingKeyState;
+
+/// Key state used by [System]. (No per-key state; behaviour is on [Context].)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyState;
+
+/// The [key::System] implementation for steno keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<R, const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize>(PhantomData<R>);
+
+impl<R, const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize>
+    System<R, ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    /// Constructs a new [System].
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R, const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> Default
+    for System<R, ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Debug, const ENTRY_COUNT: usize, const INSTRUCTION_COUNT: usize> key::System<R>
+    for System<R, ENTRY_COUNT, INSTRUCTION_COUNT>
+{
+    type Ref = Ref;
+    type Context = Context<ENTRY_COUNT, INSTRUCTION_COUNT>;
+    type Event = Event;
+    type PendingKeyState = PendingKeyState;
+    type KeyState = KeyState;
+
+    fn new_pressed_key(
+        &self,
+        keymap_index: u16,
+        _context: &Self::Context,
+        Ref(key): Ref,
+    ) -> (
+        key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
+        key::KeyEvents<Self::Event>,
+    ) {
+        let pke = key::KeyEvents::event(key::Event::key_event(keymap_index, Event::Press(key)));
+        let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp);
+        (pkr, pke)
+    }
+
+    fn update_pending_state(
+        &self,
+        _pending_state: &mut Self::PendingKeyState,
+        _keymap_index: u16,
+        _context: &Self::Context,
+        _key_ref: Ref,
+        _event: key::Event<Self::Event>,
+    ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
+        panic!()
+    }
+
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        _ref: &Self::Ref,
+        _context: &Self::Context,
+        _keymap_index: u16,
+        _event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        panic!()
+    }
+
+    fn key_output(
+        &self,
+        _key_ref: &Self::Ref,
+        _key_state: &Self::KeyState,
+    ) -> Option<key::KeyOutput> {
+        panic!()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const fn tap(key_code: u8) -> Instruction {
+        Instruction::Tap(key::KeyOutput::from_key_code(key_code))
+    }
+
+    const KAT: Stroke = Stroke::from_keys(&[Key::LeftK, Key::A, Key::RightT]);
+    const TKOG: Stroke = Stroke::from_keys(&[Key::LeftT, Key::LeftK, Key::O, Key::RightG]);
+
+    fn context(output: Output) -> Context<2, 4> {
+        // "cat" (without a space), "dog".
+        Context::from_config(Config {
+            dictionary: Slice::from_slice(&[
+                Entry {
+                    stroke: KAT,
+                    instructions: Execution {
+                        start: 0,
+                        length: 1,
+                    },
+                },
+                Entry {
+                    stroke: TKOG,
+                    instructions: Execution {
+                        start: 1,
+                        length: 3,
+                    },
+                },
+            ]),
+            instructions: Slice::from_slice(&[tap(0x06), tap(0x07), tap(0x12), tap(0x0A)]),
+            output,
+            ..Config::new()
+        })
+    }
+
+    /// Presses then releases the keys (at keymap indices 0, 1, ...),
+    ///  handling events until no more are emitted.
+    ///
+    /// Returns the virtual key presses and the serial output.
+    fn stroke_outputs(ctx: &mut Context<2, 4>, keys: &[Key]) -> (Vec<key::KeyOutput>, Vec<u8>) {
+        let mut pending: Vec<key::Event<Event>> = Vec::new();
+        for (i, &k) in keys.iter().enumerate() {
+            pending.push(key::Event::key_event(i as u16, Event::Press(k)));
+        }
+        for i in 0..keys.len() {
+            pending.push(key::Event::Input(input::Event::Release {
+                keymap_index: i as u16,
+            }));
+        }
+
+        let mut key_outputs = Vec::new();
+        let mut serial_output = Vec::new();
+        while !pending.is_empty() {
+            let ev = pending.remove(0);
+            let evs = ctx.handle_event(ev);
+            for sch_ev in evs {
+                match sch_ev.event {
+                    key::Event::Input(input::Event::VirtualKeyPress { key_output }) => {
+                        key_outputs.push(key_output)
+                    }
+                    key::Event::Keymap(keymap::KeymapEvent::SerialOutput(packet)) => {
+                        serial_output.extend_from_slice(packet.as_slice())
+                    }
+                    ev @ key::Event::Key { .. } => pending.push(ev),
+                    _ => {}
+                }
+            }
+        }
+        (key_outputs, serial_output)
+    }
+
+    #[test]
+    fn test_sizeof_ref() {
+        assert_eq!(1, core::mem::size_of::<Ref>());
+    }
+
+    #[test]
+    fn test_stroke_keys_in_steno_order() {
+        // Assemble
+        let stroke = Stroke::from_keys(&[Key::RightT, Key::A, Key::LeftK]);
+
+        // Act
+        let actual: Vec<Key> = stroke.keys().collect();
+
+        // Assert
+        assert_eq!(vec![Key::LeftK, Key::A, Key::RightT], actual);
+    }
+
+    #[test]
+    fn test_gemini_pr_packet() {
+        // Assemble
+        let stroke = Stroke::from_keys(&[Key::Number, Key::LeftS, Key::A, Key::E, Key::RightZ]);
+
+        // Act
+        let actual = stroke.gemini_pr_packet();
+
+        // Assert
+        // #1 is key 1 (byte 0), S1- key 7 (byte 1), A- key 15 (byte 2),
+        //  -E key 24 (byte 3), -Z key 41 (byte 5).
+        assert_eq!([0xA0, 0x40, 0x20, 0x08, 0x00, 0x01], actual);
+    }
+
+    #[test]
+    fn test_tx_bolt_packet_sends_only_non_empty_key_sets() {
+        // Assemble
+        let stroke = Stroke::from_keys(&[Key::LeftS, Key::RightT, Key::Number]);
+
+        // Act
+        let actual = stroke.tx_bolt_packet();
+
+        // Assert
+        // S- is set 0 bit 0; -T is set 3 bit 0; # is set 3 bit 4.
+        assert_eq!(&[0x01, 0xC0 | 0x11, 0x00], actual.as_slice());
+    }
+
+    #[test]
+    fn test_deserialize_config() {
+        // Assemble
+        let json = r#"{
+            "dictionary": [{ "stroke": 524552, "instructions": { "start": 0, "length": 1 } }],
+            "instructions": [{ "Tap": { "key_code": { "Keyboard": 6 } } }],
+            "output": "TxBolt"
+        }"#;
+
+        // Act
+        let actual: Config<1, 1> = serde_json::from_str(json).unwrap();
+
+        // Assert
+        assert_eq!(KAT, actual.dictionary[0].stroke);
+        assert_eq!(Output::TxBolt, actual.output);
+    }
+
+    #[test]
+    fn test_lookup_finds_entry() {
+        // Assemble
+        let ctx = context(Output::Keyboard);
+
+        // Act
+        let actual = ctx.config.lookup(TKOG).map(|entry| entry.instructions);
+
+        // Assert
+        assert_eq!(
+            Some(Execution {
+                start: 1,
+                length: 3
+            }),
+            actual
+        );
+    }
+
+    #[test]
+    fn test_stroke_is_sent_only_after_all_keys_released() {
+        // Assemble
+        let mut ctx = context(Output::Keyboard);
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::Press(Key::LeftK)));
+        let _ = ctx.handle_event(key::Event::key_event(1, Event::Press(Key::A)));
+
+        // Act
+        let actual = ctx.handle_event(key::Event::Input(input::Event::Release { keymap_index: 0 }));
+
+        // Assert
+        assert_eq!(0, actual.into_iter().count());
+        assert_eq!(Stroke::from_keys(&[Key::LeftK, Key::A]), ctx.stroke());
+    }
+
+    #[test]
+    fn test_keyboard_output_types_translation() {
+        // Assemble
+        let mut ctx = context(Output::Keyboard);
+
+        // Act
+        let (key_outputs, serial_output) =
+            stroke_outputs(&mut ctx, &[Key::LeftT, Key::LeftK, Key::O, Key::RightG]);
+
+        // Assert
+        let expected: Vec<key::KeyOutput> = [0x07, 0x12, 0x0A]
+            .into_iter()
+            .map(key::KeyOutput::from_key_code)
+            .collect();
+        assert_eq!(expected, key_outputs);
+        assert!(serial_output.is_empty());
+        assert!(!ctx.is_typing());
+        assert!(ctx.stroke().is_empty());
+    }
+
+    #[test]
+    fn test_keyboard_output_ignores_untranslated_stroke() {
+        // Assemble
+        let mut ctx = context(Output::Keyboard);
+
+        // Act
+        let (key_outputs, _) = stroke_outputs(&mut ctx, &[Key::LeftS]);
+
+        // Assert
+        assert!(key_outputs.is_empty());
+    }
+
+    #[test]
+    fn test_gemini_pr_output_writes_packet() {
+        // Assemble
+        let mut ctx = context(Output::GeminiPr);
+
+        // Act
+        let (key_outputs, serial_output) =
+            stroke_outputs(&mut ctx, &[Key::LeftK, Key::A, Key::RightT]);
+
+        // Assert
+        assert!(key_outputs.is_empty());
+        assert_eq!(KAT.gemini_pr_packet().as_slice(), serial_output.as_slice());
+    }
+
+    #[test]
+    fn test_tx_bolt_output_writes_packet() {
+        // Assemble
+        let mut ctx = context(Output::TxBolt);
+
+        // Act
+        let (_, serial_output) = stroke_outputs(&mut ctx, &[Key::LeftK, Key::A, Key::RightT]);
+
+        // Assert
+        assert_eq!(KAT.tx_bolt_packet().as_slice(), serial_output.as_slice());
+    }
+}
@@ -6,6 +6,8 @@ use serde::Deserialize;
 use crate::input;
 use crate::key;
 
+pub use crate::key::tap_hold::InterruptResponse;
+
 /// Reference for a tap dance key.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Ref(pub u8);
@@ -19,6 +21,17 @@ pub struct Config {
     /// Later timeouts are from the re-press that scheduled them.
     #[serde(default = "default_timeout")]
     pub timeout: u16,
+
+    /// How the tap-dance key responds to interruptions (presses of other keys).
+    ///
+    /// With [InterruptResponse::Ignore], the tap-dance only resolves on timeout
+    ///  (or when no further definitions are possible).
+    /// Otherwise, an interruption resolves the tap-dance:
+    ///  as the hold definition for the current count if the key is held
+    ///  (when another key is pressed, or tapped),
+    ///  or as the tap definition if it was released.
+    #[serde(default = "default_interrupt_response")]
+    pub interrupt_response: InterruptResponse,
 }
 
 /// The default timeout.
@@ -28,9 +41,17 @@ fn default_timeout() -> u16 {
     DEFAULT_TIMEOUT
 }
 
+/// The default interrupt response.
+pub const DEFAULT_INTERRUPT_RESPONSE: InterruptResponse = InterruptResponse::Ignore;
+
+fn default_interrupt_response() -> InterruptResponse {
+    DEFAULT_INTERRUPT_RESPONSE
+}
+
 /// Default tap dance config.
 pub const DEFAULT_CONFIG: Config = Config {
     timeout: DEFAULT_TIMEOUT,
+    interrupt_response: DEFAULT_INTERRUPT_RESPONSE,
 };
 
 impl Config {
@@ -48,12 +69,26 @@ impl Default for Config {
 }
 
 /// A key with tap-dance functionality.
+///
+/// The N-th definition is used when the key is tapped N+1 times.
+/// The N-th hold definition is used when the key is held on its N+1-th press.
+///  (e.g. index 0 is "single hold", index 1 is "double hold" or "tap then hold").
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Key<R, const MAX_TAP_DANCE_DEFINITIONS: usize> {
     /// Tap-Dance definitions.
     #[serde(bound(deserialize = "R: Deserialize<'de>"))]
     #[serde(deserialize_with = "deserialize_definitions")]
     definitions: [Option<R>; MAX_TAP_DANCE_DEFINITIONS],
+    /// Tap-Dance hold definitions.
+    #[serde(bound(deserialize = "R: Copy + Deserialize<'de>"))]
+    #[serde(default = "no_definitions")]
+    #[serde(deserialize_with = "deserialize_definitions")]
+    hold_definitions: [Option<R>; MAX_TAP_DANCE_DEFINITIONS],
+}
+
+fn no_definitions<R: Copy, const MAX_TAP_DANCE_DEFINITIONS: usize>(
+) -> [Option<R>; MAX_TAP_DANCE_DEFINITIONS] {
+    [None; MAX_TAP_DANCE_DEFINITIONS]
 }
 
 /// Deserialize definitions.
@@ -64,9 +99,12 @@ where
     R: Deserialize<'de>,
     D: serde::Deserializer<'de>,
 {
-    let defs_vec: heapless::Vec<Option<R>, MAX_TAP_DANCE_DEFINITIONS> =
+    let mut defs_vec: heapless::Vec<Option<R>, MAX_TAP_DANCE_DEFINITIONS> =
         Deserialize::deserialize(deserializer)?;
 
+    // Pad with None, so keys may have fewer definitions than the maximum.
+    while defs_vec.push(None).is_ok() {}
+
     match defs_vec.into_array() {
         Ok(arr) => Ok(arr),
         Err(_) => Err(serde::de::Error::custom(
@@ -80,7 +118,10 @@ impl<R: Copy, const MAX_TAP_DANCE_DEFINITIONS: usize> Key<R, MAX_TAP_DANCE_DEFIN
     pub const fn new(
         definitions: [Option<R>; MAX_TAP_DANCE_DEFINITIONS],
     ) -> Key<R, MAX_TAP_DANCE_DEFINITIONS> {
-        Key { definitions }
+        Key {
+            definitions,
+            hold_definitions: [None; MAX_TAP_DANCE_DEFINITIONS],
+        }
     }
 
     /// Construct the tap-dance key from the given slice of keys.
@@ -94,6 +135,46 @@ impl<R: Copy, const MAX_TAP_DANCE_DEFINITIONS: usize> Key<R, MAX_TAP_DANCE_DEFIN
         }
         Self::new(definitions)
     }
+
+    /// The tap-dance key with the given hold definitions.
+    pub const fn with_hold_definitions(self, holds: &[Option<R>]) -> Self {
+        let mut hold_definitions: [Option<R>; MAX_TAP_DANCE_DEFINITIONS] =
+            [None; MAX_TAP_DANCE_DEFINITIONS];
+        let mut idx = 0;
+        while idx < hold_definitions.len() && idx < holds.len() {
+            hold_definitions[idx] = holds[idx];
+            idx += 1;
+        }
+        Key {
+            hold_definitions,
+            ..self
+        }
+    }
+
+    /// The definition for tapping the key `press_count + 1` times.
+    pub fn tap_definition(&self, press_count: u8) -> Option<R> {
+        self.definitions
+            .get(press_count as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// The definition for holding the key on its `press_count + 1`-th press.
+    pub fn hold_definition(&self, press_count: u8) -> Option<R> {
+        self.hold_definitions
+            .get(press_count as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// The press count of the last tap or hold definition.
+    fn last_press_count(&self) -> u8 {
+        self.definitions
+            .iter()
+            .zip(self.hold_definitions.iter())
+            .rposition(|(tap, hold)| tap.is_some() || hold.is_some())
+            .unwrap_or(0) as u8
+    }
 }
 
 /// Context for [Key].
@@ -116,7 +197,12 @@ impl Context {
 
 /// Resolution of a tap-dance key. (Index of the tap-dance definition).
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct TapDanceResolution(u8);
+pub enum TapDanceResolution {
+    /// Resolved to the tap definition at the index.
+    Tap(u8),
+    /// Resolved to the hold definition at the index.
+    Hold(u8),
+}
 
 /// Events emitted by a tap-dance key.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -129,35 +215,54 @@ pub enum Event {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PendingKeyState {
     press_count: u8,
+    is_pressed: bool,
+    /// Keymap index of another key pressed while this key was held.
+    interrupting_index: Option<u16>,
 }
 
 impl PendingKeyState {
     /// Constructs the initial pressed key state
     fn new() -> PendingKeyState {
-        PendingKeyState { press_count: 0 }
+        PendingKeyState {
+            press_count: 0,
+            is_pressed: true,
+            interrupting_index: None,
+        }
     }
 
-    fn handle_event(
+    /// Resolves as the hold definition if the key is held and has one; otherwise as the tap.
+    fn hold_or_tap<R: Copy, const N: usize>(&self, key: &Key<R, N>) -> TapDanceResolution {
+        if self.is_pressed && key.hold_definition(self.press_count).is_some() {
+            TapDanceResolution::Hold(self.press_count)
+        } else {
+            TapDanceResolution::Tap(self.press_count)
+        }
+    }
+
+    fn handle_event<R: Copy, const N: usize>(
         &mut self,
         context: &Context,
+        key: &Key<R, N>,
         keymap_index: u16,
         event: key::Event<Event>,
     ) -> (Option<TapDanceResolution>, key::KeyEvents<Event>) {
+        let Context { config } = context;
+
         match event {
             key::Event::Key {
                 key_event: Event::NextPressTimeout(press_timed_out),
                 keymap_index: ev_kmi,
-            } if ev_kmi == keymap_index && press_timed_out == self.press_count => (
-                Some(TapDanceResolution(self.press_count)),
-                key::KeyEvents::no_events(),
-            ),
+            } if ev_kmi == keymap_index && press_timed_out == self.press_count => {
+                (Some(self.hold_or_tap(key)), key::KeyEvents::no_events())
+            }
 
             key::Event::Input(input::Event::Press {
                 keymap_index: ev_kmi,
             }) if ev_kmi == keymap_index => {
                 self.press_count += 1;
+                self.is_pressed = true;
+                self.interrupting_index = None;
 
-                let Context { config } = context;
                 let timeout_ev = Event::NextPressTimeout(self.press_count);
 
                 let key_ev = key::Event::Key {
@@ -172,6 +277,36 @@ impl PendingKeyState {
                 (None, pke)
             }
 
+            key::Event::Input(input::Event::Release {
+                keymap_index: ev_kmi,
+            }) if ev_kmi == keymap_index => {
+                self.is_pressed = false;
+                (None, key::KeyEvents::no_events())
+            }
+
+            key::Event::Input(input::Event::Press {
+                keymap_index: ev_kmi,
+            }) => match config.interrupt_response {
+                InterruptResponse::Ignore => (None, key::KeyEvents::no_events()),
+                InterruptResponse::HoldOnKeyPress => {
+                    (Some(self.hold_or_tap(key)), key::KeyEvents::no_events())
+                }
+                InterruptResponse::HoldOnKeyTap if self.is_pressed => {
+                    self.interrupting_index = Some(ev_kmi);
+                    (None, key::KeyEvents::no_events())
+                }
+                InterruptResponse::HoldOnKeyTap => (
+                    Some(TapDanceResolution::Tap(self.press_count)),
+                    key::KeyEvents::no_events(),
+                ),
+            },
+
+            key::Event::Input(input::Event::Release {
+                keymap_index: ev_kmi,
+            }) if self.interrupting_index == Some(ev_kmi) => {
+                (Some(self.hold_or_tap(key)), key::KeyEvents::no_events())
+            }
+
             _ => (None, key::KeyEvents::no_events()),
         }
     }
@@ -249,34 +384,31 @@ impl<
         event: key::Event<Self::Event>,
     ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
         let key = &self.keys[key_index as usize];
-        let (maybe_resolution, pke) = pending_state.handle_event(context, keymap_index, event);
-
-        if let Some(TapDanceResolution(idx)) = maybe_resolution {
-            if let Some(new_key_ref) = key.definitions[idx as usize] {
-                (
-                    Some(key::NewPressedKey::key(new_key_ref)),
-                    pke.into_events(),
-                )
+        let (maybe_resolution, pke) = pending_state.handle_event(context, key, keymap_index, event);
+
+        // When no further presses can change the definition,
+        //  resolve without waiting for the timeout.
+        // (Unless the key is held, and may yet resolve as a hold).
+        let maybe_resolution = maybe_resolution.or_else(|| {
+            let is_last_press = pending_state.press_count >= key.last_press_count();
+            let may_hold = pending_state.is_pressed
+                && key.hold_definition(pending_state.press_count).is_some();
+            if is_last_press && !may_hold {
+                Some(TapDanceResolution::Tap(pending_state.press_count))
             } else {
-                (None, pke.into_events())
+                None
             }
-        } else {
-            // check pending_state press_count against key definitions
-            let definition_count = key.definitions.iter().filter(|o| o.is_some()).count();
-            if pending_state.press_count as usize >= definition_count - 1 {
-                let idx = definition_count - 1;
-                if let Some(new_key_ref) = key.definitions[idx] {
-                    (
-                        Some(key::NewPressedKey::key(new_key_ref)),
-                        pke.into_events(),
-                    )
-                } else {
-                    (None, pke.into_events())
-                }
-            } else {
-                (None, pke.into_events())
-            }
-        }
+        });
+
+        let maybe_npk = maybe_resolution.map(|resolution| {
+            let maybe_new_key_ref = match resolution {
+                TapDanceResolution::Tap(idx) => key.tap_definition(idx),
+                TapDanceResolution::Hold(idx) => key.hold_definition(idx),
+            };
+            maybe_new_key_ref.map_or(key::NewPressedKey::NoOp, key::NewPressedKey::key)
+        });
+
+        (maybe_npk, pke.into_events())
     }
 
     fn update_state(
@@ -312,4 +444,166 @@ mod tests {
     fn test_sizeof_event() {
         assert_eq!(1, core::mem::size_of::<Event>());
     }
+
+    use key::System as _;
+
+    const KEYMAP_INDEX: u16 = 0;
+
+    const TAP_A: u8 = 1;
+    const TAP_B: u8 = 2;
+    const HOLD_A: u8 = 3;
+    const HOLD_B: u8 = 4;
+
+    type TestSystem = System<u8, [Key<u8, 3>; 1], 3>;
+
+    fn system() -> TestSystem {
+        System::new([Key::from_definitions(&[TAP_A, TAP_B])
+            .with_hold_definitions(&[Some(HOLD_A), Some(HOLD_B)])])
+    }
+
+    fn context(interrupt_response: InterruptResponse) -> Context {
+        Context::from_config(Config {
+            interrupt_response,
+            ..DEFAULT_CONFIG
+        })
+    }
+
+    fn press(keymap_index: u16) -> key::Event<Event> {
+        key::Event::Input(input::Event::Press { keymap_index })
+    }
+
+    fn release(keymap_index: u16) -> key::Event<Event> {
+        key::Event::Input(input::Event::Release { keymap_index })
+    }
+
+    fn timeout(press_count: u8) -> key::Event<Event> {
+        key::Event::key_event(KEYMAP_INDEX, Event::NextPressTimeout(press_count))
+    }
+
+    /// Presses the tap-dance key, then feeds the events; returns the resolution.
+    fn resolve(context: &Context, events: &[key::Event<Event>]) -> Option<key::NewPressedKey<u8>> {
+        let system = system();
+        let (pkr, _) = system.new_pressed_key(KEYMAP_INDEX, context, Ref(0));
+        let key::PressedKeyResult::Pending(mut pks) = pkr else {
+            panic!("Expected pending key state");
+        };
+        events.iter().find_map(|&ev| {
+            system
+                .update_pending_state(&mut pks, KEYMAP_INDEX, context, Ref(0), ev)
+                .0
+        })
+    }
+
+    #[test]
+    fn test_single_tap_resolves_tap_on_timeout() {
+        // Assemble
+        let context = context(InterruptResponse::Ignore);
+
+        // Act
+        let actual = resolve(&context, &[release(KEYMAP_INDEX), timeout(0)]);
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(TAP_A)), actual);
+    }
+
+    #[test]
+    fn test_single_hold_resolves_hold_on_timeout() {
+        // Assemble
+        let context = context(InterruptResponse::Ignore);
+
+        // Act
+        let actual = resolve(&context, &[timeout(0)]);
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(HOLD_A)), actual);
+    }
+
+    #[test]
+    fn test_tap_then_hold_resolves_second_hold() {
+        // Assemble
+        let context = context(InterruptResponse::Ignore);
+
+        // Act
+        let actual = resolve(
+            &context,
+            &[
+                release(KEYMAP_INDEX),
+                press(KEYMAP_INDEX),
+                timeout(0),
+                timeout(1),
+            ],
+        );
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(HOLD_B)), actual);
+    }
+
+    #[test]
+    fn test_double_tap_resolves_on_last_release() {
+        // Assemble
+        let context = context(InterruptResponse::Ignore);
+
+        // Act
+        let actual = resolve(
+            &context,
+            &[
+                release(KEYMAP_INDEX),
+                press(KEYMAP_INDEX),
+                release(KEYMAP_INDEX),
+            ],
+        );
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(TAP_B)), actual);
+    }
+
+    #[test]
+    fn test_interrupt_ignore_waits_for_timeout() {
+        // Assemble
+        let context = context(InterruptResponse::Ignore);
+
+        // Act
+        let actual = resolve(&context, &[press(1), release(1)]);
+
+        // Assert
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn test_interrupt_hold_on_key_press_resolves_hold() {
+        // Assemble
+        let context = context(InterruptResponse::HoldOnKeyPress);
+
+        // Act
+        let actual = resolve(&context, &[press(1)]);
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(HOLD_A)), actual);
+    }
+
+    #[test]
+    fn test_interrupt_hold_on_key_tap_resolves_hold_on_release() {
+        // Assemble
+        let context = context(InterruptResponse::HoldOnKeyTap);
+
+        // Act
+        let actual_after_press = resolve(&context, &[press(1)]);
+        let actual_after_tap = resolve(&context, &[press(1), release(1)]);
+
+        // Assert
+        assert_eq!(None, actual_after_press);
+        assert_eq!(Some(key::NewPressedKey::Key(HOLD_A)), actual_after_tap);
+    }
+
+    #[test]
+    fn test_interrupt_after_release_resolves_tap() {
+        // Assemble
+        let context = context(InterruptResponse::HoldOnKeyTap);
+
+        // Act
+        let actual = resolve(&context, &[release(KEYMAP_INDEX), press(1)]);
+
+        // Assert
+        assert_eq!(Some(key::NewPressedKey::Key(TAP_A)), actual);
+    }
 }
@@ -1458,6 +1458,8 @@ mod tests {
                 presses
             },
             recent_press_count: 1,
+            host_leds: keymap::HostLeds::NONE,
+            active_layers: 0,
         });
 
         // Act / Assert: 50ms since last press of KEYMAP_INDEX < 175.
@@ -1484,6 +1486,8 @@ mod tests {
                 presses
             },
             recent_press_count: 1,
+            host_leds: keymap::HostLeds::NONE,
+            active_layers: 0,
         });
 
         // Act / Assert: 150ms since last press >= 100.
@@ -1510,6 +1514,8 @@ mod tests {
                 presses
             },
             recent_press_count: 1,
+            host_leds: keymap::HostLeds::NONE,
+            active_layers: 0,
         });
 
         // Act / Assert: no prior press of KEYMAP_INDEX in the ring.
@@ -1530,6 +1536,8 @@ mod tests {
                 presses
             },
             recent_press_count: 1,
+            host_leds: keymap::HostLeds::NONE,
+            active_layers: 0,
         });
 
         // Act / Assert
@@ -1556,6 +1564,8 @@ mod tests {
                 presses
             },
             recent_press_count: 1,
+            host_leds: keymap::HostLeds::NONE,
+            active_layers: 0,
         });
         let sys = system();
 
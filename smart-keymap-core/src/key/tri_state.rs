@@ -16,6 +16,11 @@
 //!
 //! Classic use is Alt-Tab (a "swapper"): start holds Left Alt and taps Tab,
 //!  continue taps Tab, interrupt releases Left Alt.
+//!
+//! A key may also declare outputs which [continue through](ContinueThrough)
+//!  the session (e.g. Shift, arrow keys) rather than interrupt it,
+//!  a reverse tap (tapped by the [Ref::Reverse] key while the session is armed),
+//!  and an idle timeout after which the hold is released.
 
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -27,9 +32,81 @@ use crate::input;
 use crate::key;
 use crate::keymap;
 
-/// Reference for a tri-state key (index into [System] key data).
+/// Reference for a tri-state key.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
-pub struct Ref(pub u8);
+pub enum Ref {
+    /// Index into [System] key data for a [Key].
+    Key(u8),
+    /// Taps the armed session's [Key::reverse_tap].
+    Reverse,
+}
+
+/// The maximum number of key codes in a [ContinueThrough].
+pub const MAX_CONTINUE_THROUGH_KEY_CODES: usize = 6;
+
+/// Outputs which continue through (rather than interrupt) a tri-state session.
+///
+/// A resolved output continues through if it is a keyboard output
+///  whose key code is one of `key_codes` (or is modifier-only),
+///  and whose modifiers are all in `key_modifiers`.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "ContinueThroughJson")]
+pub struct ContinueThrough {
+    key_modifiers: key::KeyboardModifiers,
+    key_codes: [u8; MAX_CONTINUE_THROUGH_KEY_CODES],
+}
+
+#[derive(Deserialize)]
+struct ContinueThroughJson {
+    #[serde(default)]
+    key_modifiers: key::KeyboardModifiers,
+    #[serde(default)]
+    key_codes: heapless::Vec<u8, MAX_CONTINUE_THROUGH_KEY_CODES>,
+}
+
+impl From<ContinueThroughJson> for ContinueThrough {
+    fn from(
+        ContinueThroughJson {
+            key_modifiers,
+            key_codes,
+        }: ContinueThroughJson,
+    ) -> Self {
+        Self::new(key_modifiers, &key_codes)
+    }
+}
+
+impl ContinueThrough {
+    /// No outputs continue through.
+    pub const EMPTY: ContinueThrough = ContinueThrough {
+        key_modifiers: key::KeyboardModifiers::new(),
+        key_codes: [0; MAX_CONTINUE_THROUGH_KEY_CODES],
+    };
+
+    /// Constructs a set from the given modifiers and (non-zero) key codes.
+    ///
+    /// Key codes beyond [MAX_CONTINUE_THROUGH_KEY_CODES] are ignored.
+    pub const fn new(key_modifiers: key::KeyboardModifiers, key_codes: &[u8]) -> Self {
+        let mut set = Self::EMPTY;
+        set.key_modifiers = key_modifiers;
+        let mut i = 0;
+        while i < key_codes.len() && i < MAX_CONTINUE_THROUGH_KEY_CODES {
+            set.key_codes[i] = key_codes[i];
+            i += 1;
+        }
+        set
+    }
+
+    /// Whether the resolved key output continues through the session.
+    pub fn contains(&self, key_output: &key::KeyOutput) -> bool {
+        let key_code_ok = match key_output.key_code() {
+            key::KeyUsage::Keyboard(0) => true,
+            key::KeyUsage::Keyboard(kc) => self.key_codes.contains(&kc),
+            _ => false,
+        };
+        let modifiers_ok = *key_output.key_modifiers().difference(&self.key_modifiers) == 0;
+        key_code_ok && modifiers_ok && *self != Self::EMPTY
+    }
+}
 
 /// A tri-state key: virtual-hold `hold` across taps of `tap`.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
@@ -38,12 +115,51 @@ pub struct Key {
     pub hold: key::KeyOutput,
     /// Output tapped on start and on each continue.
     pub tap: key::KeyOutput,
+    /// Output tapped by [Ref::Reverse] while this key's session is armed.
+    #[serde(default)]
+    pub reverse_tap: Option<key::KeyOutput>,
+    /// Outputs which do not interrupt this key's session.
+    #[serde(default)]
+    pub continue_through: ContinueThrough,
+    /// Idle time (in ms) after which this key's session is ended.
+    #[serde(default)]
+    pub timeout: Option<u16>,
 }
 
 impl Key {
     /// Constructs a tri-state key.
     pub const fn new(hold: key::KeyOutput, tap: key::KeyOutput) -> Self {
-        Self { hold, tap }
+        Self {
+            hold,
+            tap,
+            reverse_tap: None,
+            continue_through: ContinueThrough::EMPTY,
+            timeout: None,
+        }
+    }
+
+    /// This key, with the given reverse tap.
+    pub const fn with_reverse_tap(self, reverse_tap: key::KeyOutput) -> Self {
+        Self {
+            reverse_tap: Some(reverse_tap),
+            ..self
+        }
+    }
+
+    /// This key, with the given continue-through outputs.
+    pub const fn with_continue_through(self, continue_through: ContinueThrough) -> Self {
+        Self {
+            continue_through,
+            ..self
+        }
+    }
+
+    /// This key, with the given idle timeout.
+    pub const fn with_timeout(self, timeout: u16) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
     }
 }
 
@@ -51,15 +167,22 @@ impl Key {
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Session {
     keymap_index: u16,
+    key_index: u8,
     hold: key::KeyOutput,
     tap: key::KeyOutput,
     tap_held: bool,
+    continue_through: ContinueThrough,
+    timeout: Option<u16>,
+    /// The keymap index and output of a held reverse tap.
+    reverse_held: Option<(u16, key::KeyOutput)>,
 }
 
 /// Tri-state context: at most one session is armed.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Context {
     session: Option<Session>,
+    /// Incremented on each session activity; a [Event::Timeout] is stale if it differs.
+    activity: u8,
 }
 
 impl Default for Context {
@@ -71,7 +194,10 @@ impl Default for Context {
 impl Context {
     /// Constructs an idle context.
     pub const fn new() -> Self {
-        Context { session: None }
+        Context {
+            session: None,
+            activity: 0,
+        }
     }
 
     /// Clear the session without emitting virtual releases.
@@ -89,11 +215,42 @@ impl Context {
         matches!(self.session, Some(s) if s.keymap_index == keymap_index)
     }
 
+    /// The key data index of the armed session's key.
+    pub fn session_key_index(&self) -> Option<u8> {
+        self.session.map(|s| s.key_index)
+    }
+
+    /// Records session activity, scheduling the idle timeout (if any).
+    fn touch(&mut self) -> key::KeyEvents<Event> {
+        self.activity = self.activity.wrapping_add(1);
+        match self.session {
+            Some(Session {
+                keymap_index,
+                timeout: Some(timeout),
+                ..
+            }) => key::KeyEvents::scheduled_event(key::ScheduledEvent::after(
+                timeout,
+                key::Event::key_event(
+                    keymap_index,
+                    Event::Timeout {
+                        activity: self.activity,
+                    },
+                ),
+            )),
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+
     fn end_session(&mut self) -> key::KeyEvents<Event> {
         match self.session.take() {
             None => key::KeyEvents::no_events(),
             Some(session) => {
                 let mut pke = key::KeyEvents::no_events();
+                if let Some((_, key_output)) = session.reverse_held {
+                    pke.add_event(key::Event::Input(input::Event::VirtualKeyRelease {
+                        key_output,
+                    }));
+                }
                 if session.tap_held {
                     pke.add_event(key::Event::Input(input::Event::VirtualKeyRelease {
                         key_output: session.tap,
@@ -116,10 +273,15 @@ impl Context {
                 let mut pke = self.end_session();
                 self.session = Some(Session {
                     keymap_index,
+                    key_index: 0,
                     hold,
                     tap,
                     tap_held: true,
+                    continue_through: ContinueThrough::EMPTY,
+                    timeout: None,
+                    reverse_held: None,
                 });
+                pke.extend(self.touch());
                 pke.add_event(key::Event::Input(input::Event::VirtualKeyPress {
                     key_output: hold,
                 }));
@@ -128,18 +290,63 @@ impl Context {
                 }));
                 pke
             }
+            key::Event::Key {
+                keymap_index,
+                key_event:
+                    Event::Configure {
+                        key_index,
+                        continue_through,
+                        timeout,
+                    },
+            } => match self.session.as_mut() {
+                Some(session) if session.keymap_index == keymap_index => {
+                    session.key_index = key_index;
+                    session.continue_through = continue_through;
+                    session.timeout = timeout;
+                    self.touch()
+                }
+                _ => key::KeyEvents::no_events(),
+            },
             key::Event::Key {
                 keymap_index,
                 key_event: Event::Continue,
             } => match self.session.as_mut() {
                 Some(session) if session.keymap_index == keymap_index => {
                     session.tap_held = true;
-                    key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
-                        key_output: session.tap,
-                    }))
+                    let mut pke =
+                        key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
+                            key_output: session.tap,
+                        }));
+                    pke.extend(self.touch());
+                    pke
+                }
+                _ => key::KeyEvents::no_events(),
+            },
+            key::Event::Key {
+                keymap_index,
+                key_event: Event::Reverse { key_output },
+            } => match self.session.as_mut() {
+                Some(session) if session.reverse_held.is_none() => {
+                    session.reverse_held = Some((keymap_index, key_output));
+                    let mut pke =
+                        key::KeyEvents::event(key::Event::Input(input::Event::VirtualKeyPress {
+                            key_output,
+                        }));
+                    pke.extend(self.touch());
+                    pke
                 }
                 _ => key::KeyEvents::no_events(),
             },
+            key::Event::Key {
+                key_event: Event::Timeout { activity },
+                ..
+            } => {
+                if activity == self.activity {
+                    self.end_session()
+                } else {
+                    key::KeyEvents::no_events()
+                }
+            }
             key::Event::Input(input::Event::Release { keymap_index }) => {
                 match self.session.as_mut() {
                     Some(session) if session.keymap_index == keymap_index && session.tap_held => {
@@ -148,15 +355,29 @@ impl Context {
                             key_output: session.tap,
                         }))
                     }
+                    Some(session) if matches!(session.reverse_held, Some((i, _)) if i == keymap_index) => {
+                        match session.reverse_held.take() {
+                            Some((_, key_output)) => key::KeyEvents::event(key::Event::Input(
+                                input::Event::VirtualKeyRelease { key_output },
+                            )),
+                            None => key::KeyEvents::no_events(),
+                        }
+                    }
                     _ => key::KeyEvents::no_events(),
                 }
             }
-            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput { keymap_index, .. }) => {
-                match self.session {
-                    Some(session) if session.keymap_index != keymap_index => self.end_session(),
-                    _ => key::KeyEvents::no_events(),
+            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index,
+                key_output,
+            }) => match self.session {
+                Some(session)
+                    if session.keymap_index != keymap_index
+                        && !session.continue_through.contains(&key_output) =>
+                {
+                    self.end_session()
                 }
-            }
+                _ => key::KeyEvents::no_events(),
+            },
             _ => key::KeyEvents::no_events(),
         }
     }
@@ -184,8 +405,29 @@ pub enum Event {
         /// Output to tap now.
         tap: key::KeyOutput,
     },
+    /// Follows [Event::Start]: which key started the session, and its settings.
+    ///
+    /// (Separate from [Event::Start] to keep the event small).
+    Configure {
+        /// Index into [System] key data of the key which started the session.
+        key_index: u8,
+        /// Outputs which do not interrupt the session.
+        continue_through: ContinueThrough,
+        /// Idle time (in ms) after which the session is ended.
+        timeout: Option<u16>,
+    },
     /// Re-press of the armed key: tap again; `hold` stays.
     Continue,
+    /// Press of the reverse key while a session is armed: tap `key_output`.
+    Reverse {
+        /// The armed key's reverse tap.
+        key_output: key::KeyOutput,
+    },
+    /// Idle timeout; ends the session if there has been no activity since.
+    Timeout {
+        /// The [Context] activity count when the timeout was scheduled.
+        activity: u8,
+    },
 }
 
 /// Pending key state type for tri-state keys. (No pending state.)
@@ -224,19 +466,46 @@ impl<R: Debug, Keys: Debug + Index<usize, Output = Key>> key::System<R> for Syst
         &self,
         keymap_index: u16,
         context: &Self::Context,
-        Ref(key_index): Ref,
+        key_ref: Ref,
     ) -> (
         key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
         key::KeyEvents<Self::Event>,
     ) {
-        let Key { hold, tap } = self.keys[key_index as usize];
-        let key_event = if context.is_session_for(keymap_index) {
-            Event::Continue
-        } else {
-            Event::Start { hold, tap }
-        };
         let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp);
-        let pke = key::KeyEvents::event(key::Event::key_event(keymap_index, key_event));
+        let pke = match key_ref {
+            Ref::Key(_) if context.is_session_for(keymap_index) => {
+                key::KeyEvents::event(key::Event::key_event(keymap_index, Event::Continue))
+            }
+            Ref::Key(key_index) => {
+                let key = self.keys[key_index as usize];
+                let mut pke = key::KeyEvents::event(key::Event::key_event(
+                    keymap_index,
+                    Event::Start {
+                        hold: key.hold,
+                        tap: key.tap,
+                    },
+                ));
+                pke.add_event(key::Event::key_event(
+                    keymap_index,
+                    Event::Configure {
+                        key_index,
+                        continue_through: key.continue_through,
+                        timeout: key.timeout,
+                    },
+                ));
+                pke
+            }
+            Ref::Reverse => match context
+                .session_key_index()
+                .and_then(|key_index| self.keys[key_index as usize].reverse_tap)
+            {
+                Some(key_output) => key::KeyEvents::event(key::Event::key_event(
+                    keymap_index,
+                    Event::Reverse { key_output },
+                )),
+                None => key::KeyEvents::no_events(),
+            },
+        };
         (pkr, pke)
     }
 
@@ -277,7 +546,7 @@ mod tests {
 
     #[test]
     fn test_sizeof_ref() {
-        assert_eq!(1, core::mem::size_of::<Ref>());
+        assert_eq!(2, core::mem::size_of::<Ref>());
     }
 
     #[test]
@@ -335,4 +604,120 @@ mod tests {
         );
         assert!(ctx.is_active());
     }
+
+    #[test]
+    fn continue_through_output_does_not_end_session() {
+        let mut ctx = Context::new();
+        let hold = key::KeyOutput::from_key_code(0xE2);
+        let tap = key::KeyOutput::from_key_code(0x2B);
+        let continue_through =
+            ContinueThrough::new(key::KeyboardModifiers::LEFT_SHIFT, &[0x4F, 0x50]);
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Start { hold, tap }),
+        );
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(
+                0,
+                Event::Configure {
+                    key_index: 0,
+                    continue_through,
+                    timeout: None,
+                },
+            ),
+        );
+
+        for key_output in [
+            key::KeyOutput::from_key_code(0xE1),
+            key::KeyOutput::from_key_code(0x50),
+        ] {
+            let _ = key::Context::handle_event(
+                &mut ctx,
+                key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                    keymap_index: 1,
+                    key_output,
+                }),
+            );
+            assert!(ctx.is_active());
+        }
+
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index: 1,
+                key_output: key::KeyOutput::from_key_code(0xE0),
+            }),
+        );
+        assert!(!ctx.is_active());
+    }
+
+    #[test]
+    fn timeout_ends_idle_session() {
+        let mut ctx = Context::new();
+        let hold = key::KeyOutput::from_key_code(0xE2);
+        let tap = key::KeyOutput::from_key_code(0x2B);
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Start { hold, tap }),
+        );
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(
+                0,
+                Event::Configure {
+                    key_index: 0,
+                    continue_through: ContinueThrough::EMPTY,
+                    timeout: Some(500),
+                },
+            ),
+        );
+        let stale_timeout = key::Event::key_event(
+            0,
+            Event::Timeout {
+                activity: ctx.activity,
+            },
+        );
+        assert!(pke.into_iter().any(|sch_ev| sch_ev.event == stale_timeout));
+
+        // A continue supersedes the scheduled timeout.
+        let _ = key::Context::handle_event(&mut ctx, key::Event::key_event(0, Event::Continue));
+        let _ = key::Context::handle_event(&mut ctx, stale_timeout);
+        assert!(ctx.is_active());
+
+        let activity = ctx.activity;
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Timeout { activity }),
+        );
+        assert!(!ctx.is_active());
+    }
+
+    #[test]
+    fn reverse_taps_while_session_armed() {
+        let mut ctx = Context::new();
+        let hold = key::KeyOutput::from_key_code(0xE2);
+        let tap = key::KeyOutput::from_key_code(0x2B);
+        let reverse_tap =
+            key::KeyOutput::from_key_code_with_modifiers(0x2B, key::KeyboardModifiers::LEFT_SHIFT);
+        let _ = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(0, Event::Start { hold, tap }),
+        );
+
+        let pke = key::Context::handle_event(
+            &mut ctx,
+            key::Event::key_event(
+                1,
+                Event::Reverse {
+                    key_output: reverse_tap,
+                },
+            ),
+        );
+        let expected_event = key::Event::Input(input::Event::VirtualKeyPress {
+            key_output: reverse_tap,
+        });
+        assert!(pke.into_iter().any(|sch_ev| sch_ev.event == expected_event));
+        assert!(ctx.is_active());
+    }
 }
@@ -0,0 +1,864 @@
+//! Unicode input: keys which type arbitrary Unicode text.
+//!
+//! USB HID keyboards can't send Unicode characters directly;
+//!  instead, the host's Unicode input method is used
+//!  to enter each code point as hexadecimal digits.
+//!
+//! The input method is selected by [Mode]. The context's mode
+//!  can be changed at runtime (with `Key::SetMode` or `Key::CycleMode`);
+//!  or a key can type its text with a specific mode (e.g. a key on
+//!  a named OS layer).
+//!
+//! Each code point is typed as a sequence of [automation::Instruction]s
+//!  (see [code_point_instructions]).
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use serde::Deserialize;
+
+use crate::key;
+
+use key::automation::{self, Instruction};
+
+/// The maximum number of code points in a [Text].
+pub const MAX_TEXT_LEN: usize = 8;
+
+/// The maximum length (in UTF-8 bytes) of a [Text].
+pub const MAX_TEXT_BYTES: usize = 4 * MAX_TEXT_LEN;
+
+/// The maximum number of instructions used to type a code point.
+pub const MAX_CODE_POINT_INSTRUCTIONS: usize = 10;
+
+/// The number of texts which can be queued to be typed.
+pub const TYPING_QUEUE_SIZE: usize = 4;
+
+/// The host's Unicode input method.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Linux (IBus/GTK): Ctrl+Shift+U, then the hex digits, then Space.
+    #[default]
+    Linux,
+    /// macOS "Unicode Hex Input" keyboard layout:
+    ///  the UTF-16 hex digits are typed while holding Option.
+    MacOs,
+    /// Windows, using [WinCompose](https://github.com/samhocevar/wincompose)
+    ///  with Right Alt as the compose key: Compose, U, the hex digits, then Enter.
+    WinCompose,
+    /// Windows, using Alt+Numpad hex input:
+    ///  the hex digits are typed after Numpad Plus while holding Alt.
+    ///
+    /// Requires the `EnableHexNumpad` registry setting,
+    ///  and is limited to code points in the Basic Multilingual Plane.
+    WindowsAltNumpad,
+}
+
+impl Mode {
+    /// The mode after this one, when cycling through the modes.
+    pub const fn next(self) -> Self {
+        match self {
+            Mode::Linux => Mode::MacOs,
+            Mode::MacOs => Mode::WinCompose,
+            Mode::WinCompose => Mode::WindowsAltNumpad,
+            Mode::WindowsAltNumpad => Mode::Linux,
+        }
+    }
+}
+
+/// A short string of code points, typed by a Unicode key.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "heapless::String<MAX_TEXT_BYTES>")]
+pub struct Text {
+    len: u8,
+    code_points: [char; MAX_TEXT_LEN],
+}
+
+impl Text {
+    /// An empty text.
+    pub const EMPTY: Text = Text {
+        len: 0,
+        code_points: ['\0'; MAX_TEXT_LEN],
+    };
+
+    /// Constructs a [Text] from the given string.
+    ///
+    /// Panics if the string has more than [MAX_TEXT_LEN] code points.
+    pub const fn new(text: &str) -> Self {
+        let bytes = text.as_bytes();
+        let mut code_points = ['\0'; MAX_TEXT_LEN];
+        let mut len = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            // Decode the UTF-8 sequence starting at bytes[i].
+            let b = bytes[i];
+            let (width, mut code_point) = if b < 0x80 {
+                (1, b as u32)
+            } else if b < 0xE0 {
+                (2, (b & 0x1F) as u32)
+            } else if b < 0xF0 {
+                (3, (b & 0x0F) as u32)
+            } else {
+                (4, (b & 0x07) as u32)
+            };
+            let mut j = 1;
+            while j < width {
+                code_point = (code_point << 6) | (bytes[i + j] & 0x3F) as u32;
+                j += 1;
+            }
+
+            if len == MAX_TEXT_LEN {
+                panic!("Text has too many code points");
+            }
+            code_points[len] = match char::from_u32(code_point) {
+                Some(c) => c,
+                None => panic!("Text has an invalid code point"),
+            };
+            len += 1;
+            i += width;
+        }
+        Text {
+            len: len as u8,
+            code_points,
+        }
+    }
+
+    /// The code points of the text.
+    pub fn code_points(&self) -> &[char] {
+        &self.code_points[..self.len as usize]
+    }
+}
+
+impl core::fmt::Debug for Text {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.code_points()).finish()
+    }
+}
+
+impl TryFrom<heapless::String<MAX_TEXT_BYTES>> for Text {
+    type Error = &'static str;
+
+    fn try_from(s: heapless::String<MAX_TEXT_BYTES>) -> Result<Self, Self::Error> {
+        if s.chars().count() > MAX_TEXT_LEN {
+            Err("text has too many code points")
+        } else {
+            Ok(Text::new(s.as_str()))
+        }
+    }
+}
+
+/// Reference for a Unicode key.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ref(pub Key);
+
+/// Unicode keys.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Key {
+    /// Types the text (an index into the config's texts).
+    Type {
+        /// Index of the text in [Config::texts].
+        text: u8,
+        /// The mode to type the text with.
+        ///
+        /// If `None`, the context's current mode is used.
+        #[serde(default)]
+        mode: Option<Mode>,
+    },
+    /// Sets the context's mode.
+    SetMode(Mode),
+    /// Changes the context's mode to the next mode.
+    CycleMode,
+}
+
+impl Key {
+    /// Constructs pressed-key events for this key.
+    pub fn new_pressed_key(&self, keymap_index: u16) -> key::KeyEvents<Event> {
+        let key_event = match *self {
+            Key::Type { text, mode } => Event::Type { text, mode },
+            Key::SetMode(mode) => Event::SetMode(mode),
+            Key::CycleMode => Event::CycleMode,
+        };
+        key::KeyEvents::event(key::Event::key_event(keymap_index, key_event))
+    }
+}
+
+/// Config for Unicode keys.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Config<const TEXT_COUNT: usize> {
+    /// The texts typed by the Unicode keys.
+    #[serde(default = "default_texts", deserialize_with = "deserialize_texts")]
+    pub texts: [Text; TEXT_COUNT],
+
+    /// The initial mode.
+    #[serde(default)]
+    pub mode: Mode,
+
+    /// Duration (in ticks) of each instruction.
+    #[serde(default = "default_instruction_duration")]
+    pub instruction_duration: u16,
+}
+
+fn default_texts<const TEXT_COUNT: usize>() -> [Text; TEXT_COUNT] {
+    [Text::EMPTY; TEXT_COUNT]
+}
+
+/// Deserialize texts.
+fn deserialize_texts<'de, D, const TEXT_COUNT: usize>(
+    deserializer: D,
+) -> Result<[Text; TEXT_COUNT], D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let texts_vec: heapless::Vec<Text, TEXT_COUNT> = Deserialize::deserialize(deserializer)?;
+
+    let mut texts_array: [Text; TEXT_COUNT] = [Text::EMPTY; TEXT_COUNT];
+    for (i, text) in texts_vec.iter().enumerate() {
+        texts_array[i] = *text;
+    }
+
+    Ok(texts_array)
+}
+
+/// Constructs an array of texts for the given array.
+pub const fn texts<const N: usize, const TEXT_COUNT: usize>(
+    texts: [Text; N],
+) -> [Text; TEXT_COUNT] {
+    let mut cfg_texts: [Text; TEXT_COUNT] = [Text::EMPTY; TEXT_COUNT];
+
+    if N > TEXT_COUNT {
+        panic!("Too many texts for texts array");
+    }
+
+    let mut i = 0;
+
+    while i < N {
+        cfg_texts[i] = texts[i];
+        i += 1;
+    }
+
+    cfg_texts
+}
+
+fn default_instruction_duration() -> u16 {
+    DEFAULT_INSTRUCTION_DURATION
+}
+
+/// Default instruction duration.
+pub const DEFAULT_INSTRUCTION_DURATION: u16 = automation::DEFAULT_INSTRUCTION_DURATION;
+
+impl<const TEXT_COUNT: usize> Config<TEXT_COUNT> {
+    /// Constructs a new default [Config].
+    pub const fn new() -> Self {
+        Self {
+            texts: [Text::EMPTY; TEXT_COUNT],
+            mode: Mode::Linux,
+            instruction_duration: DEFAULT_INSTRUCTION_DURATION,
+        }
+    }
+}
+
+impl<const TEXT_COUNT: usize> Default for Config<TEXT_COUNT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const KEY_U: u8 = 0x18;
+const KEY_ENTER: u8 = 0x28;
+const KEY_SPACE: u8 = 0x2C;
+const KEY_KP_PLUS: u8 = 0x57;
+
+/// The keyboard key code for the hex digit.
+fn hex_digit_key_code(digit: u8) -> u8 {
+    match digit {
+        0 => 0x27,
+        1..=9 => 0x1E + digit - 1,
+        _ => 0x04 + digit - 10,
+    }
+}
+
+/// The keypad key code for the hex digit. (A-F are typed with the letter keys).
+fn keypad_hex_digit_key_code(digit: u8) -> u8 {
+    match digit {
+        0 => 0x62,
+        1..=9 => 0x59 + digit - 1,
+        _ => 0x04 + digit - 10,
+    }
+}
+
+/// Pushes taps of the hex digits of the value.
+///
+/// At least `min_digits` digits are typed (with leading zeros).
+fn push_hex_digits(
+    instructions: &mut heapless::Vec<Instruction, MAX_CODE_POINT_INSTRUCTIONS>,
+    value: u32,
+    min_digits: usize,
+    key_code: fn(u8) -> u8,
+) {
+    let digit_count = ((32 - value.leading_zeros() as usize).div_ceil(4)).max(min_digits);
+    for i in (0..digit_count).rev() {
+        let digit = ((value >> (4 * i)) & 0xF) as u8;
+        let _ = instructions.push(Instruction::Tap(key::KeyOutput::from_key_code(key_code(
+            digit,
+        ))));
+    }
+}
+
+/// The instructions which type the code point using the given mode.
+pub fn code_point_instructions(
+    mode: Mode,
+    code_point: char,
+) -> heapless::Vec<Instruction, MAX_CODE_POINT_INSTRUCTIONS> {
+    let mut instructions = heapless::Vec::new();
+    let value = code_point as u32;
+
+    match mode {
+        Mode::Linux => {
+            let ctrl_shift =
+                key::KeyboardModifiers::LEFT_CTRL.union(&key::KeyboardModifiers::LEFT_SHIFT);
+            let _ = instructions.push(Instruction::Tap(
+                key::KeyOutput::from_key_code_with_modifiers(KEY_U, ctrl_shift),
+            ));
+            push_hex_digits(&mut instructions, value, 1, hex_digit_key_code);
+            let _ = instructions.push(Instruction::Tap(key::KeyOutput::from_key_code(KEY_SPACE)));
+        }
+        Mode::MacOs => {
+            let option = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::LEFT_ALT);
+            let _ = instructions.push(Instruction::Press(option));
+            let mut utf16 = [0u16; 2];
+            for unit in code_point.encode_utf16(&mut utf16) {
+                push_hex_digits(&mut instructions, *unit as u32, 4, hex_digit_key_code);
+            }
+            let _ = instructions.push(Instruction::Release(option));
+        }
+        Mode::WinCompose => {
+            let compose = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::RIGHT_ALT);
+            let _ = instructions.push(Instruction::Tap(compose));
+            let _ = instructions.push(Instruction::Tap(key::KeyOutput::from_key_code(KEY_U)));
+            push_hex_digits(&mut instructions, value, 1, hex_digit_key_code);
+            let _ = instructions.push(Instruction::Tap(key::KeyOutput::from_key_code(KEY_ENTER)));
+        }
+        Mode::WindowsAltNumpad => {
+            let alt = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::LEFT_ALT);
+            let _ = instructions.push(Instruction::Press(alt));
+            let _ = instructions.push(Instruction::Tap(key::KeyOutput::from_key_code(KEY_KP_PLUS)));
+            push_hex_digits(&mut instructions, value, 1, keypad_hex_digit_key_code);
+            let _ = instructions.push(Instruction::Release(alt));
+        }
+    }
+
+    instructions
+}
+
+/// A text being typed (or queued to be typed).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Typing {
+    keymap_index: u16,
+    text: u8,
+    mode: Mode,
+    code_point: u8,
+    position: u8,
+}
+
+/// Context for Unicode keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Context<const TEXT_COUNT: usize> {
+    config: Config<TEXT_COUNT>,
+    mode: Mode,
+    typing_queue: [Option<Typing>; TYPING_QUEUE_SIZE],
+}
+
+impl<const TEXT_COUNT: usize> Default for Context<TEXT_COUNT> {
+    fn default() -> Self {
+        Self::from_config(Config::new())
+    }
+}
+
+impl<const TEXT_COUNT: usize> Context<TEXT_COUNT> {
+    /// Constructs a context from the given config.
+    pub const fn from_config(config: Config<TEXT_COUNT>) -> Self {
+        Self {
+            config,
+            mode: config.mode,
+            typing_queue: [None; TYPING_QUEUE_SIZE],
+        }
+    }
+
+    /// Resets the mode to the config's mode, and stops typing.
+    pub fn reset(&mut self) {
+        *self = Self::from_config(self.config);
+    }
+
+    /// The current mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Sets the current mode.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Whether text is being typed.
+    pub fn is_typing(&self) -> bool {
+        self.typing_queue[0].is_some()
+    }
+
+    fn enqueue(&mut self, typing: Typing) {
+        // Queue is full: drop the new text.
+        if let Some(slot) = self.typing_queue.iter_mut().find(|t| t.is_none()) {
+            *slot = Some(typing);
+        }
+    }
+
+    fn type_next_instruction(&mut self) -> key::KeyEvents<Event> {
+        while let Some(typing) = self.typing_queue[0].as_mut() {
+            let code_point = self
+                .config
+                .texts
+                .get(typing.text as usize)
+                .and_then(|text| text.code_points().get(typing.code_point as usize).copied());
+
+            match code_point {
+                Some(code_point) => {
+                    let instructions = code_point_instructions(typing.mode, code_point);
+                    match instructions.get(typing.position as usize) {
+                        Some(&instruction) => {
+                            typing.position += 1;
+                            let next_key_ev =
+                                key::Event::key_event(typing.keymap_index, Event::NextInstruction);
+                            return automation::instruction_key_events(
+                                instruction,
+                                self.config.instruction_duration,
+                                next_key_ev,
+                            );
+                        }
+                        None => {
+                            typing.code_point += 1;
+                            typing.position = 0;
+                        }
+                    }
+                }
+                None => {
+                    // Finished typing the text; continue with the next in the queue.
+                    self.typing_queue.rotate_left(1);
+                    self.typing_queue[TYPING_QUEUE_SIZE - 1] = None;
+                }
+            }
+        }
+
+        key::KeyEvents::no_events()
+    }
+
+    fn handle_event(&mut self, event: key::Event<Event>) -> key::KeyEvents<Event> {
+        match event {
+            key::Event::Key {
+                key_event: Event::Type { text, mode },
+                keymap_index,
+            } => {
+                let type_immediately = !self.is_typing();
+                self.enqueue(Typing {
+                    keymap_index,
+                    text,
+                    mode: mode.unwrap_or(self.mode),
+                    code_point: 0,
+                    position: 0,
+                });
+
+                if type_immediately {
+                    self.type_next_instruction()
+                } else {
+                    key::KeyEvents::no_events()
+                }
+            }
+            key::Event::Key {
+                key_event: Event::SetMode(mode),
+                ..
+            } => {
+                self.mode = mode;
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::CycleMode,
+                ..
+            } => {
+                self.mode = self.mode.next();
+                key::KeyEvents::no_events()
+            }
+            key::Event::Key {
+                key_event: Event::NextInstruction,
+                ..
+            } => self.type_next_instruction(),
+            _ => key::KeyEvents::no_events(),
+        }
+    }
+}
+
+impl<const TEXT_COUNT: usize> key::Context for Context<TEXT_COUNT> {
+    type Event = Event;
+
+    fn handle_event(&mut self, event: key::Event<Self::Event>) -> key::KeyEvents<Self::Event> {
+        self.handle_event(event)
+    }
+
+    fn reset(&mut self) {
+        Context::reset(self);
+    }
+}
+
+/// Unicode key events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// Types (or queues) the text.
+    Type {
+        /// Index of the text in [Config::texts].
+        text: u8,
+        /// The mode to type the text with (or the context's mode, if `None`).
+        mode: Option<Mode>,
+    },
+    /// Sets the context's mode.
+    SetMode(Mode),
+    /// Changes the context's mode to the next mode.
+    CycleMode,
+    /// Indicates to the context to type the next instruction.
+    NextInstruction,
+}
+
+/// Pending key state type for Unicode keys. (No pending state.)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PendingKeyState;
+
+/// Key state used by [System]. (No per-key state; behaviour is on [Context].)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyState;
+
+/// The [key::System] implementation for Unicode keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<R, const TEXT_COUNT: usize>(PhantomData<R>);
+
+impl<R, const TEXT_COUNT: usize> System<R, TEXT_COUNT> {
+    /// Constructs a new [System].
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R, const TEXT_COUNT: usize> Default for System<R, TEXT_COUNT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Debug, const TEXT_COUNT: usize> key::System<R> for System<R, TEXT_COUNT> {
+    type Ref = Ref;
+    type Context = Context<TEXT_COUNT>;
+    type Event = Event;
+    type PendingKeyState = PendingKeyState;
+    type KeyState = KeyState;
+
+    fn new_pressed_key(
+        &self,
+        keymap_index: u16,
+        _context: &Self::Context,
+        Ref(key): Ref,
+    ) -> (
+        key::PressedKeyResult<R, Self::PendingKeyState, Self::KeyState>,
+        key::KeyEvents<Self::Event>,
+    ) {
+        let pke = key.new_pressed_key(keymap_index);
+        let pkr = key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp);
+        (pkr, pke)
+    }
+
+    fn update_pending_state(
+        &self,
+        _pending_state: &mut Self::PendingKeyState,
+        _keymap_index: u16,
+        _context: &Self::Context,
+        _key_ref: Ref,
+        _event: key::Event<Self::Event>,
+    ) -> (Option<key::NewPressedKey<R>>, key::KeyEvents<Self::Event>) {
+        panic!()
+    }
+
+    fn update_state(
+        &self,
+        _key_state: &mut Self::KeyState,
+        _ref: &Self::Ref,
+        _context: &Self::Context,
+        _keymap_index: u16,
+        _event: key::Event<Self::Event>,
+    ) -> key::KeyEvents<Self::Event> {
+        panic!()
+    }
+
+    fn key_output(
+        &self,
+        _key_ref: &Self::Ref,
+        _key_state: &Self::KeyState,
+    ) -> Option<key::KeyOutput> {
+        panic!()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use crate::input;
+
+    const fn tap(key_code: u8) -> Instruction {
+        Instruction::Tap(key::KeyOutput::from_key_code(key_code))
+    }
+
+    fn context() -> Context<2> {
+        Context::from_config(Config {
+            texts: texts([Text::new("é"), Text::new("→←")]),
+            ..Config::new()
+        })
+    }
+
+    /// Handles events until no more are emitted,
+    ///  returning the virtual key presses.
+    fn typed_key_outputs(ctx: &mut Context<2>, event: key::Event<Event>) -> Vec<key::KeyOutput> {
+        let mut pending = vec![event];
+        let mut key_outputs = Vec::new();
+        while let Some(ev) = pending.pop() {
+            let evs = ctx.handle_event(ev);
+            for sch_ev in evs {
+                match sch_ev.event {
+                    key::Event::Input(input::Event::VirtualKeyPress { key_output }) => {
+                        key_outputs.push(key_output)
+                    }
+                    ev @ key::Event::Key { .. } => pending.insert(0, ev),
+                    _ => {}
+                }
+            }
+        }
+        key_outputs
+    }
+
+    #[test]
+    fn test_sizeof_ref() {
+        assert_eq!(2, core::mem::size_of::<Ref>());
+    }
+
+    #[test]
+    fn test_text_new_decodes_code_points() {
+        // Assemble
+        let text = Text::new("aé→😀");
+
+        // Act
+        let actual = text.code_points();
+
+        // Assert
+        assert_eq!(&['a', 'é', '→', '😀'], actual);
+    }
+
+    #[test]
+    fn test_deserialize_config_texts() {
+        // Assemble
+        let json = r#"{ "texts": ["é", "→←"], "mode": "MacOs" }"#;
+
+        // Act
+        let actual: Config<2> = serde_json::from_str(json).unwrap();
+
+        // Assert
+        assert_eq!(
+            Config {
+                texts: texts([Text::new("é"), Text::new("→←")]),
+                mode: Mode::MacOs,
+                ..Config::new()
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn test_linux_instructions() {
+        // Assemble
+        let ctrl_shift_u = key::KeyOutput::from_key_code_with_modifiers(
+            KEY_U,
+            key::KeyboardModifiers::LEFT_CTRL.union(&key::KeyboardModifiers::LEFT_SHIFT),
+        );
+
+        // Act
+        let actual = code_point_instructions(Mode::Linux, 'é');
+
+        // Assert
+        // U+00E9
+        assert_eq!(
+            &[
+                Instruction::Tap(ctrl_shift_u),
+                tap(0x08),
+                tap(0x26),
+                tap(KEY_SPACE)
+            ],
+            actual.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_macos_instructions_use_utf16_surrogates() {
+        // Assemble
+        let option = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::LEFT_ALT);
+
+        // Act
+        let actual = code_point_instructions(Mode::MacOs, '😀');
+
+        // Assert
+        // U+1F600 is D83D DE00 in UTF-16.
+        assert_eq!(
+            &[
+                Instruction::Press(option),
+                tap(0x07),
+                tap(0x25),
+                tap(0x20),
+                tap(0x07),
+                tap(0x07),
+                tap(0x08),
+                tap(0x27),
+                tap(0x27),
+                Instruction::Release(option),
+            ],
+            actual.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_win_compose_instructions() {
+        // Assemble
+        let compose = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::RIGHT_ALT);
+
+        // Act
+        let actual = code_point_instructions(Mode::WinCompose, '→');
+
+        // Assert
+        // U+2192
+        assert_eq!(
+            &[
+                Instruction::Tap(compose),
+                tap(KEY_U),
+                tap(0x1F),
+                tap(0x1E),
+                tap(0x26),
+                tap(0x1F),
+                tap(KEY_ENTER),
+            ],
+            actual.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_windows_alt_numpad_instructions() {
+        // Assemble
+        let alt = key::KeyOutput::from_key_modifiers(key::KeyboardModifiers::LEFT_ALT);
+
+        // Act
+        let actual = code_point_instructions(Mode::WindowsAltNumpad, 'é');
+
+        // Assert
+        assert_eq!(
+            &[
+                Instruction::Press(alt),
+                tap(KEY_KP_PLUS),
+                tap(0x08),
+                tap(0x61),
+                Instruction::Release(alt),
+            ],
+            actual.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_type_uses_context_mode() {
+        // Assemble
+        let mut ctx = context();
+        ctx.set_mode(Mode::WindowsAltNumpad);
+
+        // Act
+        let actual = typed_key_outputs(
+            &mut ctx,
+            key::Event::key_event(
+                0,
+                Event::Type {
+                    text: 0,
+                    mode: None,
+                },
+            ),
+        );
+
+        // Assert
+        let expected: Vec<key::KeyOutput> = code_point_instructions(Mode::WindowsAltNumpad, 'é')
+            .iter()
+            .filter_map(|ins| match ins {
+                Instruction::Press(ko) | Instruction::Tap(ko) => Some(*ko),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(expected, actual);
+        assert!(!ctx.is_typing());
+    }
+
+    #[test]
+    fn test_type_with_key_mode_types_each_code_point() {
+        // Assemble
+        let mut ctx = context();
+
+        // Act
+        let actual = typed_key_outputs(
+            &mut ctx,
+            key::Event::key_event(
+                0,
+                Event::Type {
+                    text: 1,
+                    mode: Some(Mode::WinCompose),
+                },
+            ),
+        );
+
+        // Assert
+        // Compose, U, 4 hex digits, Enter; for each of the two code points.
+        assert_eq!(14, actual.len());
+        assert_eq!(Mode::Linux, ctx.mode());
+    }
+
+    #[test]
+    fn test_type_while_typing_is_queued() {
+        // Assemble
+        let mut ctx = context();
+        let _ = ctx.handle_event(key::Event::key_event(
+            0,
+            Event::Type {
+                text: 0,
+                mode: None,
+            },
+        ));
+
+        // Act
+        let actual = ctx.handle_event(key::Event::key_event(
+            1,
+            Event::Type {
+                text: 1,
+                mode: None,
+            },
+        ));
+
+        // Assert
+        assert_eq!(0, actual.into_iter().count());
+        assert_eq!(2, ctx.typing_queue.iter().flatten().count());
+    }
+
+    #[test]
+    fn test_set_mode_and_cycle_mode() {
+        // Assemble
+        let mut ctx = context();
+
+        // Act
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::SetMode(Mode::WinCompose)));
+        let _ = ctx.handle_event(key::Event::key_event(0, Event::CycleMode));
+
+        // Assert
+        assert_eq!(Mode::WindowsAltNumpad, ctx.mode());
+    }
+}
@@ -4,21 +4,24 @@ mod event_scheduler;
 /// The HID keyboard reporter.
 pub mod hid_keyboard_reporter;
 mod input_event_queue;
+mod key_overrides;
 #[cfg(feature = "std")]
 mod observed_eb_keymap;
 #[cfg(feature = "std")]
 mod observed_keymap;
 mod pending;
+mod trace;
 
 use core::cmp::PartialEq;
 use core::fmt::Debug;
 use core::marker::Copy;
 use core::ops::Index;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::input;
 use crate::key;
+use crate::split;
 
 use key::Event;
 
@@ -27,22 +30,62 @@ pub use distinct_reports::DistinctReports;
 use event_scheduler::EventScheduler;
 use hid_keyboard_reporter::HIDKeyboardReporter;
 use input_event_queue::InputEventQueue;
+pub use key_overrides::{
+    KeyOverride, KeyOverrideError, KeyOverrides, KeyOverridesContext, MAX_KEY_OVERRIDES,
+    MAX_KEY_OVERRIDE_REF_LEN,
+};
 #[cfg(feature = "std")]
 pub use observed_eb_keymap::ObservedKeymap as ObservedEventBasedKeymap;
 #[cfg(feature = "std")]
 pub use observed_keymap::ObservedKeymap;
+#[cfg(feature = "defmt")]
+pub use trace::DefmtObserver;
+#[cfg(feature = "std")]
+pub use trace::TraceRecorder;
+pub use trace::{KeymapObserver, NoOpObserver, TraceRecord};
 
 /// Maximum number of pressed keys supported.
 pub const MAX_PRESSED_KEYS: usize = 16;
 
 pub(crate) const MAX_QUEUED_INPUT_EVENTS: usize = 32;
 
+/// Maximum number of key releases which can be deferred at once.
+///
+/// Deferred releases are for pressed keys, so this allows one for each pressed key.
+///
+/// See [KeymapEvent::DeferRelease].
+pub const MAX_DEFERRED_RELEASES: usize = MAX_PRESSED_KEYS;
+
+/// Maximum number of callbacks which can be registered with the keymap.
+pub const MAX_CALLBACKS: usize = 8;
+
+/// Maximum length of a [SerialPacket].
+pub const MAX_SERIAL_PACKET_LEN: usize = 6;
+
+/// Number of bytes of serial output buffered by the keymap.
+///
+/// See [Keymap::read_serial_output].
+pub const SERIAL_OUTPUT_BUFFER_LEN: usize = 64;
+
+/// Number of keyboard usages covered by the NKRO report's bitmap. (Usages `0x00..0xE8`).
+pub const HID_NKRO_KEYBOARD_USAGE_COUNT: usize = 0xE8;
+
+/// Length of the NKRO keyboard report: a modifier byte followed by the key bitmap.
+pub const HID_NKRO_KEYBOARD_REPORT_LEN: usize = 1 + HID_NKRO_KEYBOARD_USAGE_COUNT / 8;
+
 /// Constructs an HID report or a sequence of key codes from the given sequence of [key::KeyOutput].
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct KeymapOutput {
     pressed_key_codes: heapless::Vec<key::KeyOutput, { MAX_PRESSED_KEYS }>,
 }
 
+#[cfg(feature = "defmt")]
+impl defmt::Format for KeymapOutput {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=[u8]:02x}", self.as_hid_boot_keyboard_report());
+    }
+}
+
 impl KeymapOutput {
     /// Constructs a new keymap output.
     pub fn new(pressed_key_codes: heapless::Vec<key::KeyOutput, { MAX_PRESSED_KEYS }>) -> Self {
@@ -103,6 +146,42 @@ impl KeymapOutput {
         report
     }
 
+    /// Returns the current HID keyboard report as an N-key rollover (NKRO) bitmap.
+    ///
+    /// The first byte is the modifiers byte (as in the boot keyboard report).
+    /// The remaining bytes are a bitmap of keyboard usages `0x00..0xE8`,
+    ///  where usage `kc` is bit `kc % 8` of byte `1 + kc / 8`.
+    ///
+    /// Unlike [KeymapOutput::as_hid_boot_keyboard_report],
+    ///  this is not limited to six simultaneous key codes.
+    pub fn as_hid_nkro_keyboard_report(&self) -> [u8; HID_NKRO_KEYBOARD_REPORT_LEN] {
+        let mut report = [0u8; HID_NKRO_KEYBOARD_REPORT_LEN];
+
+        let modifiers = self
+            .pressed_key_codes
+            .iter()
+            .fold(key::KeyboardModifiers::new(), |acc, &ko| {
+                acc.union(&ko.key_modifiers())
+            });
+
+        report[0] = modifiers.as_byte();
+
+        let key_codes = self
+            .pressed_key_codes
+            .iter()
+            .flat_map(|ko| match ko.key_code() {
+                key::KeyUsage::Keyboard(kc) => Some(kc as usize),
+                _ => None,
+            })
+            .filter(|&kc| kc != 0 && kc < HID_NKRO_KEYBOARD_USAGE_COUNT);
+
+        for key_code in key_codes {
+            report[1 + key_code / 8] |= 1 << (key_code % 8);
+        }
+
+        report
+    }
+
     /// Returns the pressed consumer codes.
     pub fn pressed_consumer_codes(&self) -> heapless::Vec<u8, 24> {
         self.pressed_key_codes
@@ -165,6 +244,53 @@ pub enum KeymapCallback {
     Bluetooth(BluetoothProfileCommand),
     /// A custom callback. Its behaviour is specific to the firmware implementation.
     Custom(u8, u8),
+    /// Sequence mode feedback. (e.g. for an LED indicating sequence mode).
+    Sequence(key::sequence::Feedback),
+}
+
+/// Host keyboard LED state, as sent by the host in the HID keyboard output report.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HostLeds(u8);
+
+impl HostLeds {
+    /// Byte value for Num Lock.
+    pub const NUM_LOCK_U8: u8 = 0x01;
+    /// Byte value for Caps Lock.
+    pub const CAPS_LOCK_U8: u8 = 0x02;
+    /// Byte value for Scroll Lock.
+    pub const SCROLL_LOCK_U8: u8 = 0x04;
+    /// Byte value for Compose.
+    pub const COMPOSE_U8: u8 = 0x08;
+    /// Byte value for Kana.
+    pub const KANA_U8: u8 = 0x10;
+
+    /// Const for no LEDs lit.
+    pub const NONE: HostLeds = HostLeds(0x00);
+
+    /// Constructs the LED state from the byte of an HID keyboard output report.
+    pub const fn from_byte(b: u8) -> Self {
+        HostLeds(b)
+    }
+
+    /// The byte of the HID keyboard output report.
+    pub const fn as_byte(&self) -> u8 {
+        self.0
+    }
+
+    /// Whether the host has Num Lock on.
+    pub const fn num_lock(&self) -> bool {
+        self.0 & Self::NUM_LOCK_U8 != 0
+    }
+
+    /// Whether the host has Caps Lock on.
+    pub const fn caps_lock(&self) -> bool {
+        self.0 & Self::CAPS_LOCK_U8 != 0
+    }
+
+    /// Whether the host has Scroll Lock on.
+    pub const fn scroll_lock(&self) -> bool {
+        self.0 & Self::SCROLL_LOCK_U8 != 0
+    }
 }
 
 /// Max recent physical presses tracked in [KeymapContext] (for quick-tap, etc.).
@@ -192,6 +318,17 @@ pub struct KeymapContext {
 
     /// Number of valid entries in [Self::recent_presses].
     pub recent_press_count: u8,
+
+    /// Most recent keyboard LED state reported by the host.
+    ///
+    /// Set with [Keymap::set_host_leds].
+    pub host_leds: HostLeds,
+
+    /// Bitset of active layers (bit `i` = layer `i`).
+    ///
+    /// Filled in by the keymap from [SetKeymapContext::active_layers].
+    /// Zero (i.e. only the base layer) if no family tracks layers.
+    pub active_layers: u32,
 }
 
 impl KeymapContext {
@@ -203,6 +340,8 @@ impl KeymapContext {
             pressed_modifiers: key::KeyboardModifiers::NONE,
             recent_presses: [(0, 0); MAX_RECENT_PRESSES],
             recent_press_count: 0,
+            host_leds: HostLeds::NONE,
+            active_layers: 0,
         }
     }
 
@@ -222,12 +361,15 @@ impl KeymapContext {
 ///  as `time_ms`.
 /// The omitted entry is this still-held press, already recorded
 ///  when the pending key was created.
+#[allow(clippy::too_many_arguments)]
 fn keymap_context_without_current_press(
     recent_presses: [(u16, u32); MAX_RECENT_PRESSES],
     recent_press_count: u8,
     idle_time_ms: u32,
     fallback_time_ms: u32,
     pressed_modifiers: key::KeyboardModifiers,
+    host_leds: HostLeds,
+    active_layers: u32,
     keymap_index: u16,
 ) -> KeymapContext {
     let count = recent_press_count as usize;
@@ -255,6 +397,8 @@ fn keymap_context_without_current_press(
         pressed_modifiers,
         recent_presses,
         recent_press_count,
+        host_leds,
+        active_layers,
     }
 }
 
@@ -281,10 +425,45 @@ fn push_recent_press(
     }
 }
 
+/// Schedule the event with the event scheduler, tracing it with the observer.
+fn schedule_event<E: Copy + Debug, O: KeymapObserver<E>>(
+    event_scheduler: &mut EventScheduler<E>,
+    observer: &mut O,
+    scheduled_event: key::ScheduledEvent<E>,
+) {
+    observer.observe(
+        event_scheduler.schedule_counter,
+        TraceRecord::EventScheduled {
+            schedule: scheduled_event.schedule,
+            event: scheduled_event.event,
+        },
+    );
+    event_scheduler.schedule_event(scheduled_event);
+}
+
+/// Cancel the events scheduled for the keymap index, tracing each with the observer.
+fn cancel_events<E: Copy + Debug, O: KeymapObserver<E>>(
+    event_scheduler: &mut EventScheduler<E>,
+    observer: &mut O,
+    keymap_index: u16,
+) {
+    let time_ms = event_scheduler.schedule_counter;
+    event_scheduler.cancel_events_for_keymap_index(keymap_index, |event| {
+        observer.observe(time_ms, TraceRecord::EventCancelled { event })
+    });
+}
+
 /// Trait for setting the keymap context.
 pub trait SetKeymapContext {
     /// Sets the keymap context.
     fn set_keymap_context(&mut self, context: KeymapContext);
+
+    /// Bitset of active layers (bit `i` = layer `i`), for [KeymapContext::active_layers].
+    ///
+    /// Only the base layer, for contexts which don't track layers.
+    fn active_layers(&self) -> u32 {
+        0
+    }
 }
 
 /// Report-level policy hints from aggregate context (feature-agnostic).
@@ -299,6 +478,89 @@ pub trait ReportHints {
     }
 }
 
+/// Length of [PersistentState] encoded with [PersistentState::to_bytes].
+pub const PERSISTENT_STATE_LEN: usize = 10;
+
+/// Snapshot of the runtime state which should survive a power cycle.
+///
+/// e.g. toggled or locked layers, or the default layer;
+///  firmware can store the bytes from [PersistentState::to_bytes] in flash/EEPROM,
+///  and reapply them with [Keymap::restore_persistent_state] after [Keymap::init].
+///
+/// Momentary state (held layers, sticky layers, pending keys) is not part of the snapshot.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PersistentState {
+    /// The default layer. (`0` for no default layer).
+    pub default_layer: u8,
+    /// Bitset of active layers (bit `i` = layer `i`).
+    pub active_layers: u32,
+    /// Bitset of locked layers (bit `i` = layer `i`).
+    pub locked_layers: u32,
+}
+
+impl PersistentState {
+    /// Version byte written by [PersistentState::to_bytes].
+    ///
+    /// Increment this when the encoding changes,
+    ///  so that stale stored snapshots are rejected rather than misread.
+    pub const VERSION: u8 = 1;
+
+    /// Constructs an empty snapshot.
+    pub const fn new() -> Self {
+        Self {
+            default_layer: 0,
+            active_layers: 0,
+            locked_layers: 0,
+        }
+    }
+
+    /// Encodes the snapshot as a versioned, fixed-length byte array.
+    pub fn to_bytes(&self) -> [u8; PERSISTENT_STATE_LEN] {
+        let mut bytes = [0u8; PERSISTENT_STATE_LEN];
+        bytes[0] = Self::VERSION;
+        bytes[1] = self.default_layer;
+        bytes[2..6].copy_from_slice(&self.active_layers.to_le_bytes());
+        bytes[6..10].copy_from_slice(&self.locked_layers.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a snapshot encoded with [PersistentState::to_bytes].
+    ///
+    /// Returns `None` if the bytes are too short or have a different version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [Self::VERSION, default_layer, a0, a1, a2, a3, l0, l1, l2, l3, ..] => Some(Self {
+                default_layer: *default_layer,
+                active_layers: u32::from_le_bytes([*a0, *a1, *a2, *a3]),
+                locked_layers: u32::from_le_bytes([*l0, *l1, *l2, *l3]),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Trait for exporting and restoring the [PersistentState] of a context.
+///
+/// Families with state worth persisting (e.g. layered)
+///  write their part of the snapshot, and read it back on restore.
+pub trait PersistentContext {
+    /// Writes this context's persistent state into the snapshot.
+    fn export_persistent_state(&self, _state: &mut PersistentState) {}
+
+    /// Restores this context's persistent state from the snapshot.
+    fn restore_persistent_state(&mut self, _state: &PersistentState) {}
+}
+
+/// Trait for exporting the [split::Status] of a context.
+///
+/// Families with state worth showing on the secondary half of a split keyboard
+///  (e.g. active layers, or whether Caps Word is active)
+///  write their part of the status.
+pub trait SplitStatusContext {
+    /// Writes this context's state into the split status.
+    fn export_split_status(&self, _status: &mut split::Status) {}
+}
+
 /// Events related to the keymap.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeymapEvent {
@@ -311,6 +573,61 @@ pub enum KeymapEvent {
         /// The resolved key output.
         key_output: key::KeyOutput,
     },
+    /// Defer the next physical release of the key at this keymap index.
+    ///
+    /// The next release is only passed to the context (not to the pressed keys);
+    ///  the key stays pressed until a context emits a release for it.
+    /// (e.g. a chord which is held until all of its keys are released).
+    DeferRelease {
+        /// The keymap index of the key whose release is deferred.
+        keymap_index: u16,
+    },
+    /// The keymap couldn't defer the release of the key at this keymap index.
+    ///
+    /// Emitted in response to a [KeymapEvent::DeferRelease] when
+    ///  [MAX_DEFERRED_RELEASES] releases are already deferred;
+    ///  the key's next physical release is handled as usual.
+    ReleaseNotDeferred {
+        /// The keymap index of the key whose release was not deferred.
+        keymap_index: u16,
+    },
+    /// Bytes for the keymap to write to its serial output.
+    ///
+    /// (e.g. a steno protocol packet; see [Keymap::read_serial_output]).
+    SerialOutput(SerialPacket),
+}
+
+/// A short packet of bytes written to the keymap's serial output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialPacket {
+    len: u8,
+    bytes: [u8; MAX_SERIAL_PACKET_LEN],
+}
+
+impl SerialPacket {
+    /// Constructs a packet from the given bytes.
+    ///
+    /// Panics if there are more than [MAX_SERIAL_PACKET_LEN] bytes.
+    pub const fn from_slice(slice: &[u8]) -> Self {
+        if slice.len() > MAX_SERIAL_PACKET_LEN {
+            panic!("Serial packet is too long");
+        }
+        let mut bytes = [0; MAX_SERIAL_PACKET_LEN];
+        let mut i = 0;
+        while i < slice.len() {
+            bytes[i] = slice[i];
+            i += 1;
+        }
+        Self {
+            len: slice.len() as u8,
+            bytes,
+        }
+    }
+
+    /// The bytes of the packet.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
 }
 
 #[derive(Debug)]
@@ -322,7 +639,10 @@ enum CallbackFunction {
 }
 
 /// State for a keymap that handles input, and outputs HID keyboard reports.
-pub struct Keymap<I: Index<usize, Output = R>, R, Ctx, Ev: Debug, PKS, KS, S> {
+///
+/// The keymap's [KeymapObserver] receives a [TraceRecord] of what the keymap did.
+/// (By default, [NoOpObserver], which ignores them).
+pub struct Keymap<I: Index<usize, Output = R>, R, Ctx, Ev: Debug, PKS, KS, S, O = NoOpObserver> {
     key_refs: I,
     key_system: S,
     context: Ctx,
@@ -333,10 +653,14 @@ pub struct Keymap<I: Index<usize, Output = R>, R, Ctx, Ev: Debug, PKS, KS, S> {
     /// Ring of recent physical presses for [KeymapContext::recent_presses].
     recent_presses: [(u16, u32); MAX_RECENT_PRESSES],
     recent_press_count: u8,
+    host_leds: HostLeds,
+    deferred_releases: heapless::Vec<u16, { MAX_DEFERRED_RELEASES }>,
+    serial_output: heapless::Deque<u8, { SERIAL_OUTPUT_BUFFER_LEN }>,
     hid_reporter: HIDKeyboardReporter,
     pending_state: Option<pending::PendingState<R, Ev, PKS>>,
     input_queue: InputEventQueue<{ MAX_QUEUED_INPUT_EVENTS }>,
-    callbacks: heapless::LinearMap<KeymapCallback, CallbackFunction, 2>,
+    callbacks: heapless::LinearMap<KeymapCallback, CallbackFunction, { MAX_CALLBACKS }>,
+    observer: O,
 }
 
 impl<
@@ -347,7 +671,8 @@ impl<
         PKS: Debug,
         KS: Debug,
         S: Debug,
-    > core::fmt::Debug for Keymap<I, R, Ctx, Ev, PKS, KS, S>
+        O,
+    > core::fmt::Debug for Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Keymap")
@@ -375,6 +700,23 @@ impl<
 {
     /// Constructs a new keymap with the given key definitions and context.
     pub const fn new(key_refs: I, context: Ctx, key_system: S) -> Self {
+        Self::new_with_observer(key_refs, context, key_system, NoOpObserver)
+    }
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+        O: KeymapObserver<Ev>,
+    > Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
+{
+    /// Constructs a new keymap with the given key definitions, context, and observer.
+    pub const fn new_with_observer(key_refs: I, context: Ctx, key_system: S, observer: O) -> Self {
         Self {
             key_refs,
             key_system,
@@ -385,13 +727,77 @@ impl<
             idle_time: 0,
             recent_presses: [(0, 0); MAX_RECENT_PRESSES],
             recent_press_count: 0,
+            host_leds: HostLeds::NONE,
+            deferred_releases: heapless::Vec::new(),
+            serial_output: heapless::Deque::new(),
             hid_reporter: HIDKeyboardReporter::new(),
             pending_state: None,
             input_queue: InputEventQueue::new(),
             callbacks: heapless::LinearMap::new(),
+            observer,
         }
     }
 
+    /// Replaces the keymap's observer.
+    pub fn with_observer<O2: KeymapObserver<Ev>>(
+        self,
+        observer: O2,
+    ) -> Keymap<I, R, Ctx, Ev, PKS, KS, S, O2> {
+        let Keymap {
+            key_refs,
+            key_system,
+            context,
+            pressed_inputs,
+            event_scheduler,
+            ms_per_tick,
+            idle_time,
+            recent_presses,
+            recent_press_count,
+            host_leds,
+            deferred_releases,
+            serial_output,
+            hid_reporter,
+            pending_state,
+            input_queue,
+            callbacks,
+            observer: _,
+        } = self;
+        Keymap {
+            key_refs,
+            key_system,
+            context,
+            pressed_inputs,
+            event_scheduler,
+            ms_per_tick,
+            idle_time,
+            recent_presses,
+            recent_press_count,
+            host_leds,
+            deferred_releases,
+            serial_output,
+            hid_reporter,
+            pending_state,
+            input_queue,
+            callbacks,
+            observer,
+        }
+    }
+
+    /// The keymap's observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// The keymap's observer, mutably. (e.g. for taking recorded records).
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    fn observe(&mut self, record: TraceRecord<Ev>) {
+        self.observer
+            .observe(self.event_scheduler.schedule_counter, record);
+    }
+
     /// Initializes or resets the keyboard to an initial state.
     ///
     /// Resets [key::Context] from each family's config
@@ -408,6 +814,8 @@ impl<
         self.idle_time = 0;
         self.recent_presses = [(0, 0); MAX_RECENT_PRESSES];
         self.recent_press_count = 0;
+        self.deferred_releases.clear();
+        self.serial_output.clear();
     }
 
     /// Record a physical press in the recent-press ring.
@@ -423,6 +831,21 @@ impl<
         );
     }
 
+    /// Sets the keyboard LED state reported by the host.
+    ///
+    /// Firmware should call this when it receives the HID keyboard output report.
+    /// The state is made available to key systems in [KeymapContext::host_leds],
+    ///  and is kept across [Keymap::init] (since it reflects the host's state).
+    pub fn set_host_leds(&mut self, host_leds: HostLeds) {
+        self.host_leds = host_leds;
+        self.push_keymap_context();
+    }
+
+    /// The keyboard LED state most recently reported by the host.
+    pub fn host_leds(&self) -> HostLeds {
+        self.host_leds
+    }
+
     /// Clears all registered callbacks.
     pub fn clear_callbacks(&mut self) {
         self.callbacks.clear();
@@ -455,6 +878,11 @@ impl<
         self.ms_per_tick = ms_per_tick;
     }
 
+    /// Time in ms the keymap has been ticked for, since it was initialized.
+    pub fn time_ms(&self) -> u32 {
+        self.event_scheduler.schedule_counter
+    }
+
     // If the pending key state is resolved,
     //  then clear the pending key state.
     //
@@ -464,7 +892,7 @@ impl<
     //  they were not yet paced/applied during pending -
     //  and are transferred to the global `input_queue` tail
     //  to run post-resolve in normal order.
-    fn resolve_pending_key_state(&mut self, key_state: KS) {
+    fn resolve_pending_key_state(&mut self, key_state: KS, cause: key::Event<Ev>) {
         if let Some(pending::PendingState {
             keymap_index,
             key_ref,
@@ -473,9 +901,15 @@ impl<
             ..
         }) = self.pending_state.take()
         {
+            let key_output = self.key_system.key_output(&key_ref, &key_state);
+            self.observe(TraceRecord::PendingResolved {
+                keymap_index,
+                cause,
+                key_output,
+            });
+
             // Cancel events which were scheduled for the (pending) key.
-            self.event_scheduler
-                .cancel_events_for_keymap_index(keymap_index);
+            cancel_events(&mut self.event_scheduler, &mut self.observer, keymap_index);
 
             // Add the pending state's pressed key to pressed inputs
             let _ = self.pressed_inputs.push(input::PressedInput::pressed_key(
@@ -500,7 +934,7 @@ impl<
             self.handle_pending_events();
 
             // The resolved key state has output. Emit this as an event.
-            if let Some(key_output) = self.key_system.key_output(&key_ref, &key_state) {
+            if let Some(key_output) = key_output {
                 let km_ev = KeymapEvent::ResolvedKeyOutput {
                     keymap_index,
                     key_output,
@@ -521,13 +955,17 @@ impl<
     ///
     /// Silently discards the input event if the active input queue is full.
     pub fn handle_input(&mut self, ev: input::Event) {
-        let ready = if let Some(pending_state) = self.pending_state.as_mut() {
-            pending_state.ingest_queue.push_back_or_ignore(ev);
-            pending_state.ingest_queue.pop_front_if_ready()
-        } else {
-            self.input_queue.push_back_or_ignore(ev);
-            self.input_queue.pop_front_if_ready()
+        let active_queue = match self.pending_state.as_mut() {
+            Some(pending_state) => &mut pending_state.ingest_queue,
+            None => &mut self.input_queue,
         };
+        let pushed = active_queue.push_back(ev);
+        let ready = active_queue.pop_front_if_ready();
+
+        match pushed {
+            Ok(()) => self.observe(TraceRecord::InputQueued { event: ev }),
+            Err(_) => self.observe(TraceRecord::InputDropped { event: ev }),
+        }
 
         if let Some(ie) = ready {
             // Process before clearing idle_time
@@ -586,8 +1024,9 @@ impl<
                 ev,
             );
 
-            pke.into_iter()
-                .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+            pke.into_iter().for_each(|sch_ev| {
+                schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+            });
 
             while let Some(npk) = maybe_npk.take() {
                 let pkr = match npk {
@@ -597,8 +1036,7 @@ impl<
                         //  (e.g. the chord timeout after passthrough or
                         //  chord resolve) so they do not steal a tick from
                         //  the replacement key's timeout.
-                        self.event_scheduler
-                            .cancel_events_for_keymap_index(keymap_index);
+                        cancel_events(&mut self.event_scheduler, &mut self.observer, keymap_index);
                         // Nested `new_pressed_key` uses press-time
                         //  `idle_time_ms` and omits this still-held press
                         //  from the recent-press ring so
@@ -611,6 +1049,8 @@ impl<
                             press_idle_time_ms,
                             self.event_scheduler.schedule_counter,
                             pressed_modifiers,
+                            self.host_leds,
+                            self.context.active_layers(),
                             keymap_index,
                         );
                         self.context.set_keymap_context(nested_press_ctx);
@@ -632,8 +1072,9 @@ impl<
                             key::PressedKeyResult::Pending(_) => pke.backdate(elapsed_ms),
                             _ => pke,
                         };
-                        pke.into_iter()
-                            .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+                        pke.into_iter().for_each(|sch_ev| {
+                            schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                        });
                         pkr
                     }
                     key::NewPressedKey::NoOp => {
@@ -644,14 +1085,14 @@ impl<
 
                 match pkr {
                     key::PressedKeyResult::Resolved(ks) => {
-                        self.resolve_pending_key_state(ks);
+                        self.resolve_pending_key_state(ks, ev);
                         break;
                     }
                     key::PressedKeyResult::NewPressedKey(key::NewPressedKey::Key(new_key_ref)) => {
                         maybe_npk = Some(key::NewPressedKey::Key(new_key_ref));
                     }
                     key::PressedKeyResult::NewPressedKey(key::NewPressedKey::NoOp) => {
-                        self.resolve_pending_key_state(key::NoOpKeyState.into());
+                        self.resolve_pending_key_state(key::NoOpKeyState.into(), ev);
                         break;
                     }
                     key::PressedKeyResult::Pending(pks) => {
@@ -672,10 +1113,22 @@ impl<
     }
 
     fn process_input(&mut self, ev: input::Event) {
+        self.observe(TraceRecord::InputAccepted { event: ev });
+
         if let Some(pending_state) = self.pending_state.as_mut() {
             // Paced input from the delay line: record in the session log, then apply.
             pending_state.record_input(ev);
             self.update_pending_state(ev.into());
+        } else if let Some(pos) = self.deferred_release_position(ev) {
+            // Deferred release: only the context sees it;
+            //  the key stays pressed until a context releases it.
+            self.deferred_releases.remove(pos);
+            self.context
+                .handle_event(ev.into())
+                .into_iter()
+                .for_each(|sch_ev| {
+                    schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                });
         } else {
             // Update each of the pressed keys with the event.
             self.pressed_inputs.iter_mut().for_each(|pi| {
@@ -688,14 +1141,18 @@ impl<
                     self.key_system
                         .update_state(key_state, key_ref, &self.context, *keymap_index, ev.into())
                         .into_iter()
-                        .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+                        .for_each(|sch_ev| {
+                            schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                        });
                 }
             });
 
             self.context
                 .handle_event(ev.into())
                 .into_iter()
-                .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+                .for_each(|sch_ev| {
+                    schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                });
 
             match ev {
                 input::Event::Press { keymap_index }
@@ -711,8 +1168,9 @@ impl<
                             self.key_system
                                 .new_pressed_key(keymap_index, &self.context, key_ref);
 
-                        pke.into_iter()
-                            .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+                        pke.into_iter().for_each(|sch_ev| {
+                            schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                        });
 
                         match pkr {
                             key::PressedKeyResult::Resolved(key_state) => {
@@ -763,6 +1221,7 @@ impl<
                                 let mut remaining = self.input_queue.take_all();
                                 pending_state.ingest_queue.append_all(&mut remaining);
                                 self.pending_state = Some(pending_state);
+                                self.observe(TraceRecord::PendingStarted { keymap_index });
                             }
                         }
                     }
@@ -805,19 +1264,64 @@ impl<
         self.handle_pending_events();
     }
 
+    /// Defers the next physical release of the key at `keymap_index`.
+    ///
+    /// If no more releases can be deferred, emits [KeymapEvent::ReleaseNotDeferred]
+    ///  so the context which asked can tell.
+    fn defer_release(&mut self, keymap_index: u16) {
+        if self.deferred_releases.contains(&keymap_index) {
+            return;
+        }
+
+        if self.deferred_releases.push(keymap_index).is_err() {
+            schedule_event(
+                &mut self.event_scheduler,
+                &mut self.observer,
+                key::ScheduledEvent::immediate(key::Event::Keymap(
+                    KeymapEvent::ReleaseNotDeferred { keymap_index },
+                )),
+            );
+        }
+    }
+
+    fn deferred_release_position(&self, ev: input::Event) -> Option<usize> {
+        match ev {
+            input::Event::Release { keymap_index } => self
+                .deferred_releases
+                .iter()
+                .position(|&ki| ki == keymap_index),
+            _ => None,
+        }
+    }
+
     // Called from handle_all_pending_events,
     //  and for handling the (resolving) queue of events from pending key state.
     fn handle_event(&mut self, ev: key::Event<Ev>) {
-        if let key::Event::Keymap(KeymapEvent::Callback(callback_id)) = ev {
-            match self.callbacks.get(&callback_id) {
-                Some(CallbackFunction::Rust(callback_fn)) => {
-                    callback_fn();
+        match ev {
+            key::Event::Keymap(KeymapEvent::Callback(callback_id)) => {
+                match self.callbacks.get(&callback_id) {
+                    Some(CallbackFunction::Rust(callback_fn)) => {
+                        callback_fn();
+                    }
+                    Some(CallbackFunction::ExternC(callback_fn)) => {
+                        callback_fn();
+                    }
+                    None => {}
                 }
-                Some(CallbackFunction::ExternC(callback_fn)) => {
-                    callback_fn();
+            }
+            key::Event::Keymap(KeymapEvent::DeferRelease { keymap_index }) => {
+                self.defer_release(keymap_index);
+            }
+            key::Event::Keymap(KeymapEvent::SerialOutput(packet)) => {
+                // Drop the whole packet (rather than part of it) if the buffer is full.
+                let bytes = packet.as_slice();
+                if self.serial_output.capacity() - self.serial_output.len() >= bytes.len() {
+                    bytes.iter().for_each(|&b| {
+                        let _ = self.serial_output.push_back(b);
+                    });
                 }
-                None => {}
             }
+            _ => {}
         }
 
         let was_pending = self.pending_state.is_some();
@@ -836,15 +1340,22 @@ impl<
                 self.key_system
                     .update_state(key_state, key_ref, &self.context, *keymap_index, ev)
                     .into_iter()
-                    .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+                    .for_each(|sch_ev| {
+                        schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+                    });
             }
         });
 
         // Update context with the event
+        if !matches!(ev, Event::Input(_)) {
+            self.observe(TraceRecord::ContextUpdated { event: ev });
+        }
         self.context
             .handle_event(ev)
             .into_iter()
-            .for_each(|sch_ev| self.event_scheduler.schedule_event(sch_ev));
+            .for_each(|sch_ev| {
+                schedule_event(&mut self.event_scheduler, &mut self.observer, sch_ev)
+            });
 
         if let Event::Input(input_ev) = ev {
             if was_pending {
@@ -864,6 +1375,7 @@ impl<
     fn handle_pending_events(&mut self) {
         // take from pending
         while let Some(ev) = self.event_scheduler.dequeue() {
+            self.observe(TraceRecord::EventFired { event: ev });
             self.handle_event(ev);
         }
     }
@@ -890,6 +1402,8 @@ impl<
             pressed_modifiers: self.aggregate_pressed_modifiers(),
             recent_presses: self.recent_presses,
             recent_press_count: self.recent_press_count,
+            host_leds: self.host_leds,
+            active_layers: self.context.active_layers(),
         };
         self.context.set_keymap_context(km_context);
     }
@@ -985,10 +1499,36 @@ impl<
 
     /// Updates the keymap indicating a report is sent; returns the reportable keymap output.
     pub fn report_output(&mut self) -> KeymapOutput {
+        let previous_key_outputs = self.hid_reporter.reportable_key_outputs();
+
         self.hid_reporter.update(self.pressed_keys());
         self.hid_reporter.report_sent();
 
-        KeymapOutput::new(self.hid_reporter.reportable_key_outputs())
+        let key_outputs = self.hid_reporter.reportable_key_outputs();
+        if key_outputs != previous_key_outputs {
+            self.observe(TraceRecord::ReportEmitted {
+                output: KeymapOutput::new(key_outputs.clone()),
+            });
+        }
+        KeymapOutput::new(key_outputs)
+    }
+
+    /// Moves buffered serial output (e.g. steno protocol packets) into `buf`;
+    ///  returns the number of bytes written.
+    ///
+    /// Firmware should write these bytes to its serial port (e.g. USB CDC ACM).
+    pub fn read_serial_output(&mut self, buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        while len < buf.len() {
+            match self.serial_output.pop_front() {
+                Some(b) => {
+                    buf[len] = b;
+                    len += 1;
+                }
+                None => break,
+            }
+        }
+        len
     }
 
     /// Returns the current HID keyboard report.
@@ -1019,6 +1559,125 @@ impl<
     }
 }
 
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + PersistentContext,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+        O,
+    > Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
+{
+    /// Exports a snapshot of the context's state which should survive a power cycle.
+    pub fn persistent_state(&self) -> PersistentState {
+        let mut state = PersistentState::new();
+        self.context.export_persistent_state(&mut state);
+        state
+    }
+
+    /// Restores the context's state from a snapshot taken with [Keymap::persistent_state].
+    ///
+    /// Intended to be called after [Keymap::init], before any keys are pressed.
+    pub fn restore_persistent_state(&mut self, state: &PersistentState) {
+        self.context.restore_persistent_state(state);
+    }
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + SplitStatusContext,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+        O,
+    > Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
+{
+    /// The status to send from the primary half of a split keyboard to the secondary half.
+    ///
+    /// Includes the host LED state set with [Keymap::set_host_leds].
+    pub fn split_status(&self) -> split::Status {
+        let mut status = split::Status::new();
+        status.host_leds = self.host_leds;
+        self.context.export_split_status(&mut status);
+        status
+    }
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug + serde::de::DeserializeOwned,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints + KeyOverridesContext,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+        O,
+    > Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
+{
+    /// The number of layers keys can be overridden on, including the base layer.
+    ///
+    /// Zero if the keymap doesn't support key overrides.
+    pub fn layer_count(&self) -> u8 {
+        self.context.layer_count()
+    }
+
+    /// The key overrides table, if the keymap supports key overrides.
+    pub fn key_overrides(&self) -> Option<&KeyOverrides> {
+        self.context.key_overrides()
+    }
+
+    /// The (postcard-encoded) key ref overriding the given key on the given layer, if any.
+    pub fn key_override(&self, keymap_index: u16, layer: u8) -> Option<&[u8]> {
+        self.context
+            .key_overrides()
+            .and_then(|ko| ko.get(keymap_index, layer))
+            .map(|ko| ko.key_ref_bytes())
+    }
+
+    /// Overrides the key on the given layer with the (postcard-encoded) key ref.
+    ///
+    /// The key ref must decode to one of the keymap's key refs.
+    /// Only layered keys are affected by overrides.
+    pub fn set_key_override(
+        &mut self,
+        keymap_index: u16,
+        layer: u8,
+        key_ref_bytes: &[u8],
+    ) -> Result<(), KeyOverrideError> {
+        if layer >= self.context.layer_count() {
+            return Err(KeyOverrideError::InvalidLayer);
+        }
+
+        match postcard::take_from_bytes::<R>(key_ref_bytes) {
+            Ok((_, [])) => {}
+            _ => return Err(KeyOverrideError::InvalidKeyRef),
+        }
+
+        self.context
+            .key_overrides_mut()
+            .ok_or(KeyOverrideError::Unsupported)?
+            .set(keymap_index, layer, key_ref_bytes)
+    }
+
+    /// Removes all key overrides, restoring the compiled-in keymap.
+    pub fn clear_key_overrides(&mut self) {
+        if let Some(key_overrides) = self.context.key_overrides_mut() {
+            key_overrides.clear();
+        }
+    }
+
+    /// Replaces the key overrides table, e.g. with a table previously stored in flash.
+    pub fn restore_key_overrides(&mut self, key_overrides: &KeyOverrides) {
+        if let Some(ko) = self.context.key_overrides_mut() {
+            *ko = *key_overrides;
+        }
+    }
+}
+
 /// Test-only inspection hooks for pending-state / input-queue pacing.
 ///
 /// Used by `smart-keymap-full-system-std` integration tests
@@ -1033,7 +1692,8 @@ impl<
         PKS: Debug,
         KS: Copy + Debug + From<key::NoOpKeyState>,
         S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
-    > Keymap<I, R, Ctx, Ev, PKS, KS, S>
+        O: KeymapObserver<Ev>,
+    > Keymap<I, R, Ctx, Ev, PKS, KS, S, O>
 {
     /// Whether a pending key state is active.
     pub fn test_is_pending(&self) -> bool {
@@ -1125,6 +1785,43 @@ mod tests {
         assert_eq!(expected_report, actual_report);
     }
 
+    #[test]
+    fn test_keymap_output_as_hid_nkro_keyboard_report_sets_bits() {
+        // Assemble - include modifier key left ctrl
+        let mut input: heapless::Vec<key::KeyOutput, { MAX_PRESSED_KEYS }> = heapless::Vec::new();
+        input.push(key::KeyOutput::from_key_code(0x04)).unwrap();
+        input.push(key::KeyOutput::from_key_code(0x0B)).unwrap();
+        input.push(key::KeyOutput::from_key_code(0xE0)).unwrap();
+
+        // Act - construct the output
+        let keymap_output = KeymapOutput::new(input);
+        let actual_report = keymap_output.as_hid_nkro_keyboard_report();
+
+        // Assert - check modifiers byte, and 0x04 (byte 1 bit 4), 0x0B (byte 2 bit 3).
+        let mut expected_report = [0u8; HID_NKRO_KEYBOARD_REPORT_LEN];
+        expected_report[0] = 0x01;
+        expected_report[1] = 0b0001_0000;
+        expected_report[2] = 0b0000_1000;
+        assert_eq!(expected_report, actual_report);
+    }
+
+    #[test]
+    fn test_keymap_output_as_hid_nkro_keyboard_report_reports_more_than_six_keys() {
+        // Assemble - press A through H (8 keys)
+        let mut input: heapless::Vec<key::KeyOutput, { MAX_PRESSED_KEYS }> = heapless::Vec::new();
+        for kc in 0x04..=0x0B {
+            input.push(key::KeyOutput::from_key_code(kc)).unwrap();
+        }
+
+        // Act - construct the output
+        let keymap_output = KeymapOutput::new(input);
+        let actual_report = keymap_output.as_hid_nkro_keyboard_report();
+
+        // Assert - all 8 keys are in the bitmap.
+        let reported_key_count: u32 = actual_report[1..].iter().map(|b| b.count_ones()).sum();
+        assert_eq!(8, reported_key_count);
+    }
+
     #[test]
     fn test_keymap_output_pressed_consumer_codes() {
         let mut input: heapless::Vec<key::KeyOutput, { MAX_PRESSED_KEYS }> = heapless::Vec::new();
@@ -1172,6 +1869,69 @@ mod tests {
         assert_eq!(0, context.idle_time_ms);
     }
 
+    #[test]
+    fn test_host_leds_from_byte_reads_lock_bits() {
+        // Assemble - HID keyboard output report with Caps Lock and Scroll Lock on
+        let report_byte = HostLeds::CAPS_LOCK_U8 | HostLeds::SCROLL_LOCK_U8;
+
+        // Act
+        let host_leds = HostLeds::from_byte(report_byte);
+
+        // Assert
+        assert!(!host_leds.num_lock());
+        assert!(host_leds.caps_lock());
+        assert!(host_leds.scroll_lock());
+        assert_eq!(report_byte, host_leds.as_byte());
+    }
+
+    #[test]
+    fn test_keymap_context_default_has_no_host_leds() {
+        let context = KeymapContext::new();
+        assert_eq!(HostLeds::NONE, context.host_leds);
+    }
+
+    #[test]
+    fn test_persistent_state_bytes_roundtrip() {
+        // Assemble
+        let state = PersistentState {
+            default_layer: 2,
+            active_layers: 0b1010,
+            locked_layers: 0b1000,
+        };
+
+        // Act
+        let bytes = state.to_bytes();
+        let actual_state = PersistentState::from_bytes(&bytes);
+
+        // Assert
+        assert_eq!(Some(state), actual_state);
+    }
+
+    #[test]
+    fn test_persistent_state_from_bytes_rejects_other_version() {
+        // Assemble
+        let mut bytes = PersistentState::new().to_bytes();
+        bytes[0] = PersistentState::VERSION + 1;
+
+        // Act
+        let actual_state = PersistentState::from_bytes(&bytes);
+
+        // Assert
+        assert_eq!(None, actual_state);
+    }
+
+    #[test]
+    fn test_serial_packet_from_slice_roundtrip() {
+        // Assemble
+        let bytes = [0x80, 0x01, 0x02];
+
+        // Act
+        let packet = SerialPacket::from_slice(&bytes);
+
+        // Assert
+        assert_eq!(&bytes, packet.as_slice());
+    }
+
     fn recent_presses_from(entries: &[(u16, u32)]) -> ([(u16, u32); MAX_RECENT_PRESSES], u8) {
         let mut presses = [(0, 0); MAX_RECENT_PRESSES];
         presses[..entries.len()].copy_from_slice(entries);
@@ -1235,6 +1995,8 @@ mod tests {
             0,
             50,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             2,
         );
 
@@ -1256,6 +2018,8 @@ mod tests {
             7,
             99,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             2,
         );
 
@@ -1279,6 +2043,8 @@ mod tests {
             0,
             99,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             2,
         );
 
@@ -1302,6 +2068,8 @@ mod tests {
             40,
             250,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             0,
         );
 
@@ -1321,6 +2089,8 @@ mod tests {
             40,
             250,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             2,
         );
 
@@ -1342,6 +2112,8 @@ mod tests {
             40,
             250,
             key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            0,
             0,
         );
 
@@ -1359,9 +2131,54 @@ mod tests {
         let mods = key::KeyboardModifiers::LEFT_CTRL;
 
         // Act
-        let ctx = keymap_context_without_current_press(presses, count, 0, 50, mods, 0);
+        let ctx =
+            keymap_context_without_current_press(presses, count, 0, 50, mods, HostLeds::NONE, 0, 0);
 
         // Assert -- pressed_modifiers forwarded unchanged
         assert_eq!(mods, ctx.pressed_modifiers);
     }
+
+    #[test]
+    fn test_without_current_press_passes_host_leds_through() {
+        // Assemble
+        let (presses, count) = recent_presses_from(&[(0, 50)]);
+        let host_leds = HostLeds::from_byte(HostLeds::CAPS_LOCK_U8);
+
+        // Act
+        let ctx = keymap_context_without_current_press(
+            presses,
+            count,
+            0,
+            50,
+            key::KeyboardModifiers::NONE,
+            host_leds,
+            0,
+            0,
+        );
+
+        // Assert
+        assert_eq!(host_leds, ctx.host_leds);
+    }
+
+    #[test]
+    fn test_without_current_press_passes_active_layers_through() {
+        // Assemble
+        let (presses, count) = recent_presses_from(&[(0, 50)]);
+        let active_layers = 0b110;
+
+        // Act
+        let ctx = keymap_context_without_current_press(
+            presses,
+            count,
+            0,
+            50,
+            key::KeyboardModifiers::NONE,
+            HostLeds::NONE,
+            active_layers,
+            0,
+        );
+
+        // Assert
+        assert_eq!(active_layers, ctx.active_layers);
+    }
 }
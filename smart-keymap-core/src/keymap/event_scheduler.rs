@@ -84,7 +84,15 @@ impl<E: Debug> EventScheduler<E> {
         );
     }
 
-    pub fn cancel_events_for_keymap_index(&mut self, keymap_index: u16) {
+    /// Cancels the scheduled events for the keymap index,
+    ///  calling `on_cancel` with each event which was still live.
+    pub fn cancel_events_for_keymap_index(
+        &mut self,
+        keymap_index: u16,
+        mut on_cancel: impl FnMut(Event<E>),
+    ) where
+        E: Copy,
+    {
         self.scheduled_events
             .iter_mut()
             .for_each(|scheduled_event| {
@@ -92,8 +100,9 @@ impl<E: Debug> EventScheduler<E> {
                     keymap_index: ki, ..
                 } = scheduled_event.event
                 {
-                    if ki == keymap_index {
+                    if ki == keymap_index && scheduled_event.live {
                         scheduled_event.live = false;
+                        on_cancel(scheduled_event.event);
                     }
                 }
             });
@@ -120,15 +129,65 @@ impl<E: Debug> EventScheduler<E> {
         self.pending_events.dequeue().flatten()
     }
 
-    /// Returns the time until the soonest scheduled event (0 if pending),
+    /// Returns the time until the soonest scheduled event (0 if pending or overdue),
     ///  or None if there are no pending nor scheduled events
+    ///
+    /// Only one scheduled event is made pending per tick,
+    ///  so events scheduled for the same time may be overdue.
     pub fn next_event_time(&self) -> Option<u32> {
         if self.pending_events.is_empty() {
             self.scheduled_events
                 .last()
-                .map(|e| e.time - self.schedule_counter)
+                .map(|e| e.time.saturating_sub(self.schedule_counter))
         } else {
             Some(0)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_events_for_keymap_index_calls_back_with_live_events() {
+        // Assemble
+        let mut scheduler: EventScheduler<u8> = EventScheduler::new();
+        scheduler.schedule_after(20, Event::key_event(0, 1));
+        scheduler.schedule_after(10, Event::key_event(1, 2));
+        scheduler.schedule_after(30, Event::key_event(0, 3));
+
+        // Act
+        let mut cancelled: heapless::Vec<Event<u8>, 4> = heapless::Vec::new();
+        scheduler.cancel_events_for_keymap_index(0, |ev| {
+            let _ = cancelled.push(ev);
+        });
+        scheduler.cancel_events_for_keymap_index(0, |ev| {
+            let _ = cancelled.push(ev);
+        });
+
+        // Assert
+        assert_eq!(
+            &[Event::key_event(0, 3), Event::key_event(0, 1)],
+            cancelled.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_next_event_time_is_zero_for_overdue_events() {
+        // Assemble
+        let mut scheduler: EventScheduler<u8> = EventScheduler::new();
+        scheduler.schedule_after(1, Event::key_event(0, 1));
+        scheduler.schedule_after(1, Event::key_event(1, 2));
+        scheduler.schedule_after(1, Event::key_event(2, 3));
+
+        // Act
+        scheduler.tick(1);
+        let _ = scheduler.dequeue();
+        scheduler.tick(1);
+        let _ = scheduler.dequeue();
+
+        // Assert
+        assert_eq!(Some(0), scheduler.next_event_time());
+    }
+}
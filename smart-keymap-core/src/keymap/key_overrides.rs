@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of entries in a [KeyOverrides] table.
+pub const MAX_KEY_OVERRIDES: usize = 16;
+
+/// Maximum length of a postcard-encoded key ref in a [KeyOverride].
+pub const MAX_KEY_OVERRIDE_REF_LEN: usize = 8;
+
+/// Errors when editing a [KeyOverrides] table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOverrideError {
+    /// The table has no space for another entry.
+    Full,
+    /// The layer is outside of the keymap's layers.
+    InvalidLayer,
+    /// The encoded key ref is empty or longer than [MAX_KEY_OVERRIDE_REF_LEN].
+    InvalidLength,
+    /// The encoded key ref doesn't decode to a key ref of the keymap.
+    InvalidKeyRef,
+    /// The keymap doesn't support key overrides.
+    Unsupported,
+}
+
+impl core::fmt::Display for KeyOverrideError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            KeyOverrideError::Full => write!(f, "key override table is full"),
+            KeyOverrideError::InvalidLayer => write!(f, "invalid layer"),
+            KeyOverrideError::InvalidLength => write!(f, "invalid key ref length"),
+            KeyOverrideError::InvalidKeyRef => write!(f, "invalid key ref"),
+            KeyOverrideError::Unsupported => write!(f, "key overrides not supported"),
+        }
+    }
+}
+
+/// A key ref which replaces the compiled-in key ref of a layered key, on one layer.
+///
+/// The key ref is stored postcard-encoded,
+///  so that the table doesn't depend on the keymap's `Ref` type.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyOverride {
+    /// Keymap index of the overridden key.
+    pub keymap_index: u16,
+    /// The overridden layer. (`0` is the base key, `1..` the layered keys).
+    pub layer: u8,
+    key_ref_len: u8,
+    key_ref: [u8; MAX_KEY_OVERRIDE_REF_LEN],
+}
+
+impl KeyOverride {
+    /// The postcard-encoded key ref.
+    pub fn key_ref_bytes(&self) -> &[u8] {
+        &self.key_ref[..self.key_ref_len as usize]
+    }
+
+    /// Decodes the key ref.
+    pub fn key_ref<R: serde::de::DeserializeOwned>(&self) -> Option<R> {
+        postcard::from_bytes(self.key_ref_bytes()).ok()
+    }
+}
+
+/// Runtime table of key overrides for [crate::key::layered::LayeredKey]s.
+///
+/// The table is kept in RAM as part of the layered context,
+///  and survives [Keymap::init](super::Keymap::init).
+/// Firmware can store the table in flash (e.g. with postcard),
+///  and reapply it with [Keymap::restore_key_overrides](super::Keymap::restore_key_overrides).
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyOverrides {
+    len: u8,
+    entries: [KeyOverride; MAX_KEY_OVERRIDES],
+}
+
+impl KeyOverrides {
+    /// Constructs an empty table.
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            entries: [KeyOverride {
+                keymap_index: 0,
+                layer: 0,
+                key_ref_len: 0,
+                key_ref: [0; MAX_KEY_OVERRIDE_REF_LEN],
+            }; MAX_KEY_OVERRIDES],
+        }
+    }
+
+    /// Whether the table has no entries.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of entries in the table.
+    pub const fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// The entries in the table.
+    pub fn iter(&self) -> impl Iterator<Item = &KeyOverride> {
+        self.entries[..self.len as usize].iter()
+    }
+
+    /// The override for the given key and layer, if any.
+    pub fn get(&self, keymap_index: u16, layer: u8) -> Option<&KeyOverride> {
+        self.iter()
+            .find(|ko| ko.keymap_index == keymap_index && ko.layer == layer)
+    }
+
+    /// Sets the (postcard-encoded) key ref for the given key and layer.
+    ///
+    /// Replaces any existing override for that key and layer.
+    pub fn set(
+        &mut self,
+        keymap_index: u16,
+        layer: u8,
+        key_ref_bytes: &[u8],
+    ) -> Result<(), KeyOverrideError> {
+        let key_ref_len = key_ref_bytes.len();
+        if key_ref_len == 0 || key_ref_len > MAX_KEY_OVERRIDE_REF_LEN {
+            return Err(KeyOverrideError::InvalidLength);
+        }
+
+        let mut key_ref = [0; MAX_KEY_OVERRIDE_REF_LEN];
+        key_ref[..key_ref_len].copy_from_slice(key_ref_bytes);
+        let key_override = KeyOverride {
+            keymap_index,
+            layer,
+            key_ref_len: key_ref_len as u8,
+            key_ref,
+        };
+
+        let len = self.len as usize;
+        match self.entries[..len]
+            .iter()
+            .position(|ko| ko.keymap_index == keymap_index && ko.layer == layer)
+        {
+            Some(pos) => self.entries[pos] = key_override,
+            None if len < MAX_KEY_OVERRIDES => {
+                self.entries[len] = key_override;
+                self.len += 1;
+            }
+            None => return Err(KeyOverrideError::Full),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the override for the given key and layer, if any.
+    pub fn remove(&mut self, keymap_index: u16, layer: u8) {
+        let len = self.len as usize;
+        if let Some(pos) = self.entries[..len]
+            .iter()
+            .position(|ko| ko.keymap_index == keymap_index && ko.layer == layer)
+        {
+            self.entries.copy_within(pos + 1..len, pos);
+            self.len -= 1;
+        }
+    }
+
+    /// Removes all overrides.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Trait for contexts which hold a [KeyOverrides] table.
+///
+/// The default implementation is for keymaps without layered keys,
+///  which have no keys to override.
+pub trait KeyOverridesContext {
+    /// The number of layers keys can be overridden on, including the base layer.
+    fn layer_count(&self) -> u8 {
+        0
+    }
+
+    /// The key overrides table.
+    fn key_overrides(&self) -> Option<&KeyOverrides> {
+        None
+    }
+
+    /// The key overrides table, for editing.
+    fn key_overrides_mut(&mut self) -> Option<&mut KeyOverrides> {
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_replaces_existing_override() {
+        // Assemble
+        let mut overrides = KeyOverrides::new();
+        overrides.set(3, 1, &[0x01, 0x04]).unwrap();
+
+        // Act
+        overrides.set(3, 1, &[0x01, 0x05]).unwrap();
+
+        // Assert
+        assert_eq!(1, overrides.len());
+        assert_eq!(&[0x01, 0x05], overrides.get(3, 1).unwrap().key_ref_bytes());
+    }
+
+    #[test]
+    fn test_set_rejects_when_full() {
+        // Assemble
+        let mut overrides = KeyOverrides::new();
+        for i in 0..MAX_KEY_OVERRIDES {
+            overrides.set(i as u16, 0, &[0x00]).unwrap();
+        }
+
+        // Act
+        let actual = overrides.set(MAX_KEY_OVERRIDES as u16, 0, &[0x00]);
+
+        // Assert
+        assert_eq!(Err(KeyOverrideError::Full), actual);
+    }
+
+    #[test]
+    fn test_remove_keeps_other_overrides() {
+        // Assemble
+        let mut overrides = KeyOverrides::new();
+        overrides.set(0, 0, &[0x00]).unwrap();
+        overrides.set(1, 0, &[0x01]).unwrap();
+        overrides.set(2, 0, &[0x02]).unwrap();
+
+        // Act
+        overrides.remove(1, 0);
+
+        // Assert
+        assert_eq!(2, overrides.len());
+        assert!(overrides.get(1, 0).is_none());
+        assert_eq!(&[0x02], overrides.get(2, 0).unwrap().key_ref_bytes());
+    }
+}
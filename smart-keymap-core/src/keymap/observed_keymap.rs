@@ -61,6 +61,11 @@ impl<
         distinct_reports.update(keymap.report_output().as_hid_boot_keyboard_report());
     }
 
+    /// Proxies [keymap::Keymap::set_host_leds].
+    pub fn set_host_leds(&mut self, host_leds: keymap::HostLeds) {
+        self.keymap.set_host_leds(host_leds);
+    }
+
     /// Proxies [keymap::Keymap::tick], updating reports appropriately.
     pub fn tick(&mut self) {
         let ObservedKeymap {
@@ -79,6 +84,11 @@ impl<
         keymap::KeymapOutput::new(keymap.pressed_keys()).as_hid_boot_keyboard_report()
     }
 
+    /// Proxies [keymap::Keymap::read_serial_output].
+    pub fn read_serial_output(&mut self, buf: &mut [u8]) -> usize {
+        self.keymap.read_serial_output(buf)
+    }
+
     /// Reference to distinct reports.
     pub fn distinct_reports(&self) -> &keymap::DistinctReports {
         &self.distinct_reports
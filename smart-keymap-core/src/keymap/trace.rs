@@ -0,0 +1,236 @@
+use crate::input;
+use crate::key;
+
+use super::KeymapOutput;
+
+/// Typed records of what the [super::Keymap] did, passed to its [KeymapObserver].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceRecord<Ev> {
+    /// The input was added to the keymap's input queue.
+    InputQueued {
+        /// The input.
+        event: input::Event,
+    },
+    /// The input was discarded, since the keymap's input queue was full.
+    InputDropped {
+        /// The input.
+        event: input::Event,
+    },
+    /// The input was taken from the input queue and processed.
+    ///
+    /// Inputs which are replayed after a pending key resolves
+    ///  are accepted again.
+    InputAccepted {
+        /// The input.
+        event: input::Event,
+    },
+    /// A pressed key's state is pending.
+    PendingStarted {
+        /// The keymap index of the pending key.
+        keymap_index: u16,
+    },
+    /// The pending key's state was resolved.
+    PendingResolved {
+        /// The keymap index of the pending key.
+        keymap_index: u16,
+        /// The event which resolved the pending key.
+        ///
+        /// e.g. an [key::Event::Input] for a key press which interrupted a tap-hold key,
+        ///  or an [key::Event::Key] for a tap-hold key's timeout.
+        cause: key::Event<Ev>,
+        /// The output of the resolved key, if any.
+        key_output: Option<key::KeyOutput>,
+    },
+    /// An event was scheduled.
+    EventScheduled {
+        /// When the event is scheduled for.
+        schedule: key::Schedule,
+        /// The event.
+        event: key::Event<Ev>,
+    },
+    /// A scheduled event was due, and was handled.
+    EventFired {
+        /// The event.
+        event: key::Event<Ev>,
+    },
+    /// A scheduled event was cancelled before it was due.
+    ///
+    /// (e.g. a tap-hold key's timeout, when the key resolved by interrupt).
+    EventCancelled {
+        /// The event.
+        event: key::Event<Ev>,
+    },
+    /// The key context handled an event (which may have changed the context's state,
+    ///  e.g. activating a layer).
+    ContextUpdated {
+        /// The event.
+        event: key::Event<Ev>,
+    },
+    /// The keymap's reported output changed.
+    ///
+    /// (Reporting the same output again isn't traced).
+    ReportEmitted {
+        /// The reported output.
+        output: KeymapOutput,
+    },
+}
+
+/// Receives [TraceRecord]s from a [super::Keymap].
+///
+/// See [super::Keymap::new_with_observer] and [super::Keymap::with_observer].
+pub trait KeymapObserver<Ev> {
+    /// Called with each trace record, and the keymap's time in ms.
+    fn observe(&mut self, time_ms: u32, record: TraceRecord<Ev>);
+}
+
+/// A [KeymapObserver] which ignores all records.
+///
+/// The default observer for [super::Keymap].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoOpObserver;
+
+impl<Ev> KeymapObserver<Ev> for NoOpObserver {
+    #[inline(always)]
+    fn observe(&mut self, _time_ms: u32, _record: TraceRecord<Ev>) {}
+}
+
+/// A [KeymapObserver] which logs records with `defmt`.
+#[cfg(feature = "defmt")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefmtObserver;
+
+#[cfg(feature = "defmt")]
+impl<Ev> KeymapObserver<Ev> for DefmtObserver {
+    fn observe(&mut self, time_ms: u32, record: TraceRecord<Ev>) {
+        defmt::trace!("{=u32} ms: {}", time_ms, record);
+    }
+}
+
+/// Writes the key event, without the key system's event
+///  (which is specific to the key family, so may not implement `defmt::Format`).
+#[cfg(feature = "defmt")]
+fn format_key_event<Ev>(f: defmt::Formatter, event: &key::Event<Ev>) {
+    match event {
+        key::Event::Input(event) => defmt::write!(f, "Input({})", event),
+        key::Event::Key { keymap_index, .. } => {
+            defmt::write!(f, "Key {{ keymap_index: {=u16} }}", keymap_index)
+        }
+        key::Event::Keymap(_) => defmt::write!(f, "Keymap"),
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<Ev> defmt::Format for TraceRecord<Ev> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            TraceRecord::InputQueued { event } => defmt::write!(f, "InputQueued {}", event),
+            TraceRecord::InputDropped { event } => defmt::write!(f, "InputDropped {}", event),
+            TraceRecord::InputAccepted { event } => defmt::write!(f, "InputAccepted {}", event),
+            TraceRecord::PendingStarted { keymap_index } => {
+                defmt::write!(f, "PendingStarted {=u16}", keymap_index)
+            }
+            TraceRecord::PendingResolved {
+                keymap_index,
+                cause,
+                key_output,
+            } => {
+                defmt::write!(f, "PendingResolved {=u16} by ", keymap_index);
+                format_key_event(f, cause);
+                defmt::write!(f, ": {}", key_output);
+            }
+            TraceRecord::EventScheduled { schedule, event } => {
+                defmt::write!(f, "EventScheduled {} ", schedule);
+                format_key_event(f, event);
+            }
+            TraceRecord::EventFired { event } => {
+                defmt::write!(f, "EventFired ");
+                format_key_event(f, event);
+            }
+            TraceRecord::EventCancelled { event } => {
+                defmt::write!(f, "EventCancelled ");
+                format_key_event(f, event);
+            }
+            TraceRecord::ContextUpdated { event } => {
+                defmt::write!(f, "ContextUpdated ");
+                format_key_event(f, event);
+            }
+            TraceRecord::ReportEmitted { output } => defmt::write!(f, "ReportEmitted {}", output),
+        }
+    }
+}
+
+/// A [KeymapObserver] which records each record, with its time in ms.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRecorder<Ev>(Vec<(u32, TraceRecord<Ev>)>);
+
+#[cfg(feature = "std")]
+impl<Ev> Default for TraceRecorder<Ev> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Ev> TraceRecorder<Ev> {
+    /// Constructs a new, empty trace recorder.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// The recorded records, with the time in ms each was recorded at.
+    pub fn entries(&self) -> &[(u32, TraceRecord<Ev>)] {
+        &self.0
+    }
+
+    /// The recorded records.
+    pub fn records(&self) -> impl Iterator<Item = &TraceRecord<Ev>> {
+        self.0.iter().map(|(_, record)| record)
+    }
+
+    /// Removes and returns the recorded records.
+    pub fn take(&mut self) -> Vec<(u32, TraceRecord<Ev>)> {
+        core::mem::take(&mut self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Ev> KeymapObserver<Ev> for TraceRecorder<Ev> {
+    fn observe(&mut self, time_ms: u32, record: TraceRecord<Ev>) {
+        self.0.push((time_ms, record));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_recorder_records_entries_in_order() {
+        let mut recorder: TraceRecorder<()> = TraceRecorder::new();
+        let press = input::Event::Press { keymap_index: 0 };
+
+        recorder.observe(0, TraceRecord::InputQueued { event: press });
+        recorder.observe(1, TraceRecord::InputAccepted { event: press });
+
+        assert_eq!(
+            &[
+                (0, TraceRecord::InputQueued { event: press }),
+                (1, TraceRecord::InputAccepted { event: press }),
+            ],
+            recorder.entries()
+        );
+    }
+
+    #[test]
+    fn test_trace_recorder_take_clears_entries() {
+        let mut recorder: TraceRecorder<()> = TraceRecorder::new();
+        let press = input::Event::Press { keymap_index: 0 };
+        recorder.observe(0, TraceRecord::InputQueued { event: press });
+
+        let entries = recorder.take();
+
+        assert_eq!(1, entries.len());
+        assert_eq!(0, recorder.records().count());
+    }
+}
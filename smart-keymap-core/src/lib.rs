@@ -17,6 +17,8 @@
 
 /// Structs for input to the keymap.
 pub mod input;
+/// Compact binary logs of timestamped input events, for replaying input sessions.
+pub mod input_log;
 /// Smart key interface and implementations.
 ///
 /// The core interface is [key::System], and its associated [key::Context],
@@ -25,6 +27,9 @@ pub mod key;
 /// Keymap implementation.
 pub mod keymap;
 
+/// Raw HID protocol for editing the keymap at runtime.
+pub mod raw_hid;
+
 /// Split keyboard support.
 pub mod split;
 
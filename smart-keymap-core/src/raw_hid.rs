@@ -0,0 +1,527 @@
+use core::fmt::Debug;
+use core::ops::Index;
+
+use crate::key;
+use crate::keymap;
+
+/// Length of the raw HID reports used by the protocol (both directions).
+pub const REPORT_LEN: usize = 32;
+
+/// Version of the protocol, as reported by [Request::GetProtocolVersion].
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// A raw HID report.
+pub type Report = [u8; REPORT_LEN];
+
+/// A postcard-encoded key ref.
+pub type KeyRefBytes = heapless::Vec<u8, { keymap::MAX_KEY_OVERRIDE_REF_LEN }>;
+
+/// Command ID for [Request::GetProtocolVersion].
+pub const COMMAND_GET_PROTOCOL_VERSION: u8 = 0x01;
+/// Command ID for [Request::GetLayerCount].
+pub const COMMAND_GET_LAYER_COUNT: u8 = 0x02;
+/// Command ID for [Request::GetKey].
+pub const COMMAND_GET_KEY: u8 = 0x03;
+/// Command ID for [Request::SetKey].
+pub const COMMAND_SET_KEY: u8 = 0x04;
+/// Command ID for [Request::ResetKeys].
+pub const COMMAND_RESET_KEYS: u8 = 0x05;
+
+/// Status byte of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The request succeeded.
+    Ok,
+    /// The command ID isn't known.
+    UnknownCommand,
+    /// The request report couldn't be decoded.
+    Malformed,
+    /// The layer is outside of the keymap's layers.
+    InvalidLayer,
+    /// The key ref doesn't decode to a key ref of the keymap.
+    InvalidKeyRef,
+    /// The key override table is full.
+    Full,
+    /// The keymap doesn't support key overrides.
+    Unsupported,
+}
+
+impl Status {
+    /// The status as a byte.
+    pub const fn as_byte(self) -> u8 {
+        match self {
+            Status::Ok => 0x00,
+            Status::UnknownCommand => 0x01,
+            Status::Malformed => 0x02,
+            Status::InvalidLayer => 0x03,
+            Status::InvalidKeyRef => 0x04,
+            Status::Full => 0x05,
+            Status::Unsupported => 0x06,
+        }
+    }
+
+    /// The status for the given byte.
+    pub const fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0x00 => Some(Status::Ok),
+            0x01 => Some(Status::UnknownCommand),
+            0x02 => Some(Status::Malformed),
+            0x03 => Some(Status::InvalidLayer),
+            0x04 => Some(Status::InvalidKeyRef),
+            0x05 => Some(Status::Full),
+            0x06 => Some(Status::Unsupported),
+            _ => None,
+        }
+    }
+}
+
+impl From<keymap::KeyOverrideError> for Status {
+    fn from(e: keymap::KeyOverrideError) -> Self {
+        match e {
+            keymap::KeyOverrideError::Full => Status::Full,
+            keymap::KeyOverrideError::InvalidLayer => Status::InvalidLayer,
+            keymap::KeyOverrideError::InvalidLength => Status::InvalidKeyRef,
+            keymap::KeyOverrideError::InvalidKeyRef => Status::InvalidKeyRef,
+            keymap::KeyOverrideError::Unsupported => Status::Unsupported,
+        }
+    }
+}
+
+/// Request sent from the host to the keyboard.
+///
+/// Layers are numbered from `0` (the base key of a layered key).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Get the protocol version.
+    GetProtocolVersion,
+    /// Get the number of layers (including the base layer).
+    GetLayerCount,
+    /// Get the key override for the given key and layer.
+    GetKey {
+        /// The layer.
+        layer: u8,
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// Override the key ref of the given key on the given layer.
+    SetKey {
+        /// The layer.
+        layer: u8,
+        /// The keymap index.
+        keymap_index: u16,
+        /// The postcard-encoded key ref.
+        key_ref: KeyRefBytes,
+    },
+    /// Remove all key overrides.
+    ResetKeys,
+}
+
+impl Request {
+    /// The command ID of the request.
+    pub const fn command(&self) -> u8 {
+        match self {
+            Request::GetProtocolVersion => COMMAND_GET_PROTOCOL_VERSION,
+            Request::GetLayerCount => COMMAND_GET_LAYER_COUNT,
+            Request::GetKey { .. } => COMMAND_GET_KEY,
+            Request::SetKey { .. } => COMMAND_SET_KEY,
+            Request::ResetKeys => COMMAND_RESET_KEYS,
+        }
+    }
+
+    /// Encodes the request as a report.
+    pub fn to_report(&self) -> Report {
+        let mut report = [0u8; REPORT_LEN];
+        report[0] = self.command();
+        match self {
+            Request::GetKey {
+                layer,
+                keymap_index,
+            } => {
+                report[1] = *layer;
+                report[2..4].copy_from_slice(&keymap_index.to_le_bytes());
+            }
+            Request::SetKey {
+                layer,
+                keymap_index,
+                key_ref,
+            } => {
+                report[1] = *layer;
+                report[2..4].copy_from_slice(&keymap_index.to_le_bytes());
+                report[4] = key_ref.len() as u8;
+                report[5..5 + key_ref.len()].copy_from_slice(key_ref);
+            }
+            _ => {}
+        }
+        report
+    }
+
+    /// Decodes the request from a report.
+    pub fn from_report(report: &[u8]) -> Result<Self, Status> {
+        match report {
+            [COMMAND_GET_PROTOCOL_VERSION, ..] => Ok(Request::GetProtocolVersion),
+            [COMMAND_GET_LAYER_COUNT, ..] => Ok(Request::GetLayerCount),
+            [COMMAND_GET_KEY, layer, i0, i1, ..] => Ok(Request::GetKey {
+                layer: *layer,
+                keymap_index: u16::from_le_bytes([*i0, *i1]),
+            }),
+            [COMMAND_SET_KEY, layer, i0, i1, len, rest @ ..] => {
+                let key_ref = rest
+                    .get(..*len as usize)
+                    .and_then(|bytes| KeyRefBytes::from_slice(bytes).ok())
+                    .ok_or(Status::Malformed)?;
+                Ok(Request::SetKey {
+                    layer: *layer,
+                    keymap_index: u16::from_le_bytes([*i0, *i1]),
+                    key_ref,
+                })
+            }
+            [COMMAND_RESET_KEYS, ..] => Ok(Request::ResetKeys),
+            [COMMAND_GET_KEY | COMMAND_SET_KEY, ..] | [] => Err(Status::Malformed),
+            _ => Err(Status::UnknownCommand),
+        }
+    }
+}
+
+/// Response sent from the keyboard to the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// The protocol version.
+    ProtocolVersion(u8),
+    /// The number of layers (including the base layer).
+    LayerCount(u8),
+    /// The key override for a key and layer.
+    Key {
+        /// The layer.
+        layer: u8,
+        /// The keymap index.
+        keymap_index: u16,
+        /// The postcard-encoded key ref; `None` if the compiled-in key is used.
+        key_ref: Option<KeyRefBytes>,
+    },
+    /// The key was set.
+    KeySet,
+    /// All key overrides were removed.
+    KeysReset,
+    /// The request failed.
+    Error {
+        /// The command ID of the failed request.
+        command: u8,
+        /// Why the request failed.
+        status: Status,
+    },
+}
+
+impl Response {
+    /// Encodes the response as a report.
+    pub fn to_report(&self) -> Report {
+        let mut report = [0u8; REPORT_LEN];
+        match self {
+            Response::ProtocolVersion(version) => {
+                report[0] = COMMAND_GET_PROTOCOL_VERSION;
+                report[2] = *version;
+            }
+            Response::LayerCount(count) => {
+                report[0] = COMMAND_GET_LAYER_COUNT;
+                report[2] = *count;
+            }
+            Response::Key {
+                layer,
+                keymap_index,
+                key_ref,
+            } => {
+                report[0] = COMMAND_GET_KEY;
+                report[2] = *layer;
+                report[3..5].copy_from_slice(&keymap_index.to_le_bytes());
+                if let Some(key_ref) = key_ref {
+                    report[5] = key_ref.len() as u8;
+                    report[6..6 + key_ref.len()].copy_from_slice(key_ref);
+                }
+            }
+            Response::KeySet => report[0] = COMMAND_SET_KEY,
+            Response::KeysReset => report[0] = COMMAND_RESET_KEYS,
+            Response::Error { command, status } => {
+                report[0] = *command;
+                report[1] = status.as_byte();
+            }
+        }
+        report
+    }
+
+    /// Decodes the response from a report.
+    pub fn from_report(report: &[u8]) -> Result<Self, Status> {
+        let (command, status, payload) = match report {
+            [command, status, payload @ ..] => (*command, *status, payload),
+            _ => return Err(Status::Malformed),
+        };
+
+        match Status::from_byte(status) {
+            Some(Status::Ok) => {}
+            Some(status) => return Ok(Response::Error { command, status }),
+            None => return Err(Status::Malformed),
+        }
+
+        match (command, payload) {
+            (COMMAND_GET_PROTOCOL_VERSION, [version, ..]) => {
+                Ok(Response::ProtocolVersion(*version))
+            }
+            (COMMAND_GET_LAYER_COUNT, [count, ..]) => Ok(Response::LayerCount(*count)),
+            (COMMAND_GET_KEY, [layer, i0, i1, len, rest @ ..]) => {
+                let key_ref = match *len as usize {
+                    0 => None,
+                    len => Some(
+                        rest.get(..len)
+                            .and_then(|bytes| KeyRefBytes::from_slice(bytes).ok())
+                            .ok_or(Status::Malformed)?,
+                    ),
+                };
+                Ok(Response::Key {
+                    layer: *layer,
+                    keymap_index: u16::from_le_bytes([*i0, *i1]),
+                    key_ref,
+                })
+            }
+            (COMMAND_SET_KEY, _) => Ok(Response::KeySet),
+            (COMMAND_RESET_KEYS, _) => Ok(Response::KeysReset),
+            _ => Err(Status::Malformed),
+        }
+    }
+}
+
+/// A keymap which can be edited through the protocol.
+pub trait KeymapEditor {
+    /// The number of layers (including the base layer).
+    fn layer_count(&self) -> u8;
+
+    /// The key override for the given key and layer, if any.
+    fn key_override(&self, keymap_index: u16, layer: u8) -> Option<KeyRefBytes>;
+
+    /// Overrides the key on the given layer with the (postcard-encoded) key ref.
+    fn set_key_override(
+        &mut self,
+        keymap_index: u16,
+        layer: u8,
+        key_ref: &[u8],
+    ) -> Result<(), keymap::KeyOverrideError>;
+
+    /// Removes all key overrides.
+    fn clear_key_overrides(&mut self);
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug + serde::de::DeserializeOwned,
+        Ctx: Debug
+            + key::Context<Event = Ev>
+            + keymap::SetKeymapContext
+            + keymap::ReportHints
+            + keymap::KeyOverridesContext,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+    > KeymapEditor for keymap::Keymap<I, R, Ctx, Ev, PKS, KS, S>
+{
+    fn layer_count(&self) -> u8 {
+        keymap::Keymap::layer_count(self)
+    }
+
+    fn key_override(&self, keymap_index: u16, layer: u8) -> Option<KeyRefBytes> {
+        keymap::Keymap::key_override(self, keymap_index, layer)
+            .and_then(|bytes| KeyRefBytes::from_slice(bytes).ok())
+    }
+
+    fn set_key_override(
+        &mut self,
+        keymap_index: u16,
+        layer: u8,
+        key_ref: &[u8],
+    ) -> Result<(), keymap::KeyOverrideError> {
+        keymap::Keymap::set_key_override(self, keymap_index, layer, key_ref)
+    }
+
+    fn clear_key_overrides(&mut self) {
+        keymap::Keymap::clear_key_overrides(self)
+    }
+}
+
+/// Handles the request, returning the response.
+pub fn handle_request<E: KeymapEditor>(editor: &mut E, request: &Request) -> Response {
+    let command = request.command();
+    match request {
+        Request::GetProtocolVersion => Response::ProtocolVersion(PROTOCOL_VERSION),
+        Request::GetLayerCount => Response::LayerCount(editor.layer_count()),
+        Request::GetKey {
+            layer,
+            keymap_index,
+        } => {
+            if *layer >= editor.layer_count() {
+                Response::Error {
+                    command,
+                    status: Status::InvalidLayer,
+                }
+            } else {
+                Response::Key {
+                    layer: *layer,
+                    keymap_index: *keymap_index,
+                    key_ref: editor.key_override(*keymap_index, *layer),
+                }
+            }
+        }
+        Request::SetKey {
+            layer,
+            keymap_index,
+            key_ref,
+        } => match editor.set_key_override(*keymap_index, *layer, key_ref) {
+            Ok(()) => Response::KeySet,
+            Err(e) => Response::Error {
+                command,
+                status: e.into(),
+            },
+        },
+        Request::ResetKeys => {
+            editor.clear_key_overrides();
+            Response::KeysReset
+        }
+    }
+}
+
+/// Handles a raw HID report received from the host, returning the report to send back.
+pub fn handle_report<E: KeymapEditor>(editor: &mut E, report: &[u8]) -> Report {
+    match Request::from_report(report) {
+        Ok(request) => handle_request(editor, &request).to_report(),
+        Err(status) => Response::Error {
+            command: report.first().copied().unwrap_or(0),
+            status,
+        }
+        .to_report(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    /// A [KeymapEditor] with a bare [keymap::KeyOverrides] table.
+    struct TableEditor {
+        layer_count: u8,
+        key_overrides: keymap::KeyOverrides,
+    }
+
+    impl KeymapEditor for TableEditor {
+        fn layer_count(&self) -> u8 {
+            self.layer_count
+        }
+
+        fn key_override(&self, keymap_index: u16, layer: u8) -> Option<KeyRefBytes> {
+            self.key_overrides
+                .get(keymap_index, layer)
+                .map(|ko| KeyRefBytes::from_slice(ko.key_ref_bytes()).unwrap())
+        }
+
+        fn set_key_override(
+            &mut self,
+            keymap_index: u16,
+            layer: u8,
+            key_ref: &[u8],
+        ) -> Result<(), keymap::KeyOverrideError> {
+            if layer >= self.layer_count {
+                return Err(keymap::KeyOverrideError::InvalidLayer);
+            }
+            self.key_overrides.set(keymap_index, layer, key_ref)
+        }
+
+        fn clear_key_overrides(&mut self) {
+            self.key_overrides.clear();
+        }
+    }
+
+    #[test]
+    fn test_request_set_key_report_roundtrip() {
+        // Assemble
+        let request = Request::SetKey {
+            layer: 2,
+            keymap_index: 0x0102,
+            key_ref: KeyRefBytes::from_slice(&[0x03, 0x04]).unwrap(),
+        };
+
+        // Act
+        let report = request.to_report();
+        let actual_request = Request::from_report(&report).unwrap();
+
+        // Assert
+        assert_eq!(&[0x04, 0x02, 0x02, 0x01, 0x02, 0x03, 0x04], &report[..7]);
+        assert_eq!(request, actual_request);
+    }
+
+    #[test]
+    fn test_request_from_report_rejects_unknown_command() {
+        // Assemble
+        let report = [0xFFu8; REPORT_LEN];
+
+        // Act
+        let actual = Request::from_report(&report);
+
+        // Assert
+        assert_eq!(Err(Status::UnknownCommand), actual);
+    }
+
+    #[test]
+    fn test_handle_report_set_then_get_key() {
+        // Assemble
+        let mut editor = TableEditor {
+            layer_count: 3,
+            key_overrides: keymap::KeyOverrides::new(),
+        };
+        let key_ref = KeyRefBytes::from_slice(&[0x05, 0x06]).unwrap();
+        let set_key = Request::SetKey {
+            layer: 1,
+            keymap_index: 7,
+            key_ref: key_ref.clone(),
+        };
+        let get_key = Request::GetKey {
+            layer: 1,
+            keymap_index: 7,
+        };
+
+        // Act
+        let set_response = handle_report(&mut editor, &set_key.to_report());
+        let get_response = handle_report(&mut editor, &get_key.to_report());
+
+        // Assert
+        assert_eq!(Ok(Response::KeySet), Response::from_report(&set_response));
+        assert_eq!(
+            Ok(Response::Key {
+                layer: 1,
+                keymap_index: 7,
+                key_ref: Some(key_ref),
+            }),
+            Response::from_report(&get_response)
+        );
+    }
+
+    #[test]
+    fn test_handle_report_get_key_on_invalid_layer_is_error() {
+        // Assemble
+        let mut editor = TableEditor {
+            layer_count: 1,
+            key_overrides: keymap::KeyOverrides::new(),
+        };
+        let get_key = Request::GetKey {
+            layer: 1,
+            keymap_index: 0,
+        };
+
+        // Act
+        let response = handle_report(&mut editor, &get_key.to_report());
+
+        // Assert
+        assert_eq!(
+            Ok(Response::Error {
+                command: COMMAND_GET_KEY,
+                status: Status::InvalidLayer,
+            }),
+            Response::from_report(&response)
+        );
+    }
+}
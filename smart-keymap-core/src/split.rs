@@ -1,91 +1,436 @@
+//! Protocol for communication between the halves of a split keyboard.
+//!
+//! Each [Message] is sent as a fixed-length frame of [BUFFER_SIZE] bytes:
+//!  the [PROTOCOL_VERSION], the message's sequence number,
+//!  the postcard-encoded [Payload] (zero-padded to [PAYLOAD_LEN] bytes),
+//!  and a CRC-16 of the preceding bytes;
+//!  COBS-encoded, and terminated with a `0x00` byte.
+//!
+//! Frames with an unexpected version or a bad CRC are rejected
+//!  (rather than misparsed), and gaps in the sequence numbers
+//!  can be counted with a [Receiver].
+
 use postcard;
 use serde::{Deserialize, Serialize};
 
 use crate::input;
+use crate::keymap;
+
+/// Version of the split protocol.
+///
+/// Increment this when the frame or payload encoding changes,
+///  so that halves running different firmware reject each other's frames.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Maximum length of the postcard-encoded [Payload] in a frame.
+pub const PAYLOAD_LEN: usize = 14;
+
+/// Length of the frame before COBS encoding (version, sequence number, payload, CRC).
+const RAW_FRAME_LEN: usize = 2 + PAYLOAD_LEN + 2;
 
 /// Size of message buffer for serializing and deserializing messages.
-pub const BUFFER_SIZE: usize = 4;
+///
+/// (COBS adds one byte of overhead, plus the `0x00` terminator).
+pub const BUFFER_SIZE: usize = RAW_FRAME_LEN + 2;
+
+/// Errors from serializing or deserializing a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The encoded payload doesn't fit in a frame.
+    Overflow,
+    /// The bytes are not a (complete) COBS frame.
+    Framing,
+    /// The frame was sent with a different protocol version.
+    Version(u8),
+    /// The frame's CRC does not match its contents.
+    Crc,
+    /// The payload could not be decoded.
+    Payload,
+}
+
+/// Computes the CRC-16/CCITT-FALSE checksum of the bytes.
+pub const fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    let mut i = 0;
+    while i < bytes.len() {
+        crc ^= (bytes[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// State of the primary half, sent to the secondary half.
+///
+/// e.g. so the secondary half can show the active layers,
+///  or the host's Caps Lock LED.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    /// Bitset of active layers (bit `i` = layer `i`).
+    pub active_layers: u32,
+    /// Whether Caps Word is active.
+    pub caps_word_active: bool,
+    /// Whether Key Lock is watching for the next key to lock.
+    pub key_lock_watching: bool,
+    /// Whether Key Lock has a key locked.
+    pub key_locked: bool,
+    /// The number of keys Key Lock has locked.
+    pub key_lock_count: u8,
+    /// The host's keyboard LED state.
+    pub host_leds: keymap::HostLeds,
+}
+
+impl Status {
+    /// Constructs a status with no active layers, features, or LEDs.
+    pub const fn new() -> Self {
+        Self {
+            active_layers: 0,
+            caps_word_active: false,
+            key_lock_watching: false,
+            key_locked: false,
+            key_lock_count: 0,
+            host_leds: keymap::HostLeds::NONE,
+        }
+    }
+}
+
+/// Content of a [Message].
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Payload {
+    /// An input event (including virtual key events).
+    InputEvent(input::Event),
+    /// The status of the primary half.
+    Status(Status),
+}
 
 /// Message sent from one split keyboard half to the other.
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 pub struct Message {
-    /// The input event.
-    pub input_event: input::Event,
+    /// Sequence number of the message.
+    ///
+    /// Incremented (wrapping) for each message sent, so the receiver can detect dropped messages.
+    pub seq: u8,
+    /// The message content.
+    pub payload: Payload,
 }
 
 impl Message {
-    /// Create a new message.
+    /// Create a new message for the input event, with sequence number `0`.
+    ///
+    /// Use a [Transmitter] to number the messages.
     pub fn new(input_event: input::Event) -> Self {
-        Self { input_event }
+        Self {
+            seq: 0,
+            payload: Payload::InputEvent(input_event),
+        }
     }
 
-    /// Serialize the message into a bytes.
-    pub fn serialize(&self) -> [u8; BUFFER_SIZE] {
+    /// The message's input event, if it has one.
+    pub fn input_event(&self) -> Option<input::Event> {
+        match self.payload {
+            Payload::InputEvent(input_event) => Some(input_event),
+            _ => None,
+        }
+    }
+
+    /// The message's status, if it has one.
+    pub fn status(&self) -> Option<Status> {
+        match self.payload {
+            Payload::Status(status) => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Serialize the message into a frame of bytes.
+    ///
+    /// Returns [Error::Overflow] if the encoded payload is longer than [PAYLOAD_LEN].
+    pub fn serialize(&self) -> Result<[u8; BUFFER_SIZE], Error> {
+        let mut raw = [0u8; RAW_FRAME_LEN];
+        raw[0] = PROTOCOL_VERSION;
+        raw[1] = self.seq;
+        postcard::to_slice(&self.payload, &mut raw[2..2 + PAYLOAD_LEN])
+            .map_err(|_| Error::Overflow)?;
+        let crc = crc16(&raw[..2 + PAYLOAD_LEN]);
+        raw[2 + PAYLOAD_LEN..].copy_from_slice(&crc.to_le_bytes());
+
         let mut buf = [0u8; BUFFER_SIZE];
-        let _ = postcard::to_slice_cobs(self, &mut buf);
-        buf
+        postcard::to_slice_cobs(&raw, &mut buf).map_err(|_| Error::Overflow)?;
+        Ok(buf)
     }
 
-    /// Serialize the message into a bytes.
-    pub fn deserialize(bytes: &[u8]) -> postcard::Result<Message> {
+    /// Deserialize a frame of bytes into a message.
+    pub fn deserialize(bytes: &[u8]) -> Result<Message, Error> {
         let mut buf = [0u8; BUFFER_SIZE];
-        buf[..BUFFER_SIZE].copy_from_slice(&bytes[..BUFFER_SIZE]);
-        postcard::from_bytes_cobs(&mut buf)
+        let bytes = bytes.get(..BUFFER_SIZE).ok_or(Error::Framing)?;
+        buf.copy_from_slice(bytes);
+        if buf[BUFFER_SIZE - 1] != 0x00 {
+            return Err(Error::Framing);
+        }
+        let raw: [u8; RAW_FRAME_LEN] =
+            postcard::from_bytes_cobs(&mut buf).map_err(|_| Error::Framing)?;
+
+        let (data, crc_bytes) = raw.split_at(2 + PAYLOAD_LEN);
+        if crc16(data).to_le_bytes() != crc_bytes {
+            return Err(Error::Crc);
+        }
+        match data {
+            [PROTOCOL_VERSION, seq, payload @ ..] => {
+                let payload = postcard::from_bytes(payload).map_err(|_| Error::Payload)?;
+                Ok(Message { seq: *seq, payload })
+            }
+            [version, ..] => Err(Error::Version(*version)),
+            [] => Err(Error::Framing),
+        }
     }
 }
 
 /// Receives bytes from split transport, deserializes into messages.
 /// Adds byte to the buffer and tries to deserialize a message.
-pub fn receive_byte(buf: &mut [u8; BUFFER_SIZE], byte: u8) -> postcard::Result<Message> {
+pub fn receive_byte(buf: &mut [u8; BUFFER_SIZE], byte: u8) -> Result<Message, Error> {
     buf.rotate_left(1);
     buf[BUFFER_SIZE - 1] = byte;
     Message::deserialize(buf)
 }
 
+/// Numbers the messages sent to the other half.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Transmitter {
+    next_seq: u8,
+}
+
+impl Transmitter {
+    /// Constructs a new [Transmitter].
+    pub const fn new() -> Self {
+        Self { next_seq: 0 }
+    }
+
+    /// The next message, with the given payload.
+    pub fn message(&mut self, payload: Payload) -> Message {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        Message { seq, payload }
+    }
+
+    /// Serializes the next message with the given payload.
+    ///
+    /// A message which fails to serialize still uses up its sequence number,
+    ///  so the receiver counts it as dropped.
+    pub fn serialize(&mut self, payload: Payload) -> Result<[u8; BUFFER_SIZE], Error> {
+        self.message(payload).serialize()
+    }
+}
+
+/// Receives messages from the other half,
+///  counting dropped messages and corrupted frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Receiver {
+    buf: [u8; BUFFER_SIZE],
+    last_seq: Option<u8>,
+    dropped_messages: u32,
+    corrupted_frames: u32,
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Receiver {
+    /// Constructs a new [Receiver].
+    pub const fn new() -> Self {
+        Self {
+            buf: [0u8; BUFFER_SIZE],
+            last_seq: None,
+            dropped_messages: 0,
+            corrupted_frames: 0,
+        }
+    }
+
+    /// Adds the byte to the receive buffer,
+    ///  returning a message if the byte completes a valid frame.
+    pub fn receive_byte(&mut self, byte: u8) -> Option<Message> {
+        match receive_byte(&mut self.buf, byte) {
+            Ok(message) => {
+                if let Some(last_seq) = self.last_seq {
+                    let gap = message.seq.wrapping_sub(last_seq).wrapping_sub(1);
+                    self.dropped_messages = self.dropped_messages.saturating_add(gap as u32);
+                }
+                self.last_seq = Some(message.seq);
+                Some(message)
+            }
+            Err(Error::Crc | Error::Version(_) | Error::Payload | Error::Overflow) => {
+                self.corrupted_frames = self.corrupted_frames.saturating_add(1);
+                None
+            }
+            Err(Error::Framing) => {
+                if byte == 0x00 {
+                    // A frame terminator which didn't complete a frame.
+                    self.corrupted_frames = self.corrupted_frames.saturating_add(1);
+                }
+                None
+            }
+        }
+    }
+
+    /// Number of messages missed, according to gaps in the sequence numbers.
+    pub fn dropped_messages(&self) -> u32 {
+        self.dropped_messages
+    }
+
+    /// Number of frames rejected for a bad CRC, version, or payload.
+    pub fn corrupted_frames(&self) -> u32 {
+        self.corrupted_frames
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use super::*;
 
+    use crate::key;
+
+    fn largest_payloads() -> [Payload; 2] {
+        [
+            Payload::InputEvent(input::Event::VirtualKeyPress {
+                key_output: key::KeyOutput::from_usage_with_modifiers(
+                    key::KeyUsage::Mouse(key::MouseOutput {
+                        pressed_buttons: 0xFF,
+                        x: -128,
+                        y: -128,
+                        vertical_scroll: -128,
+                        horizontal_scroll: -128,
+                    }),
+                    key::KeyboardModifiers::from_byte(0xFF),
+                ),
+            }),
+            Payload::Status(Status {
+                active_layers: u32::MAX,
+                caps_word_active: true,
+                key_lock_watching: true,
+                key_locked: true,
+                key_lock_count: u8::MAX,
+                host_leds: keymap::HostLeds::from_byte(0xFF),
+            }),
+        ]
+    }
+
     #[test]
-    fn test_ser_press() {
+    fn test_payloads_fit_in_frame() {
         // Assemble
-        let input_event = input::Event::Press { keymap_index: 4 };
-        let msg = Message { input_event };
+        let payloads = largest_payloads();
 
         // Act
-        let actual_ser = msg.serialize();
+        let mut buf = [0u8; 32];
+        let lengths = payloads.map(|p| postcard::to_slice(&p, &mut buf).unwrap().len());
 
         // Assert
-        assert_eq!(&actual_ser, &[0x01, 0x02, 0x04, 0x00]);
+        for len in lengths {
+            assert!(len <= PAYLOAD_LEN, "payload length {} too long", len);
+        }
     }
 
     #[test]
-    fn test_ser_release() {
+    fn test_crc16_check_value() {
         // Assemble
-        let input_event = input::Event::Release { keymap_index: 4 };
-        let msg = Message { input_event };
+        let bytes = b"123456789";
 
         // Act
-        let actual_ser = msg.serialize();
+        let actual_crc = crc16(bytes);
 
         // Assert
-        assert_eq!(&actual_ser, &[0x03, 0x01, 0x04, 0x00]);
+        assert_eq!(0x29B1, actual_crc);
+    }
+
+    #[test]
+    fn test_ser_press() {
+        // Assemble
+        let input_event = input::Event::Press { keymap_index: 4 };
+        let msg = Message::new(input_event);
+
+        // Act
+        let actual_ser = msg.serialize().unwrap();
+
+        // Assert
+        assert_eq!(BUFFER_SIZE, actual_ser.len());
+        assert_eq!(0x00, actual_ser[BUFFER_SIZE - 1]);
+        assert!(actual_ser[..BUFFER_SIZE - 1].iter().all(|&b| b != 0x00));
+    }
+
+    #[test]
+    fn test_ser_deser_roundtrip() {
+        // Assemble
+        let mut transmitter = Transmitter::new();
+        let payloads = [
+            Payload::InputEvent(input::Event::Press { keymap_index: 4 }),
+            Payload::InputEvent(input::Event::Release { keymap_index: 300 }),
+            Payload::InputEvent(input::Event::VirtualKeyRelease {
+                key_output: key::KeyOutput::from_key_code(0x04),
+            }),
+        ];
+        let [largest_event, largest_status] = largest_payloads();
+
+        // Act + Assert
+        for payload in payloads.into_iter().chain([largest_event, largest_status]) {
+            let msg = transmitter.message(payload);
+            let actual_msg = Message::deserialize(&msg.serialize().unwrap()).unwrap();
+            assert_eq!(msg, actual_msg);
+        }
     }
 
     #[test]
     fn test_deser_press() {
         // Assemble
-        let ser = &[0x01, 0x02, 0x04, 0x00];
+        let input_event = input::Event::Press { keymap_index: 4 };
+        let ser = Message::new(input_event).serialize().unwrap();
 
         // Act
-        let actual_msg = Message::deserialize(ser).unwrap();
+        let actual_msg = Message::deserialize(&ser).unwrap();
 
         // Assert
+        assert_eq!(Some(input_event), actual_msg.input_event());
+    }
+
+    #[test]
+    fn test_deser_rejects_corrupted_byte() {
+        // Assemble
         let input_event = input::Event::Press { keymap_index: 4 };
-        let expected_msg = Message { input_event };
+        let ser = Message::new(input_event).serialize().unwrap();
+
+        // Act + Assert
+        for i in 0..BUFFER_SIZE - 1 {
+            let mut corrupted = ser;
+            // Flip a bit (without introducing a 0x00 frame delimiter).
+            corrupted[i] ^= if corrupted[i] == 0x01 { 0x02 } else { 0x01 };
+            let actual = Message::deserialize(&corrupted);
+            assert!(actual.is_err(), "corrupted byte {} was accepted", i);
+        }
+    }
+
+    #[test]
+    fn test_deser_rejects_other_version() {
+        // Assemble
+        let mut raw = [0u8; RAW_FRAME_LEN];
+        raw[0] = PROTOCOL_VERSION + 1;
+        let crc = crc16(&raw[..2 + PAYLOAD_LEN]);
+        raw[2 + PAYLOAD_LEN..].copy_from_slice(&crc.to_le_bytes());
+        let mut ser = [0u8; BUFFER_SIZE];
+        postcard::to_slice_cobs(&raw, &mut ser).unwrap();
+
+        // Act
+        let actual = Message::deserialize(&ser);
 
-        assert_eq!(&actual_msg, &expected_msg);
+        // Assert
+        assert_eq!(Err(Error::Version(PROTOCOL_VERSION + 1)), actual);
     }
 
     #[test]
@@ -100,7 +445,7 @@ mod tests {
         // Act
         let mut actual_messages: Vec<Message> = Vec::new();
         for msg in input_messages {
-            let ser = msg.serialize();
+            let ser = msg.serialize().unwrap();
             for &byte in ser.iter() {
                 if let Ok(msg) = receive_byte(&mut buf, byte) {
                     actual_messages.push(msg);
@@ -111,4 +456,38 @@ mod tests {
         // Assert
         assert_eq!(input_messages, &actual_messages.as_slice());
     }
+
+    #[test]
+    fn test_receiver_counts_dropped_and_corrupted() {
+        // Assemble
+        let mut transmitter = Transmitter::new();
+        let press = Payload::InputEvent(input::Event::Press { keymap_index: 1 });
+        let status = Status {
+            active_layers: 0b10,
+            host_leds: keymap::HostLeds::from_byte(keymap::HostLeds::CAPS_LOCK_U8),
+            ..Status::new()
+        };
+        let first = transmitter.serialize(press).unwrap();
+        let _dropped = transmitter.serialize(press).unwrap();
+        let mut corrupted = transmitter.serialize(press).unwrap();
+        // Flip a bit (without introducing a 0x00 frame delimiter).
+        corrupted[3] ^= if corrupted[3] == 0x01 { 0x02 } else { 0x01 };
+        let last = transmitter.serialize(Payload::Status(status)).unwrap();
+        let mut receiver = Receiver::new();
+
+        // Act
+        let actual_messages: Vec<Message> = [first, corrupted, last]
+            .iter()
+            .flatten()
+            .filter_map(|&b| receiver.receive_byte(b))
+            .collect();
+
+        // Assert
+        let actual_statuses: Vec<Status> =
+            actual_messages.iter().filter_map(|m| m.status()).collect();
+        assert_eq!(2, actual_messages.len());
+        assert_eq!(vec![status], actual_statuses);
+        assert_eq!(2, receiver.dropped_messages());
+        assert_eq!(1, receiver.corrupted_frames());
+    }
 }
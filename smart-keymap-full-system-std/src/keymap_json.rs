@@ -0,0 +1,127 @@
+//! Constructing a full-profile [Keymap] from a keymap's JSON serialization.
+//!
+//! The JSON is the `json_deserializable_keymap` field of `keymap-ncl-to-json.ncl`
+//!  (e.g. as evaluated by `smart_keymap_nickel_helper::nickel_json_value_for_keymap`).
+
+use serde::Deserialize;
+
+use smart_keymap::key;
+use smart_keymap::keymap;
+
+use crate::init::CHORDED_MAX_CHORDS;
+use crate::init::CHORDED_MAX_CHORD_SIZE;
+use crate::init::CHORDED_MAX_OVERLAPPING_CHORD_SIZE;
+use crate::init::LAYERED_LAYER_COUNT;
+use crate::init::SEQUENCE_MAX_OVERLAPPING;
+use crate::init::TAP_DANCE_MAX_DEFINITIONS as TAP_DANCE_MAX_DEFS;
+use crate::key_system::{Config, Context, Event, KeyState, PendingKeyState, Ref, System};
+
+const CHORDED_MAX_PRESSED_INDICES: usize = CHORDED_MAX_CHORD_SIZE * 2;
+
+/// Full-profile keymap, with key refs and key data stored in [Vec]s.
+pub type Keymap = keymap::Keymap<Vec<Ref>, Ref, Context, Event, PendingKeyState, KeyState, System>;
+
+/// Each key family's key data.
+#[derive(Deserialize, Default)]
+pub struct KeyVecs {
+    #[serde(default)]
+    automation: Vec<key::automation::Key>,
+    #[serde(default)]
+    callback: Vec<key::callback::Key>,
+    #[serde(default)]
+    chorded: Vec<
+        key::chorded::Key<
+            Ref,
+            { CHORDED_MAX_CHORDS },
+            { CHORDED_MAX_CHORD_SIZE },
+            { CHORDED_MAX_OVERLAPPING_CHORD_SIZE },
+            { CHORDED_MAX_PRESSED_INDICES },
+        >,
+    >,
+    #[serde(default)]
+    chorded_auxiliary: Vec<
+        key::chorded::AuxiliaryKey<
+            Ref,
+            { CHORDED_MAX_CHORDS },
+            { CHORDED_MAX_CHORD_SIZE },
+            { CHORDED_MAX_PRESSED_INDICES },
+        >,
+    >,
+    #[serde(default)]
+    consumer: Vec<key::consumer::Key>,
+    #[serde(default)]
+    history: Vec<key::history::AdaptiveKey>,
+    #[serde(default)]
+    key_repeat: Vec<key::key_repeat::Key<Ref>>,
+    #[serde(default)]
+    keyboard: Vec<key::keyboard::Key>,
+    #[serde(default)]
+    layered: Vec<key::layered::LayeredKey<Ref, LAYERED_LAYER_COUNT>>,
+    #[serde(default)]
+    layer_modifiers: Vec<key::layered::ModifierKey>,
+    #[serde(default)]
+    mod_conditioned: Vec<key::mod_conditioned::Key<Ref>>,
+    #[serde(default)]
+    mouse: Vec<key::mouse::Key>,
+    #[serde(default)]
+    sequence: Vec<key::sequence::Key<Ref, SEQUENCE_MAX_OVERLAPPING>>,
+    #[serde(default)]
+    sequence_auxiliary: Vec<key::sequence::AuxiliaryKey<Ref>>,
+    #[serde(default)]
+    sticky: Vec<key::sticky::Key>,
+    #[serde(default)]
+    tap_dance: Vec<key::tap_dance::Key<Ref, TAP_DANCE_MAX_DEFS>>,
+    #[serde(default)]
+    tap_hold: Vec<key::tap_hold::Key<Ref>>,
+    #[serde(default)]
+    tri_state: Vec<key::tri_state::Key>,
+}
+
+impl KeyVecs {
+    /// Constructs the composite [System] from the key data.
+    pub fn into_system(self) -> System {
+        System::new(
+            key::automation::System::new(self.automation),
+            key::callback::System::new(self.callback),
+            key::chorded::System::new(self.chorded, self.chorded_auxiliary),
+            key::consumer::System::new(self.consumer),
+            key::history::System::new(self.history),
+            key::key_repeat::System::new(self.key_repeat),
+            key::keyboard::System::new(self.keyboard),
+            key::layered::System::new(self.layer_modifiers, self.layered),
+            key::mod_conditioned::System::new(self.mod_conditioned),
+            key::mouse::System::new(self.mouse),
+            key::sequence::System::new(self.sequence, self.sequence_auxiliary),
+            key::sticky::System::new(self.sticky),
+            key::tap_dance::System::new(self.tap_dance),
+            key::tap_hold::System::new(self.tap_hold),
+            key::tri_state::System::new(self.tri_state),
+        )
+    }
+}
+
+/// A keymap's JSON serialization.
+#[derive(Deserialize)]
+pub struct KeymapJson {
+    /// The key system config.
+    pub config: Config,
+    /// The keymap's key refs, one per keymap index.
+    pub key_refs: Vec<Ref>,
+    /// The keymap's key data.
+    #[serde(default)]
+    pub key_data: KeyVecs,
+}
+
+impl KeymapJson {
+    /// Constructs the keymap.
+    pub fn into_keymap(self) -> Keymap {
+        let context = Context::from_config(self.config);
+        let system = self.key_data.into_system();
+        keymap::Keymap::new(self.key_refs, context, system)
+    }
+}
+
+/// Deserializes a keymap from its JSON serialization.
+pub fn keymap_from_json(json: &str) -> serde_json::Result<Keymap> {
+    serde_json::from_str::<KeymapJson>(json).map(KeymapJson::into_keymap)
+}
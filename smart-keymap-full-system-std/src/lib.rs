@@ -18,9 +18,11 @@
 pub mod init {
     pub use smart_keymap::init::{
         AUTOMATION_INSTRUCTION_COUNT, CHORDED_MAX_CHORDS, CHORDED_MAX_CHORD_SIZE,
-        CHORDED_MAX_OVERLAPPING_CHORD_SIZE, CONDITIONAL_LAYER_COUNT, HISTORY_ALT_REPEAT_RULE_COUNT,
-        LAYERED_LAYER_COUNT, SEQUENCE_MAX_OVERLAPPING, SEQUENCE_MAX_SEQUENCES,
-        SEQUENCE_MAX_SEQUENCE_LEN, TAP_DANCE_MAX_DEFINITIONS,
+        CHORDED_MAX_OVERLAPPING_CHORD_SIZE, CONDITIONAL_LAYER_COUNT, DYNAMIC_MACRO_BUFFER_SIZE,
+        DYNAMIC_MACRO_SLOT_COUNT, HISTORY_ALT_REPEAT_RULE_COUNT, LAYERED_LAYER_COUNT,
+        SEQUENCE_MAX_OVERLAPPING, SEQUENCE_MAX_SEQUENCES, SEQUENCE_MAX_SEQUENCE_LEN,
+        STENO_DICTIONARY_ENTRY_COUNT, STENO_INSTRUCTION_COUNT, TAP_DANCE_MAX_DEFINITIONS,
+        UNICODE_TEXT_COUNT,
     };
 
     include!(concat!(env!("OUT_DIR"), "/composite_full_vec.rs"));
@@ -28,3 +30,5 @@ pub mod init {
 
 /// Full-profile, Vec-backed composite key system (re-export from [`init`]).
 pub use init::key_system;
+
+pub mod keymap_json;
@@ -35,6 +35,7 @@ fn tap_hold_interrupt_keymap(
             smart_keymap::key::chorded::System::new(Vec::new(), Vec::new()),
             smart_keymap::key::consumer::System::new(Vec::new()),
             smart_keymap::key::history::System::new(Vec::new()),
+            smart_keymap::key::key_repeat::System::new(Vec::new()),
             smart_keymap::key::keyboard::System::new(vec![smart_keymap::key::keyboard::Key {
                 key_code: 0x05,
                 modifiers: smart_keymap::key::KeyboardModifiers::new(),
@@ -76,6 +77,7 @@ macro_rules! simple_keyboard_keymap {
                 smart_keymap::key::chorded::System::new(Vec::new(), Vec::new()),
                 smart_keymap::key::consumer::System::new(Vec::new()),
                 smart_keymap::key::history::System::new(Vec::new()),
+                smart_keymap::key::key_repeat::System::new(Vec::new()),
                 smart_keymap::key::keyboard::System::new(Vec::new()),
                 smart_keymap::key::layered::System::new(Vec::new(), Vec::new()),
                 smart_keymap::key::mod_conditioned::System::new(Vec::new()),
@@ -592,6 +594,7 @@ fn test_keymap_many_input_events_without_tick_or_report() {
                 smart_keymap::key::chorded::System::new(Vec::new(), Vec::new()),
                 smart_keymap::key::consumer::System::new(Vec::new()),
                 smart_keymap::key::history::System::new(Vec::new()),
+                smart_keymap::key::key_repeat::System::new(Vec::new()),
                 smart_keymap::key::keyboard::System::new(Vec::new()),
                 smart_keymap::key::layered::System::new(Vec::new(), Vec::new()),
                 smart_keymap::key::mod_conditioned::System::new(Vec::new()),
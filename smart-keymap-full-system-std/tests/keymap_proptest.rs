@@ -0,0 +1,316 @@
+//! Property-based tests of the keymap state machine,
+//!  over the full-profile composite key_system.
+//!
+//! Random sequences of key presses, releases and idle time are run against
+//!  a keymap with keys from each of the key families, checking invariants which
+//!  should hold for any input.
+
+use proptest::prelude::*;
+
+use smart_keymap::input;
+use smart_keymap::key::tap_hold::InterruptResponse;
+use smart_keymap::keymap::MAX_PRESSED_KEYS;
+
+use smart_keymap_full_system_std::key_system;
+
+const KEY_COUNT: usize = 17;
+
+/// Keys from this index on may keep outputs after they're released:
+///  caps word, a tri-state session, a sticky modifier, and a locked key.
+const LATCHING_KEYS_START: usize = 13;
+
+/// Bound on the steps for the keymap to settle,
+///  so a rescheduling loop fails the test instead of hanging it.
+const MAX_SETTLE_STEPS: usize = 10_000;
+
+type Keymap = smart_keymap::keymap::Keymap<
+    [key_system::Ref; KEY_COUNT],
+    key_system::Ref,
+    key_system::Context,
+    key_system::Event,
+    key_system::PendingKeyState,
+    key_system::KeyState,
+    key_system::System,
+>;
+
+const fn key_code(key_code: u8) -> key_system::Ref {
+    key_system::Ref::Keyboard(smart_keymap::key::keyboard::Ref::KeyCode(key_code))
+}
+
+/// Keys:
+///
+/// 0. tap-hold: tap `A`, hold layer 1.
+/// 1. layered: `B`, or `C` on layer 1.
+/// 2. tap-hold: tap `D`, hold `LeftCtrl`.
+/// 3. `LeftShift`.
+/// 4. chorded: `F`, or `E` when chorded with 5.
+/// 5. chorded (auxiliary): `G`.
+/// 6. tap-dance: `H`, `I`.
+/// 7. sequence start.
+/// 8. sequence: `K`, or `J` when in sequence with 9.
+/// 9. sequence (auxiliary): `L`.
+/// 10. key repeat: history repeat.
+/// 11. auto-shift: `M`.
+/// 12. automation: tap `N`.
+/// 13. caps word.
+/// 14. tri-state: hold `LeftAlt`, tap `Tab`.
+/// 15. sticky: `LeftShift`.
+/// 16. key lock.
+fn keymap(interrupt_response: InterruptResponse) -> Keymap {
+    use smart_keymap::key::{
+        auto_shift, automation, caps_word, chorded, history, key_lock, key_repeat, keyboard,
+        layered, sequence, sticky, tap_dance, tap_hold, tri_state,
+    };
+    use smart_keymap::key::{KeyOutput, KeyboardModifiers};
+    use smart_keymap::slice::Slice;
+
+    let mut config = key_system::Config::new();
+    config.tap_hold.default_profile.interrupt_response = interrupt_response;
+    config.chorded.chords = Slice::from_slice(&[chorded::ChordIndices::from_slice(&[4, 5])]);
+    config.sequence.sequences =
+        Slice::from_slice(&[sequence::SequenceIndices::from_slice(&[8, 9])]);
+    config.automation.instructions =
+        automation::instructions([automation::Instruction::Tap(KeyOutput::from_key_code(0x11))]);
+
+    smart_keymap::keymap::Keymap::new(
+        [
+            key_system::Ref::TapHold(tap_hold::Ref(0)),
+            key_system::Ref::Layered(layered::Ref::Layered(0)),
+            key_system::Ref::TapHold(tap_hold::Ref(1)),
+            key_code(0xE1),
+            key_system::Ref::Chorded(chorded::Ref::Chorded(0)),
+            key_system::Ref::Chorded(chorded::Ref::Auxiliary(0)),
+            key_system::Ref::TapDance(tap_dance::Ref(0)),
+            key_system::Ref::Sequence(sequence::Ref::SequenceStart),
+            key_system::Ref::Sequence(sequence::Ref::Sequence(0)),
+            key_system::Ref::Sequence(sequence::Ref::Auxiliary(0)),
+            key_system::Ref::KeyRepeat(key_repeat::Ref(0)),
+            key_system::Ref::AutoShift(auto_shift::Ref(auto_shift::Key::new(0x10))),
+            key_system::Ref::Automation(automation::Ref(0)),
+            key_system::Ref::CapsWord(caps_word::Ref(caps_word::Key::ToggleCapsWord)),
+            key_system::Ref::TriState(tri_state::Ref::Key(0)),
+            key_system::Ref::Sticky(sticky::Ref(0)),
+            key_system::Ref::KeyLock(key_lock::Ref(key_lock::Key::KeyLock)),
+        ],
+        key_system::Context::from_config(config),
+        key_system::System::new(
+            automation::System::new(vec![automation::Key {
+                automation_instructions: automation::KeyInstructions {
+                    on_press: automation::Execution {
+                        start: 0,
+                        length: 1,
+                    },
+                    while_pressed: automation::Execution::EMPTY,
+                    on_release: automation::Execution::EMPTY,
+                },
+            }]),
+            smart_keymap::key::callback::System::new(Vec::new()),
+            chorded::System::new(
+                vec![chorded::Key::new(&[(0, key_code(0x08))], key_code(0x09))],
+                vec![chorded::AuxiliaryKey::new(key_code(0x0A))],
+            ),
+            smart_keymap::key::consumer::System::new(Vec::new()),
+            history::System::new(Vec::new()),
+            key_repeat::System::new(vec![key_repeat::Key::new(key_system::Ref::History(
+                history::Ref(history::Key::Repeat),
+            ))]),
+            keyboard::System::new(Vec::new()),
+            layered::System::new(
+                vec![layered::ModifierKey::hold(1)],
+                vec![layered::LayeredKey::new(
+                    key_code(0x05),
+                    [Some(key_code(0x06))],
+                )],
+            ),
+            smart_keymap::key::mod_conditioned::System::new(Vec::new()),
+            smart_keymap::key::mouse::System::new(Vec::new()),
+            sequence::System::new(
+                vec![sequence::Key::new(&[(0, key_code(0x0D))], key_code(0x0E))],
+                vec![sequence::AuxiliaryKey::new(key_code(0x0F))],
+            ),
+            sticky::System::new(vec![sticky::Key::new(KeyboardModifiers::LEFT_SHIFT)]),
+            tap_dance::System::new(vec![tap_dance::Key::from_definitions(&[
+                key_code(0x0B),
+                key_code(0x0C),
+            ])]),
+            tap_hold::System::new(vec![
+                tap_hold::Key {
+                    tap: key_code(0x04),
+                    hold: key_system::Ref::Layered(layered::Ref::Modifier(0)),
+                    profile: 0,
+                },
+                tap_hold::Key {
+                    tap: key_code(0x07),
+                    hold: key_code(0xE0),
+                    profile: 0,
+                },
+            ]),
+            tri_state::System::new(vec![tri_state::Key::new(
+                KeyOutput::from_key_code(0xE2),
+                KeyOutput::from_key_code(0x2B),
+            )]),
+        ),
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// After some time, press the key if it's released, or release it if it's pressed.
+    Toggle { keymap_index: u16, after_ms: u32 },
+    /// Tick the keymap, without input.
+    Idle { ms: u32 },
+}
+
+fn interrupt_response() -> impl Strategy<Value = InterruptResponse> {
+    prop_oneof![
+        Just(InterruptResponse::Ignore),
+        Just(InterruptResponse::HoldOnKeyPress),
+        Just(InterruptResponse::HoldOnKeyTap),
+    ]
+}
+
+/// Steps toggling keys with keymap index less than `key_count`.
+fn step(key_count: usize) -> impl Strategy<Value = Step> {
+    prop_oneof![
+        4 => (0..key_count as u16, 0u32..=150)
+            .prop_map(|(keymap_index, after_ms)| Step::Toggle { keymap_index, after_ms }),
+        1 => (1u32..=500).prop_map(|ms| Step::Idle { ms }),
+    ]
+}
+
+fn steps(key_count: usize) -> impl Strategy<Value = Vec<Step>> {
+    prop::collection::vec(step(key_count), 0..48)
+}
+
+/// The pending key's session log must have room for the next input;
+///  otherwise an input would be silently dropped from its replay.
+fn check_pending_queue(keymap: &Keymap) -> Result<(), TestCaseError> {
+    if let Some(len) = keymap.test_pending_queued_events_len() {
+        prop_assert!(
+            len < MAX_PRESSED_KEYS,
+            "pending session log is full ({} events)",
+            len
+        );
+    }
+    Ok(())
+}
+
+fn tick_for(keymap: &mut Keymap, ms: u32) -> Result<(), TestCaseError> {
+    for _ in 0..ms {
+        keymap.tick();
+        check_pending_queue(keymap)?;
+    }
+    Ok(())
+}
+
+/// Runs the steps against the keymap, returning which keys are still pressed.
+fn run_steps(keymap: &mut Keymap, steps: &[Step]) -> Result<[bool; KEY_COUNT], TestCaseError> {
+    let mut pressed = [false; KEY_COUNT];
+
+    for &step in steps {
+        match step {
+            Step::Toggle {
+                keymap_index,
+                after_ms,
+            } => {
+                tick_for(keymap, after_ms)?;
+
+                let is_pressed = &mut pressed[keymap_index as usize];
+                let ev = if *is_pressed {
+                    input::Event::Release { keymap_index }
+                } else {
+                    input::Event::Press { keymap_index }
+                };
+                *is_pressed = !*is_pressed;
+
+                keymap.handle_input(ev);
+                check_pending_queue(keymap)?;
+            }
+            Step::Idle { ms } => tick_for(keymap, ms)?,
+        }
+    }
+
+    Ok(pressed)
+}
+
+/// Releases the keys which are still pressed.
+fn release_all(keymap: &mut Keymap, pressed: [bool; KEY_COUNT]) {
+    for (keymap_index, _) in pressed.iter().enumerate().filter(|(_, &p)| p) {
+        keymap.handle_input(input::Event::Release {
+            keymap_index: keymap_index as u16,
+        });
+    }
+}
+
+/// Ticks the keymap until it has no scheduled events or queued inputs.
+fn settle(keymap: &mut Keymap) -> Result<(), TestCaseError> {
+    for _ in 0..MAX_SETTLE_STEPS {
+        if !keymap.has_scheduled_events() {
+            return Ok(());
+        }
+        if keymap.tick_to_next_scheduled_event().is_none() {
+            keymap.tick();
+        }
+        check_pending_queue(keymap)?;
+    }
+    Err(TestCaseError::fail(format!(
+        "keymap did not settle after {} steps",
+        MAX_SETTLE_STEPS
+    )))
+}
+
+proptest! {
+    #[test]
+    fn releasing_all_keys_releases_all_outputs(
+        interrupt_response in interrupt_response(),
+        steps in steps(LATCHING_KEYS_START),
+    ) {
+        // Assemble
+        let mut keymap = keymap(interrupt_response);
+        let pressed = run_steps(&mut keymap, &steps)?;
+
+        // Act
+        release_all(&mut keymap, pressed);
+        settle(&mut keymap)?;
+
+        // Assert
+        prop_assert!(!keymap.test_is_pending());
+        prop_assert_eq!(0, keymap.test_input_queue_len());
+        prop_assert!(
+            keymap.pressed_keys().is_empty(),
+            "pressed keys: {:?}",
+            keymap.pressed_keys()
+        );
+        prop_assert_eq!(
+            [0; 8],
+            keymap.report_output().as_hid_boot_keyboard_report()
+        );
+    }
+
+    #[test]
+    fn tick_to_next_scheduled_event_terminates(
+        interrupt_response in interrupt_response(),
+        steps in steps(KEY_COUNT),
+    ) {
+        // Assemble
+        //  (A held key repeat key schedules repeats for as long as it's held).
+        let mut keymap = keymap(interrupt_response);
+        let pressed = run_steps(&mut keymap, &steps)?;
+        release_all(&mut keymap, pressed);
+
+        // Act
+        let mut ticks = 0;
+        while keymap.tick_to_next_scheduled_event().is_some() {
+            ticks += 1;
+            prop_assert!(
+                ticks < MAX_SETTLE_STEPS,
+                "still scheduling events after {} calls",
+                ticks
+            );
+        }
+
+        // Assert
+        prop_assert_eq!(None, keymap.tick_to_next_scheduled_event());
+        check_pending_queue(&keymap)?;
+    }
+}
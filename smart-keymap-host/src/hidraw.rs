@@ -0,0 +1,180 @@
+//! [Transport] for Linux `hidraw` devices.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::raw_hid::{Report, REPORT_LEN};
+use crate::Transport;
+
+/// How long to wait for the keyboard's response, by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A `/dev/hidrawN` device.
+#[derive(Debug)]
+pub struct HidrawDevice {
+    file: File,
+    timeout: Duration,
+}
+
+impl HidrawDevice {
+    /// Opens the hidraw device at the given path (e.g. `/dev/hidraw3`).
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Self {
+            file,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Sets how long [Transport::receive_report] waits for the keyboard's response.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Waits until the device has a report to read,
+    ///  failing with [io::ErrorKind::TimedOut] after the timeout.
+    fn wait_readable(&self) -> io::Result<()> {
+        let mut pollfd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = self.timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+        loop {
+            match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "timed out waiting for the keyboard's response",
+                    ))
+                }
+                n if n > 0 => return Ok(()),
+                _ => {
+                    let e = io::Error::last_os_error();
+                    if e.kind() != io::ErrorKind::Interrupted {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Transport for HidrawDevice {
+    fn send_report(&mut self, report: &Report) -> io::Result<()> {
+        // The first byte written is the report ID;
+        //  zero for devices which don't use numbered reports.
+        let mut buf = [0u8; 1 + REPORT_LEN];
+        buf[1..].copy_from_slice(report);
+        self.file.write_all(&buf)
+    }
+
+    fn receive_report(&mut self) -> io::Result<Report> {
+        self.wait_readable()?;
+        let mut report = [0u8; REPORT_LEN];
+        self.file.read_exact(&mut report)?;
+        Ok(report)
+    }
+}
+
+/// A hidraw device, as listed under `/sys/class/hidraw`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Path of the device node (e.g. `/dev/hidraw3`).
+    pub path: PathBuf,
+    /// USB vendor ID.
+    pub vendor_id: u16,
+    /// USB product ID.
+    pub product_id: u16,
+    /// The device's name (`HID_NAME`).
+    pub name: String,
+}
+
+/// Parses the `HID_ID` and `HID_NAME` of a hidraw device's `uevent` file.
+fn parse_uevent(uevent: &str) -> Option<(u16, u16, String)> {
+    let mut ids = None;
+    let mut name = String::new();
+    for line in uevent.lines() {
+        if let Some(hid_id) = line.strip_prefix("HID_ID=") {
+            // HID_ID=<bus>:<vendor>:<product>, as zero-padded hex.
+            let mut parts = hid_id.split(':').skip(1);
+            let vendor_id = u32::from_str_radix(parts.next()?, 16).ok()?;
+            let product_id = u32::from_str_radix(parts.next()?, 16).ok()?;
+            ids = Some((vendor_id as u16, product_id as u16));
+        } else if let Some(hid_name) = line.strip_prefix("HID_NAME=") {
+            name = hid_name.to_string();
+        }
+    }
+    ids.map(|(vendor_id, product_id)| (vendor_id, product_id, name))
+}
+
+/// Lists the hidraw devices on this host.
+pub fn devices() -> io::Result<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    for entry in fs::read_dir("/sys/class/hidraw")? {
+        let entry = entry?;
+        let uevent = match fs::read_to_string(entry.path().join("device/uevent")) {
+            Ok(uevent) => uevent,
+            Err(_) => continue,
+        };
+        if let Some((vendor_id, product_id, name)) = parse_uevent(&uevent) {
+            devices.push(DeviceInfo {
+                path: Path::new("/dev").join(entry.file_name()),
+                vendor_id,
+                product_id,
+                name,
+            });
+        }
+    }
+    devices.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(devices)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uevent() {
+        // Assemble
+        let uevent =
+            "DRIVER=hid-generic\nHID_ID=0003:0000CAFE:00000005\nHID_NAME=rgoulter smart keyboard\n";
+
+        // Act
+        let actual = parse_uevent(uevent);
+
+        // Assert
+        assert_eq!(
+            Some((0xCAFE, 0x0005, "rgoulter smart keyboard".to_string())),
+            actual
+        );
+    }
+
+    #[test]
+    fn test_receive_report_times_out_without_response() {
+        use std::os::fd::FromRawFd;
+
+        // Assemble -- the read end of a pipe which is never written to
+        let mut fds = [0 as libc::c_int; 2];
+        assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+        let _write_end = unsafe { File::from_raw_fd(fds[1]) };
+        let mut device = HidrawDevice {
+            file: unsafe { File::from_raw_fd(fds[0]) },
+            timeout: DEFAULT_TIMEOUT,
+        }
+        .with_timeout(Duration::from_millis(10));
+
+        // Act
+        let actual = device.receive_report();
+
+        // Assert
+        assert_eq!(
+            io::ErrorKind::TimedOut,
+            actual.map(|_| ()).unwrap_err().kind()
+        );
+    }
+}
@@ -0,0 +1,282 @@
+#![warn(missing_docs)]
+#![warn(clippy::unwrap_used)]
+#![warn(clippy::expect_used)]
+
+//! Host-side library for editing a smart-keymap keyboard's keymap at runtime.
+//!
+//! Talks the [raw_hid] protocol to the keyboard through a [Transport]:
+//!  [hidraw::HidrawDevice] for keyboards connected to a Linux host,
+//!  or [MemoryTransport] which handles requests with an in-process [raw_hid::KeymapEditor].
+
+use std::collections::VecDeque;
+use std::io;
+
+pub use smart_keymap_core::raw_hid;
+
+use raw_hid::{KeyRefBytes, Report, Request, Response, Status};
+
+#[cfg(target_os = "linux")]
+pub mod hidraw;
+
+/// Sends and receives raw HID reports to and from a keyboard.
+pub trait Transport {
+    /// Sends a report to the keyboard.
+    fn send_report(&mut self, report: &Report) -> io::Result<()>;
+
+    /// Receives a report from the keyboard.
+    fn receive_report(&mut self) -> io::Result<Report>;
+}
+
+/// Errors from a [Client] request.
+#[derive(Debug)]
+pub enum Error {
+    /// The transport failed.
+    Io(io::Error),
+    /// The keyboard's response couldn't be decoded.
+    Malformed,
+    /// The keyboard rejected the request.
+    Device(Status),
+    /// The keyboard responded with a response for a different request.
+    UnexpectedResponse(Response),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "transport error: {}", e),
+            Error::Malformed => write!(f, "malformed response"),
+            Error::Device(status) => write!(f, "request failed: {:?}", status),
+            Error::UnexpectedResponse(response) => {
+                write!(f, "unexpected response: {:?}", response)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Client for the [raw_hid] protocol.
+#[derive(Debug)]
+pub struct Client<T: Transport> {
+    transport: T,
+}
+
+impl<T: Transport> Client<T> {
+    /// Constructs a client using the given transport.
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Consumes the client, returning its transport.
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Sends the request, and receives the keyboard's response.
+    pub fn request(&mut self, request: &Request) -> Result<Response, Error> {
+        self.transport.send_report(&request.to_report())?;
+        let report = self.transport.receive_report()?;
+        match Response::from_report(&report) {
+            Ok(Response::Error { status, .. }) => Err(Error::Device(status)),
+            Ok(response) => Ok(response),
+            Err(_) => Err(Error::Malformed),
+        }
+    }
+
+    /// The keyboard's protocol version.
+    pub fn protocol_version(&mut self) -> Result<u8, Error> {
+        match self.request(&Request::GetProtocolVersion)? {
+            Response::ProtocolVersion(version) => Ok(version),
+            response => Err(Error::UnexpectedResponse(response)),
+        }
+    }
+
+    /// The number of layers keys can be set on (including the base layer).
+    pub fn layer_count(&mut self) -> Result<u8, Error> {
+        match self.request(&Request::GetLayerCount)? {
+            Response::LayerCount(count) => Ok(count),
+            response => Err(Error::UnexpectedResponse(response)),
+        }
+    }
+
+    /// The (postcard-encoded) key ref set for the key on the layer;
+    ///  `None` if the keyboard uses its compiled-in key.
+    pub fn get_key(&mut self, keymap_index: u16, layer: u8) -> Result<Option<KeyRefBytes>, Error> {
+        match self.request(&Request::GetKey {
+            layer,
+            keymap_index,
+        })? {
+            Response::Key { key_ref, .. } => Ok(key_ref),
+            response => Err(Error::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Sets the key on the layer to the (postcard-encoded) key ref.
+    pub fn set_key(&mut self, keymap_index: u16, layer: u8, key_ref: &[u8]) -> Result<(), Error> {
+        let key_ref =
+            KeyRefBytes::from_slice(key_ref).map_err(|_| Error::Device(Status::InvalidKeyRef))?;
+        match self.request(&Request::SetKey {
+            layer,
+            keymap_index,
+            key_ref,
+        })? {
+            Response::KeySet => Ok(()),
+            response => Err(Error::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Removes all keys set on the keyboard, restoring its compiled-in keymap.
+    pub fn reset_keys(&mut self) -> Result<(), Error> {
+        match self.request(&Request::ResetKeys)? {
+            Response::KeysReset => Ok(()),
+            response => Err(Error::UnexpectedResponse(response)),
+        }
+    }
+}
+
+/// A [Transport] which handles reports in-process with a [raw_hid::KeymapEditor].
+///
+/// Useful for tests and simulators.
+#[derive(Debug)]
+pub struct MemoryTransport<E: raw_hid::KeymapEditor> {
+    editor: E,
+    responses: VecDeque<Report>,
+}
+
+impl<E: raw_hid::KeymapEditor> MemoryTransport<E> {
+    /// Constructs a transport for the given editor.
+    pub fn new(editor: E) -> Self {
+        Self {
+            editor,
+            responses: VecDeque::new(),
+        }
+    }
+
+    /// The editor.
+    pub fn editor(&self) -> &E {
+        &self.editor
+    }
+}
+
+impl<E: raw_hid::KeymapEditor> Transport for MemoryTransport<E> {
+    fn send_report(&mut self, report: &Report) -> io::Result<()> {
+        let response = raw_hid::handle_report(&mut self.editor, report);
+        self.responses.push_back(response);
+        Ok(())
+    }
+
+    fn receive_report(&mut self) -> io::Result<Report> {
+        self.responses
+            .pop_front()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::WouldBlock, "no response"))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use smart_keymap_core::keymap::{KeyOverrideError, KeyOverrides};
+
+    /// A [raw_hid::KeymapEditor] with a bare [KeyOverrides] table.
+    #[derive(Debug, Default)]
+    struct TableEditor {
+        key_overrides: KeyOverrides,
+    }
+
+    const LAYER_COUNT: u8 = 4;
+
+    impl raw_hid::KeymapEditor for TableEditor {
+        fn layer_count(&self) -> u8 {
+            LAYER_COUNT
+        }
+
+        fn key_override(&self, keymap_index: u16, layer: u8) -> Option<KeyRefBytes> {
+            self.key_overrides
+                .get(keymap_index, layer)
+                .map(|ko| KeyRefBytes::from_slice(ko.key_ref_bytes()).unwrap())
+        }
+
+        fn set_key_override(
+            &mut self,
+            keymap_index: u16,
+            layer: u8,
+            key_ref: &[u8],
+        ) -> Result<(), KeyOverrideError> {
+            if layer >= LAYER_COUNT {
+                return Err(KeyOverrideError::InvalidLayer);
+            }
+            self.key_overrides.set(keymap_index, layer, key_ref)
+        }
+
+        fn clear_key_overrides(&mut self) {
+            self.key_overrides.clear();
+        }
+    }
+
+    fn client() -> Client<MemoryTransport<TableEditor>> {
+        Client::new(MemoryTransport::new(TableEditor::default()))
+    }
+
+    #[test]
+    fn test_protocol_version_and_layer_count() {
+        // Assemble
+        let mut client = client();
+
+        // Act
+        let version = client.protocol_version().unwrap();
+        let layer_count = client.layer_count().unwrap();
+
+        // Assert
+        assert_eq!(raw_hid::PROTOCOL_VERSION, version);
+        assert_eq!(LAYER_COUNT, layer_count);
+    }
+
+    #[test]
+    fn test_set_key_then_get_key() {
+        // Assemble
+        let mut client = client();
+
+        // Act
+        client.set_key(12, 2, &[0x03, 0x00, 0x05]).unwrap();
+        let actual_key = client.get_key(12, 2).unwrap();
+        let unset_key = client.get_key(12, 1).unwrap();
+
+        // Assert
+        assert_eq!(Some(&[0x03, 0x00, 0x05][..]), actual_key.as_deref());
+        assert_eq!(None, unset_key);
+    }
+
+    #[test]
+    fn test_reset_keys_clears_overrides() {
+        // Assemble
+        let mut client = client();
+        client.set_key(0, 0, &[0x01]).unwrap();
+
+        // Act
+        client.reset_keys().unwrap();
+
+        // Assert
+        assert_eq!(None, client.get_key(0, 0).unwrap());
+        assert!(client.into_transport().editor().key_overrides.is_empty());
+    }
+
+    #[test]
+    fn test_set_key_on_invalid_layer_is_device_error() {
+        // Assemble
+        let mut client = client();
+
+        // Act
+        let actual = client.set_key(0, LAYER_COUNT, &[0x01]);
+
+        // Assert
+        assert!(matches!(actual, Err(Error::Device(Status::InvalidLayer))));
+    }
+}
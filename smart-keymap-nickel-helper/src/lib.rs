@@ -8,6 +8,8 @@ use std::sync::{Mutex, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
+use sha2::{Digest, Sha256};
+
 /// Environment variable controlling Nickel wall-clock timeout (seconds).
 ///
 /// - unset → default ([`DEFAULT_NICKEL_TIMEOUT_SECS`])
@@ -351,6 +353,26 @@ pub fn nickel_board_rs_for_board_path(
     )
 }
 
+/// Evaluates the Nickel expr for a board, returning the json serialization of its `board` field.
+pub fn nickel_json_value_for_board_path(
+    NickelEvalInputs {
+        ncl_import_path,
+        input_path,
+    }: NickelEvalInputs,
+) -> NickelResult {
+    let import_path_arg = format!("--import-path={}", ncl_import_path);
+    run_nickel(
+        &[
+            "export",
+            "--format=json",
+            import_path_arg.as_str(),
+            "--field=board",
+            input_path.to_str().unwrap(),
+        ],
+        None,
+    )
+}
+
 /// Tries running the given source through `rustfmt`.
 pub fn rustfmt(rust_src: String) -> String {
     let spawn_rustfmt_result = Command::new("rustfmt")
@@ -526,6 +548,9 @@ pub fn nickel_composite_full_vec_rs(ncl_import_path: &str) -> NickelResult {
 /// - for `.ncl` inputs, the Nickel import tree used by codegen
 /// - for `.ncl` inputs, relative `import`s that resolve on disk (config-repo
 ///   keymaps that re-export another layout, e.g. `../split_3x5+3/keymap.ncl`)
+///
+/// Returns the module's source (as evaluated, before formatting),
+///  or `None` if `env_var` isn't set.
 pub fn codegen_rust_module(
     CodegenInputs {
         env_var,
@@ -534,7 +559,7 @@ pub fn codegen_rust_module(
         ncl_import_path,
         nickel_eval_fn,
     }: CodegenInputs,
-) {
+) -> Option<String> {
     println!("cargo:rerun-if-env-changed={}", env_var);
     println!("cargo:rerun-if-env-changed={}", NICKEL_TIMEOUT_ENV);
     println!("cargo::rustc-check-cfg=cfg({})", cfg_name);
@@ -558,6 +583,7 @@ pub fn codegen_rust_module(
 
             // Copy the custom module file to the output directory
             fs::copy(&custom_module_path, &dest_path).unwrap();
+            Some(fs::read_to_string(&custom_module_path).unwrap())
         } else if custom_module_path.ends_with(".ncl") {
             println!("cargo:rustc-cfg={}", cfg_name);
 
@@ -569,8 +595,9 @@ pub fn codegen_rust_module(
             }) {
                 Ok(keymap_rs) => {
                     let mut file = fs::File::create(&dest_path).unwrap();
-                    let formatted = rustfmt(keymap_rs);
+                    let formatted = rustfmt(keymap_rs.clone());
                     file.write_all(formatted.as_bytes()).unwrap();
+                    Some(keymap_rs)
                 }
                 Err(NickelError::NickelNotFound) => {
                     panic!("`nickel` not found in PATH");
@@ -588,15 +615,59 @@ pub fn codegen_rust_module(
         } else {
             panic!("Unsupported {}: {}", env_var, custom_module_path);
         }
+    } else {
+        None
     }
 }
 
+/// Hash identifying a keymap build,
+///  computed from the `smart-keymap` crate version and the keymap's generated `keymap.rs`.
+///
+/// (The generated source, before it's formatted with `rustfmt`).
+///
+/// Recorded in the header of input logs (`smart_keymap::input_log`),
+///  so that replaying a log can check it was captured with the same keymap.
+pub fn keymap_build_hash(crate_version: &str, keymap_rs: &[u8]) -> u32 {
+    let digest: [u8; 32] = Sha256::new()
+        .chain_update(crate_version.as_bytes())
+        .chain_update([0])
+        .chain_update(keymap_rs)
+        .finalize()
+        .into();
+    u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/// Generates a module with a `KEYMAP_BUILD_HASH` const,
+///  the [keymap_build_hash] of the crate version and the keymap source.
+///
+/// `keymap_rs` is the keymap module returned by [codegen_rust_module];
+///  without a custom keymap, the hash is of the crate version alone.
+pub fn codegen_keymap_build_hash(
+    crate_version: &str,
+    keymap_rs: Option<&str>,
+    module_basename: &str,
+) {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let dest_path = Path::new(&out_dir).join(module_basename);
+
+    let hash = keymap_build_hash(crate_version, keymap_rs.unwrap_or_default().as_bytes());
+
+    let module = format!(
+        "/// Hash identifying the keymap build.\n\
+         ///\n\
+         /// Computed from the crate version ({}) and the generated keymap source.\n\
+         pub const KEYMAP_BUILD_HASH: u32 = {:#010x};\n",
+        crate_version, hash
+    );
+    fs::write(&dest_path, module).unwrap();
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        clear_nickel_eval_cache, disk_cache, eval_cache, get_or_eval_json, nickel_timeout,
-        wait_with_optional_timeout, NickelError, NickelJsonExport, DEFAULT_NICKEL_TIMEOUT_SECS,
-        NICKEL_TIMEOUT_ENV,
+        clear_nickel_eval_cache, disk_cache, eval_cache, get_or_eval_json, keymap_build_hash,
+        nickel_timeout, wait_with_optional_timeout, NickelError, NickelJsonExport,
+        DEFAULT_NICKEL_TIMEOUT_SECS, NICKEL_TIMEOUT_ENV,
     };
     use std::process::{Command, Stdio};
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -612,6 +683,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keymap_build_hash_depends_on_keymap_source() {
+        let keymap = b"pub mod init { /* A */ }";
+        assert_eq!(
+            keymap_build_hash("0.1.0", keymap),
+            keymap_build_hash("0.1.0", keymap)
+        );
+        assert_ne!(
+            keymap_build_hash("0.1.0", keymap),
+            keymap_build_hash("0.1.0", b"pub mod init { /* B */ }")
+        );
+    }
+
+    #[test]
+    fn keymap_build_hash_depends_on_crate_version() {
+        let keymap = b"pub mod init { /* A */ }";
+        assert_ne!(
+            keymap_build_hash("0.1.0", keymap),
+            keymap_build_hash("0.2.0", keymap)
+        );
+        assert_ne!(0, keymap_build_hash("0.1.0", b""));
+    }
+
     #[test]
     fn clear_nickel_eval_cache_empties_entries() {
         let key = NickelJsonExport::keymap("/ncl", "{ keys = [] }");
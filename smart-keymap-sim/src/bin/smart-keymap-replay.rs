@@ -0,0 +1,146 @@
+//! Replays an input log captured by firmware (see `smart_keymap::input_log`)
+//!  against a keymap, and diffs the resulting HID keyboard reports
+//!  against an expected reports file.
+//!
+//! e.g.:
+//!
+//! ```sh
+//! smart-keymap-replay --keymap keymap.ncl --log misfire.bin --expected misfire.reports
+//! ```
+//!
+//! The reports file has one report per line, as hex bytes (e.g. `00 00 04 00 00 00 00 00`).
+//! Use `--bless` to write the replayed reports to the expected reports file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use smart_keymap::input_log;
+use smart_keymap_full_system_std::keymap_json::{keymap_from_json, Keymap};
+use smart_keymap_nickel_helper::{
+    keymap_build_hash, nickel_json_value_for_keymap, nickel_keymap_rs_for_keymap_path, NickelError,
+    NickelEvalInputs,
+};
+
+use smart_keymap_sim::replay::{diff_reports, format_reports, parse_reports, replay};
+
+#[derive(Debug, Parser)]
+#[command(about = "Replays an input log against a keymap, and diffs the HID reports")]
+struct Args {
+    /// Path of the keymap .ncl.
+    #[arg(long)]
+    keymap: PathBuf,
+
+    /// Path of the input log.
+    #[arg(long)]
+    log: PathBuf,
+
+    /// Path of the expected reports. (Without it, the reports are printed).
+    #[arg(long)]
+    expected: Option<PathBuf>,
+
+    /// Write the replayed reports to the expected reports file.
+    #[arg(long, requires = "expected")]
+    bless: bool,
+
+    /// Nickel import path (the smart-keymap `ncl/` directory).
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../ncl"))]
+    ncl_import_path: String,
+}
+
+fn nickel_error_message(e: NickelError) -> String {
+    match e {
+        NickelError::NickelNotFound => "`nickel` not found on PATH. Please install it.".into(),
+        NickelError::EvalError(message) => format!("error evaluating nickel:\n\n{}", message),
+        NickelError::Timeout { timeout_secs } => {
+            format!("nickel evaluation timed out after {}s", timeout_secs)
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("error reading {}: {}", path.display(), e))
+}
+
+fn load_keymap(args: &Args, keymap_ncl: &str) -> Result<Keymap, String> {
+    let json = nickel_json_value_for_keymap(args.ncl_import_path.clone(), keymap_ncl)
+        .map_err(nickel_error_message)?;
+    keymap_from_json(&json).map_err(|e| format!("error deserializing keymap: {}", e))
+}
+
+/// The keymap build hash of firmware built with the keymap (and this version of smart-keymap).
+fn build_hash(args: &Args) -> Result<u32, String> {
+    let keymap_rs = nickel_keymap_rs_for_keymap_path(NickelEvalInputs {
+        ncl_import_path: &args.ncl_import_path,
+        input_path: &args.keymap,
+    })
+    .map_err(nickel_error_message)?;
+    Ok(keymap_build_hash(
+        env!("CARGO_PKG_VERSION"),
+        keymap_rs.as_bytes(),
+    ))
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let keymap_ncl = read_file(&args.keymap)?;
+    let log_bytes =
+        fs::read(&args.log).map_err(|e| format!("error reading {}: {}", args.log.display(), e))?;
+
+    let (header, records) = input_log::decode(&log_bytes)
+        .map_err(|e| format!("error decoding {}: {:?}", args.log.display(), e))?;
+    let records: Vec<input_log::Record> = records
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("error decoding {}: {:?}", args.log.display(), e))?;
+
+    let hash = build_hash(&args)?;
+    if header.keymap_build_hash != hash {
+        eprintln!(
+            "warning: log was captured with keymap build hash {:#010x}, but {} has hash {:#010x}",
+            header.keymap_build_hash,
+            args.keymap.display(),
+            hash
+        );
+    }
+
+    let mut keymap = load_keymap(&args, &keymap_ncl)?;
+    let reports = replay(&mut keymap, records);
+    let actual = reports.reports();
+
+    let Some(expected_path) = &args.expected else {
+        print!("{}", format_reports(actual));
+        return Ok(true);
+    };
+
+    if args.bless {
+        fs::write(expected_path, format_reports(actual))
+            .map_err(|e| format!("error writing {}: {}", expected_path.display(), e))?;
+        return Ok(true);
+    }
+
+    let expected = parse_reports(&read_file(expected_path)?)
+        .map_err(|e| format!("error parsing {}: {}", expected_path.display(), e))?;
+    match diff_reports(&expected, actual) {
+        None => Ok(true),
+        Some(diff) => {
+            println!(
+                "replayed reports differ from {} (- expected, + actual):",
+                expected_path.display()
+            );
+            print!("{}", diff);
+            Ok(false)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,512 @@
+#![warn(missing_docs)]
+#![warn(clippy::unwrap_used)]
+#![warn(clippy::expect_used)]
+
+//! Simulates a smart keymap, recording a timeline of what the keymap did.
+//!
+//! The [Simulator] drives a keymap with [Input]s
+//!  (the `ncl/inputs.ncl` press/release/tap/wait vocabulary, serialized by `inputs-to-json.ncl`),
+//!  and records a timeline [Entry] for each input event,
+//!  each scheduled event (and whether it fired or was cancelled),
+//!  each pending key and its resolution,
+//!  and each change to the HID keyboard report.
+//!
+//! The [replay] module replays input logs captured by firmware.
+
+use std::fmt::{self, Debug};
+use std::ops::Index;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use smart_keymap::input;
+use smart_keymap::key;
+use smart_keymap::keymap::{Keymap, ReportHints, SetKeymapContext, TraceRecord, TraceRecorder};
+
+pub mod replay;
+
+/// Upper bound on ticks when settling the keymap.
+///
+/// (A key which keeps rescheduling work would otherwise never settle).
+pub const MAX_SETTLE_TICKS: u32 = 10_000;
+
+/// An input to the simulated keymap.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    /// Press the key at the keymap index.
+    Press {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// Release the key at the keymap index.
+    Release {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// Press, then release the key at the keymap index.
+    Tap {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// Tick the keymap for the duration (in ms).
+    Wait {
+        /// Duration in ms.
+        duration: u16,
+    },
+}
+
+/// Error parsing an [Input] from a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseInputError(String);
+
+impl fmt::Display for ParseInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected `press N`, `release N`, `tap N`, or `wait MS`; got `{}`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseInputError {}
+
+impl FromStr for Input {
+    type Err = ParseInputError;
+
+    /// Parses a command like `press 0`, `release 0`, `tap 0`, or `wait 200`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseInputError(s.to_string());
+        let mut words = s.split_whitespace();
+        let (Some(command), Some(arg), None) = (words.next(), words.next(), words.next()) else {
+            return Err(err());
+        };
+        let arg: u16 = arg.parse().map_err(|_| err())?;
+        match command {
+            "press" => Ok(Input::Press { keymap_index: arg }),
+            "release" => Ok(Input::Release { keymap_index: arg }),
+            "tap" => Ok(Input::Tap { keymap_index: arg }),
+            "wait" => Ok(Input::Wait { duration: arg }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Something which happened in the simulated keymap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    /// A key was pressed.
+    Press {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// A key was released.
+    Release {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// An event was scheduled.
+    Scheduled {
+        /// Time in ms until the event is due.
+        delay_ms: u32,
+        /// The event (Debug formatted).
+        event: String,
+    },
+    /// A scheduled event was handled.
+    Fired {
+        /// The event (Debug formatted).
+        event: String,
+    },
+    /// A scheduled event was cancelled.
+    Cancelled {
+        /// The event (Debug formatted).
+        event: String,
+    },
+    /// A key's state became pending.
+    Pending {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// A pending key's state was resolved.
+    Resolved {
+        /// The keymap index.
+        keymap_index: u16,
+    },
+    /// The HID keyboard report changed.
+    Report {
+        /// The HID boot keyboard report.
+        report: [u8; 8],
+    },
+}
+
+impl fmt::Display for TimelineEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimelineEvent::Press { keymap_index } => write!(f, "press     {}", keymap_index),
+            TimelineEvent::Release { keymap_index } => write!(f, "release   {}", keymap_index),
+            TimelineEvent::Scheduled { delay_ms, event } => {
+                write!(f, "scheduled {} (in {} ms)", event, delay_ms)
+            }
+            TimelineEvent::Fired { event } => write!(f, "fired     {}", event),
+            TimelineEvent::Cancelled { event } => write!(f, "cancelled {}", event),
+            TimelineEvent::Pending { keymap_index } => write!(f, "pending   {}", keymap_index),
+            TimelineEvent::Resolved { keymap_index } => write!(f, "resolved  {}", keymap_index),
+            TimelineEvent::Report { report } => {
+                write!(f, "report   ")?;
+                for b in report {
+                    write!(f, " {:02X}", b)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A [TimelineEvent], and the time it happened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// Time in ms since the simulation started.
+    pub time_ms: u32,
+    /// What happened.
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6} ms  {}", self.time_ms, self.event)
+    }
+}
+
+/// The timeline event for the trace record, if any.
+fn timeline_event<Ev: Debug>(record: TraceRecord<Ev>) -> Option<TimelineEvent> {
+    match record {
+        TraceRecord::PendingStarted { keymap_index } => {
+            Some(TimelineEvent::Pending { keymap_index })
+        }
+        TraceRecord::PendingResolved { keymap_index, .. } => {
+            Some(TimelineEvent::Resolved { keymap_index })
+        }
+        TraceRecord::EventScheduled { schedule, event } => {
+            let delay_ms = match schedule {
+                key::Schedule::Immediate => 0,
+                key::Schedule::After(delay) => delay.into(),
+            };
+            Some(TimelineEvent::Scheduled {
+                delay_ms,
+                event: format!("{:?}", event),
+            })
+        }
+        TraceRecord::EventFired { event } => Some(TimelineEvent::Fired {
+            event: format!("{:?}", event),
+        }),
+        TraceRecord::EventCancelled { event } => Some(TimelineEvent::Cancelled {
+            event: format!("{:?}", event),
+        }),
+        TraceRecord::InputQueued { .. }
+        | TraceRecord::InputDropped { .. }
+        | TraceRecord::InputAccepted { .. }
+        | TraceRecord::ContextUpdated { .. }
+        | TraceRecord::ReportEmitted { .. } => None,
+    }
+}
+
+/// Drives a keymap with [Input]s, recording a timeline of [Entry]s.
+///
+/// Like `ObservedKeymap`, the keymap is ticked once after each key press or release.
+///
+/// The keymap's [TraceRecord]s are recorded with a [TraceRecorder],
+///  and taken into the timeline after each input or tick.
+#[derive(Debug)]
+pub struct Simulator<I: Index<usize, Output = R>, R, Ctx, Ev: Debug, PKS, KS, S> {
+    keymap: Keymap<I, R, Ctx, Ev, PKS, KS, S, TraceRecorder<Ev>>,
+    time_ms: u32,
+    report: [u8; 8],
+    entries: Vec<Entry>,
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+    > Simulator<I, R, Ctx, Ev, PKS, KS, S>
+{
+    /// Constructs a simulator for the keymap.
+    pub fn new(keymap: Keymap<I, R, Ctx, Ev, PKS, KS, S>) -> Self {
+        Self {
+            keymap: keymap.with_observer(TraceRecorder::new()),
+            time_ms: 0,
+            report: [0; 8],
+            entries: Vec::new(),
+        }
+    }
+
+    /// The simulated keymap.
+    pub fn keymap(&self) -> &Keymap<I, R, Ctx, Ev, PKS, KS, S, TraceRecorder<Ev>> {
+        &self.keymap
+    }
+
+    /// Time in ms since the simulation started.
+    pub fn time_ms(&self) -> u32 {
+        self.time_ms
+    }
+
+    /// The timeline entries recorded so far.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Takes the timeline entries recorded so far.
+    pub fn take_entries(&mut self) -> Vec<Entry> {
+        core::mem::take(&mut self.entries)
+    }
+
+    /// Handles the input.
+    pub fn handle_input(&mut self, input: Input) {
+        match input {
+            Input::Press { keymap_index } => {
+                self.record(TimelineEvent::Press { keymap_index });
+                self.handle_input_event(input::Event::Press { keymap_index });
+            }
+            Input::Release { keymap_index } => {
+                self.record(TimelineEvent::Release { keymap_index });
+                self.handle_input_event(input::Event::Release { keymap_index });
+            }
+            Input::Tap { keymap_index } => {
+                self.handle_input(Input::Press { keymap_index });
+                self.handle_input(Input::Release { keymap_index });
+            }
+            Input::Wait { duration } => {
+                for _ in 0..duration {
+                    self.tick();
+                }
+            }
+        }
+    }
+
+    /// Ticks the keymap until it has no scheduled events (or for at most [MAX_SETTLE_TICKS]).
+    pub fn settle(&mut self) {
+        for _ in 0..MAX_SETTLE_TICKS {
+            if !self.keymap.has_scheduled_events() {
+                return;
+            }
+            self.tick();
+        }
+    }
+
+    /// Ticks the keymap by 1 ms.
+    pub fn tick(&mut self) {
+        self.keymap.tick();
+        self.time_ms += 1;
+        self.observe();
+    }
+
+    fn handle_input_event(&mut self, ev: input::Event) {
+        self.keymap.handle_input(ev);
+        self.observe();
+        self.tick();
+    }
+
+    fn record(&mut self, event: TimelineEvent) {
+        self.entries.push(Entry {
+            time_ms: self.time_ms,
+            event,
+        });
+    }
+
+    /// Records the keymap's trace records, and changes to its report.
+    fn observe(&mut self) {
+        let records = self.keymap.observer_mut().take();
+        for (_, record) in records {
+            if let Some(event) = timeline_event(record) {
+                self.record(event);
+            }
+        }
+
+        let report = self.keymap.report_output().as_hid_boot_keyboard_report();
+        if report != self.report {
+            self.report = report;
+            self.record(TimelineEvent::Report { report });
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use smart_keymap::init::{Context, Event, KeyState, PendingKeyState, Ref, System};
+    use smart_keymap::init::{CONTEXT, SYSTEM};
+    use smart_keymap::key::keyboard;
+
+    type TestSimulator =
+        Simulator<[Ref; 2], Ref, Context, Event, PendingKeyState, KeyState, System>;
+
+    fn simulator() -> TestSimulator {
+        let key_refs = [
+            Ref::Keyboard(keyboard::Ref::KeyCode(0x04)),
+            Ref::Keyboard(keyboard::Ref::KeyCode(0xE1)),
+        ];
+        Simulator::new(Keymap::new(key_refs, CONTEXT, SYSTEM))
+    }
+
+    #[test]
+    fn test_input_from_str() {
+        assert_eq!(Ok(Input::Press { keymap_index: 3 }), "press 3".parse());
+        assert_eq!(
+            Ok(Input::Release { keymap_index: 3 }),
+            " release  3 ".parse()
+        );
+        assert_eq!(Ok(Input::Tap { keymap_index: 0 }), "tap 0".parse());
+        assert_eq!(Ok(Input::Wait { duration: 200 }), "wait 200".parse());
+        assert!("press".parse::<Input>().is_err());
+        assert!("press A".parse::<Input>().is_err());
+        assert!("hold 1".parse::<Input>().is_err());
+        assert!("tap 1 2".parse::<Input>().is_err());
+    }
+
+    #[test]
+    fn test_input_deserializes_inputs_to_json() {
+        let inputs: Vec<Input> = serde_json::from_str(
+            r#"[{ "Press": { "keymap_index": 0 } }, { "Wait": { "duration": 10 } }]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Input::Press { keymap_index: 0 },
+                Input::Wait { duration: 10 }
+            ],
+            inputs
+        );
+    }
+
+    #[test]
+    fn test_timeline_event_for_trace_records() {
+        // Assemble
+        let timeout = key::Event::key_event(0, 7u8);
+        let press = input::Event::Press { keymap_index: 1 };
+
+        // Act
+        let scheduled = timeline_event(TraceRecord::EventScheduled {
+            schedule: key::Schedule::After(200),
+            event: timeout,
+        });
+        let cancelled = timeline_event(TraceRecord::EventCancelled { event: timeout });
+        let resolved = timeline_event::<u8>(TraceRecord::PendingResolved {
+            keymap_index: 0,
+            cause: key::Event::Input(press),
+            key_output: None,
+        });
+        let queued = timeline_event::<u8>(TraceRecord::InputQueued { event: press });
+
+        // Assert
+        assert_eq!(
+            Some(TimelineEvent::Scheduled {
+                delay_ms: 200,
+                event: format!("{:?}", timeout)
+            }),
+            scheduled
+        );
+        assert_eq!(
+            Some(TimelineEvent::Cancelled {
+                event: format!("{:?}", timeout)
+            }),
+            cancelled
+        );
+        assert_eq!(Some(TimelineEvent::Resolved { keymap_index: 0 }), resolved);
+        assert_eq!(None, queued);
+    }
+
+    #[test]
+    fn test_simulator_records_inputs_and_reports() {
+        // Assemble
+        let mut sim = simulator();
+
+        // Act
+        sim.handle_input(Input::Press { keymap_index: 1 });
+        sim.handle_input(Input::Tap { keymap_index: 0 });
+        sim.handle_input(Input::Wait { duration: 5 });
+        sim.handle_input(Input::Release { keymap_index: 1 });
+
+        // Assert
+        let expected = vec![
+            Entry {
+                time_ms: 0,
+                event: TimelineEvent::Press { keymap_index: 1 },
+            },
+            Entry {
+                time_ms: 0,
+                event: TimelineEvent::Report {
+                    report: [0x02, 0, 0, 0, 0, 0, 0, 0],
+                },
+            },
+            Entry {
+                time_ms: 1,
+                event: TimelineEvent::Press { keymap_index: 0 },
+            },
+            Entry {
+                time_ms: 1,
+                event: TimelineEvent::Report {
+                    report: [0x02, 0, 0x04, 0, 0, 0, 0, 0],
+                },
+            },
+            Entry {
+                time_ms: 2,
+                event: TimelineEvent::Release { keymap_index: 0 },
+            },
+            Entry {
+                time_ms: 2,
+                event: TimelineEvent::Report {
+                    report: [0x02, 0, 0, 0, 0, 0, 0, 0],
+                },
+            },
+            Entry {
+                time_ms: 8,
+                event: TimelineEvent::Release { keymap_index: 1 },
+            },
+            Entry {
+                time_ms: 8,
+                event: TimelineEvent::Report { report: [0; 8] },
+            },
+        ];
+        assert_eq!(expected, sim.entries());
+        assert_eq!(9, sim.time_ms());
+    }
+
+    #[test]
+    fn test_take_entries_clears_entries() {
+        let mut sim = simulator();
+        sim.handle_input(Input::Tap { keymap_index: 0 });
+
+        let entries = sim.take_entries();
+
+        assert_eq!(4, entries.len());
+        assert!(sim.entries().is_empty());
+    }
+
+    #[test]
+    fn test_entry_display_and_json() {
+        let entry = Entry {
+            time_ms: 12,
+            event: TimelineEvent::Report {
+                report: [0x02, 0, 0x04, 0, 0, 0, 0, 0],
+            },
+        };
+
+        assert_eq!(
+            "    12 ms  report    02 00 04 00 00 00 00 00",
+            entry.to_string()
+        );
+        assert_eq!(
+            r#"{"time_ms":12,"type":"report","report":[2,0,4,0,0,0,0,0]}"#,
+            serde_json::to_string(&entry).unwrap()
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Simulates a smart keymap, printing a timeline of input events,
+//!  scheduled events, pending key resolutions, and HID reports.
+//!
+//! e.g.:
+//!
+//! ```sh
+//! smart-keymap-sim --keymap smart-keymap-uinput/examples/keymap-ansi-60.ncl --inputs inputs.ncl
+//! ```
+//!
+//! where `inputs.ncl` is a list of inputs, using the `ncl/inputs.ncl` vocabulary:
+//!
+//! ```nickel
+//! [
+//!   press (K.A & K.hold K.LeftCtrl),
+//!   wait 250,
+//!   release (K.A & K.hold K.LeftCtrl),
+//! ]
+//! ```
+//!
+//! Without `--inputs` (or with `--repl`), inputs are read from stdin, one per line:
+//!  either `press N`, `release N`, `tap N`, `wait MS` (where `N` is a keymap index),
+//!  `settle` (tick until there are no scheduled events),
+//!  or a Nickel expression for a list of inputs.
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use smart_keymap_full_system_std::key_system::{
+    Context, Event, KeyState, PendingKeyState, Ref, System,
+};
+use smart_keymap_full_system_std::keymap_json::{keymap_from_json, Keymap};
+use smart_keymap_nickel_helper::{
+    nickel_json_value_for_inputs, nickel_json_value_for_keymap, NickelError,
+};
+
+use smart_keymap_sim::{Entry, Input, Simulator};
+
+type KeymapSimulator = Simulator<Vec<Ref>, Ref, Context, Event, PendingKeyState, KeyState, System>;
+
+#[derive(Debug, Parser)]
+#[command(about = "Simulates a smart keymap, printing a timeline of what the keymap did")]
+struct Args {
+    /// Path of the keymap .ncl.
+    #[arg(long)]
+    keymap: PathBuf,
+
+    /// Path of an inputs .ncl (a list of `press`, `release`, `tap`, `wait` inputs).
+    #[arg(long)]
+    inputs: Option<PathBuf>,
+
+    /// Read inputs from stdin (after any `--inputs`).
+    #[arg(long)]
+    repl: bool,
+
+    /// Print the timeline as JSON lines.
+    #[arg(long)]
+    json: bool,
+
+    /// Nickel import path (the smart-keymap `ncl/` directory).
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../ncl"))]
+    ncl_import_path: String,
+}
+
+fn nickel_error_message(e: NickelError) -> String {
+    match e {
+        NickelError::NickelNotFound => "`nickel` not found on PATH. Please install it.".into(),
+        NickelError::EvalError(message) => format!("error evaluating nickel:\n\n{}", message),
+        NickelError::Timeout { timeout_secs } => {
+            format!("nickel evaluation timed out after {}s", timeout_secs)
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("error reading {}: {}", path.display(), e))
+}
+
+fn load_keymap(args: &Args, keymap_ncl: &str) -> Result<Keymap, String> {
+    let json = nickel_json_value_for_keymap(args.ncl_import_path.clone(), keymap_ncl)
+        .map_err(nickel_error_message)?;
+    keymap_from_json(&json).map_err(|e| format!("error deserializing keymap: {}", e))
+}
+
+fn load_inputs(args: &Args, keymap_ncl: &str, inputs_ncl: &str) -> Result<Vec<Input>, String> {
+    let json = nickel_json_value_for_inputs(args.ncl_import_path.clone(), keymap_ncl, inputs_ncl)
+        .map_err(nickel_error_message)?;
+    serde_json::from_str(&json).map_err(|e| format!("error deserializing inputs: {}", e))
+}
+
+fn print_entries(args: &Args, entries: Vec<Entry>) {
+    for entry in entries {
+        if args.json {
+            match serde_json::to_string(&entry) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("error serializing entry: {}", e),
+            }
+        } else {
+            println!("{}", entry);
+        }
+    }
+}
+
+fn repl(args: &Args, keymap_ncl: &str, sim: &mut KeymapSimulator) -> Result<(), String> {
+    let stdin = io::stdin();
+    loop {
+        eprint!("{:>6} ms> ", sim.time_ms());
+        let _ = io::stderr().flush();
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(e) => return Err(format!("error reading stdin: {}", e)),
+        }
+
+        match line.trim() {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            "settle" => sim.settle(),
+            command => match command.parse::<Input>() {
+                Ok(input) => sim.handle_input(input),
+                Err(_) => match load_inputs(args, keymap_ncl, command) {
+                    Ok(inputs) => inputs.into_iter().for_each(|input| sim.handle_input(input)),
+                    Err(message) => eprintln!("{}", message),
+                },
+            },
+        }
+        print_entries(args, sim.take_entries());
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let keymap_ncl = read_file(&args.keymap)?;
+    let mut sim = Simulator::new(load_keymap(&args, &keymap_ncl)?);
+
+    if let Some(inputs_path) = &args.inputs {
+        let inputs_ncl = read_file(inputs_path)?;
+        for input in load_inputs(&args, &keymap_ncl, &inputs_ncl)? {
+            sim.handle_input(input);
+        }
+        sim.settle();
+        print_entries(&args, sim.take_entries());
+    }
+
+    if args.inputs.is_none() || args.repl {
+        repl(&args, &keymap_ncl, &mut sim)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Replays an input log (see [smart_keymap::input_log]) against a keymap,
+//!  collecting the distinct HID keyboard reports,
+//!  for comparing against the expected reports.
+
+use std::fmt::{self, Debug, Write};
+use std::ops::Index;
+
+use smart_keymap::input_log::Record;
+use smart_keymap::key;
+use smart_keymap::keymap::{DistinctReports, Keymap, ReportHints, SetKeymapContext};
+
+use crate::MAX_SETTLE_TICKS;
+
+/// Replays the records against the keymap, returning the distinct HID keyboard reports.
+///
+/// Each record is passed to [Keymap::handle_input_after_time],
+///  ticking to each scheduled event due before the record
+///  (so reports in between are observed).
+/// After the last record, the keymap is ticked until it has no scheduled events.
+pub fn replay<
+    I: Debug + Index<usize, Output = R>,
+    R: Copy + Debug,
+    Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints,
+    Ev: Copy + Debug,
+    PKS: Debug,
+    KS: Copy + Debug + From<key::NoOpKeyState>,
+    S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+>(
+    keymap: &mut Keymap<I, R, Ctx, Ev, PKS, KS, S>,
+    records: impl IntoIterator<Item = Record>,
+) -> DistinctReports {
+    let mut reports = DistinctReports::new();
+    reports.update(keymap.report_output().as_hid_boot_keyboard_report());
+
+    let mut next_event_ms = None;
+    for Record { delta_ms, event } in records {
+        let mut remaining_ms = delta_ms;
+        while let Some(event_ms) = next_event_ms.filter(|&ms| ms < remaining_ms) {
+            remaining_ms -= event_ms;
+            next_event_ms = keymap.tick_to_next_scheduled_event();
+            reports.update(keymap.report_output().as_hid_boot_keyboard_report());
+        }
+
+        next_event_ms = keymap.handle_input_after_time(remaining_ms, event);
+        reports.update(keymap.report_output().as_hid_boot_keyboard_report());
+    }
+
+    for _ in 0..MAX_SETTLE_TICKS {
+        if !keymap.has_scheduled_events() {
+            break;
+        }
+        keymap.tick();
+        reports.update(keymap.report_output().as_hid_boot_keyboard_report());
+    }
+
+    reports
+}
+
+/// Formats the reports, one per line, as hex bytes.
+///
+/// e.g. `00 00 04 00 00 00 00 00`.
+pub fn format_reports(reports: &[[u8; 8]]) -> String {
+    let mut s = String::new();
+    for report in reports {
+        let line: Vec<String> = report.iter().map(|b| format!("{:02X}", b)).collect();
+        let _ = writeln!(s, "{}", line.join(" "));
+    }
+    s
+}
+
+/// Error parsing a reports file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseReportsError {
+    /// The (1-based) line number of the malformed report.
+    pub line: usize,
+}
+
+impl fmt::Display for ParseReportsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: expected a report of 8 hex bytes (e.g. `00 00 04 00 00 00 00 00`)",
+            self.line
+        )
+    }
+}
+
+impl std::error::Error for ParseReportsError {}
+
+/// Parses reports formatted by [format_reports].
+///
+/// Blank lines, and lines starting with `#`, are ignored.
+pub fn parse_reports(s: &str) -> Result<Vec<[u8; 8]>, ParseReportsError> {
+    s.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let bytes: Vec<u8> = line
+                .split_whitespace()
+                .map(|b| u8::from_str_radix(b, 16))
+                .collect::<Result<_, _>>()
+                .map_err(|_| ParseReportsError { line: line_number })?;
+            bytes
+                .try_into()
+                .map_err(|_| ParseReportsError { line: line_number })
+        })
+        .collect()
+}
+
+/// Compares the expected and actual reports,
+///  returning a line-by-line diff if they differ.
+///
+/// Lines only in the expected reports are prefixed with `-`,
+///  lines only in the actual reports with `+`.
+pub fn diff_reports(expected: &[[u8; 8]], actual: &[[u8; 8]]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {
+                let _ = write!(diff, "  {}", format_reports(&[*e]));
+            }
+            (e, a) => {
+                if let Some(e) = e {
+                    let _ = write!(diff, "- {}", format_reports(&[*e]));
+                }
+                if let Some(a) = a {
+                    let _ = write!(diff, "+ {}", format_reports(&[*a]));
+                }
+            }
+        }
+    }
+    Some(diff)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use smart_keymap::init::Ref;
+    use smart_keymap::init::{CONTEXT, SYSTEM};
+    use smart_keymap::input;
+    use smart_keymap::input_log::InputLog;
+    use smart_keymap::key::keyboard;
+
+    #[test]
+    fn test_replay_reports_each_distinct_report() {
+        let key_refs = [
+            Ref::Keyboard(keyboard::Ref::KeyCode(0x04)),
+            Ref::Keyboard(keyboard::Ref::KeyCode(0xE1)),
+        ];
+        let mut keymap = Keymap::new(key_refs, CONTEXT, SYSTEM);
+        let mut log: InputLog<8> = InputLog::new(0);
+        log.record(100, input::Event::Press { keymap_index: 1 });
+        log.record(150, input::Event::Press { keymap_index: 0 });
+        log.record(200, input::Event::Release { keymap_index: 0 });
+        log.record(250, input::Event::Release { keymap_index: 1 });
+
+        let reports = replay(&mut keymap, log.records());
+
+        let expected_reports: &[[u8; 8]] = &[
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0x02, 0, 0, 0, 0, 0, 0, 0],
+            [0x02, 0, 0x04, 0, 0, 0, 0, 0],
+            [0x02, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        assert_eq!(expected_reports, reports.reports());
+    }
+
+    #[test]
+    fn test_parse_reports_roundtrips_format_reports() {
+        let reports = [[0, 0, 0, 0, 0, 0, 0, 0], [0x02, 0, 0x04, 0, 0, 0, 0, 0]];
+
+        let s = format_reports(&reports);
+
+        assert_eq!("00 00 00 00 00 00 00 00\n02 00 04 00 00 00 00 00\n", s);
+        assert_eq!(Ok(reports.to_vec()), parse_reports(&s));
+    }
+
+    #[test]
+    fn test_parse_reports_ignores_comments_and_blank_lines() {
+        let s = "# press A\n\n00 00 04 00 00 00 00 00\n";
+
+        assert_eq!(Ok(vec![[0, 0, 0x04, 0, 0, 0, 0, 0]]), parse_reports(s));
+    }
+
+    #[test]
+    fn test_parse_reports_rejects_short_report() {
+        let s = "00 00 00 00 00 00 00 00\n00 00 04\n";
+
+        assert_eq!(Err(ParseReportsError { line: 2 }), parse_reports(s));
+    }
+
+    #[test]
+    fn test_diff_reports_marks_differing_lines() {
+        let expected = [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0x04, 0, 0, 0, 0, 0]];
+        let actual = [[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0x05, 0, 0, 0, 0, 0]];
+
+        let diff = diff_reports(&expected, &actual);
+
+        assert_eq!(
+            Some(
+                "  00 00 00 00 00 00 00 00\n\
+                 - 00 00 04 00 00 00 00 00\n\
+                 + 00 00 05 00 00 00 00 00\n"
+                    .to_string()
+            ),
+            diff
+        );
+        assert_eq!(None, diff_reports(&expected, &expected));
+    }
+}
@@ -0,0 +1,144 @@
+//! [InputSource] for Linux `evdev` devices.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{InputSource, KeyEvent};
+
+/// Size of a `struct input_event`.
+pub(crate) const INPUT_EVENT_LEN: usize = core::mem::size_of::<libc::input_event>();
+
+/// Size of the `struct timeval` at the start of a `struct input_event`.
+const TIMEVAL_LEN: usize = core::mem::size_of::<libc::timeval>();
+
+/// Synchronization event type.
+pub(crate) const EV_SYN: u16 = 0x00;
+/// Key event type.
+pub(crate) const EV_KEY: u16 = 0x01;
+
+/// `_IOW('E', 0x90, int)`
+const EVIOCGRAB: libc::Ioctl = 0x4004_4590;
+
+/// Key event value for a key release.
+const KEY_RELEASE: i32 = 0;
+/// Key event value for a key press.
+const KEY_PRESS: i32 = 1;
+
+/// Parses the type, code, and value of a `struct input_event`.
+pub(crate) fn parse_input_event(buf: &[u8; INPUT_EVENT_LEN]) -> (u16, u16, i32) {
+    let field = |offset: usize| buf[TIMEVAL_LEN + offset];
+    (
+        u16::from_ne_bytes([field(0), field(1)]),
+        u16::from_ne_bytes([field(2), field(3)]),
+        i32::from_ne_bytes([field(4), field(5), field(6), field(7)]),
+    )
+}
+
+/// Serializes a `struct input_event` with the given type, code, and value (and zero time).
+pub(crate) fn input_event_bytes(type_: u16, code: u16, value: i32) -> [u8; INPUT_EVENT_LEN] {
+    let mut buf = [0u8; INPUT_EVENT_LEN];
+    buf[TIMEVAL_LEN..TIMEVAL_LEN + 2].copy_from_slice(&type_.to_ne_bytes());
+    buf[TIMEVAL_LEN + 2..TIMEVAL_LEN + 4].copy_from_slice(&code.to_ne_bytes());
+    buf[TIMEVAL_LEN + 4..].copy_from_slice(&value.to_ne_bytes());
+    buf
+}
+
+/// A `/dev/input/eventN` device.
+#[derive(Debug)]
+pub struct EvdevDevice {
+    file: File,
+    opened: Instant,
+}
+
+impl EvdevDevice {
+    /// Opens the evdev device at the given path (e.g. `/dev/input/event3`).
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).open(path)?;
+        Ok(Self {
+            file,
+            opened: Instant::now(),
+        })
+    }
+
+    /// Grabs the device, so that its events are only read by this process.
+    ///
+    /// (Otherwise, the host would see both the keyboard's events and the keymap's output).
+    pub fn grab(&mut self) -> io::Result<()> {
+        let grab: libc::c_int = 1;
+        // SAFETY: EVIOCGRAB takes an int argument, passed by value.
+        let res = unsafe { libc::ioctl(self.file.as_raw_fd(), EVIOCGRAB, grab) };
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Waits up to `timeout` for the device to be readable.
+    fn poll(&self, timeout: Duration) -> io::Result<bool> {
+        let mut fds = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout_ms = timeout.as_micros().div_ceil(1000);
+        let timeout_ms = libc::c_int::try_from(timeout_ms).unwrap_or(libc::c_int::MAX);
+        // SAFETY: fds is a single, valid pollfd.
+        match unsafe { libc::poll(&mut fds, 1, timeout_ms) } {
+            res if res < 0 => {
+                let e = io::Error::last_os_error();
+                if e.kind() == io::ErrorKind::Interrupted {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl InputSource for EvdevDevice {
+    fn elapsed(&self) -> Duration {
+        self.opened.elapsed()
+    }
+
+    fn read_event(&mut self, timeout: Duration) -> io::Result<Option<KeyEvent>> {
+        if !self.poll(timeout)? {
+            return Ok(None);
+        }
+
+        let mut buf = [0u8; INPUT_EVENT_LEN];
+        self.file.read_exact(&mut buf)?;
+
+        // Sync, autorepeat, and non-key events are ignored.
+        match parse_input_event(&buf) {
+            (EV_KEY, code, KEY_PRESS) => Ok(Some(KeyEvent::press(code))),
+            (EV_KEY, code, KEY_RELEASE) => Ok(Some(KeyEvent::release(code))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_event_bytes_roundtrip() {
+        let buf = input_event_bytes(EV_KEY, 30, KEY_PRESS);
+
+        assert_eq!((EV_KEY, 30, KEY_PRESS), parse_input_event(&buf));
+    }
+
+    #[test]
+    fn test_input_event_bytes_has_zero_time() {
+        let buf = input_event_bytes(EV_KEY, 30, KEY_PRESS);
+
+        assert!(buf[..TIMEVAL_LEN].iter().all(|&b| b == 0));
+    }
+}
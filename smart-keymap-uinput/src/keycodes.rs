@@ -0,0 +1,61 @@
+//! Translation from HID keyboard usages to Linux input key codes.
+
+/// Linux key codes, indexed by HID keyboard usage (0x00..=0xE7).
+///
+/// Matches the `hid_keyboard` table of the Linux kernel's `hid-input.c`.
+/// Zero (`KEY_RESERVED`) where there's no corresponding key code.
+#[rustfmt::skip]
+const HID_KEYBOARD: [u8; 0xE8] = [
+      0,   0,   0,   0,  30,  48,  46,  32,  18,  33,  34,  35,  23,  36,  37,  38,
+     50,  49,  24,  25,  16,  19,  31,  20,  22,  47,  17,  45,  21,  44,   2,   3,
+      4,   5,   6,   7,   8,   9,  10,  11,  28,   1,  14,  15,  57,  12,  13,  26,
+     27,  43,  43,  39,  40,  41,  51,  52,  53,  58,  59,  60,  61,  62,  63,  64,
+     65,  66,  67,  68,  87,  88,  99,  70, 119, 110, 102, 104, 111, 107, 109, 106,
+    105, 108, 103,  69,  98,  55,  74,  78,  96,  79,  80,  81,  75,  76,  77,  71,
+     72,  73,  82,  83,  86, 127, 116, 117, 183, 184, 185, 186, 187, 188, 189, 190,
+    191, 192, 193, 194, 134, 138, 130, 132, 128, 129, 131, 137, 133, 135, 136, 113,
+    115, 114,   0,   0,   0, 121,   0,  89,  93, 124,  92,  94,  95,   0,   0,   0,
+    122, 123,  90,  91,  85,   0,   0,   0,   0,   0,   0,   0, 111,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0, 179, 180,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0, 111,   0,   0,   0,   0,   0,   0,   0,
+     29,  42,  56, 125,  97,  54, 100, 126,
+];
+
+/// Returns the Linux key code (e.g. `KEY_A`) for the given HID keyboard usage (e.g. 0x04).
+pub fn linux_key_code(hid_usage: u8) -> Option<u16> {
+    match HID_KEYBOARD.get(hid_usage as usize) {
+        Some(&0) | None => None,
+        Some(&code) => Some(code.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linux_key_code_letters_and_digits() {
+        assert_eq!(Some(30), linux_key_code(0x04)); // A
+        assert_eq!(Some(44), linux_key_code(0x1D)); // Z
+        assert_eq!(Some(2), linux_key_code(0x1E)); // 1
+        assert_eq!(Some(11), linux_key_code(0x27)); // 0
+    }
+
+    #[test]
+    fn test_linux_key_code_modifiers() {
+        assert_eq!(Some(29), linux_key_code(0xE0)); // LeftCtrl
+        assert_eq!(Some(42), linux_key_code(0xE1)); // LeftShift
+        assert_eq!(Some(125), linux_key_code(0xE3)); // LeftGUI
+        assert_eq!(Some(126), linux_key_code(0xE7)); // RightGUI
+    }
+
+    #[test]
+    fn test_linux_key_code_none_for_reserved_usages() {
+        assert_eq!(None, linux_key_code(0x00));
+        assert_eq!(None, linux_key_code(0x01));
+        assert_eq!(None, linux_key_code(0xA5));
+        assert_eq!(None, linux_key_code(0xE8));
+    }
+}
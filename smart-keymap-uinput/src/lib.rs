@@ -0,0 +1,445 @@
+#![warn(missing_docs)]
+#![warn(clippy::unwrap_used)]
+#![warn(clippy::expect_used)]
+
+//! Runs a smart keymap in userspace, on a Linux host.
+//!
+//! Key events are read from a keyboard through an [InputSource]
+//!  (e.g. [evdev::EvdevDevice], or a [Recording] of key events),
+//!  mapped to keymap indices using a [Board],
+//!  and the keymap's output is written to an [OutputSink]
+//!  (e.g. a [uinput::UinputDevice] virtual keyboard).
+//!
+//! Only the keymap's keyboard output is written; consumer and mouse output are ignored.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io;
+use std::ops::Index;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use smart_keymap::input;
+use smart_keymap::key;
+use smart_keymap::keymap::{self, Keymap, ReportHints, SetKeymapContext};
+
+pub mod keycodes;
+
+#[cfg(target_os = "linux")]
+pub mod evdev;
+#[cfg(target_os = "linux")]
+pub mod uinput;
+
+/// A key press or release, using Linux key codes (e.g. `KEY_A`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// The Linux key code.
+    pub code: u16,
+    /// Whether the key was pressed (or released).
+    pub pressed: bool,
+}
+
+impl KeyEvent {
+    /// Constructs a key press event.
+    pub const fn press(code: u16) -> Self {
+        Self {
+            code,
+            pressed: true,
+        }
+    }
+
+    /// Constructs a key release event.
+    pub const fn release(code: u16) -> Self {
+        Self {
+            code,
+            pressed: false,
+        }
+    }
+}
+
+/// Reads key events from a keyboard.
+pub trait InputSource {
+    /// Time elapsed since the source was opened.
+    fn elapsed(&self) -> Duration;
+
+    /// Waits up to `timeout` for the next key event.
+    ///
+    /// Returns `Ok(None)` if no key event was read,
+    ///  and an [io::ErrorKind::UnexpectedEof] error when there are no more events.
+    fn read_event(&mut self, timeout: Duration) -> io::Result<Option<KeyEvent>>;
+}
+
+/// Writes key events to the host.
+pub trait OutputSink {
+    /// Writes a key press or release.
+    fn write_key(&mut self, event: KeyEvent) -> io::Result<()>;
+
+    /// Indicates the written key events should be reported to the host.
+    fn sync(&mut self) -> io::Result<()>;
+}
+
+impl OutputSink for Vec<KeyEvent> {
+    fn write_key(&mut self, event: KeyEvent) -> io::Result<()> {
+        self.push(event);
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An [InputSource] which replays key events at given times.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    events: VecDeque<(Duration, KeyEvent)>,
+    elapsed: Duration,
+}
+
+impl Recording {
+    /// Constructs a recording from key events, with the time (since the start) of each event.
+    pub fn new(events: impl IntoIterator<Item = (Duration, KeyEvent)>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl InputSource for Recording {
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    fn read_event(&mut self, timeout: Duration) -> io::Result<Option<KeyEvent>> {
+        match self.events.front() {
+            Some(&(time, event)) if time <= self.elapsed + timeout => {
+                self.events.pop_front();
+                self.elapsed = self.elapsed.max(time);
+                Ok(Some(event))
+            }
+            Some(_) => {
+                self.elapsed += timeout;
+                Ok(None)
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+/// Describes which of a keyboard's keys are used by the keymap.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Board {
+    /// The Linux key code for each keymap index.
+    pub keys: Vec<u16>,
+}
+
+impl Board {
+    /// Deserializes a board from its JSON serialization.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the keymap index for the given Linux key code, if the board uses that key.
+    pub fn keymap_index(&self, code: u16) -> Option<u16> {
+        self.keys
+            .iter()
+            .position(|&k| k == code)
+            .and_then(|idx| idx.try_into().ok())
+    }
+}
+
+/// Runs a keymap with key events from an [InputSource], writing its output to an [OutputSink].
+///
+/// Key events for keys which aren't on the [Board] are passed through to the sink.
+#[derive(Debug)]
+pub struct Daemon<I: Index<usize, Output = R>, R, Ctx, Ev: Debug, PKS, KS, S> {
+    keymap: Keymap<I, R, Ctx, Ev, PKS, KS, S>,
+    board: Board,
+    pressed_codes: Vec<u16>,
+    ticks: u64,
+}
+
+impl<
+        I: Debug + Index<usize, Output = R>,
+        R: Copy + Debug,
+        Ctx: Debug + key::Context<Event = Ev> + SetKeymapContext + ReportHints,
+        Ev: Copy + Debug,
+        PKS: Debug,
+        KS: Copy + Debug + From<key::NoOpKeyState>,
+        S: key::System<R, Ref = R, Context = Ctx, Event = Ev, PendingKeyState = PKS, KeyState = KS>,
+    > Daemon<I, R, Ctx, Ev, PKS, KS, S>
+{
+    /// Constructs a daemon for the given keymap and board.
+    pub fn new(keymap: Keymap<I, R, Ctx, Ev, PKS, KS, S>, board: Board) -> Self {
+        Self {
+            keymap,
+            board,
+            pressed_codes: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    /// The keymap being run.
+    pub fn keymap(&self) -> &Keymap<I, R, Ctx, Ev, PKS, KS, S> {
+        &self.keymap
+    }
+
+    /// Handles a key event from the keyboard.
+    pub fn handle_key_event(
+        &mut self,
+        event: KeyEvent,
+        sink: &mut impl OutputSink,
+    ) -> io::Result<()> {
+        match self.board.keymap_index(event.code) {
+            Some(keymap_index) if event.pressed => {
+                self.keymap
+                    .handle_input(input::Event::Press { keymap_index });
+                Ok(())
+            }
+            Some(keymap_index) => {
+                self.keymap
+                    .handle_input(input::Event::Release { keymap_index });
+                Ok(())
+            }
+            None => {
+                sink.write_key(event)?;
+                sink.sync()
+            }
+        }
+    }
+
+    /// Ticks the keymap (by 1 ms), writing changes to its output to the sink.
+    pub fn tick(&mut self, sink: &mut impl OutputSink) -> io::Result<()> {
+        self.keymap.tick();
+        self.ticks += 1;
+
+        let output = self.keymap.report_output();
+        self.write_output(&output, sink)
+    }
+
+    /// Runs the keymap until the source has no more events.
+    ///
+    /// The keymap is ticked for each millisecond elapsed by the source.
+    pub fn run(
+        &mut self,
+        source: &mut impl InputSource,
+        sink: &mut impl OutputSink,
+    ) -> io::Result<()> {
+        loop {
+            let next_tick = Duration::from_millis(self.ticks + 1);
+            let event = match source.read_event(next_tick.saturating_sub(source.elapsed())) {
+                Ok(event) => event,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            // Catch the keymap up to the time of the event before handling it.
+            let elapsed_ms = source.elapsed().as_millis();
+            while u128::from(self.ticks) < elapsed_ms {
+                self.tick(sink)?;
+            }
+
+            if let Some(event) = event {
+                self.handle_key_event(event, sink)?;
+            }
+        }
+    }
+
+    fn write_output(
+        &mut self,
+        output: &keymap::KeymapOutput,
+        sink: &mut impl OutputSink,
+    ) -> io::Result<()> {
+        let codes: Vec<u16> = output
+            .pressed_key_codes()
+            .into_iter()
+            .filter_map(keycodes::linux_key_code)
+            .collect();
+
+        if codes == self.pressed_codes {
+            return Ok(());
+        }
+
+        for &code in self.pressed_codes.iter().filter(|c| !codes.contains(c)) {
+            sink.write_key(KeyEvent::release(code))?;
+        }
+        for &code in codes.iter().filter(|c| !self.pressed_codes.contains(c)) {
+            sink.write_key(KeyEvent::press(code))?;
+        }
+        self.pressed_codes = codes;
+
+        sink.sync()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use smart_keymap::init::{Context, Event, KeyState, PendingKeyState, Ref, System};
+    use smart_keymap::init::{CONTEXT, SYSTEM};
+    use smart_keymap::key::keyboard;
+
+    const KEY_A: u16 = 30;
+    const KEY_B: u16 = 48;
+    const KEY_LEFTSHIFT: u16 = 42;
+    const KEY_ESC: u16 = 1;
+
+    type TestDaemon = Daemon<[Ref; 3], Ref, Context, Event, PendingKeyState, KeyState, System>;
+
+    /// A daemon with a 3-key board (A, B, LeftShift) and a keymap
+    ///  with keys B, A, LeftShift.
+    fn daemon() -> TestDaemon {
+        let key_refs = [
+            Ref::Keyboard(keyboard::Ref::KeyCode(0x05)),
+            Ref::Keyboard(keyboard::Ref::KeyCode(0x04)),
+            Ref::Keyboard(keyboard::Ref::KeyCode(0xE1)),
+        ];
+        let keymap = Keymap::new(key_refs, CONTEXT, SYSTEM);
+        let board = Board {
+            keys: vec![KEY_A, KEY_B, KEY_LEFTSHIFT],
+        };
+        Daemon::new(keymap, board)
+    }
+
+    #[test]
+    fn test_board_from_json() {
+        let board = Board::from_json(r#"{ "keys": [30, 48] }"#).unwrap();
+
+        assert_eq!(Some(0), board.keymap_index(KEY_A));
+        assert_eq!(Some(1), board.keymap_index(KEY_B));
+        assert_eq!(None, board.keymap_index(KEY_ESC));
+    }
+
+    #[test]
+    fn test_recording_reads_events_at_their_time() {
+        // Assemble
+        let mut recording = Recording::new([(Duration::from_millis(2), KeyEvent::press(KEY_A))]);
+
+        // Act
+        let first = recording.read_event(Duration::from_millis(1)).unwrap();
+        let second = recording.read_event(Duration::from_millis(1)).unwrap();
+        let third = recording.read_event(Duration::from_millis(1));
+
+        // Assert
+        assert_eq!(None, first);
+        assert_eq!(Some(KeyEvent::press(KEY_A)), second);
+        assert_eq!(Duration::from_millis(2), recording.elapsed());
+        assert_eq!(io::ErrorKind::UnexpectedEof, third.unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_daemon_writes_keymap_output() {
+        // Assemble
+        let mut daemon = daemon();
+        let mut sink: Vec<KeyEvent> = Vec::new();
+
+        // Act
+        daemon
+            .handle_key_event(KeyEvent::press(KEY_A), &mut sink)
+            .unwrap();
+        daemon.tick(&mut sink).unwrap();
+        daemon
+            .handle_key_event(KeyEvent::release(KEY_A), &mut sink)
+            .unwrap();
+        daemon.tick(&mut sink).unwrap();
+
+        // Assert - board key A is keymap index 0, which is keymap key B.
+        assert_eq!(vec![KeyEvent::press(KEY_B), KeyEvent::release(KEY_B)], sink);
+    }
+
+    #[test]
+    fn test_daemon_writes_modifier_output() {
+        // Assemble
+        let mut daemon = daemon();
+        let mut sink: Vec<KeyEvent> = Vec::new();
+
+        // Act
+        daemon
+            .handle_key_event(KeyEvent::press(KEY_LEFTSHIFT), &mut sink)
+            .unwrap();
+        daemon.tick(&mut sink).unwrap();
+
+        // Assert
+        assert_eq!(vec![KeyEvent::press(KEY_LEFTSHIFT)], sink);
+    }
+
+    #[test]
+    fn test_daemon_passes_through_keys_not_on_board() {
+        // Assemble
+        let mut daemon = daemon();
+        let mut sink: Vec<KeyEvent> = Vec::new();
+
+        // Act
+        daemon
+            .handle_key_event(KeyEvent::press(KEY_ESC), &mut sink)
+            .unwrap();
+        daemon
+            .handle_key_event(KeyEvent::release(KEY_ESC), &mut sink)
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![KeyEvent::press(KEY_ESC), KeyEvent::release(KEY_ESC)],
+            sink
+        );
+    }
+
+    #[test]
+    fn test_daemon_run_ticks_up_to_event_time_before_handling_event() {
+        // Assemble
+        let mut daemon = daemon();
+        let mut recording = Recording::new([
+            (Duration::from_millis(5), KeyEvent::press(KEY_A)),
+            (Duration::from_millis(5), KeyEvent::press(KEY_ESC)),
+            (Duration::from_millis(10), KeyEvent::release(KEY_ESC)),
+        ]);
+        let mut sink: Vec<KeyEvent> = Vec::new();
+
+        // Act
+        daemon.run(&mut recording, &mut sink).unwrap();
+
+        // Assert - the board key is handled at 5ms, so its output is written on the next tick,
+        //  after the passed-through key from the same time.
+        assert_eq!(
+            vec![
+                KeyEvent::press(KEY_ESC),
+                KeyEvent::press(KEY_B),
+                KeyEvent::release(KEY_ESC),
+            ],
+            sink
+        );
+    }
+
+    #[test]
+    fn test_daemon_runs_recording() {
+        // Assemble
+        let mut daemon = daemon();
+        let mut recording = Recording::new([
+            (Duration::from_millis(5), KeyEvent::press(KEY_LEFTSHIFT)),
+            (Duration::from_millis(10), KeyEvent::press(KEY_B)),
+            (Duration::from_millis(20), KeyEvent::release(KEY_B)),
+            (Duration::from_millis(25), KeyEvent::release(KEY_LEFTSHIFT)),
+            (Duration::from_millis(30), KeyEvent::press(KEY_ESC)),
+        ]);
+        let mut sink: Vec<KeyEvent> = Vec::new();
+
+        // Act
+        daemon.run(&mut recording, &mut sink).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                KeyEvent::press(KEY_LEFTSHIFT),
+                KeyEvent::press(KEY_A),
+                KeyEvent::release(KEY_A),
+                KeyEvent::release(KEY_LEFTSHIFT),
+                KeyEvent::press(KEY_ESC),
+            ],
+            sink
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Runs a smart keymap on a Linux host, reading from an evdev keyboard
+//!  and writing to a uinput virtual keyboard.
+//!
+//! e.g.:
+//!
+//! ```sh
+//! sudo smart-keymap-uinput \
+//!   --device /dev/input/by-path/platform-i8042-serio-0-event-kbd \
+//!   --board smart-keymap-uinput/examples/board-ansi-60.ncl \
+//!   --keymap smart-keymap-uinput/examples/keymap-ansi-60.ncl
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use clap::Parser;
+
+use smart_keymap_full_system_std::keymap_json::KeymapJson;
+use smart_keymap_nickel_helper::{
+    nickel_json_value_for_board_path, nickel_json_value_for_keymap, NickelError, NickelEvalInputs,
+};
+
+use smart_keymap_uinput::evdev::EvdevDevice;
+use smart_keymap_uinput::uinput::{DeviceConfig, UinputDevice};
+use smart_keymap_uinput::{Board, Daemon};
+
+/// Delay before grabbing the keyboard,
+///  so the host sees the release of the key used to start the daemon.
+const GRAB_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Parser)]
+#[command(about = "Runs a smart keymap on a Linux host, using evdev and uinput")]
+struct Args {
+    /// Path of the keyboard's evdev device (e.g. /dev/input/event3).
+    #[arg(long)]
+    device: PathBuf,
+
+    /// Path of the board .ncl, which maps the keyboard's key codes to keymap indices.
+    #[arg(long)]
+    board: PathBuf,
+
+    /// Path of the keymap .ncl.
+    #[arg(long)]
+    keymap: PathBuf,
+
+    /// Nickel import path (the smart-keymap `ncl/` directory).
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../ncl"))]
+    ncl_import_path: String,
+
+    /// Name of the virtual keyboard.
+    #[arg(long, default_value = "smart-keymap")]
+    name: String,
+
+    /// Don't grab the keyboard. (The host will also see the keyboard's key events).
+    #[arg(long)]
+    no_grab: bool,
+}
+
+fn nickel_error_message(e: NickelError) -> String {
+    match e {
+        NickelError::NickelNotFound => "`nickel` not found on PATH. Please install it.".into(),
+        NickelError::EvalError(message) => format!("error evaluating nickel:\n\n{}", message),
+        NickelError::Timeout { timeout_secs } => {
+            format!("nickel evaluation timed out after {}s", timeout_secs)
+        }
+    }
+}
+
+fn load_board(args: &Args) -> Result<Board, String> {
+    let json = nickel_json_value_for_board_path(NickelEvalInputs {
+        ncl_import_path: &args.ncl_import_path,
+        input_path: &args.board,
+    })
+    .map_err(nickel_error_message)?;
+    Board::from_json(&json).map_err(|e| format!("error deserializing board: {}", e))
+}
+
+fn load_keymap(args: &Args) -> Result<KeymapJson, String> {
+    let keymap_ncl = fs::read_to_string(&args.keymap)
+        .map_err(|e| format!("error reading {}: {}", args.keymap.display(), e))?;
+    let json = nickel_json_value_for_keymap(args.ncl_import_path.clone(), &keymap_ncl)
+        .map_err(nickel_error_message)?;
+    serde_json::from_str(&json).map_err(|e| format!("error deserializing keymap: {}", e))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let board = load_board(&args)?;
+    let keymap_json = load_keymap(&args)?;
+    if board.keys.len() > keymap_json.key_refs.len() {
+        return Err(format!(
+            "board has {} keys, but keymap only has {} keys",
+            board.keys.len(),
+            keymap_json.key_refs.len()
+        ));
+    }
+
+    let mut device = EvdevDevice::open(&args.device)
+        .map_err(|e| format!("error opening {}: {}", args.device.display(), e))?;
+    let mut uinput = UinputDevice::create(&DeviceConfig {
+        name: args.name.clone(),
+        ..DeviceConfig::default()
+    })
+    .map_err(|e| format!("error creating uinput device: {}", e))?;
+
+    if !args.no_grab {
+        thread::sleep(GRAB_DELAY);
+        device
+            .grab()
+            .map_err(|e| format!("error grabbing {}: {}", args.device.display(), e))?;
+    }
+
+    let mut daemon = Daemon::new(keymap_json.into_keymap(), board);
+    daemon
+        .run(&mut device, &mut uinput)
+        .map_err(|e| format!("error: {}", e))
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! [OutputSink] for a Linux `uinput` virtual keyboard.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::os::fd::AsRawFd;
+
+use crate::evdev::{input_event_bytes, EV_KEY, EV_SYN};
+use crate::{KeyEvent, OutputSink};
+
+/// Path of the uinput device node.
+pub const UINPUT_PATH: &str = "/dev/uinput";
+
+/// `_IO('U', 1)`
+const UI_DEV_CREATE: libc::Ioctl = 0x5501;
+/// `_IO('U', 2)`
+const UI_DEV_DESTROY: libc::Ioctl = 0x5502;
+/// `_IOW('U', 3, struct uinput_setup)`
+const UI_DEV_SETUP: libc::Ioctl = 0x405C_5503;
+/// `_IOW('U', 100, int)`
+const UI_SET_EVBIT: libc::Ioctl = 0x4004_5564;
+/// `_IOW('U', 101, int)`
+const UI_SET_KEYBIT: libc::Ioctl = 0x4004_5565;
+
+/// Bus type for virtual devices.
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// Sync event code which reports the preceding events.
+const SYN_REPORT: u16 = 0;
+
+/// Key codes the virtual keyboard can emit (up to `KEY_MAX`).
+///
+/// Skips the button codes (`BTN_MISC` to `BTN_GEAR_UP`, `BTN_DPAD_*`, and `BTN_TRIGGER_HAPPY*`),
+///  which would make the device look like a mouse or joystick.
+const KEY_CODE_RANGES: [RangeInclusive<u16>; 3] = [0x001..=0x0FF, 0x160..=0x21F, 0x224..=0x2BF];
+
+/// Device identity for the virtual keyboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceConfig {
+    /// The device's name.
+    pub name: String,
+    /// Vendor ID.
+    pub vendor_id: u16,
+    /// Product ID.
+    pub product_id: u16,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self {
+            name: "smart-keymap".to_string(),
+            vendor_id: 0xCAFE,
+            product_id: 0x0000,
+        }
+    }
+}
+
+/// A virtual keyboard, created using `/dev/uinput`.
+///
+/// The virtual keyboard is destroyed when dropped.
+#[derive(Debug)]
+pub struct UinputDevice {
+    file: File,
+}
+
+/// Converts the result of an `ioctl` call to an [io::Result].
+fn check(res: libc::c_int) -> io::Result<()> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+impl UinputDevice {
+    /// Creates a virtual keyboard.
+    pub fn create(config: &DeviceConfig) -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).open(UINPUT_PATH)?;
+
+        let fd = file.as_raw_fd();
+
+        // SAFETY: UI_SET_EVBIT and UI_SET_KEYBIT take an int argument, passed by value.
+        check(unsafe { libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_KEY)) })?;
+        for code in KEY_CODE_RANGES.into_iter().flatten() {
+            check(unsafe { libc::ioctl(fd, UI_SET_KEYBIT, libc::c_int::from(code)) })?;
+        }
+
+        let mut name = [0 as libc::c_char; libc::UINPUT_MAX_NAME_SIZE];
+        let name_bytes = config.name.as_bytes();
+        let name_len = name_bytes.len().min(libc::UINPUT_MAX_NAME_SIZE - 1);
+        for (dst, &src) in name.iter_mut().zip(&name_bytes[..name_len]) {
+            *dst = src as libc::c_char;
+        }
+        let setup = libc::uinput_setup {
+            id: libc::input_id {
+                bustype: BUS_VIRTUAL,
+                vendor: config.vendor_id,
+                product: config.product_id,
+                version: 1,
+            },
+            name,
+            ff_effects_max: 0,
+        };
+        // SAFETY: UI_DEV_SETUP takes a pointer to a uinput_setup; UI_DEV_CREATE takes no argument.
+        check(unsafe { libc::ioctl(fd, UI_DEV_SETUP, &setup as *const libc::uinput_setup) })?;
+        check(unsafe { libc::ioctl(fd, UI_DEV_CREATE) })?;
+
+        Ok(Self { file })
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        // SAFETY: UI_DEV_DESTROY takes no argument.
+        let _ = unsafe { libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY) };
+    }
+}
+
+impl OutputSink for UinputDevice {
+    fn write_key(&mut self, event: KeyEvent) -> io::Result<()> {
+        self.file
+            .write_all(&input_event_bytes(EV_KEY, event.code, event.pressed.into()))
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.file
+            .write_all(&input_event_bytes(EV_SYN, SYN_REPORT, 0))
+    }
+}
@@ -32,18 +32,34 @@
 //!  and `keymap_tick` functions.
 //! The `keymap_tick` function should be called every ms, and should copy the
 //!  HID keyboard report to the given buffer.
+//!
+//! ## Input Log
+//!
+//! The most recent input events are kept in an input log.
+//! Firmware can dump it with `keymap_input_log_encode` (e.g. when a misfire is noticed),
+//!  and the log replayed against the keymap with `smart-keymap-replay`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use smart_keymap::{input, key, keymap, new_keymap, split, Keymap};
+use smart_keymap::{input, input_log, key, keymap, new_keymap, raw_hid, split, Keymap};
+
+/// Length of a buffer for serializing/deserializing split keyboard messages.
+pub const MESSAGE_BUFFER_LEN: usize = 20;
 
-/// Length of a buffer for serializing/deserializing split keyboard events.
-pub const MESSAGE_BUFFER_LEN: usize = 4;
+const _: () = assert!(MESSAGE_BUFFER_LEN == split::BUFFER_SIZE);
 
 /// Callback ID for "reset keyboard"
 pub const KEYMAP_CALLBACK_RESET: u8 = 0;
 /// Callback ID for "enter bootloader mode"
 pub const KEYMAP_CALLBACK_BOOTLOADER: u8 = 1;
+/// Callback ID for "sequence mode armed"
+pub const KEYMAP_CALLBACK_SEQUENCE_ARMED: u8 = 2;
+/// Callback ID for "sequence step accepted"
+pub const KEYMAP_CALLBACK_SEQUENCE_CONTINUING: u8 = 3;
+/// Callback ID for "sequence mode ended without a sequence"
+pub const KEYMAP_CALLBACK_SEQUENCE_ABORTED: u8 = 4;
+/// Callback ID for "sequence mode ended by resolving a sequence"
+pub const KEYMAP_CALLBACK_SEQUENCE_RESOLVED: u8 = 5;
 
 /// Length of a KeymapHidReport.keyboard array.
 pub const KEYMAP_HID_REPORT_KEYBOARD_LEN: usize = 8;
@@ -54,6 +70,32 @@ pub const KEYMAP_HID_REPORT_CUSTOM_LEN: usize = 6;
 /// Length of a KeymapHidReport.consumer array.
 pub const KEYMAP_HID_REPORT_CONSUMER_LEN: usize = 4;
 
+/// Length of a buffer for a snapshot of the keymap's persistent state.
+pub const KEYMAP_PERSISTENT_STATE_LEN: usize = 10;
+
+/// Length of a KeymapHidReport.keyboard_nkro array.
+pub const KEYMAP_HID_REPORT_KEYBOARD_NKRO_LEN: usize = 30;
+
+/// Length of a raw HID report for editing the keymap.
+pub const KEYMAP_RAW_HID_REPORT_LEN: usize = 32;
+
+// cbindgen can't evaluate a path to another crate's const,
+//  so the FFI lengths are literals checked against the core consts.
+const _: () = assert!(KEYMAP_HID_REPORT_KEYBOARD_NKRO_LEN == keymap::HID_NKRO_KEYBOARD_REPORT_LEN);
+const _: () = assert!(KEYMAP_PERSISTENT_STATE_LEN == keymap::PERSISTENT_STATE_LEN);
+const _: () = assert!(KEYMAP_RAW_HID_REPORT_LEN == raw_hid::REPORT_LEN);
+
+/// Number of the most recent input events kept in the input log.
+const INPUT_LOG_CAPACITY: usize = 32;
+
+/// Maximum length of the encoded input log. (See `keymap_input_log_encode`).
+pub const KEYMAP_INPUT_LOG_MAX_LEN: usize = 777;
+
+const _: () = assert!(
+    KEYMAP_INPUT_LOG_MAX_LEN
+        == input_log::HEADER_LEN + INPUT_LOG_CAPACITY * input_log::MAX_RECORD_LEN
+);
+
 /// Input event type.
 #[repr(C)]
 pub enum KeymapInputEventType {
@@ -69,15 +111,37 @@ pub enum KeymapInputEventType {
 
 /// Input event.
 ///
-/// LIMITATION: split transport for virtual keys only works for basic key codes.
+/// For virtual key events, the low byte of `value` is the key code,
+///  and the high byte is the keyboard modifiers.
+///
+/// LIMITATION: virtual key events only carry keyboard key codes
+///  (not consumer, custom or mouse usages).
 #[repr(C)]
 pub struct KeymapInputEvent {
     /// Whether the event is a press or a release.
     pub event_type: KeymapInputEventType,
-    /// The keymap index of the event.
+    /// The keymap index of the event (or key output of a virtual key event).
     pub value: u16,
 }
 
+/// Converts the `value` of a virtual key event into its key output.
+fn virtual_key_output(value: u16) -> key::KeyOutput {
+    let [key_code, modifiers] = value.to_le_bytes();
+    key::KeyOutput::from_key_code_with_modifiers(
+        key_code,
+        key::KeyboardModifiers::from_byte(modifiers),
+    )
+}
+
+/// Converts the key output of a virtual key event into its `value`.
+fn virtual_key_value(key_output: key::KeyOutput) -> u16 {
+    let key_code = match key_output.key_code() {
+        key::KeyUsage::Keyboard(key_code) => key_code,
+        _ => 0x00,
+    };
+    u16::from_le_bytes([key_code, key_output.key_modifiers().as_byte()])
+}
+
 impl From<KeymapInputEvent> for input::Event {
     fn from(KeymapInputEvent { event_type, value }: KeymapInputEvent) -> Self {
         match event_type {
@@ -87,14 +151,12 @@ impl From<KeymapInputEvent> for input::Event {
             KeymapInputEventType::KeymapEventRelease => input::Event::Release {
                 keymap_index: value,
             },
-            KeymapInputEventType::KeymapEventVirtualPress => {
-                let key_output = key::KeyOutput::from_key_code(value as u8);
-                input::Event::VirtualKeyPress { key_output }
-            }
-            KeymapInputEventType::KeymapEventVirtualRelease => {
-                let key_output = key::KeyOutput::from_key_code(value as u8);
-                input::Event::VirtualKeyRelease { key_output }
-            }
+            KeymapInputEventType::KeymapEventVirtualPress => input::Event::VirtualKeyPress {
+                key_output: virtual_key_output(value),
+            },
+            KeymapInputEventType::KeymapEventVirtualRelease => input::Event::VirtualKeyRelease {
+                key_output: virtual_key_output(value),
+            },
         }
     }
 }
@@ -114,10 +176,44 @@ impl From<input::Event> for KeymapInputEvent {
                 event_type: KeymapInputEventType::KeymapEventRelease,
                 value,
             },
-            // LIMITATION: split transport for virtual keys not implemented
-            input::Event::VirtualKeyPress { .. } => todo!(),
-            // LIMITATION: split transport for virtual keys not implemented
-            input::Event::VirtualKeyRelease { .. } => todo!(),
+            input::Event::VirtualKeyPress { key_output } => KeymapInputEvent {
+                event_type: KeymapInputEventType::KeymapEventVirtualPress,
+                value: virtual_key_value(key_output),
+            },
+            input::Event::VirtualKeyRelease { key_output } => KeymapInputEvent {
+                event_type: KeymapInputEventType::KeymapEventVirtualRelease,
+                value: virtual_key_value(key_output),
+            },
+        }
+    }
+}
+
+/// Status of the primary half of a split keyboard, sent to the secondary half.
+#[repr(C)]
+pub struct KeymapSplitStatus {
+    /// Bitset of active layers (bit `i` = layer `i`).
+    pub active_layers: u32,
+    /// Whether Caps Word is active.
+    pub caps_word_active: bool,
+    /// Whether Key Lock is watching for the next key to lock.
+    pub key_lock_watching: bool,
+    /// Whether Key Lock has a key locked.
+    pub key_locked: bool,
+    /// The number of keys Key Lock has locked.
+    pub key_lock_count: u8,
+    /// The host's keyboard LED state (as in the HID keyboard output report).
+    pub host_leds: u8,
+}
+
+impl From<split::Status> for KeymapSplitStatus {
+    fn from(status: split::Status) -> Self {
+        KeymapSplitStatus {
+            active_layers: status.active_layers,
+            caps_word_active: status.caps_word_active,
+            key_lock_watching: status.key_lock_watching,
+            key_locked: status.key_locked,
+            key_lock_count: status.key_lock_count,
+            host_leds: status.host_leds.as_byte(),
         }
     }
 }
@@ -160,6 +256,10 @@ pub struct KeymapHidReport {
     pub consumer: [u8; KEYMAP_HID_REPORT_CONSUMER_LEN],
     /// HID mouse report.
     pub mouse: KeymapHidMouseReport,
+    /// HID N-key rollover keyboard report.
+    ///
+    /// A modifiers byte followed by a bitmap of keyboard usages `0x00..0xE8`.
+    pub keyboard_nkro: [u8; KEYMAP_HID_REPORT_KEYBOARD_NKRO_LEN],
 }
 
 impl KeymapHidReport {
@@ -178,6 +278,8 @@ impl KeymapHidReport {
         self.consumer[..consumer_len].copy_from_slice(&consumer_codes[..consumer_len]);
 
         self.mouse = keymap_output.pressed_mouse_output().into();
+
+        self.keyboard_nkro = keymap_output.as_hid_nkro_keyboard_report();
     }
 }
 
@@ -249,12 +351,24 @@ impl From<BluetoothProfileCommand> for keymap::BluetoothProfileCommand {
 
 static mut KEYMAP: Keymap = new_keymap();
 
+static mut SPLIT_TRANSMITTER: split::Transmitter = split::Transmitter::new();
+
+static mut SPLIT_RECEIVER: split::Receiver = split::Receiver::new();
+
+/// The split status most recently received from the primary half.
+static mut SPLIT_STATUS: split::Status = split::Status::new();
+
+/// The input events most recently registered with the keymap.
+static mut INPUT_LOG: input_log::InputLog<INPUT_LOG_CAPACITY> =
+    input_log::InputLog::new(smart_keymap::KEYMAP_BUILD_HASH);
+
 /// Initialize the global keymap instance.
 #[allow(static_mut_refs)]
 #[no_mangle]
 pub extern "C" fn keymap_init() {
     unsafe {
         KEYMAP.init();
+        INPUT_LOG.clear();
     }
 }
 
@@ -272,7 +386,9 @@ pub extern "C" fn keymap_set_ms_per_tick(ms_per_tick: u8) {
 #[no_mangle]
 pub extern "C" fn keymap_register_input_event(event: KeymapInputEvent) {
     unsafe {
-        KEYMAP.handle_input(event.into());
+        let event: input::Event = event.into();
+        KEYMAP.handle_input(event);
+        INPUT_LOG.record(KEYMAP.time_ms(), event);
     }
 }
 
@@ -296,6 +412,18 @@ pub extern "C" fn keymap_register_input_keyrelease(keymap_index: u16) {
     });
 }
 
+/// Register the host's keyboard LED state with the global keymap instance.
+///
+/// `leds` is the byte of the HID keyboard output report
+///  (bit 0 Num Lock, bit 1 Caps Lock, bit 2 Scroll Lock, ...).
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub extern "C" fn keymap_register_host_leds(leds: u8) {
+    unsafe {
+        KEYMAP.set_host_leds(keymap::HostLeds::from_byte(leds));
+    }
+}
+
 /// Run Keymap processing.
 ///
 /// Should be called every ms.
@@ -336,7 +464,9 @@ pub unsafe extern "C" fn keymap_register_input_after_ms(
     report: &mut KeymapHidReport,
 ) -> u32 {
     unsafe {
-        let next_ev = KEYMAP.handle_input_after_time(delta_ms, event.into());
+        let event: input::Event = event.into();
+        let next_ev = KEYMAP.handle_input_after_time(delta_ms, event);
+        INPUT_LOG.record(KEYMAP.time_ms(), event);
 
         let keymap_output = KEYMAP.report_output();
 
@@ -404,6 +534,10 @@ pub unsafe extern "C" fn keymap_clear_callbacks() {
 /// callback_id should be one of:
 /// - KEYMAP_CALLBACK_RESET
 /// - KEYMAP_CALLBACK_BOOTLOADER
+/// - KEYMAP_CALLBACK_SEQUENCE_ARMED
+/// - KEYMAP_CALLBACK_SEQUENCE_CONTINUING
+/// - KEYMAP_CALLBACK_SEQUENCE_ABORTED
+/// - KEYMAP_CALLBACK_SEQUENCE_RESOLVED
 ///
 /// # Safety
 ///
@@ -419,6 +553,18 @@ pub unsafe extern "C" fn keymap_register_callback(
         _ if callback_id == KEYMAP_CALLBACK_BOOTLOADER => {
             Some(keymap::KeymapCallback::ResetToBootloader)
         }
+        _ if callback_id == KEYMAP_CALLBACK_SEQUENCE_ARMED => Some(
+            keymap::KeymapCallback::Sequence(key::sequence::Feedback::Armed),
+        ),
+        _ if callback_id == KEYMAP_CALLBACK_SEQUENCE_CONTINUING => Some(
+            keymap::KeymapCallback::Sequence(key::sequence::Feedback::Continuing),
+        ),
+        _ if callback_id == KEYMAP_CALLBACK_SEQUENCE_ABORTED => Some(
+            keymap::KeymapCallback::Sequence(key::sequence::Feedback::Aborted),
+        ),
+        _ if callback_id == KEYMAP_CALLBACK_SEQUENCE_RESOLVED => Some(
+            keymap::KeymapCallback::Sequence(key::sequence::Feedback::Resolved),
+        ),
         _ => None,
     } {
         unsafe {
@@ -462,39 +608,217 @@ pub unsafe extern "C" fn keymap_register_bluetooth_callback(
     }
 }
 
-/// Serializes the given event into the given buffer.
+/// Copies a snapshot of the keymap's persistent state
+///  (e.g. toggled layers, default layer) into the given buffer.
+///
+/// Firmware can store this (e.g. in flash/EEPROM),
+///  and reapply it with `keymap_restore_persistent_state` after `keymap_init`.
 ///
 /// # Safety
 ///
-/// `buf` must point to a buffer large enough for the serialized message.
+/// `buf` must point to a buffer of at least `KEYMAP_PERSISTENT_STATE_LEN` bytes.
+#[allow(static_mut_refs)]
 #[no_mangle]
-pub unsafe extern "C" fn keymap_serialize_event(buf: *mut u8, event: KeymapInputEvent) {
+pub unsafe extern "C" fn keymap_export_persistent_state(buf: *mut u8) {
     unsafe {
-        let message = split::Message::new(event.into());
-        let message_bytes = message.serialize();
-        core::ptr::copy_nonoverlapping(message_bytes.as_ptr(), buf, message_bytes.len());
+        let state_bytes: [u8; KEYMAP_PERSISTENT_STATE_LEN] = KEYMAP.persistent_state().to_bytes();
+        core::ptr::copy_nonoverlapping(state_bytes.as_ptr(), buf, state_bytes.len());
     }
 }
 
-/// Deserializes the given bytes into the given pointer;
-/// returns true if successful, false if fails.
+/// Restores the keymap's persistent state from the given buffer;
+/// returns true if successful, false if the buffer isn't a valid snapshot.
+///
+/// # Safety
+///
+/// `buf` must point to a buffer of at least `KEYMAP_PERSISTENT_STATE_LEN` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_restore_persistent_state(buf: *const u8) -> bool {
+    unsafe {
+        let state_bytes = core::slice::from_raw_parts(buf, KEYMAP_PERSISTENT_STATE_LEN);
+        match keymap::PersistentState::from_bytes(state_bytes) {
+            Some(state) => {
+                KEYMAP.restore_persistent_state(&state);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Handles a raw HID report (for editing the keymap) received from the host,
+/// and writes the report to send back into `response`.
+///
+/// # Safety
+///
+/// `report` and `response` must each point to a buffer of at least
+///  `KEYMAP_RAW_HID_REPORT_LEN` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_raw_hid_receive(report: *const u8, response: *mut u8) {
+    unsafe {
+        let report = core::slice::from_raw_parts(report, KEYMAP_RAW_HID_REPORT_LEN);
+        let response_report: [u8; KEYMAP_RAW_HID_REPORT_LEN] =
+            raw_hid::handle_report(&mut KEYMAP, report);
+        core::ptr::copy_nonoverlapping(response_report.as_ptr(), response, response_report.len());
+    }
+}
+
+/// Serializes the given event into the given buffer;
+/// returns true if the event was serialized, false otherwise.
+///
+/// # Safety
+///
+/// `buf` must point to a buffer of at least `MESSAGE_BUFFER_LEN` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_serialize_event(buf: *mut u8, event: KeymapInputEvent) -> bool {
+    unsafe {
+        match SPLIT_TRANSMITTER.serialize(split::Payload::InputEvent(event.into())) {
+            Ok(message_bytes) => {
+                core::ptr::copy_nonoverlapping(message_bytes.as_ptr(), buf, message_bytes.len());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Serializes the keymap's split status (active layers, host LEDs, etc.)
+///  into the given buffer, for sending from the primary half to the secondary half;
+/// returns true if the status was serialized, false otherwise.
+///
+/// # Safety
+///
+/// `buf` must point to a buffer of at least `MESSAGE_BUFFER_LEN` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_serialize_split_status(buf: *mut u8) -> bool {
+    unsafe {
+        let status = KEYMAP.split_status();
+        match SPLIT_TRANSMITTER.serialize(split::Payload::Status(status)) {
+            Ok(message_bytes) => {
+                core::ptr::copy_nonoverlapping(message_bytes.as_ptr(), buf, message_bytes.len());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Receives a byte from the other half, deserializing a completed message
+///  into the given pointer;
+/// returns true if an input event was received, false otherwise.
+///
+/// A received split status is stored, and can be read with `keymap_split_status`.
+///
+/// Dropped messages and corrupted frames are counted,
+///  see `keymap_split_dropped_messages` and `keymap_split_corrupted_frames`.
 ///
 /// # Safety
 ///
 /// `event` must be a valid, aligned pointer to a writable [KeymapInputEvent].
+#[allow(static_mut_refs)]
 #[no_mangle]
-pub unsafe extern "C" fn keymap_message_buffer_receive_byte(
-    buf: &mut [u8; MESSAGE_BUFFER_LEN],
+pub unsafe extern "C" fn keymap_split_receive_byte(
     recv_byte: u8,
     event: *mut KeymapInputEvent,
 ) -> bool {
-    let res = split::receive_byte(buf, recv_byte);
-    match res {
-        Ok(message) => {
-            *event = message.input_event.into();
-            true
+    unsafe {
+        match SPLIT_RECEIVER.receive_byte(recv_byte) {
+            Some(split::Message {
+                payload: split::Payload::InputEvent(input_event),
+                ..
+            }) => {
+                *event = input_event.into();
+                true
+            }
+            Some(split::Message {
+                payload: split::Payload::Status(status),
+                ..
+            }) => {
+                SPLIT_STATUS = status;
+                false
+            }
+            None => false,
         }
-        Err(_) => false,
+    }
+}
+
+/// The number of messages from the other half which were missed,
+///  according to gaps in their sequence numbers.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub extern "C" fn keymap_split_dropped_messages() -> u32 {
+    unsafe { SPLIT_RECEIVER.dropped_messages() }
+}
+
+/// The number of frames from the other half which were rejected
+///  for a bad CRC, version, or payload.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub extern "C" fn keymap_split_corrupted_frames() -> u32 {
+    unsafe { SPLIT_RECEIVER.corrupted_frames() }
+}
+
+/// Copies the split status most recently received from the primary half
+///  into the given pointer.
+///
+/// # Safety
+///
+/// `status` must be a valid, aligned pointer to a writable [KeymapSplitStatus].
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_split_status(status: *mut KeymapSplitStatus) {
+    unsafe {
+        *status = SPLIT_STATUS.into();
+    }
+}
+
+/// Copies up to `len` bytes of the keymap's buffered serial output
+///  (e.g. steno protocol packets) into the given buffer;
+///  returns the number of bytes copied.
+///
+/// Firmware should write these bytes to its serial port (e.g. USB CDC ACM).
+///
+/// # Safety
+///
+/// `buf` must point to a buffer of at least `len` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_read_serial_output(buf: *mut u8, len: usize) -> usize {
+    unsafe {
+        let buf = core::slice::from_raw_parts_mut(buf, len);
+        KEYMAP.read_serial_output(buf)
+    }
+}
+
+/// Encodes the log of the most recent input events into the given buffer
+///  (e.g. to dump over a debug channel when a misfire is noticed);
+///  returns the number of bytes written, or 0 if the buffer is too small.
+///
+/// The log can be replayed against the keymap with `smart-keymap-replay`.
+/// The encoded log is at most `KEYMAP_INPUT_LOG_MAX_LEN` bytes.
+///
+/// # Safety
+///
+/// `buf` must point to a buffer of at least `len` bytes.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub unsafe extern "C" fn keymap_input_log_encode(buf: *mut u8, len: usize) -> usize {
+    unsafe {
+        let buf = core::slice::from_raw_parts_mut(buf, len);
+        INPUT_LOG.encode(buf).unwrap_or(0)
+    }
+}
+
+/// Clears the log of input events.
+#[allow(static_mut_refs)]
+#[no_mangle]
+pub extern "C" fn keymap_input_log_clear() {
+    unsafe {
+        INPUT_LOG.clear();
     }
 }
 
@@ -63,14 +63,23 @@
 #[doc(inline)]
 pub use smart_keymap_core::input;
 #[doc(inline)]
+pub use smart_keymap_core::input_log;
+#[doc(inline)]
 pub use smart_keymap_core::key;
 #[doc(inline)]
 pub use smart_keymap_core::keymap;
 #[doc(inline)]
+pub use smart_keymap_core::raw_hid;
+#[doc(inline)]
 pub use smart_keymap_core::slice;
 #[doc(inline)]
 pub use smart_keymap_core::split;
 
+// `keymap!` derives the aggregate `Ref` / `Config` through this,
+// so call sites don't need their own serde dependency.
+#[doc(hidden)]
+pub use serde;
+
 // Generated modules and the default `init` shell refer to engine paths as
 // `smart_keymap::…`. Inside this package that name is this crate.
 extern crate self as smart_keymap;
@@ -87,6 +96,12 @@ pub mod init {
     /// Number of instructions used by the [crate::key::automation] implementation.
     pub const AUTOMATION_INSTRUCTION_COUNT: usize = 1024;
 
+    /// Number of slots for the [crate::key::dynamic_macro] implementation.
+    pub const DYNAMIC_MACRO_SLOT_COUNT: usize = 2;
+
+    /// Number of instructions each [crate::key::dynamic_macro] slot can record.
+    pub const DYNAMIC_MACRO_BUFFER_SIZE: usize = 64;
+
     /// Number of alternate-repeat rules for the [crate::key::history] implementation.
     ///
     /// Generous default for the full-system / cucumber shell; per-keymap codegen
@@ -120,8 +135,20 @@ pub mod init {
     /// The maximum number of sequences sharing a primary key.
     pub const SEQUENCE_MAX_OVERLAPPING: usize = 4;
 
-    /// The tap-dance definitions.
-    pub const TAP_DANCE_MAX_DEFINITIONS: usize = 3;
+    /// Number of dictionary entries used by the [crate::key::steno] implementation.
+    pub const STENO_DICTIONARY_ENTRY_COUNT: usize = 64;
+
+    /// Number of instructions used by the [crate::key::steno] implementation.
+    pub const STENO_INSTRUCTION_COUNT: usize = 256;
+
+    /// The maximum number of tap (or hold) definitions of a tap-dance key.
+    ///
+    /// Generous default for the full-system / cucumber shell; per-keymap codegen
+    /// uses the largest count from the keymap.
+    pub const TAP_DANCE_MAX_DEFINITIONS: usize = 8;
+
+    /// Number of texts used by the [crate::key::unicode] implementation.
+    pub const UNICODE_TEXT_COUNT: usize = 16;
 
     /// Trivial composite shell: keyboard family only (matches codegen shape).
     pub mod key_system {
@@ -200,6 +227,12 @@ pub mod init {
 
         impl keymap::ReportHints for Context {}
 
+        impl keymap::PersistentContext for Context {}
+
+        impl keymap::SplitStatusContext for Context {}
+
+        impl keymap::KeyOverridesContext for Context {}
+
         /// Aggregate event.
         #[derive(Debug, Clone, Copy, PartialEq)]
         pub enum Event {
@@ -398,6 +431,8 @@ include!(concat!(env!("OUT_DIR"), "/keymap.rs"));
 
 pub use init::{Keymap, CONTEXT, KEY_REFS, SYSTEM};
 
+include!(concat!(env!("OUT_DIR"), "/keymap_build_hash.rs"));
+
 /// Constructs a new keymap.
 pub const fn new_keymap() -> Keymap {
     Keymap::new(KEY_REFS, CONTEXT, SYSTEM)
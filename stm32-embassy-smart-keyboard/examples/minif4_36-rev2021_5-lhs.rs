@@ -361,6 +361,9 @@ async fn keyboard_backend(
             BackendMessage::Event(event) => {
                 backend.event(event);
             }
+            BackendMessage::SplitStatus(status) => {
+                backend.apply_split_status(&status);
+            }
             BackendMessage::Tick => {
                 if report_success {
                     backend.tick();
@@ -480,7 +483,7 @@ impl<'d> TransportReader<'d> {
         if self.uart.read(&mut buf).await.is_ok() {
             Message::deserialize(&buf)
                 .ok()
-                .map(|Message { input_event }: Message| input_event)
+                .and_then(|message| message.input_event())
         } else {
             None
         }
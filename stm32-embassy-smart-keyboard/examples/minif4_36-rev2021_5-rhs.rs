@@ -12,7 +12,7 @@ use panic_halt as _;
 use keyberon_smart_keyboard::input::smart_keymap::keymap_index_of;
 use keyberon_smart_keyboard::input::MatrixScanner;
 
-use smart_keymap::split::Message;
+use smart_keymap::split::{Payload, Transmitter};
 
 use board::KEYMAP_INDICES;
 
@@ -216,16 +216,17 @@ async fn main(_spawner: Spawner) {
         panic!("uart init failed");
     };
     let (mut tx, _) = usart.split();
+    let mut transmitter = Transmitter::new();
 
     loop {
         Timer::after_millis(1).await;
 
         for event in keyboard.events() {
             if let Some(input_event) = keymap_index_of(&KEYMAP_INDICES, event) {
-                let message = Message { input_event };
-                let buf = message.serialize();
-                let _ = tx.write(&buf).await;
-                let _ = tx.flush().await;
+                if let Ok(buf) = transmitter.serialize(Payload::InputEvent(input_event)) {
+                    let _ = tx.write(&buf).await;
+                    let _ = tx.flush().await;
+                }
             }
         }
     }
@@ -91,7 +91,7 @@ mod app {
 
     use keyberon_smart_keyboard::input::smart_keymap::keymap_index_of;
     use keyberon_smart_keyboard::input::smart_keymap::KeyboardBackend;
-    use smart_keymap::split::BUFFER_SIZE;
+    use smart_keymap::split::{Payload, Status};
 
     use stm32f4_rtic_smart_keyboard::split::app_prelude::*;
 
@@ -104,6 +104,7 @@ mod app {
     struct SharedResources {
         usb_dev: UsbDevice,
         usb_class: UsbClass,
+        pending_split_status: Option<Status>,
     }
 
     #[local]
@@ -113,13 +114,13 @@ mod app {
         report_success: bool,
         timer: timer::CounterUs<pac::TIM3>,
         previous_consumer: MultipleConsumerReport,
+        previous_split_status: Status,
         split_conn_tx: TransportWriter,
         split_conn_rx: TransportReader,
     }
 
     #[init(local = [
         ep_memory: [u32; 1024] = [0; 1024],
-        rx_buf: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE],
         usb_bus: Option<UsbBusAllocator<UsbBusType>> = None
     ])]
     fn init(c: init::Context) -> (SharedResources, LocalResources, init::Monotonics) {
@@ -165,15 +166,15 @@ mod app {
 
         let backend = KeyboardBackend::new();
 
-        let (split_conn_tx, split_conn_rx) = split_app_init::init_serial(
-            &clocks,
-            (gpiob.pb6, gpiob.pb7),
-            c.device.USART1,
-            c.local.rx_buf,
-        );
+        let (split_conn_tx, split_conn_rx) =
+            split_app_init::init_serial(&clocks, (gpiob.pb6, gpiob.pb7), c.device.USART1);
 
         (
-            SharedResources { usb_dev, usb_class },
+            SharedResources {
+                usb_dev,
+                usb_class,
+                pending_split_status: None,
+            },
             LocalResources {
                 timer,
                 keyboard,
@@ -182,6 +183,7 @@ mod app {
                 previous_consumer: MultipleConsumerReport {
                     codes: [Consumer::Unassigned; 4],
                 },
+                previous_split_status: Status::new(),
                 split_conn_rx,
                 split_conn_tx,
             },
@@ -192,8 +194,14 @@ mod app {
     #[task(binds = USART1, priority = 5, local = [split_conn_rx])]
     fn rx(c: rx::Context) {
         let rx::LocalResources { split_conn_rx } = c.local;
-        if let Some(event) = split_conn_rx.read() {
-            let _ = layout::spawn(BackendMessage::Event(event));
+        match split_conn_rx.read() {
+            Some(Payload::InputEvent(event)) => {
+                let _ = layout::spawn(BackendMessage::Event(event));
+            }
+            Some(Payload::Status(status)) => {
+                let _ = layout::spawn(BackendMessage::SplitStatus(status));
+            }
+            None => {}
         }
     }
 
@@ -209,16 +217,18 @@ mod app {
         (usb_dev, usb_class).lock(usb_poll);
     }
 
-    #[task(priority = 3, capacity = 8, shared = [usb_class, usb_dev], local = [backend, report_success, previous_consumer])]
+    #[task(priority = 3, capacity = 8, shared = [usb_class, usb_dev, pending_split_status], local = [backend, report_success, previous_consumer, previous_split_status])]
     fn layout(c: layout::Context, message: BackendMessage) {
         let layout::SharedResources {
             mut usb_class,
             mut usb_dev,
+            mut pending_split_status,
         } = c.shared;
         let layout::LocalResources {
             backend,
             report_success,
             previous_consumer,
+            previous_split_status,
         } = c.local;
         match message {
             BackendMessage::Tick => {
@@ -230,6 +240,14 @@ mod app {
                     return;
                 }
 
+                // Only the half connected to the host (the primary half)
+                //  sends its status to the other half.
+                let split_status = backend.split_status();
+                if split_status != *previous_split_status {
+                    *previous_split_status = split_status;
+                    pending_split_status.lock(|s| *s = Some(split_status));
+                }
+
                 usb_class.lock(|k| {
                     let res = k.device::<NKROBootKeyboard<'_, _>, _>().write_report(
                         backend
@@ -288,11 +306,17 @@ mod app {
             BackendMessage::Event(event) => {
                 backend.event(event);
             }
+            BackendMessage::SplitStatus(status) => {
+                backend.apply_split_status(&status);
+            }
         };
     }
 
-    #[task(binds = TIM3, priority = 1, local = [keyboard, timer, split_conn_tx])]
+    #[task(binds = TIM3, priority = 1, shared = [pending_split_status], local = [keyboard, timer, split_conn_tx])]
     fn tick(c: tick::Context) {
+        let tick::SharedResources {
+            mut pending_split_status,
+        } = c.shared;
         let tick::LocalResources {
             keyboard,
             split_conn_tx,
@@ -308,6 +332,10 @@ mod app {
             }
         }
 
+        if let Some(status) = pending_split_status.lock(Option::take) {
+            split_conn_tx.write_status(status);
+        }
+
         let _ = layout::spawn(BackendMessage::Tick);
     }
 }
@@ -10,7 +10,7 @@ use hal::{
     Listen,
 };
 
-use smart_keymap::split::BUFFER_SIZE;
+use smart_keymap::split::{Receiver, Transmitter};
 
 use crate::split::transport::{TransportReader, TransportWriter};
 
@@ -18,7 +18,6 @@ pub fn init_serial(
     clocks: &Clocks,
     (pb6, pb7): (gpiob::PB6, gpiob::PB7),
     usart1: USART1,
-    buf: &'static mut [u8; BUFFER_SIZE],
 ) -> (TransportWriter, TransportReader) {
     let pins = (pb6.into_alternate(), pb7.into_alternate());
     let Ok(mut serial) = Serial::new(
@@ -32,5 +31,14 @@ pub fn init_serial(
     serial.listen(Event::RxNotEmpty);
 
     let (tx, rx) = serial.split();
-    (TransportWriter { tx }, TransportReader { buf, rx })
+    (
+        TransportWriter {
+            tx,
+            transmitter: Transmitter::new(),
+        },
+        TransportReader {
+            receiver: Receiver::new(),
+            rx,
+        },
+    )
 }
@@ -10,33 +10,43 @@ use hal::{
 
 use smart_keymap::input::Event;
 
-use smart_keymap::split::{receive_byte, Message, BUFFER_SIZE};
+use smart_keymap::split::{Payload, Receiver, Status, Transmitter};
 
 pub struct TransportReader {
-    pub buf: &'static mut [u8; BUFFER_SIZE],
+    pub receiver: Receiver,
     pub rx: Rx<USART1>,
 }
 
 pub struct TransportWriter {
     pub tx: Tx<USART1>,
+    pub transmitter: Transmitter,
 }
 
 impl TransportReader {
-    pub fn read(&mut self) -> Option<Event> {
+    pub fn read(&mut self) -> Option<Payload> {
         self.rx
             .read()
             .ok()
-            .and_then(|b: u8| receive_byte(self.buf, b).ok())
-            .map(|Message { input_event }: Message| input_event)
+            .and_then(|b: u8| self.receiver.receive_byte(b))
+            .map(|message| message.payload)
     }
 }
 
 impl TransportWriter {
     pub fn write(&mut self, input_event: Event) {
-        let message = Message { input_event };
-        for b in message.serialize() {
-            let _ = block!(self.tx.write(b));
+        self.send(Payload::InputEvent(input_event));
+    }
+
+    pub fn write_status(&mut self, status: Status) {
+        self.send(Payload::Status(status));
+    }
+
+    fn send(&mut self, payload: Payload) {
+        if let Ok(message_bytes) = self.transmitter.serialize(payload) {
+            for b in message_bytes {
+                let _ = block!(self.tx.write(b));
+            }
+            let _ = block!(self.tx.flush());
         }
-        let _ = block!(self.tx.flush());
     }
 }
@@ -162,6 +162,46 @@ void test_keyboard_double_keypress(void) {
   TEST_ASSERT_EQUAL_UINT8_ARRAY(expected_report, actual_report->keyboard, 8);
 }
 
+void test_keyboard_input_log_encodes_registered_inputs(void) {
+  uint8_t expected_magic[5] = {'S', 'K', 'I', 'L', 1};
+  // delta_ms, event (0: press, 1: release), keymap_index
+  uint8_t expected_records[6] = {0, 0, KM_KEY_A, 1, 1, KM_KEY_A};
+  uint8_t log[KEYMAP_INPUT_LOG_MAX_LEN] = {};
+  KeymapHidReport report = {};
+
+  // assemble: init keymap
+  keymap_init();
+
+  // act: tap A, then encode the input log
+  keymap_register_input_event((struct KeymapInputEvent){
+      .event_type = KeymapEventPress, .value = KM_KEY_A});
+  keymap_tick(&report);
+  keymap_register_input_event((struct KeymapInputEvent){
+      .event_type = KeymapEventRelease, .value = KM_KEY_A});
+  keymap_tick(&report);
+  size_t len = keymap_input_log_encode(log, sizeof(log));
+
+  // assert: header (magic, version, build hash), then the press and release
+  TEST_ASSERT_EQUAL_UINT32(9 + 6, len);
+  TEST_ASSERT_EQUAL_UINT8_ARRAY(expected_magic, log, 5);
+  TEST_ASSERT_EQUAL_UINT8_ARRAY(expected_records, log + 9, 6);
+}
+
+void test_keyboard_input_log_encode_needs_room_for_log(void) {
+  uint8_t log[4] = {};
+
+  // assemble: init keymap
+  keymap_init();
+
+  // act: press A, then encode the input log into a too-small buffer
+  keymap_register_input_event((struct KeymapInputEvent){
+      .event_type = KeymapEventPress, .value = KM_KEY_A});
+  size_t len = keymap_input_log_encode(log, sizeof(log));
+
+  // assert: nothing written
+  TEST_ASSERT_EQUAL_UINT32(0, len);
+}
+
 #else
 #error "requires SUITE_KEYBOARD"
 #endif
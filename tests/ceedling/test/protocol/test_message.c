@@ -1,3 +1,4 @@
+#include <stdbool.h>
 #include <stdint.h>
 
 #include "unity.h"
@@ -8,18 +9,22 @@ void setUp(void) {}
 
 void tearDown(void) {}
 
-void test_keymap_serialise_event_press(void) {
-  uint8_t expected_message[4] = {0x01, 0x02, 0x04, 0x00};
-
+void test_keymap_serialise_event_press_is_terminated_frame(void) {
   KeymapInputEvent event = {
       .event_type = KeymapEventPress,
       .value = 4,
   };
-  uint8_t actual_message[4] = {0};
+  uint8_t actual_message[MESSAGE_BUFFER_LEN] = {0};
+
+  bool serialized = keymap_serialize_event(actual_message, event);
 
-  keymap_serialize_event(actual_message, event);
+  TEST_ASSERT_TRUE(serialized);
 
-  TEST_ASSERT_EQUAL_UINT8_ARRAY(expected_message, actual_message, 4);
+  // COBS-encoded frame: no zero bytes until the terminator.
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN - 1; i++) {
+    TEST_ASSERT_NOT_EQUAL_UINT8(0x00, actual_message[i]);
+  }
+  TEST_ASSERT_EQUAL_UINT8(0x00, actual_message[MESSAGE_BUFFER_LEN - 1]);
 }
 
 void test_keymap_deserialise_event_press(void) {
@@ -28,16 +33,110 @@ void test_keymap_deserialise_event_press(void) {
       .event_type = KeymapEventPress,
       .value = 4,
   };
-  uint8_t input[4] = {0x01, 0x02, 0x04, 0x00};
-  uint8_t buf[4] = {0};
+  uint8_t input[MESSAGE_BUFFER_LEN] = {0};
+  keymap_serialize_event(input, expected_event);
+  KeymapInputEvent actual_event = {0};
+  bool received_event = false;
+
+  // act: feed serialized bytes one at a time
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    received_event = keymap_split_receive_byte(input[i], &actual_event);
+  }
+
+  // assert: reconstructed event matches original
+  TEST_ASSERT_TRUE(received_event);
+  TEST_ASSERT_EQUAL_MEMORY(&expected_event, &actual_event,
+                           sizeof(KeymapInputEvent));
+}
+
+void test_keymap_deserialise_virtual_event_with_modifiers(void) {
+  // assemble: virtual press of LCtrl+A (key code 0x04, modifiers 0x01)
+  KeymapInputEvent expected_event = {
+      .event_type = KeymapEventVirtualPress,
+      .value = 0x0104,
+  };
+  uint8_t input[MESSAGE_BUFFER_LEN] = {0};
+  keymap_serialize_event(input, expected_event);
   KeymapInputEvent actual_event = {0};
+  bool received_event = false;
 
   // act: feed serialized bytes one at a time
-  for (uint8_t i = 0; i < 4; i++) {
-    keymap_message_buffer_receive_byte(&buf, input[i], &actual_event);
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    received_event = keymap_split_receive_byte(input[i], &actual_event);
   }
 
   // assert: reconstructed event matches original
+  TEST_ASSERT_TRUE(received_event);
   TEST_ASSERT_EQUAL_MEMORY(&expected_event, &actual_event,
                            sizeof(KeymapInputEvent));
 }
+
+void test_keymap_deserialise_rejects_corrupted_byte(void) {
+  // assemble: serialized event, with a corrupted byte
+  KeymapInputEvent event = {
+      .event_type = KeymapEventPress,
+      .value = 4,
+  };
+  uint8_t input[MESSAGE_BUFFER_LEN] = {0};
+  keymap_serialize_event(input, event);
+  input[4] ^= (input[4] == 0x01) ? 0x02 : 0x01;
+  KeymapInputEvent actual_event = {0};
+  bool received_event = false;
+
+  // act: feed serialized bytes one at a time
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    received_event |= keymap_split_receive_byte(input[i], &actual_event);
+  }
+
+  // assert: no event received
+  TEST_ASSERT_FALSE(received_event);
+}
+
+void test_keymap_split_status_is_received(void) {
+  // assemble: host has Caps Lock on
+  keymap_init();
+  keymap_register_host_leds(0x02);
+  uint8_t input[MESSAGE_BUFFER_LEN] = {0};
+  keymap_serialize_split_status(input);
+  KeymapInputEvent actual_event = {0};
+  bool received_event = false;
+  KeymapSplitStatus actual_status = {0};
+
+  // act: feed serialized bytes one at a time
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    received_event |= keymap_split_receive_byte(input[i], &actual_event);
+  }
+  keymap_split_status(&actual_status);
+
+  // assert: status received (not as an input event)
+  TEST_ASSERT_FALSE(received_event);
+  TEST_ASSERT_EQUAL_UINT8(0x02, actual_status.host_leds);
+}
+
+void test_keymap_split_counts_dropped_message(void) {
+  // assemble: three serialized events, the second of which is never sent
+  KeymapInputEvent event = {
+      .event_type = KeymapEventPress,
+      .value = 4,
+  };
+  uint8_t first[MESSAGE_BUFFER_LEN] = {0};
+  uint8_t dropped[MESSAGE_BUFFER_LEN] = {0};
+  uint8_t last[MESSAGE_BUFFER_LEN] = {0};
+  keymap_serialize_event(first, event);
+  keymap_serialize_event(dropped, event);
+  keymap_serialize_event(last, event);
+  KeymapInputEvent actual_event = {0};
+
+  // act: feed the first and last messages one byte at a time
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    keymap_split_receive_byte(first[i], &actual_event);
+  }
+  uint32_t dropped_before = keymap_split_dropped_messages();
+  for (uint8_t i = 0; i < MESSAGE_BUFFER_LEN; i++) {
+    keymap_split_receive_byte(last[i], &actual_event);
+  }
+
+  // assert: the gap in sequence numbers is counted
+  TEST_ASSERT_EQUAL_UINT32(dropped_before + 1,
+                           keymap_split_dropped_messages());
+}
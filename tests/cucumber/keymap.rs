@@ -7,7 +7,6 @@ use cucumber::{given, then, when, World};
 use serde::Deserialize;
 
 use smart_keymap::input;
-use smart_keymap::key;
 use smart_keymap::keymap;
 
 use smart_keymap_nickel_helper::{
@@ -17,15 +16,10 @@ use smart_keymap_nickel_helper::{
 
 // Full composite key system from smart-keymap-full-system-std (Vec storage).
 use smart_keymap_full_system_std::key_system::{
-    Config, Context, Event, KeyState, PendingKeyState, Ref, System,
+    Context, Event, KeyState, PendingKeyState, Ref, System,
 };
 
-use smart_keymap_full_system_std::init::CHORDED_MAX_CHORDS;
-use smart_keymap_full_system_std::init::CHORDED_MAX_CHORD_SIZE;
-use smart_keymap_full_system_std::init::CHORDED_MAX_OVERLAPPING_CHORD_SIZE;
-use smart_keymap_full_system_std::init::LAYERED_LAYER_COUNT;
-use smart_keymap_full_system_std::init::SEQUENCE_MAX_OVERLAPPING;
-use smart_keymap_full_system_std::init::TAP_DANCE_MAX_DEFINITIONS as TAP_DANCE_MAX_DEFS;
+use smart_keymap_full_system_std::keymap_json::{keymap_from_json, Keymap};
 
 /// Workspace root (parent of the `smart-keymap-full-system-std` package).
 fn workspace_root() -> PathBuf {
@@ -39,12 +33,9 @@ fn ncl_import_path() -> String {
     workspace_root().join("ncl").to_string_lossy().into_owned()
 }
 
-type Keymap = keymap::Keymap<Vec<Ref>, Ref, Context, Event, PendingKeyState, KeyState, System>;
 type ObservedKeymap =
     keymap::ObservedKeymap<Vec<Ref>, Ref, Context, Event, PendingKeyState, KeyState, System>;
 
-const CHORDED_MAX_PRESSED_INDICES: usize = CHORDED_MAX_CHORD_SIZE * 2;
-
 /// Keymap with basic keycodes, useful for the "check report equivalences" step.
 const TEST_KEYMAP_NCL: &str = r#"
   let K = import "keys.ncl" in
@@ -62,13 +53,15 @@ const TEST_KEYMAP_NCL: &str = r#"
 #[derive(Debug)]
 enum LoadedKeymap {
     NoKeymap,
-    Keymap { keymap: ObservedKeymap },
+    // Boxed: the full key system's keymap is large,
+    //  and the world is held in cucumber's (deeply nested) futures.
+    Keymap { keymap: Box<ObservedKeymap> },
 }
 
 impl LoadedKeymap {
     pub fn new(keymap: Keymap) -> Self {
         LoadedKeymap::Keymap {
-            keymap: ObservedKeymap::new(keymap),
+            keymap: Box::new(ObservedKeymap::new(keymap)),
         }
     }
 
@@ -127,106 +120,18 @@ impl Default for KeymapWorld {
     }
 }
 
-#[derive(Deserialize, Default)]
-struct KeyVecs {
-    #[serde(default)]
-    automation: Vec<key::automation::Key>,
-    #[serde(default)]
-    callback: Vec<key::callback::Key>,
-    #[serde(default)]
-    chorded: Vec<
-        key::chorded::Key<
-            Ref,
-            { CHORDED_MAX_CHORDS },
-            { CHORDED_MAX_CHORD_SIZE },
-            { CHORDED_MAX_OVERLAPPING_CHORD_SIZE },
-            { CHORDED_MAX_PRESSED_INDICES },
-        >,
-    >,
-    #[serde(default)]
-    chorded_auxiliary: Vec<
-        key::chorded::AuxiliaryKey<
-            Ref,
-            { CHORDED_MAX_CHORDS },
-            { CHORDED_MAX_CHORD_SIZE },
-            { CHORDED_MAX_PRESSED_INDICES },
-        >,
-    >,
-    #[serde(default)]
-    consumer: Vec<key::consumer::Key>,
-    #[serde(default)]
-    history: Vec<key::history::AdaptiveKey>,
-    #[serde(default)]
-    keyboard: Vec<key::keyboard::Key>,
-    #[serde(default)]
-    layered: Vec<key::layered::LayeredKey<Ref, LAYERED_LAYER_COUNT>>,
-    #[serde(default)]
-    layer_modifiers: Vec<key::layered::ModifierKey>,
-    #[serde(default)]
-    mod_conditioned: Vec<key::mod_conditioned::Key<Ref>>,
-    #[serde(default)]
-    mouse: Vec<key::mouse::Key>,
-    #[serde(default)]
-    sequence: Vec<key::sequence::Key<Ref, SEQUENCE_MAX_OVERLAPPING>>,
-    #[serde(default)]
-    sequence_auxiliary: Vec<key::sequence::AuxiliaryKey<Ref>>,
-    #[serde(default)]
-    sticky: Vec<key::sticky::Key>,
-    #[serde(default)]
-    tap_dance: Vec<key::tap_dance::Key<Ref, TAP_DANCE_MAX_DEFS>>,
-    #[serde(default)]
-    tap_hold: Vec<key::tap_hold::Key<Ref>>,
-    #[serde(default)]
-    tri_state: Vec<key::tri_state::Key>,
-}
-
-#[derive(Deserialize)]
-struct DocstringKeymap {
-    config: Config,
-    key_refs: Vec<Ref>,
-    #[serde(default)]
-    key_data: KeyVecs,
-}
-
-fn system_from_key_data(keys: KeyVecs) -> System {
-    System::new(
-        smart_keymap::key::automation::System::new(keys.automation),
-        smart_keymap::key::callback::System::new(keys.callback),
-        smart_keymap::key::chorded::System::new(keys.chorded, keys.chorded_auxiliary),
-        smart_keymap::key::consumer::System::new(keys.consumer),
-        smart_keymap::key::history::System::new(keys.history),
-        smart_keymap::key::keyboard::System::new(keys.keyboard),
-        smart_keymap::key::layered::System::new(keys.layer_modifiers, keys.layered),
-        smart_keymap::key::mod_conditioned::System::new(keys.mod_conditioned),
-        smart_keymap::key::mouse::System::new(keys.mouse),
-        smart_keymap::key::sequence::System::new(keys.sequence, keys.sequence_auxiliary),
-        smart_keymap::key::sticky::System::new(keys.sticky),
-        smart_keymap::key::tap_dance::System::new(keys.tap_dance),
-        smart_keymap::key::tap_hold::System::new(keys.tap_hold),
-        smart_keymap::key::tri_state::System::new(keys.tri_state),
-    )
-}
-
 fn load_keymap(keymap_ncl: &str) -> Keymap {
     match nickel_json_value_for_keymap(ncl_import_path(), keymap_ncl) {
-        Ok(json) => {
-            let keymap_result: serde_json::Result<DocstringKeymap> = serde_json::from_str(&json);
-            match keymap_result {
-                Ok(keymap) => {
-                    let key_refs = keymap.key_refs;
-                    let context = Context::from_config(keymap.config);
-                    let system = system_from_key_data(keymap.key_data);
-                    keymap::Keymap::new(key_refs, context, system)
-                }
-                Err(e) => {
-                    panic!(
-                        "\n\nerror deserializing JSON:\n\nDeserialization Error:\n\n{}\n\nJSON:\n{}",
-                        e,
-                        json,
-                    )
-                }
+        Ok(json) => match keymap_from_json(&json) {
+            Ok(keymap) => keymap,
+            Err(e) => {
+                panic!(
+                    "\n\nerror deserializing JSON:\n\nDeserialization Error:\n\n{}\n\nJSON:\n{}",
+                    e,
+                    json,
+                )
             }
-        }
+        },
         Err(e) => match e {
             NickelError::NickelNotFound => panic!("`nickel` not found on PATH. Please install it."),
             NickelError::EvalError(nickel_error_message) => panic!(
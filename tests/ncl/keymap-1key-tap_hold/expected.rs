@@ -13,6 +13,12 @@ pub mod init {
     /// The maximum number of overlapping chords for a chorded key.
     pub const CHORDED_MAX_OVERLAPPING_CHORD_SIZE: usize = 0;
 
+    /// Number of slots for the [smart_keymap::key::dynamic_macro] implementation.
+    pub const DYNAMIC_MACRO_SLOT_COUNT: usize = 0;
+
+    /// Number of instructions each [smart_keymap::key::dynamic_macro] slot can record.
+    pub const DYNAMIC_MACRO_BUFFER_SIZE: usize = 0;
+
     /// Number of alternate-repeat rules for the [crate::key::history] implementation.
     pub const HISTORY_ALT_REPEAT_RULE_COUNT: usize = 0;
 
@@ -31,9 +37,18 @@ pub mod init {
     /// The maximum number of sequences sharing a primary key.
     pub const SEQUENCE_MAX_OVERLAPPING: usize = 0;
 
+    /// Number of dictionary entries used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_DICTIONARY_ENTRY_COUNT: usize = 0;
+
+    /// Number of instructions used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_INSTRUCTION_COUNT: usize = 0;
+
     /// The tap-dance definitions.
     pub const TAP_DANCE_MAX_DEFINITIONS: usize = 0;
 
+    /// Number of texts used by the [smart_keymap::key::unicode] implementation.
+    pub const UNICODE_TEXT_COUNT: usize = 0;
+
     const KEYBOARD: usize = 0;
     const TAP_HOLD: usize = 1;
 
@@ -130,6 +145,12 @@ pub mod init {
             }
         }
 
+        impl keymap::PersistentContext for Context {}
+
+        impl keymap::SplitStatusContext for Context {}
+
+        impl keymap::KeyOverridesContext for Context {}
+
         /// Aggregate event.
         #[derive(Debug, Clone, Copy, PartialEq)]
         pub enum Event {
@@ -13,6 +13,12 @@ pub mod init {
     /// The maximum number of overlapping chords for a chorded key.
     pub const CHORDED_MAX_OVERLAPPING_CHORD_SIZE: usize = 0;
 
+    /// Number of slots for the [smart_keymap::key::dynamic_macro] implementation.
+    pub const DYNAMIC_MACRO_SLOT_COUNT: usize = 0;
+
+    /// Number of instructions each [smart_keymap::key::dynamic_macro] slot can record.
+    pub const DYNAMIC_MACRO_BUFFER_SIZE: usize = 0;
+
     /// Number of alternate-repeat rules for the [crate::key::history] implementation.
     pub const HISTORY_ALT_REPEAT_RULE_COUNT: usize = 0;
 
@@ -31,9 +37,18 @@ pub mod init {
     /// The maximum number of sequences sharing a primary key.
     pub const SEQUENCE_MAX_OVERLAPPING: usize = 0;
 
+    /// Number of dictionary entries used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_DICTIONARY_ENTRY_COUNT: usize = 0;
+
+    /// Number of instructions used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_INSTRUCTION_COUNT: usize = 0;
+
     /// The tap-dance definitions.
     pub const TAP_DANCE_MAX_DEFINITIONS: usize = 0;
 
+    /// Number of texts used by the [smart_keymap::key::unicode] implementation.
+    pub const UNICODE_TEXT_COUNT: usize = 0;
+
     const KEYBOARD: usize = 0;
     const LAYERED: usize = 1;
     const LAYER_MODIFIERS: usize = 1;
@@ -137,6 +152,10 @@ pub mod init {
                 self.keymap_context = context;
                 self.tap_hold.update_keymap_context(&context);
             }
+
+            fn active_layers(&self) -> u32 {
+                self.layered.active_layers_bitset().bits()
+            }
         }
 
         impl keymap::ReportHints for Context {
@@ -145,6 +164,36 @@ pub mod init {
             }
         }
 
+        impl keymap::PersistentContext for Context {
+            fn export_persistent_state(&self, state: &mut keymap::PersistentState) {
+                self.layered.export_persistent_state(state);
+            }
+
+            fn restore_persistent_state(&mut self, state: &keymap::PersistentState) {
+                self.layered.restore_persistent_state(state);
+            }
+        }
+
+        impl keymap::SplitStatusContext for Context {
+            fn export_split_status(&self, status: &mut smart_keymap::split::Status) {
+                keymap::SplitStatusContext::export_split_status(&self.layered, status);
+            }
+        }
+
+        impl keymap::KeyOverridesContext for Context {
+            fn layer_count(&self) -> u8 {
+                keymap::KeyOverridesContext::layer_count(&self.layered)
+            }
+
+            fn key_overrides(&self) -> Option<&keymap::KeyOverrides> {
+                keymap::KeyOverridesContext::key_overrides(&self.layered)
+            }
+
+            fn key_overrides_mut(&mut self) -> Option<&mut keymap::KeyOverrides> {
+                keymap::KeyOverridesContext::key_overrides_mut(&mut self.layered)
+            }
+        }
+
         /// Aggregate event.
         #[derive(Debug, Clone, Copy, PartialEq)]
         pub enum Event {
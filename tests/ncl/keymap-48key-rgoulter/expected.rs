@@ -13,6 +13,12 @@ pub mod init {
     /// The maximum number of overlapping chords for a chorded key.
     pub const CHORDED_MAX_OVERLAPPING_CHORD_SIZE: usize = 1;
 
+    /// Number of slots for the [smart_keymap::key::dynamic_macro] implementation.
+    pub const DYNAMIC_MACRO_SLOT_COUNT: usize = 0;
+
+    /// Number of instructions each [smart_keymap::key::dynamic_macro] slot can record.
+    pub const DYNAMIC_MACRO_BUFFER_SIZE: usize = 0;
+
     /// Number of alternate-repeat rules for the [crate::key::history] implementation.
     pub const HISTORY_ALT_REPEAT_RULE_COUNT: usize = 0;
 
@@ -31,9 +37,18 @@ pub mod init {
     /// The maximum number of sequences sharing a primary key.
     pub const SEQUENCE_MAX_OVERLAPPING: usize = 0;
 
+    /// Number of dictionary entries used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_DICTIONARY_ENTRY_COUNT: usize = 0;
+
+    /// Number of instructions used by the [smart_keymap::key::steno] implementation.
+    pub const STENO_INSTRUCTION_COUNT: usize = 0;
+
     /// The tap-dance definitions.
     pub const TAP_DANCE_MAX_DEFINITIONS: usize = 2;
 
+    /// Number of texts used by the [smart_keymap::key::unicode] implementation.
+    pub const UNICODE_TEXT_COUNT: usize = 0;
+
     const CALLBACK: usize = 7;
     const CHORDED: usize = 4;
     const CHORDED_AUXILIARY: usize = 4;
@@ -86,6 +101,8 @@ pub mod init {
             >,
             /// Config for [smart_keymap::key::layered].
             pub layered: smart_keymap::key::layered::Config<{ super::CONDITIONAL_LAYER_COUNT }>,
+            /// Config for [smart_keymap::key::mouse].
+            pub mouse: smart_keymap::key::mouse::Config,
             /// Config for [smart_keymap::key::sticky].
             pub sticky: smart_keymap::key::sticky::Config,
             /// Config for [smart_keymap::key::tap_dance].
@@ -104,6 +121,7 @@ pub mod init {
                 Self {
                     chorded: smart_keymap::key::chorded::Config::new(),
                     layered: smart_keymap::key::layered::Config::new(),
+                    mouse: smart_keymap::key::mouse::Config::new(),
                     sticky: smart_keymap::key::sticky::Config::new(),
                     tap_dance: smart_keymap::key::tap_dance::Config::new(),
                     tap_hold: smart_keymap::key::tap_hold::Config::new(),
@@ -144,7 +162,7 @@ pub mod init {
                     consumer: smart_keymap::key::consumer::Context,
                     keyboard: smart_keymap::key::keyboard::Context,
                     layered: smart_keymap::key::layered::Context::from_config(config.layered),
-                    mouse: smart_keymap::key::mouse::Context,
+                    mouse: smart_keymap::key::mouse::Context::from_config(config.mouse),
                     sticky: smart_keymap::key::sticky::Context::from_config(config.sticky),
                     tap_dance: smart_keymap::key::tap_dance::Context::from_config(config.tap_dance),
                     tap_hold: smart_keymap::key::tap_hold::Context::from_config(config.tap_hold),
@@ -173,6 +191,9 @@ pub mod init {
                 if let Ok(e) = event.try_into_key_event() {
                     pke.extend(self.layered.handle_event(e).into_events());
                 }
+                if let Ok(e) = event.try_into_key_event() {
+                    pke.extend(self.mouse.handle_event(e).into_events());
+                }
                 if let Ok(e) = event.try_into_key_event() {
                     pke.extend(self.sticky.handle_event(e).into_events());
                 }
@@ -199,6 +220,10 @@ pub mod init {
                 self.chorded.update_keymap_context(&context);
                 self.tap_hold.update_keymap_context(&context);
             }
+
+            fn active_layers(&self) -> u32 {
+                self.layered.active_layers_bitset().bits()
+            }
         }
 
         impl keymap::ReportHints for Context {
@@ -207,6 +232,36 @@ pub mod init {
             }
         }
 
+        impl keymap::PersistentContext for Context {
+            fn export_persistent_state(&self, state: &mut keymap::PersistentState) {
+                self.layered.export_persistent_state(state);
+            }
+
+            fn restore_persistent_state(&mut self, state: &keymap::PersistentState) {
+                self.layered.restore_persistent_state(state);
+            }
+        }
+
+        impl keymap::SplitStatusContext for Context {
+            fn export_split_status(&self, status: &mut smart_keymap::split::Status) {
+                keymap::SplitStatusContext::export_split_status(&self.layered, status);
+            }
+        }
+
+        impl keymap::KeyOverridesContext for Context {
+            fn layer_count(&self) -> u8 {
+                keymap::KeyOverridesContext::layer_count(&self.layered)
+            }
+
+            fn key_overrides(&self) -> Option<&keymap::KeyOverrides> {
+                keymap::KeyOverridesContext::key_overrides(&self.layered)
+            }
+
+            fn key_overrides_mut(&mut self) -> Option<&mut keymap::KeyOverrides> {
+                keymap::KeyOverridesContext::key_overrides_mut(&mut self.layered)
+            }
+        }
+
         /// Aggregate event.
         #[derive(Debug, Clone, Copy, PartialEq)]
         pub enum Event {
@@ -899,6 +954,21 @@ pub mod init {
                             smart_keymap::key::KeyEvents::no_events()
                         }
                     }
+                    (Ref::Mouse(key_ref), KeyState::Mouse(key_state)) => {
+                        if let Ok(event) = event.try_into_key_event() {
+                            self.mouse
+                                .update_state(
+                                    key_state,
+                                    key_ref,
+                                    &context.mouse,
+                                    keymap_index,
+                                    event,
+                                )
+                                .into_events()
+                        } else {
+                            smart_keymap::key::KeyEvents::no_events()
+                        }
+                    }
                     (Ref::Sticky(key_ref), KeyState::Sticky(key_state)) => {
                         if let Ok(event) = event.try_into_key_event() {
                             self.sticky
@@ -1011,6 +1081,7 @@ pub mod init {
             ..smart_keymap::key::chorded::Config::new()
         },
         layered: smart_keymap::key::layered::Config::new(),
+        mouse: smart_keymap::key::mouse::Config::new(),
         sticky: smart_keymap::key::sticky::Config::new(),
         tap_dance: smart_keymap::key::tap_dance::Config::new(),
         tap_hold: smart_keymap::key::tap_hold::Config {
@@ -1034,6 +1105,7 @@ pub mod init {
             ..smart_keymap::key::chorded::Config::new()
         },
         layered: smart_keymap::key::layered::Config::new(),
+        mouse: smart_keymap::key::mouse::Config::new(),
         sticky: smart_keymap::key::sticky::Config::new(),
         tap_dance: smart_keymap::key::tap_dance::Config::new(),
         tap_hold: smart_keymap::key::tap_hold::Config {
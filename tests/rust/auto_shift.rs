@@ -0,0 +1,196 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn auto_shift_tap_is_unshifted() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.auto_shift K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    assert_eq!(expected_reports, keymap.distinct_reports().reports());
+}
+
+#[test]
+fn auto_shift_hold_is_shifted() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.auto_shift K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- hold past the default timeout
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    for _ in 0..200 {
+        keymap.tick();
+    }
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LSHFT, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    assert_eq!(expected_reports, keymap.distinct_reports().reports());
+}
+
+#[test]
+fn auto_shift_roll_is_unshifted() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.auto_shift K.A,
+                    K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- press A, then B before the timeout
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    for _ in 0..200 {
+        keymap.tick();
+    }
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert -- A then B, never shifted
+    let reports = keymap.distinct_reports().reports().to_vec();
+    assert_eq!([0, 0, KC_A, 0, 0, 0, 0, 0], reports[1]);
+    assert!(
+        reports.iter().any(|r| r[2..].contains(&KC_B)),
+        "expected B, got {:02X?}",
+        reports
+    );
+    assert!(
+        reports.iter().all(|r| r[0] == 0),
+        "expected no modifiers, got {:02X?}",
+        reports
+    );
+}
+
+#[test]
+fn auto_shift_with_caps_word_outputs_immediately() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.caps_word.toggle,
+                    K.auto_shift K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- tap Caps Word, then press (and keep holding) A
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    for _ in 0..10 {
+        keymap.tick();
+    }
+
+    // Assert -- A is output (shifted by Caps Word) without waiting for the timeout
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LSHFT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LSHFT, 0, KC_A, 0, 0, 0, 0, 0],
+    ];
+    assert_eq!(expected_reports, keymap.distinct_reports().reports());
+}
+
+#[test]
+fn auto_shift_disabled_class_is_unshifted() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.auto_shift.classes.numeric = false,
+                keys = [
+                    K.auto_shift K.N1,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- hold past the timeout
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    for _ in 0..200 {
+        keymap.tick();
+    }
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_1, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    assert_eq!(expected_reports, keymap.distinct_reports().reports());
+}
+
+#[test]
+fn auto_shift_repeat_on_hold_keeps_shifted_key_pressed() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.auto_shift.repeat_on_hold = true,
+                keys = [
+                    K.auto_shift K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- hold past the timeout
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    for _ in 0..200 {
+        keymap.tick();
+    }
+
+    // Assert -- shifted A still pressed
+    assert_eq!(
+        [MOD_LSHFT, 0, KC_A, 0, 0, 0, 0, 0],
+        keymap.boot_keyboard_report()
+    );
+}
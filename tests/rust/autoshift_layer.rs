@@ -6,6 +6,7 @@ use smart_keymap_macros::keymap;
 
 // Extra autoshift: `layer |> AL.autoshift` folds existing `hold` into inner hold.
 // `inner_hold` profile: no timeout, HoldOnKeyPress → `A` on tap, mod on interrupt.
+// Plain keys use the native auto-shift key (default 175ms timeout).
 
 #[test]
 fn autoshift_hrm_tap_is_plain() {
@@ -138,7 +139,7 @@ fn autoshift_plain_hold_is_shifted() {
         "#
     ));
 
-    // Act -- hold past outer 200ms
+    // Act -- hold past auto-shift timeout
     keymap.handle_input(input::Event::Press { keymap_index: 0 });
     for _ in 0..250 {
         keymap.tick();
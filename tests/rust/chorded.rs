@@ -4,6 +4,7 @@ mod over_layered_tap_hold;
 mod overlapping;
 mod overlapping_aux;
 mod overlapping_simultaneous;
+mod per_chord;
 mod required_idle_time;
 mod tap_hold;
 mod tap_hold_over_tap_hold;
@@ -0,0 +1,228 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn release_all_holds_chord_until_all_keys_released() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                chords = [
+                    { indices = [0, 1], key = K.C, release = "All", },
+                ],
+                keys = [
+                    K.A, K.B, K.D,
+                ],
+            }
+        "#
+    ));
+
+    // Act: release the completing key (1), press D, then release 0.
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert: C is held until both 0 and 1 are released.
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, KC_D, 0, 0, 0, 0],
+        [0, 0, KC_D, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn release_any_releases_chord_when_other_key_released() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            let CH = import "chording.ncl" in
+            {
+                chords = [
+                    { indices = [0, 1], key = K.C, } & CH.release.any,
+                ],
+                keys = [
+                    K.A, K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: release the non-completing key (0).
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn press_after_press_within_resolves_as_passthrough() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                chords = [
+                    { indices = [0, 1], key = K.C, press_within = 20, },
+                ],
+                keys = [
+                    K.A, K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: press 1 after 50ms; within the chord timeout, but not press_within.
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+
+    for _ in 0..50 {
+        keymap.tick();
+    }
+
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, KC_B, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn chord_timeout_overrides_config_timeout() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.chorded.timeout = 200,
+                chords = [
+                    { indices = [0, 1], key = K.C, timeout = 50, },
+                ],
+                keys = [
+                    K.A, K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: hold 0 for longer than the chord's timeout, but less than the config timeout.
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+
+    for _ in 0..100 {
+        keymap.tick();
+    }
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn chord_with_layers_is_inactive_on_other_layers() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            let CH = import "chording.ncl" in
+            {
+                chords = [
+                    { indices = [1, 2], key = K.C, } & CH.on_layers [1],
+                ],
+                layers = [
+                    [K.layer_mod.hold 1, K.A, K.B],
+                    [K.TTTT, K.TTTT, K.D],
+                ],
+            }
+        "#
+    ));
+
+    // Act: press the chord on the base layer.
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, KC_B, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn chord_with_layers_is_active_on_its_layer() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            let CH = import "chording.ncl" in
+            {
+                chords = [
+                    { indices = [1, 2], key = K.C, } & CH.on_layers [1],
+                ],
+                layers = [
+                    [K.layer_mod.hold 1, K.A, K.B],
+                    [K.TTTT, K.TTTT, K.D],
+                ],
+            }
+        "#
+    ));
+
+    // Act: hold layer 1, then press the chord.
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
@@ -0,0 +1,92 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn dynamic_macro_plays_back_recorded_keys() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.DM_REC1,
+                    K.DM_RSTP,
+                    K.DM_PLY1,
+                    K.A,
+                    K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: record tapping A then B, then play it back.
+    for keymap_index in [0, 3, 4, 1, 2] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_B, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        // Playback
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_B, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn dynamic_macro_slots_are_independent() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.DM_REC1,
+                    K.DM_REC2,
+                    K.DM_PLY2,
+                    K.A,
+                    K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: record A into slot 1, B into slot 2 (pressing a record key stops recording),
+    //  then play slot 2.
+    for keymap_index in [0, 3, 0, 1, 4, 1, 2] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_B, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        // Playback of slot 2
+        [0, 0, KC_B, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
@@ -12,17 +12,23 @@ pub const KC_E: u8 = 0x08;
 pub const KC_F: u8 = 0x09;
 pub const KC_G: u8 = 0x0A;
 pub const KC_H: u8 = 0x0B;
+pub const KC_I: u8 = 0x0C;
 pub const KC_K: u8 = 0x0E;
 pub const KC_L: u8 = 0x0F;
 pub const KC_M: u8 = 0x10;
 pub const KC_N: u8 = 0x11;
 pub const KC_O: u8 = 0x12;
 pub const KC_P: u8 = 0x13;
+pub const KC_T: u8 = 0x17;
 pub const KC_U: u8 = 0x18;
+pub const KC_1: u8 = 0x1E;
+pub const KC_9: u8 = 0x26;
+pub const KC_0: u8 = 0x27;
 pub const KC_ESCAPE: u8 = 0x29;
 pub const KC_TAB: u8 = 0x2B;
 pub const KC_BACKSPACE: u8 = 0x2A;
 pub const KC_SPACE: u8 = 0x2C;
+pub const KC_MINUS: u8 = 0x2D;
 pub const KC_GRAVE: u8 = 0x35;
 pub const KC_SLASH: u8 = 0x38;
 pub const KC_DELETE: u8 = 0x4C;
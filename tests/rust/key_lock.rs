@@ -32,11 +32,11 @@ fn key_lock_holds_next_key_until_pressed_again() {
 
     keymap.tick_until_no_scheduled_events();
 
-    // Assert: A still held (via virtual key) with B pressed
+    // Assert: A still held with B pressed
     let expected_reports: &[[u8; 8]] = &[
         [0, 0, 0, 0, 0, 0, 0, 0],
         [0, 0, KC_A, 0, 0, 0, 0, 0],
-        // release A: still held via virtual key (no distinct change if same report)
+        // release A: release is deferred (no distinct change)
         [0, 0, KC_A, KC_B, 0, 0, 0, 0],
     ];
     let actual_reports = keymap.distinct_reports();
@@ -76,8 +76,7 @@ fn key_lock_unlocks_when_same_key_pressed_again() {
     let expected_reports: &[[u8; 8]] = &[
         [0, 0, 0, 0, 0, 0, 0, 0],
         [0, 0, KC_A, 0, 0, 0, 0, 0],
-        // unlock press: A still held by physical press after VK release
-        // unlock release: A gone
+        // unlock press: A released
         [0, 0, 0, 0, 0, 0, 0, 0],
         [0, 0, KC_B, 0, 0, 0, 0, 0],
     ];
@@ -152,3 +151,116 @@ fn key_lock_toggle_watching_cancels_without_locking() {
     let actual_reports = keymap.distinct_reports();
     assert_eq!(expected_reports, actual_reports.reports());
 }
+
+#[test]
+fn key_lock_locks_multiple_keys() {
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.key_lock,
+                    K.LeftShift,
+                    K.LeftCtrl,
+                    K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Lock LeftShift, then lock LeftCtrl
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+    keymap.handle_input(input::Event::Release { keymap_index: 2 });
+
+    // Press A
+    keymap.handle_input(input::Event::Press { keymap_index: 3 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LSHFT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LCTL_LSHFT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LCTL_LSHFT, 0, KC_A, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn key_lock_holds_layer_modifier() {
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                layers = [
+                    [K.key_lock, K.layer_mod.hold 1, K.A],
+                    [K.TTTT, K.TTTT, K.B],
+                ],
+            }
+        "#
+    ));
+
+    // Arm, lock the layer modifier
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+
+    // Press the key on the locked layer
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    let expected_reports: &[[u8; 8]] = &[[0, 0, 0, 0, 0, 0, 0, 0], [0, 0, KC_B, 0, 0, 0, 0, 0]];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn key_lock_config_release_on_key_lock() {
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.key_lock.release = "OnKeyLock",
+                keys = [
+                    K.key_lock,
+                    K.A,
+                ],
+            }
+        "#
+    ));
+
+    // Arm and lock A
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+
+    // Tapping A again keeps it locked
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+
+    // Tapping key lock twice releases all locked keys
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
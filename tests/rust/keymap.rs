@@ -1,20 +1,27 @@
+mod auto_shift;
 mod automation;
 mod autoshift_layer;
 mod caps_word;
 mod chorded;
 mod consumer;
 mod custom;
+mod dynamic_macro;
 mod hid_keycodes;
 mod history;
 mod key_lock;
+mod key_repeat;
 mod layered;
 mod mod_conditioned;
 mod mouse;
+mod raw_hid;
 mod sequence;
+mod steno;
 mod sticky;
 mod tap_dance;
 mod tap_hold;
+mod trace;
 mod tri_state;
+mod unicode;
 
 mod ms_per_tick;
 
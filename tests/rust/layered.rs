@@ -5,6 +5,7 @@ mod set_active_layers;
 mod sticky;
 mod sticky_timeout;
 mod tap_hold;
+mod tap_toggle;
 mod tlex;
 mod toggle;
 
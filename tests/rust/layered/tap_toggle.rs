@@ -0,0 +1,149 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn press_active_layer_when_layer_mod_tap_toggle_held() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                layers = [
+                    [K.layer_mod.tap_toggle 1, K.A],
+                    [K.TTTT, K.B],
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+
+    // Assert
+    let expected_report: [u8; 8] = [0, 0, KC_B, 0, 0, 0, 0, 0];
+    let actual_report = keymap.boot_keyboard_report();
+    assert_eq!(expected_report, actual_report);
+}
+
+#[test]
+fn press_active_layer_when_layer_mod_tap_toggle_tapped_default_times() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                layers = [
+                    [K.layer_mod.tap_toggle 1, K.A],
+                    [K.TTTT, K.B],
+                ],
+            }
+        "#
+    ));
+
+    // Act -- tap 5 times (default tap_toggle_taps)
+    for _ in 0..5 {
+        keymap.handle_input(input::Event::Press { keymap_index: 0 });
+        keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    }
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+
+    // Assert
+    let expected_report: [u8; 8] = [0, 0, KC_B, 0, 0, 0, 0, 0];
+    let actual_report = keymap.boot_keyboard_report();
+    assert_eq!(expected_report, actual_report);
+}
+
+#[test]
+fn press_base_layer_when_layer_mod_tap_toggle_tapped_too_few_times() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                layers = [
+                    [K.layer_mod.tap_toggle 1, K.A],
+                    [K.TTTT, K.B],
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    for _ in 0..4 {
+        keymap.handle_input(input::Event::Press { keymap_index: 0 });
+        keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    }
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+
+    // Assert
+    let expected_report: [u8; 8] = [0, 0, KC_A, 0, 0, 0, 0, 0];
+    let actual_report = keymap.boot_keyboard_report();
+    assert_eq!(expected_report, actual_report);
+}
+
+#[test]
+fn press_base_layer_when_layer_mod_tap_toggle_taps_too_slow() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.layered.tap_toggle_taps = 2,
+                config.layered.tap_toggle_timeout = 100,
+                layers = [
+                    [K.layer_mod.tap_toggle 1, K.A],
+                    [K.TTTT, K.B],
+                ],
+            }
+        "#
+    ));
+
+    // Act -- wait past the timeout between taps
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    for _ in 0..101 {
+        keymap.tick();
+    }
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+
+    // Assert
+    let expected_report: [u8; 8] = [0, 0, KC_A, 0, 0, 0, 0, 0];
+    let actual_report = keymap.boot_keyboard_report();
+    assert_eq!(expected_report, actual_report);
+}
+
+#[test]
+fn press_active_layer_when_layer_mod_sticky_toggle_double_tapped() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                layers = [
+                    [K.layer_mod.sticky_toggle 1, K.A, K.B],
+                    [K.TTTT, K.K, K.L],
+                ],
+            }
+        "#
+    ));
+
+    // Act -- double tap, then tap two keys
+    for _ in 0..2 {
+        keymap.handle_input(input::Event::Press { keymap_index: 0 });
+        keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    }
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.handle_input(input::Event::Press { keymap_index: 2 });
+
+    // Assert -- layer stays active after the first key
+    let expected_report: [u8; 8] = [0, 0, KC_L, 0, 0, 0, 0, 0];
+    let actual_report = keymap.boot_keyboard_report();
+    assert_eq!(expected_report, actual_report);
+}
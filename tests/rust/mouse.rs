@@ -168,3 +168,95 @@ fn modified_mouse_key_clears_on_release() {
     );
     assert_eq!([0u8; 8], report_output.as_hid_boot_keyboard_report());
 }
+
+#[test]
+fn mouse_cursor_key_accelerates_while_held() {
+    // Assemble
+    let mut keymap = smart_keymap_macros::keymap!(
+        r#"
+        let K = import "keys.ncl" in
+        {
+            config.mouse = {
+                delay_ms = 100,
+                interval_ms = 20,
+                move_delta = 2,
+                max_speed = 12,
+                time_to_max_ms = 1000,
+            },
+            keys = [
+                K.MouseRight,
+            ],
+        }
+        "#
+    );
+
+    // Act -- press 'MouseRight', and hold it until after max speed
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.tick();
+    let initial_output = keymap.report_output().pressed_mouse_output();
+    for _ in 0..2000 {
+        keymap.tick();
+    }
+    let held_output = keymap.report_output().pressed_mouse_output();
+
+    // Assert -- cursor moves at move_delta, then at max_speed
+    assert_eq!(
+        key::MouseOutput {
+            x: 2,
+            ..key::MouseOutput::NO_OUTPUT
+        },
+        initial_output
+    );
+    assert_eq!(
+        key::MouseOutput {
+            x: 12,
+            ..key::MouseOutput::NO_OUTPUT
+        },
+        held_output
+    );
+}
+
+#[test]
+fn mouse_gear_key_sets_constant_cursor_speed() {
+    // Assemble
+    let mut keymap = smart_keymap_macros::keymap!(
+        r#"
+        let K = import "keys.ncl" in
+        {
+            config.mouse = {
+                move_delta = 2,
+                max_speed = 12,
+                gears = [
+                    { move_speed = 1, wheel_speed = 1 },
+                    { move_speed = 8, wheel_speed = 1 },
+                    { move_speed = 30, wheel_speed = 3 },
+                ],
+            },
+            keys = [
+                K.MouseGear2 & K.LeftShift,
+                K.MouseRight,
+            ],
+        }
+        "#
+    );
+
+    // Act -- hold the gear key, then hold 'MouseRight'
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.tick();
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    for _ in 0..2000 {
+        keymap.tick();
+    }
+    let report_output = keymap.report_output();
+
+    // Assert -- gear speed, not the accelerated speed;
+    //  and the gear key reports no output (not even its modifiers)
+    assert_eq!(
+        key::MouseOutput {
+            x: 30,
+            ..key::MouseOutput::NO_OUTPUT
+        },
+        report_output.pressed_mouse_output()
+    );
+    assert_eq!([0u8; 8], report_output.as_hid_boot_keyboard_report());
+}
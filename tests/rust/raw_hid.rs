@@ -0,0 +1,163 @@
+use smart_keymap::input;
+use smart_keymap::raw_hid::{self, Request, Response, Status};
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+/// postcard encoding of `Ref::Keyboard(keyboard::Ref::KeyCode(KC_D))`:
+///  the aggregate `Keyboard` variant index, the `KeyCode` variant index, then the key code.
+const KEY_REF_D: [u8; 3] = [0x00, 0x00, KC_D];
+
+fn send_request<E: raw_hid::KeymapEditor>(editor: &mut E, request: Request) -> Response {
+    let response_report = raw_hid::handle_report(editor, &request.to_report());
+    Response::from_report(&response_report).unwrap()
+}
+
+#[test]
+fn raw_hid_set_key_overrides_layered_key_output() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A & K.hold (K.layer_mod.hold 1),
+                    K.B & { layered = [ K.C ] },
+                ],
+            }
+        "#
+    );
+
+    // Act -- override the base layer of the layered key, then tap it
+    let set_key_response = send_request(
+        &mut keymap,
+        Request::SetKey {
+            layer: 0,
+            keymap_index: 1,
+            key_ref: raw_hid::KeyRefBytes::from_slice(&KEY_REF_D).unwrap(),
+        },
+    );
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.tick();
+    let actual_report = keymap.report_output().as_hid_boot_keyboard_report();
+
+    // Assert
+    assert_eq!(Response::KeySet, set_key_response);
+    assert_eq!([0, 0, KC_D, 0, 0, 0, 0, 0], actual_report);
+}
+
+#[test]
+fn raw_hid_get_key_returns_override() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A & K.hold (K.layer_mod.hold 1),
+                    K.B & { layered = [ K.C ] },
+                ],
+            }
+        "#
+    );
+    send_request(
+        &mut keymap,
+        Request::SetKey {
+            layer: 1,
+            keymap_index: 1,
+            key_ref: raw_hid::KeyRefBytes::from_slice(&KEY_REF_D).unwrap(),
+        },
+    );
+
+    // Act
+    let response = send_request(
+        &mut keymap,
+        Request::GetKey {
+            layer: 1,
+            keymap_index: 1,
+        },
+    );
+
+    // Assert
+    assert_eq!(
+        Response::Key {
+            layer: 1,
+            keymap_index: 1,
+            key_ref: Some(raw_hid::KeyRefBytes::from_slice(&KEY_REF_D).unwrap()),
+        },
+        response
+    );
+}
+
+#[test]
+fn raw_hid_set_key_rejects_invalid_key_ref() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A & K.hold (K.layer_mod.hold 1),
+                    K.B & { layered = [ K.C ] },
+                ],
+            }
+        "#
+    );
+
+    // Act -- 0x7F isn't a variant index of the keymap's Ref
+    let set_key_response = send_request(
+        &mut keymap,
+        Request::SetKey {
+            layer: 0,
+            keymap_index: 1,
+            key_ref: raw_hid::KeyRefBytes::from_slice(&[0x7F, 0x00]).unwrap(),
+        },
+    );
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.tick();
+    let actual_report = keymap.report_output().as_hid_boot_keyboard_report();
+
+    // Assert -- the compiled-in key is unchanged
+    assert_eq!(
+        Response::Error {
+            command: raw_hid::COMMAND_SET_KEY,
+            status: Status::InvalidKeyRef,
+        },
+        set_key_response
+    );
+    assert_eq!([0, 0, KC_B, 0, 0, 0, 0, 0], actual_report);
+}
+
+#[test]
+fn raw_hid_reset_keys_restores_compiled_keymap() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A & K.hold (K.layer_mod.hold 1),
+                    K.B & { layered = [ K.C ] },
+                ],
+            }
+        "#
+    );
+    send_request(
+        &mut keymap,
+        Request::SetKey {
+            layer: 0,
+            keymap_index: 1,
+            key_ref: raw_hid::KeyRefBytes::from_slice(&KEY_REF_D).unwrap(),
+        },
+    );
+
+    // Act
+    let reset_response = send_request(&mut keymap, Request::ResetKeys);
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.tick();
+    let actual_report = keymap.report_output().as_hid_boot_keyboard_report();
+
+    // Assert
+    assert_eq!(Response::KeysReset, reset_response);
+    assert_eq!([0, 0, KC_B, 0, 0, 0, 0, 0], actual_report);
+}
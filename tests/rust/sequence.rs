@@ -196,3 +196,84 @@ fn sequence_indices_layout_string_reverse_of_scan_order() {
     let actual_reports = keymap.distinct_reports();
     assert_eq!(expected_reports, actual_reports.reports());
 }
+
+#[test]
+fn sequence_timeout_resolves_longest_match() {
+    // Assemble: [1] → C, [1, 2] → D
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                sequences = [
+                    { indices = [1], key = K.C },
+                    { indices = [1, 2], key = K.D },
+                ],
+                config.sequence.timeout = 50,
+                keys = [
+                    K.sequence_start,
+                    K.A,
+                    K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act: start, then A; wait out the timeout
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+
+    for _ in 0..100 {
+        keymap.tick();
+    }
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn sequence_text_types_string() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                sequences = [
+                    { indices = [1, 2], text = "hi" },
+                ],
+                keys = [
+                    K.sequence_start,
+                    K.A,
+                    K.B,
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    for keymap_index in [0, 1, 2] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_H, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_I, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
@@ -0,0 +1,80 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn steno_stroke_types_dictionary_translation() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.steno.dictionary = { "KAT" = "cat" },
+                keys = [
+                    K.STN_KL, K.STN_A, K.STN_TR,
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    for keymap_index in [0, 1, 2] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+    }
+    for keymap_index in [0, 1, 2] {
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_C, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_A, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_T, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_SPACE, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn steno_stroke_writes_gemini_pr_packet() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.steno.output = "GeminiPr",
+                keys = [
+                    K.STN_KL, K.STN_A, K.STN_TR,
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    for keymap_index in [0, 1, 2] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+    }
+    for keymap_index in [0, 1, 2] {
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    // K- (byte 1, 0x08), A- (byte 2, 0x20), -T (byte 4, 0x04).
+    let mut buf = [0u8; 16];
+    let len = keymap.read_serial_output(&mut buf);
+    assert_eq!(&[0x80, 0x08, 0x20, 0x00, 0x04, 0x00], &buf[..len]);
+    assert_eq!([0, 0, 0, 0, 0, 0, 0, 0], keymap.boot_keyboard_report());
+}
@@ -0,0 +1,164 @@
+use smart_keymap::input;
+use smart_keymap::key;
+use smart_keymap::keymap;
+
+use smart_keymap_macros::keymap;
+
+use keymap::{TraceRecord, TraceRecorder};
+
+#[test]
+fn trace_records_tap_hold_resolved_as_hold_by_timeout() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.tap_hold.timeout = 200,
+                keys = [
+                    K.A & K.hold K.LeftCtrl
+                ],
+            }
+        "#
+    )
+    .with_observer(TraceRecorder::new());
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    while keymap.has_scheduled_events() {
+        keymap.tick();
+    }
+
+    // Assert
+    let records: Vec<_> = keymap.observer().records().cloned().collect();
+    assert!(records.contains(&TraceRecord::PendingStarted { keymap_index: 0 }));
+
+    let resolved = records.iter().find_map(|record| match record {
+        TraceRecord::PendingResolved {
+            keymap_index,
+            cause,
+            key_output,
+        } => Some((*keymap_index, *cause, *key_output)),
+        _ => None,
+    });
+    assert!(
+        matches!(
+            resolved,
+            Some((0, key::Event::Key { keymap_index: 0, .. }, Some(key_output)))
+                if key_output == key::KeyOutput::from_key_code(0xE0)
+        ),
+        "resolved: {:?}",
+        resolved
+    );
+}
+
+#[test]
+fn trace_records_tap_hold_resolved_as_hold_by_interrupt() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                config.tap_hold.interrupt_response = "HoldOnKeyTap",
+                keys = [
+                    K.A & K.hold K.LeftCtrl,
+                    K.B,
+                ],
+            }
+        "#
+    )
+    .with_observer(TraceRecorder::new());
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.tick();
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.tick();
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.tick();
+
+    // Assert
+    let resolved = keymap.observer().records().find_map(|record| match record {
+        TraceRecord::PendingResolved {
+            keymap_index,
+            cause,
+            key_output,
+        } => Some((*keymap_index, *cause, *key_output)),
+        _ => None,
+    });
+    assert_eq!(
+        Some((
+            0,
+            key::Event::Input(input::Event::Release { keymap_index: 1 }),
+            Some(key::KeyOutput::from_key_code(0xE0)),
+        )),
+        resolved
+    );
+}
+
+#[test]
+fn trace_records_inputs_and_reports_in_order() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A,
+                ],
+            }
+        "#
+    )
+    .with_observer(TraceRecorder::new());
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    let output = keymap.report_output();
+
+    // Assert
+    let press = input::Event::Press { keymap_index: 0 };
+    let expected_records = vec![
+        TraceRecord::InputQueued { event: press },
+        TraceRecord::InputAccepted { event: press },
+        TraceRecord::ContextUpdated {
+            event: key::Event::Keymap(keymap::KeymapEvent::ResolvedKeyOutput {
+                keymap_index: 0,
+                key_output: key::KeyOutput::from_key_code(0x04),
+            }),
+        },
+        TraceRecord::ReportEmitted { output },
+    ];
+    let actual_records: Vec<_> = keymap.observer().records().cloned().collect();
+    assert_eq!(expected_records, actual_records);
+}
+
+#[test]
+fn trace_records_report_only_when_it_changes() {
+    // Assemble
+    let mut keymap = keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.A,
+                ],
+            }
+        "#
+    )
+    .with_observer(TraceRecorder::new());
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    let output = keymap.report_output();
+    keymap.report_output();
+    keymap.tick();
+    keymap.report_output();
+
+    // Assert
+    let reports: Vec<_> = keymap
+        .observer()
+        .records()
+        .filter(|record| matches!(record, TraceRecord::ReportEmitted { .. }))
+        .cloned()
+        .collect();
+    assert_eq!(vec![TraceRecord::ReportEmitted { output }], reports);
+}
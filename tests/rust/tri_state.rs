@@ -134,3 +134,113 @@ fn custom_hold_tap_ctrl_tab() {
     let actual_reports = keymap.distinct_reports();
     assert_eq!(expected_reports, actual_reports.reports());
 }
+
+#[test]
+fn continue_through_arrow_keeps_alt_held() {
+    // Assemble -- Alt-Tab tri-state which Left continues through
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.tri_state.custom {
+                        hold = K.LeftAlt,
+                        tap = K.Tab,
+                        continue_through = [K.LeftShift, K.Left, K.Right],
+                    },
+                    K.Left,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- open the session, then tap Left
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert -- Left is sent with Alt still held
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_TAB, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_LEFT, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn reverse_key_taps_reverse_tap() {
+    // Assemble -- Alt-Tab tri-state with Shift+Tab reverse, and a reverse key
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.tri_state.custom {
+                        hold = K.LeftAlt,
+                        tap = K.Tab,
+                        reverse_tap = K.LeftShift & K.Tab,
+                    },
+                    K.tri_state.reverse,
+                ],
+            }
+        "#
+    ));
+
+    // Act -- open the session, then tap the reverse key
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.handle_input(input::Event::Press { keymap_index: 1 });
+    keymap.handle_input(input::Event::Release { keymap_index: 1 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert -- Shift+Tab is tapped with Alt held
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_TAB, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT | MOD_LSHFT, 0, KC_TAB, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn timeout_releases_alt() {
+    // Assemble -- Alt-Tab tri-state with a 500ms idle timeout
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.tri_state.custom {
+                        hold = K.LeftAlt,
+                        tap = K.Tab,
+                        timeout = 500,
+                    },
+                ],
+            }
+        "#
+    ));
+
+    // Act -- tap the tri-state key, then idle
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert -- Alt is released after the timeout
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_TAB, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
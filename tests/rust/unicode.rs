@@ -0,0 +1,86 @@
+use smart_keymap::input;
+use smart_keymap::keymap::ObservedKeymap;
+
+use crate::hid_keycodes::*;
+use smart_keymap_macros::keymap;
+
+#[test]
+fn unicode_key_types_linux_hex_input() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.unicode "é",
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    keymap.handle_input(input::Event::Press { keymap_index: 0 });
+    keymap.handle_input(input::Event::Release { keymap_index: 0 });
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    // Ctrl+Shift+U, "e9", Space
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LCTL_LSHFT, 0, KC_U, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_E, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_9, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, KC_SPACE, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}
+
+#[test]
+fn unicode_mode_key_changes_input_method() {
+    // Assemble
+    let mut keymap = ObservedKeymap::new(keymap!(
+        r#"
+            let K = import "keys.ncl" in
+            {
+                keys = [
+                    K.UC_MAC,
+                    K.unicode "é",
+                ],
+            }
+        "#
+    ));
+
+    // Act
+    for keymap_index in [0, 1] {
+        keymap.handle_input(input::Event::Press { keymap_index });
+        keymap.handle_input(input::Event::Release { keymap_index });
+    }
+
+    keymap.tick_until_no_scheduled_events();
+
+    // Assert
+    // "00e9" while holding Option
+    #[rustfmt::skip]
+    let expected_reports: &[[u8; 8]] = &[
+        [0, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_E, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, KC_9, 0, 0, 0, 0, 0],
+        [MOD_LALT, 0, 0, 0, 0, 0, 0, 0],
+        [0, 0, 0, 0, 0, 0, 0, 0],
+    ];
+    let actual_reports = keymap.distinct_reports();
+    assert_eq!(expected_reports, actual_reports.reports());
+}